@@ -0,0 +1,102 @@
+// How many hands a second one core plays out of a `FastShoe`, next to the
+// same hands through `Deck` and the engine. The hands are played as the
+// simulator plays them, hitting to 17, on a flat model of the round: no
+// insurance, doubles or splits, the dealer standing on soft 17. Nothing in
+// the loop allocates.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use rand::{SeedableRng, StdRng};
+
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::settlement::settle;
+use rust_blackjack::shoe::FastShoe;
+use rust_blackjack::{continue_with_game, deal_with_action, Action, Deck, GameState, HandTotals};
+
+const HANDS: u64 = 5_000_000;
+const ENGINE_HANDS: u64 = 200_000;
+
+fn draw(shoe: &mut FastShoe, hand: &mut HandTotals) {
+    let card = shoe.deal().expect("a shoe is reshuffled before it runs low");
+    hand.add(&card.value);
+}
+
+fn is_natural(hand: &HandTotals) -> bool {
+    hand.best() == Option::Some(21)
+}
+
+// One hand, in half bets: a natural pays 3, a win 2.
+fn play(shoe: &mut FastShoe) -> i64 {
+    let (mut player, mut dealer) = (HandTotals { hard: 0, aces: 0 }, HandTotals { hard: 0, aces: 0 });
+    for _ in 0..2 {
+        draw(shoe, &mut player);
+        draw(shoe, &mut dealer);
+    }
+    match (is_natural(&player), is_natural(&dealer)) {
+        (true, true) => return 0,
+        (true, false) => return 3,
+        (false, true) => return -2,
+        (false, false) => (),
+    }
+    while player.best().is_some_and(|total| total < 17) {
+        draw(shoe, &mut player);
+    }
+    let player_total = match player.best() {
+        Option::Some(total) => total,
+        Option::None => return -2,
+    };
+    while dealer.best().is_some_and(|total| total < 17) {
+        draw(shoe, &mut dealer);
+    }
+    match dealer.best() {
+        Option::Some(total) if total > player_total => -2,
+        Option::Some(total) if total == player_total => 0,
+        _ => 2,
+    }
+}
+
+fn main() {
+    let mut rng: StdRng = SeedableRng::from_seed(&[5usize][..]);
+    let mut shoe = FastShoe::new(6, 75).expect("six decks is a valid shoe");
+    shoe.shuffle(&mut rng);
+
+    let started = Instant::now();
+    let mut net = 0;
+    for _ in 0..HANDS {
+        if shoe.needs_shuffle() {
+            shoe.shuffle(&mut rng);
+        }
+        net += play(black_box(&mut shoe));
+    }
+    let fast = HANDS as f64 / started.elapsed().as_secs_f64();
+    println!("FastShoe: {:.0} hands a second, {:+.4} bets a hand", fast, net as f64 / 2.0 / HANDS as f64);
+
+    let rules = GameRules { decks: 6, ..GameRules::default() };
+    let mut deck = Deck::with_decks(rules.decks);
+    deck.shuffle(&mut rng);
+    deck.place_cut_card(rules.penetration);
+    let started = Instant::now();
+    let mut net = 0;
+    for _ in 0..ENGINE_HANDS {
+        if deck.needs_shuffle() {
+            deck = Deck::with_decks(rules.decks);
+            deck.shuffle(&mut rng);
+            deck.place_cut_card(rules.penetration);
+        }
+        let mut state = GameState::start_with_rules(deck, rules.clone());
+        while continue_with_game(&state) {
+            let action = match state.player_state().hand.total() {
+                _ if state.legal_actions().contains(&Action::DeclineInsurance) => Action::DeclineInsurance,
+                Option::Some(total) if total < 17 => Action::Hit,
+                _ => Action::Stand,
+            };
+            state = deal_with_action(&action, state).expect("hitting and standing are always legal");
+        }
+        net += settle(&state, 2, &[]).net;
+        deck = state.player_state().deck.clone();
+    }
+    let engine = ENGINE_HANDS as f64 / started.elapsed().as_secs_f64();
+    println!("Deck and GameState: {:.0} hands a second, {:+.4} bets a hand", engine, net as f64 / 2.0 / ENGINE_HANDS as f64);
+    println!("{:.1}x faster", fast / engine);
+}
@@ -0,0 +1,99 @@
+// Timings for the hot paths the simulator and solver lean on: counting a
+// hand and playing hands out. criterion isn't a dependency, so this is a
+// small harness of its own on std's clock; run it with `cargo bench`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use rand::{SeedableRng, StdRng};
+
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::simulation;
+use rust_blackjack::{
+    best_hand_value, calculate_current_hand_value, card_value_to_hand_value, combine_possible_values, Card, CardValue,
+    Deck, HandValue,
+};
+
+const MEASURE_FOR: Duration = Duration::from_secs(1);
+
+// Runs `work` in batches until a second has passed and returns the time
+// each call took, in nanoseconds.
+fn measure<F: FnMut()>(name: &str, mut work: F) -> f64 {
+    let mut calls = 0u64;
+    let started = Instant::now();
+    while started.elapsed() < MEASURE_FOR {
+        for _ in 0..100 {
+            work();
+        }
+        calls += 100;
+    }
+    let nanos = started.elapsed().as_nanos() as f64 / calls as f64;
+    println!("  {:<38} {:>12.1} ns", name, nanos);
+    nanos
+}
+
+// The evaluation the hard total and ace count replaced: every way of
+// counting each card, combined card by card.
+fn every_way(hand: &[CardValue]) -> Vec<HandValue> {
+    hand.iter().map(card_value_to_hand_value).fold(vec![HandValue { value: 0 }], |x, y| combine_possible_values(&x, &y))
+}
+
+// Hands as a round leaves them: two cards, drawn to until they reach 17.
+fn sample_hands() -> Vec<Vec<Card>> {
+    let mut rng: StdRng = SeedableRng::from_seed(&[7usize][..]);
+    let mut deck = Deck::with_decks(6);
+    deck.shuffle(&mut rng);
+    let mut hands = Vec::new();
+    while deck.remaining() > 10 {
+        let mut hand: Vec<Card> = (0..2).filter_map(|_| deck.draw_card()).collect();
+        while best_hand_value(&hand).is_some_and(|total| total < 17) {
+            hand.extend(deck.draw_card());
+        }
+        hands.push(hand);
+    }
+    hands
+}
+
+fn main() {
+    let hands = sample_hands();
+    let values: Vec<Vec<CardValue>> = hands.iter().map(|hand| hand.iter().map(|card| card.value).collect()).collect();
+    let per_hand = |nanos: f64| nanos / hands.len() as f64;
+
+    println!("Counting {} hands, all of them:", hands.len());
+    let before = per_hand(measure("every way, card by card", || {
+        for hand in &values {
+            black_box(every_way(black_box(hand)));
+        }
+    }));
+    let after = per_hand(measure("calculate_current_hand_value", || {
+        for hand in &values {
+            black_box(calculate_current_hand_value(black_box(hand)));
+        }
+    }));
+    let best = per_hand(measure("best_hand_value", || {
+        for hand in &hands {
+            black_box(best_hand_value(black_box(hand)));
+        }
+    }));
+    println!("{:.1} ns, {:.1} ns and {:.1} ns a hand: {:.1}x faster", before, after, best, before / after);
+
+    println!("Playing hands with the simulator:");
+    let rules = GameRules { decks: 6, ..GameRules::default() };
+    let mut rng: StdRng = SeedableRng::from_seed(&[11usize][..]);
+    let batch = 1_000;
+    let nanos = measure("1000 hands, a shoe each", || {
+        let report = simulation::simulate(
+            batch,
+            || {
+                let mut deck = Deck::with_decks(rules.decks);
+                deck.shuffle(&mut rng);
+                deck
+            },
+            &rules,
+            10,
+            &[],
+        );
+        black_box(report);
+    });
+    println!("{:.0} hands a second", batch as f64 * 1e9 / nanos);
+}
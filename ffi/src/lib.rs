@@ -0,0 +1,2 @@
+// Declared in include/rust_blackjack.h.
+pub use rust_blackjack::ffi::*;
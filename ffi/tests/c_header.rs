@@ -0,0 +1,69 @@
+// include/rust_blackjack.h is written by hand, so this holds it to the
+// library: a C caller compiled against the header alone links to the cdylib,
+// plays a hand, and has the constants and struct layouts the Rust side has.
+// The functions' argument types aren't checked, since C links by name alone.
+
+use std::env;
+use std::mem::{self, offset_of};
+use std::path::Path;
+use std::process::Command;
+
+use rust_blackjack::ffi::{
+    BlackjackCard, BlackjackState, BLACKJACK_BLACKJACK_WIN, BLACKJACK_CONTINUING, BLACKJACK_LOST, BLACKJACK_MAX_CARDS,
+    BLACKJACK_PUSH, BLACKJACK_SURRENDERED, BLACKJACK_WON,
+};
+
+#[cfg(unix)]
+#[test]
+fn a_c_caller_builds_against_the_header_and_plays_a_hand() {
+    let root = concat!(env!("CARGO_MANIFEST_DIR"), "/..");
+    // A target directory of its own, as the one running the tests is locked.
+    let target = Path::new(env!("CARGO_TARGET_TMPDIR")).join("c_header");
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let manifest = format!("{}/Cargo.toml", root);
+    let built = Command::new(cargo)
+        .args(["build", "-p", "blackjack-ffi", "--offline", "--manifest-path", &manifest, "--target-dir"])
+        .arg(&target)
+        .output()
+        .expect("cargo could not be run");
+    assert!(built.status.success(), "{}", String::from_utf8_lossy(&built.stderr));
+
+    let library = target.join("debug");
+    let caller = target.join("caller");
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let compiled = Command::new(compiler)
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror", "-I", &format!("{}/include", root)])
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/caller.c"))
+        .arg("-o")
+        .arg(&caller)
+        .arg(format!("-L{}", library.display()))
+        .arg(format!("-Wl,-rpath,{}", library.display()))
+        .arg("-lblackjack_ffi")
+        .output()
+        .expect("no C compiler could be run; set CC to one");
+    assert!(compiled.status.success(), "{}", String::from_utf8_lossy(&compiled.stderr));
+
+    let ran = Command::new(&caller).output().expect("the C caller could not be run");
+    assert!(ran.status.success(), "{}", String::from_utf8_lossy(&ran.stderr));
+    let printed: Vec<usize> =
+        String::from_utf8_lossy(&ran.stdout).split_whitespace().map(|field| field.parse().expect("a number")).collect();
+    let statuses = [
+        BLACKJACK_CONTINUING,
+        BLACKJACK_WON,
+        BLACKJACK_LOST,
+        BLACKJACK_PUSH,
+        BLACKJACK_SURRENDERED,
+        BLACKJACK_BLACKJACK_WIN,
+    ];
+    assert_eq!(printed[0], BLACKJACK_MAX_CARDS);
+    assert_eq!(printed[1..7], statuses.map(|status| status as usize));
+    assert_eq!(
+        printed[7..],
+        [
+            mem::size_of::<BlackjackCard>(),
+            mem::size_of::<BlackjackState>(),
+            offset_of!(BlackjackState, dealer_len),
+            offset_of!(BlackjackState, dealer),
+        ]
+    );
+}
@@ -0,0 +1,72 @@
+/* A C program written against include/rust_blackjack.h alone: it plays one
+ * hand through the library and prints the header's constants and the layout
+ * it gives the structs, for tests/c_header.rs to hold against the Rust side. */
+
+#include <stddef.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "rust_blackjack.h"
+
+static int fail(const char *why) {
+    fprintf(stderr, "%s\n", why);
+    return 1;
+}
+
+int main(void) {
+    BlackjackGame *game = blackjack_game_new(7);
+    BlackjackState state;
+    char *json;
+    uint32_t i;
+    int turns;
+
+    if (game == NULL) {
+        return fail("blackjack_game_new returned NULL");
+    }
+    if (blackjack_game_state(game, &state) != 0) {
+        return fail("blackjack_game_state failed");
+    }
+    if (state.hand_len < 2 || state.hand_len > BLACKJACK_MAX_CARDS) {
+        return fail("the hand doesn't have two to BLACKJACK_MAX_CARDS cards");
+    }
+    for (i = 0; i < state.hand_len; i++) {
+        if (state.hand[i].rank < 2 || state.hand[i].rank > 14 || state.hand[i].suit > 3) {
+            return fail("a card is out of range");
+        }
+    }
+    if (blackjack_game_apply_action(game, "fold") != -1 || blackjack_game_apply_action(NULL, "stand") != -1) {
+        return fail("a bad action or game wasn't refused");
+    }
+    /* Standing ends any hand, once insurance has been turned down. */
+    for (turns = 0; state.status == BLACKJACK_CONTINUING; turns++) {
+        if (turns == 2) {
+            return fail("the hand didn't end");
+        }
+        if (blackjack_game_apply_action(game, "no-insurance") != 0 && blackjack_game_apply_action(game, "stand") != 0) {
+            return fail("neither no-insurance nor stand was legal");
+        }
+        blackjack_game_state(game, &state);
+    }
+    json = blackjack_game_state_json(game);
+    if (json == NULL || strstr(json, "\"status\"") == NULL) {
+        return fail("blackjack_game_state_json didn't describe the hand");
+    }
+    blackjack_string_free(json);
+    blackjack_game_free(game);
+
+    printf(
+        "%d %d %d %d %d %d %d %lu %lu %lu %lu\n",
+        BLACKJACK_MAX_CARDS,
+        BLACKJACK_CONTINUING,
+        BLACKJACK_WON,
+        BLACKJACK_LOST,
+        BLACKJACK_PUSH,
+        BLACKJACK_SURRENDERED,
+        BLACKJACK_BLACKJACK_WIN,
+        (unsigned long) sizeof(BlackjackCard),
+        (unsigned long) sizeof(BlackjackState),
+        (unsigned long) offsetof(BlackjackState, dealer_len),
+        (unsigned long) offsetof(BlackjackState, dealer)
+    );
+    return 0;
+}
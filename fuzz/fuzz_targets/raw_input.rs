@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use rust_blackjack::wasm::{new_game, state_json};
+
+// The first four bytes seed the shuffle and the rest is typed at the game,
+// so the fuzzer explores both the shoe and the input.
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 4 {
+        return;
+    }
+    let seed = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+    let mut game = new_game(seed);
+    game.apply_raw_input(&String::from_utf8_lossy(&data[4..]));
+    state_json(&game);
+});
@@ -0,0 +1,67 @@
+#ifndef RUST_BLACKJACK_H
+#define RUST_BLACKJACK_H
+
+/* C interface to the rust-blackjack engine, libblackjack_ffi, built with
+ * `cargo build -p blackjack-ffi`. Mirrors src/ffi.rs. */
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The most cards BlackjackState holds per hand. A longer hand, which only a
+ * shoe of several decks can deal, is cut to its first BLACKJACK_MAX_CARDS
+ * cards, and its length to BLACKJACK_MAX_CARDS; blackjack_game_state_json
+ * lists every card. */
+#define BLACKJACK_MAX_CARDS 12
+
+#define BLACKJACK_CONTINUING 0
+#define BLACKJACK_WON 1
+#define BLACKJACK_LOST 2
+#define BLACKJACK_PUSH 3
+#define BLACKJACK_SURRENDERED 4
+#define BLACKJACK_BLACKJACK_WIN 5
+
+typedef struct BlackjackGame BlackjackGame;
+
+typedef struct BlackjackCard {
+    /* 2-10 for pip cards, 11 jack, 12 queen, 13 king, 14 ace. */
+    uint8_t rank;
+    /* 0 clubs, 1 hearts, 2 diamonds, 3 spades. */
+    uint8_t suit;
+} BlackjackCard;
+
+typedef struct BlackjackState {
+    int32_t status;
+    /* Highest hand value that doesn't exceed 21, or 0 once busted. */
+    uint32_t best_value;
+    /* At most BLACKJACK_MAX_CARDS, even if the hand holds more. */
+    uint32_t hand_len;
+    BlackjackCard hand[BLACKJACK_MAX_CARDS];
+    /* Face-up dealer cards only; the hole card shows up once the round is over. */
+    /* At most BLACKJACK_MAX_CARDS, as for hand_len. */
+    uint32_t dealer_len;
+    BlackjackCard dealer[BLACKJACK_MAX_CARDS];
+} BlackjackState;
+
+BlackjackGame *blackjack_game_new(uint32_t seed);
+
+/* Returns 0 on success, -1 for an unknown or illegal action or a NULL argument. */
+int32_t blackjack_game_apply_action(BlackjackGame *game, const char *action);
+
+/* Returns 0 on success, -1 for a NULL argument. */
+int32_t blackjack_game_state(const BlackjackGame *game, BlackjackState *out);
+
+/* Free the result with blackjack_string_free. */
+char *blackjack_game_state_json(const BlackjackGame *game);
+
+void blackjack_string_free(char *json);
+
+void blackjack_game_free(BlackjackGame *game);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
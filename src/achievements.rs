@@ -0,0 +1,74 @@
+use crate::stats::SessionStats;
+use crate::{best_hand_value, GameState};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Achievement {
+    FirstBlackjack,
+    FiveInARow,
+    DoubleOnElevenWin,
+    DoubledBankroll,
+}
+
+impl Achievement {
+    pub const ALL_VALUES: [Achievement; 4] = [
+        Achievement::FirstBlackjack,
+        Achievement::FiveInARow,
+        Achievement::DoubleOnElevenWin,
+        Achievement::DoubledBankroll,
+    ];
+
+    // Stored in the achievements file, so these never change.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Achievement::FirstBlackjack => "first-blackjack",
+            Achievement::FiveInARow => "five-in-a-row",
+            Achievement::DoubleOnElevenWin => "double-on-eleven",
+            Achievement::DoubledBankroll => "doubled-bankroll",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Achievement> {
+        Achievement::ALL_VALUES.iter().find(|achievement| achievement.name() == name).cloned()
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Achievement::FirstBlackjack => "Natural",
+            Achievement::FiveInARow => "Hot streak",
+            Achievement::DoubleOnElevenWin => "Textbook double",
+            Achievement::DoubledBankroll => "Double up",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Achievement::FirstBlackjack => "Be dealt your first blackjack",
+            Achievement::FiveInARow => "Win five hands in a row",
+            Achievement::DoubleOnElevenWin => "Double down on 11 and win",
+            Achievement::DoubledBankroll => "Grow a bankroll to twice what you sat down with",
+        }
+    }
+}
+
+// The achievements a finished hand earns, given the session so far with the
+// hand already recorded in `stats`. Earning one again is harmless; callers
+// keep whichever they haven't unlocked yet.
+pub fn earned(state: &GameState, stats: &SessionStats, bankroll: i64, starting_bankroll: u64) -> Vec<Achievement> {
+    let player_state = state.player_state();
+    let won = matches!(state, GameState::GameWon(_) | GameState::BlackjackWin(_));
+    let mut earned = Vec::new();
+    if player_state.hand.is_blackjack() {
+        earned.push(Achievement::FirstBlackjack);
+    }
+    if stats.streak >= 5 {
+        earned.push(Achievement::FiveInARow);
+    }
+    let opening = &player_state.hand[..player_state.hand.card_count().min(2)];
+    if won && player_state.doubled && best_hand_value(opening) == Option::Some(11) {
+        earned.push(Achievement::DoubleOnElevenWin);
+    }
+    if starting_bankroll > 0 && bankroll >= 2 * starting_bankroll as i64 {
+        earned.push(Achievement::DoubledBankroll);
+    }
+    earned
+}
@@ -0,0 +1,215 @@
+use rand::StdRng;
+
+use crate::betting::{stake, BetContext, CountSpread};
+use crate::counting::HI_LO;
+use crate::rules::GameRules;
+use crate::settlement::settle;
+use crate::simulation::{new_shoe, parallel_batch, Merge};
+use crate::strategy::basic_strategy_for;
+use crate::{continue_with_game, deal_with_action, Deck, GameState};
+
+// Settling in hundredths of a unit keeps 3:2, 6:5 and half-bet surrenders
+// exact in integer chips.
+const UNIT: u64 = 100;
+
+// 1.96 standard errors either side of the mean covers 95% of a normal
+// distribution.
+const Z_95: f64 = 1.96;
+
+// Per-hand results in units of the initial bet, accumulated so the mean and
+// its spread can be recovered without keeping every hand.
+#[derive(Debug, Default)]
+pub struct EdgeReport {
+    pub hands: u64,
+    pub total: f64,
+    pub sum_of_squares: f64,
+}
+
+impl Merge for EdgeReport {
+    fn merge(mut self, other: EdgeReport) -> EdgeReport {
+        self.hands += other.hands;
+        self.total += other.total;
+        self.sum_of_squares += other.sum_of_squares;
+        self
+    }
+}
+
+impl EdgeReport {
+    // The player's expected result per hand; the house edge is its negative.
+    pub fn expected_value(&self) -> f64 {
+        if self.hands == 0 {
+            0.0
+        } else {
+            self.total / self.hands as f64
+        }
+    }
+
+    pub fn house_edge(&self) -> f64 {
+        -self.expected_value()
+    }
+
+    pub fn standard_deviation(&self) -> f64 {
+        if self.hands < 2 {
+            return 0.0;
+        }
+        let mean = self.expected_value();
+        let variance = (self.sum_of_squares - self.hands as f64 * mean * mean) / (self.hands - 1) as f64;
+        variance.max(0.0).sqrt()
+    }
+
+    pub fn standard_error(&self) -> f64 {
+        if self.hands == 0 {
+            0.0
+        } else {
+            self.standard_deviation() / (self.hands as f64).sqrt()
+        }
+    }
+
+    // 95% confidence interval for the house edge.
+    pub fn confidence_interval(&self) -> (f64, f64) {
+        let margin = Z_95 * self.standard_error();
+        (self.house_edge() - margin, self.house_edge() + margin)
+    }
+}
+
+fn play_hands(hands: u64, rules: &GameRules, rng: &mut StdRng) -> EdgeReport {
+    let mut report = EdgeReport::default();
+    let mut shuffler = rules.shuffle_model.shuffler(rng);
+    for _ in 0..hands {
+        let mut deck = Deck::with_decks(rules.decks);
+        deck.shuffle(shuffler.as_mut());
+        let mut state = GameState::start_with_rules(deck, rules.clone());
+        while continue_with_game(&state) {
+            let action = basic_strategy_for(&state);
+            state = deal_with_action(&action, state).expect("basic strategy only picks legal actions");
+        }
+        let result = settle(&state, UNIT, &[]).net as f64 / UNIT as f64;
+        report.hands += 1;
+        report.total += result;
+        report.sum_of_squares += result * result;
+    }
+    report
+}
+
+// Plays `hands` hands of basic strategy under `rules`, split across
+// `threads` workers that each shuffle with their own seed, starting from
+// `seed`.
+pub fn analyze(rules: &GameRules, hands: u64, seed: u64, threads: usize) -> EdgeReport {
+    parallel_batch(hands, threads, seed, |share, rng| play_hands(share, rules, rng))
+}
+
+// What betting a Hi-Lo spread is worth, from per-hand results in chips.
+#[derive(Debug, Default)]
+pub struct BankrollAnalysis {
+    pub results: EdgeReport,
+    // Opening bets, before any doubles.
+    pub wagered: u64,
+}
+
+impl Merge for BankrollAnalysis {
+    fn merge(self, other: BankrollAnalysis) -> BankrollAnalysis {
+        BankrollAnalysis { results: self.results.merge(other.results), wagered: self.wagered + other.wagered }
+    }
+}
+
+impl BankrollAnalysis {
+    // Expected chips won per hand.
+    pub fn win_rate(&self) -> f64 {
+        self.results.expected_value()
+    }
+
+    pub fn standard_deviation(&self) -> f64 {
+        self.results.standard_deviation()
+    }
+
+    pub fn average_bet(&self) -> f64 {
+        if self.results.hands == 0 {
+            0.0
+        } else {
+            self.wagered as f64 / self.results.hands as f64
+        }
+    }
+
+    // The hands it takes for the expected win to equal one standard
+    // deviation, after which the edge starts to show through the swings.
+    // None unless the spread wins.
+    pub fn n0(&self) -> Option<f64> {
+        let win_rate = self.win_rate();
+        if win_rate <= 0.0 {
+            Option::None
+        } else {
+            Option::Some((self.standard_deviation() / win_rate).powi(2))
+        }
+    }
+
+    pub fn hourly_win(&self, hands_per_hour: u64) -> f64 {
+        self.win_rate() * hands_per_hour as f64
+    }
+
+    // The chance of ever losing `bankroll` playing on indefinitely, by the
+    // usual diffusion estimate exp(-2 * win rate * bankroll / variance). A
+    // spread that loses on average is ruined for certain.
+    pub fn risk_of_ruin(&self, bankroll: u64) -> f64 {
+        let win_rate = self.win_rate();
+        let variance = self.standard_deviation().powi(2);
+        if win_rate <= 0.0 {
+            1.0
+        } else if variance == 0.0 {
+            0.0
+        } else {
+            (-2.0 * win_rate * bankroll as f64 / variance).exp()
+        }
+    }
+
+    // The bankroll that keeps the risk of ruin down to `risk`.
+    pub fn bankroll_for_risk(&self, risk: f64) -> Option<f64> {
+        let win_rate = self.win_rate();
+        if win_rate <= 0.0 {
+            Option::None
+        } else {
+            Option::Some(-risk.ln() * self.standard_deviation().powi(2) / (2.0 * win_rate))
+        }
+    }
+}
+
+fn play_spread(hands: u64, rules: &GameRules, base_bet: u64, max_units: u64, rng: &mut StdRng) -> BankrollAnalysis {
+    let mut analysis = BankrollAnalysis::default();
+    let mut spread = CountSpread { max_units };
+    let mut shuffler = rules.shuffle_model.shuffler(rng);
+    let mut shoe = new_shoe(rules, shuffler.as_mut());
+    for _ in 0..hands {
+        if shoe.needs_shuffle() {
+            shoe = new_shoe(rules, shuffler.as_mut());
+        }
+        // The win rate is measured without a bankroll to run out of.
+        let context = BetContext { bankroll: u64::MAX, base_bet, true_count: HI_LO.shoe_true_count(&shoe) };
+        let bet = stake(&mut spread, &context);
+        let mut state = GameState::start_with_rules(shoe, rules.clone());
+        while continue_with_game(&state) {
+            let action = basic_strategy_for(&state);
+            state = deal_with_action(&action, state).expect("basic strategy only picks legal actions");
+        }
+        let result = settle(&state, bet, &[]).net as f64;
+        analysis.results.hands += 1;
+        analysis.results.total += result;
+        analysis.results.sum_of_squares += result * result;
+        analysis.wagered += bet;
+        shoe = state.discard_hand();
+    }
+    analysis
+}
+
+// Plays `hands` hands of basic strategy under `rules`, betting `base_bet`
+// and spreading up to `max_units` of it on the Hi-Lo true count, split
+// across `threads` workers as `analyze` does. Shoes are dealt to the cut
+// card so the count means something.
+pub fn analyze_bankroll(
+    rules: &GameRules,
+    hands: u64,
+    base_bet: u64,
+    max_units: u64,
+    seed: u64,
+    threads: usize,
+) -> BankrollAnalysis {
+    parallel_batch(hands, threads, seed, |share, rng| play_spread(share, rules, base_bet, max_units, rng))
+}
@@ -0,0 +1,58 @@
+use crate::{Card, CardSuit, CardValue};
+
+// Cards drawn as little ASCII faces side by side, for terminals where
+// "Card { suit: Hearts, value: Queen }" is hard to read at a glance.
+
+const CARD_HEIGHT: usize = 5;
+
+fn rank(value: &CardValue) -> &'static str {
+    match value {
+        CardValue::Two => "2",
+        CardValue::Three => "3",
+        CardValue::Four => "4",
+        CardValue::Five => "5",
+        CardValue::Six => "6",
+        CardValue::Seven => "7",
+        CardValue::Eight => "8",
+        CardValue::Nine => "9",
+        CardValue::Ten => "10",
+        CardValue::Jack => "J",
+        CardValue::Queen => "Q",
+        CardValue::King => "K",
+        CardValue::Ace => "A",
+    }
+}
+
+// Letters rather than the suit symbols, to stay within ASCII.
+fn suit(suit: &CardSuit) -> char {
+    match suit {
+        CardSuit::Clubs => 'C',
+        CardSuit::Hearts => 'H',
+        CardSuit::Diamonds => 'D',
+        CardSuit::Spades => 'S',
+    }
+}
+
+fn face(card: &Card) -> [String; CARD_HEIGHT] {
+    let rank = rank(&card.value);
+    [
+        "+-----+".to_string(),
+        format!("|{:<5}|", rank),
+        format!("|  {}  |", suit(&card.suit)),
+        format!("|{:>5}|", rank),
+        "+-----+".to_string(),
+    ]
+}
+
+fn back() -> [String; CARD_HEIGHT] {
+    ["+-----+", "|#####|", "|#####|", "|#####|", "+-----+"].map(str::to_string)
+}
+
+// The cards face up, followed by `face_down` backs.
+pub fn render_cards(cards: &[Card], face_down: usize) -> String {
+    let drawn: Vec<[String; CARD_HEIGHT]> = cards.iter().map(face).chain((0..face_down).map(|_| back())).collect();
+    (0..CARD_HEIGHT)
+        .map(|line| drawn.iter().map(|card| card[line].as_str()).collect::<Vec<&str>>().join(" "))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
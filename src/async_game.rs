@@ -0,0 +1,232 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+use std::time::Duration;
+
+use crate::events::{self, EventSink};
+use crate::strategy::{StandPat, Strategy};
+use crate::{continue_with_game, Action, GameState};
+
+// Games that wait for their decisions instead of blocking a thread on them.
+// Decisions arrive over an `ActionReceiver`, from a socket reader, a GUI or
+// another thread, and a hand is a future that finishes once the dealer has
+// played. The futures only need std, so any executor runs them; `block_on`
+// and `block_on_all` below are enough for a server thread running several
+// tables at once.
+
+struct Inbox {
+    actions: VecDeque<Action>,
+    waker: Option<Waker>,
+    senders: usize,
+}
+
+impl Inbox {
+    fn wake(&mut self) {
+        if let Option::Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+pub struct ActionSender {
+    inbox: Arc<Mutex<Inbox>>,
+}
+
+pub struct ActionReceiver {
+    inbox: Arc<Mutex<Inbox>>,
+}
+
+// The table reading decisions gets the receiver; whoever makes them, the
+// sender, which can be cloned. Once every sender is gone the receiver sees
+// the end of the decisions.
+pub fn action_channel() -> (ActionSender, ActionReceiver) {
+    let inbox = Arc::new(Mutex::new(Inbox { actions: VecDeque::new(), waker: Option::None, senders: 1 }));
+    (ActionSender { inbox: Arc::clone(&inbox) }, ActionReceiver { inbox })
+}
+
+impl ActionSender {
+    pub fn send(&self, action: Action) {
+        let mut inbox = self.inbox.lock().expect("action inbox poisoned");
+        inbox.actions.push_back(action);
+        inbox.wake();
+    }
+}
+
+impl Clone for ActionSender {
+    fn clone(&self) -> ActionSender {
+        self.inbox.lock().expect("action inbox poisoned").senders += 1;
+        ActionSender { inbox: Arc::clone(&self.inbox) }
+    }
+}
+
+impl Drop for ActionSender {
+    fn drop(&mut self) {
+        if let Ok(mut inbox) = self.inbox.lock() {
+            inbox.senders -= 1;
+            inbox.wake();
+        }
+    }
+}
+
+// The next decision, or `default` once `limit` has passed without one. A
+// timer thread is started the first time the future waits.
+pub struct NextAction<'a> {
+    receiver: &'a mut ActionReceiver,
+    limit: Option<(Duration, Action)>,
+    expired: Arc<AtomicBool>,
+    timing: bool,
+}
+
+impl ActionReceiver {
+    // The next decision; None once every sender is gone with nothing left
+    // to read.
+    pub fn recv(&mut self) -> NextAction<'_> {
+        NextAction { receiver: self, limit: Option::None, expired: Arc::new(AtomicBool::new(false)), timing: false }
+    }
+
+    pub fn recv_within(&mut self, limit: Duration, default: Action) -> NextAction<'_> {
+        NextAction {
+            receiver: self,
+            limit: Option::Some((limit, default)),
+            expired: Arc::new(AtomicBool::new(false)),
+            timing: false,
+        }
+    }
+}
+
+impl Future for NextAction<'_> {
+    type Output = Option<Action>;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context) -> Poll<Option<Action>> {
+        let this = self.get_mut();
+        let mut inbox = this.receiver.inbox.lock().expect("action inbox poisoned");
+        if let Option::Some(action) = inbox.actions.pop_front() {
+            return Poll::Ready(Option::Some(action));
+        }
+        if let Option::Some((limit, default)) = &this.limit {
+            if this.expired.load(Ordering::SeqCst) {
+                return Poll::Ready(Option::Some(default.clone()));
+            }
+            if !this.timing {
+                this.timing = true;
+                let (limit, expired, timed_inbox) = (*limit, Arc::clone(&this.expired), Arc::clone(&this.receiver.inbox));
+                thread::spawn(move || {
+                    thread::sleep(limit);
+                    expired.store(true, Ordering::SeqCst);
+                    if let Ok(mut inbox) = timed_inbox.lock() {
+                        inbox.wake();
+                    }
+                });
+            }
+        }
+        if inbox.senders == 0 {
+            return Poll::Ready(Option::None);
+        }
+        inbox.waker = Option::Some(context.waker().clone());
+        Poll::Pending
+    }
+}
+
+pub struct AsyncGame {
+    state: GameState,
+    decision_time: Option<Duration>,
+}
+
+impl AsyncGame {
+    pub fn new(state: GameState) -> AsyncGame {
+        AsyncGame { state, decision_time: Option::None }
+    }
+
+    // Each decision has `limit` to arrive, after which the player stands.
+    pub fn with_decision_time(self, limit: Duration) -> AsyncGame {
+        AsyncGame { decision_time: Option::Some(limit), ..self }
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    // Plays the hand to the end, waiting for each decision in turn. Illegal
+    // ones are skipped. When the decisions run out with the hand still in
+    // play, the hand is abandoned and its bet surrendered.
+    pub async fn play<S: EventSink>(self, actions: &mut ActionReceiver, mut sink: S) -> GameState {
+        let mut state = self.state;
+        while continue_with_game(&state) {
+            let action = match self.decision_time {
+                Option::Some(limit) => actions.recv_within(limit, StandPat.choose(&state)).await,
+                Option::None => actions.recv().await,
+            };
+            state = match action {
+                Option::Some(action) => match events::act(&action, state, &mut sink) {
+                    Ok(state) => state,
+                    Err(illegal) => *illegal.state,
+                },
+                Option::None => {
+                    let abandoned = state.clone().abandon();
+                    for event in events::changes(&state, &abandoned) {
+                        sink.emit(event);
+                    }
+                    abandoned
+                },
+            };
+        }
+        state
+    }
+}
+
+struct Signal {
+    woken: Mutex<bool>,
+    condvar: Condvar,
+}
+
+impl Wake for Signal {
+    fn wake(self: Arc<Self>) {
+        *self.woken.lock().expect("signal poisoned") = true;
+        self.condvar.notify_one();
+    }
+}
+
+impl Signal {
+    fn wait(&self) {
+        let mut woken = self.woken.lock().expect("signal poisoned");
+        while !*woken {
+            woken = self.condvar.wait(woken).expect("signal poisoned");
+        }
+        *woken = false;
+    }
+}
+
+// Runs one future to completion on this thread, sleeping while it waits.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut outputs = block_on_all(vec![Box::pin(future)]);
+    outputs.pop().expect("one future, one output")
+}
+
+// Runs the futures together on this thread, each table waiting on its own
+// decisions without holding up the others, and returns their outputs in
+// order once all have finished.
+pub fn block_on_all<'a, T>(futures: Vec<Pin<Box<dyn Future<Output = T> + 'a>>>) -> Vec<T> {
+    let signal = Arc::new(Signal { woken: Mutex::new(false), condvar: Condvar::new() });
+    let waker = Waker::from(Arc::clone(&signal));
+    let mut context = Context::from_waker(&waker);
+    let mut pending: Vec<Option<Pin<Box<dyn Future<Output = T> + 'a>>>> = futures.into_iter().map(Option::Some).collect();
+    let mut outputs: Vec<Option<T>> = pending.iter().map(|_| Option::None).collect();
+    while pending.iter().any(Option::is_some) {
+        for (slot, output) in pending.iter_mut().zip(outputs.iter_mut()) {
+            if let Option::Some(future) = slot {
+                if let Poll::Ready(value) = future.as_mut().poll(&mut context) {
+                    *output = Option::Some(value);
+                    *slot = Option::None;
+                }
+            }
+        }
+        if pending.iter().any(Option::is_some) {
+            signal.wait();
+        }
+    }
+    outputs.into_iter().map(|output| output.expect("every future finished")).collect()
+}
@@ -0,0 +1,143 @@
+// What a bettor knows when the next hand is about to be dealt.
+#[derive(Clone, Debug)]
+pub struct BetContext {
+    pub bankroll: u64,
+    // The table minimum, and the unit spreads are counted in.
+    pub base_bet: u64,
+    // Hi-Lo, from the cards dealt out of the current shoe so far.
+    pub true_count: f64,
+}
+
+// How much to stake on each hand. Bets below the base bet or above the
+// bankroll are brought back inside them by `stake`.
+pub trait BetStrategy {
+    fn name(&self) -> &str;
+    fn bet(&mut self, context: &BetContext) -> u64;
+    // The result of the hand just bet on.
+    fn record(&mut self, _net: i64) {}
+}
+
+// What actually goes on the table: at least the base bet, never more than
+// the bankroll.
+pub fn stake(strategy: &mut dyn BetStrategy, context: &BetContext) -> u64 {
+    strategy.bet(context).max(context.base_bet).min(context.bankroll)
+}
+
+// The same bet every hand.
+pub struct FlatBet;
+
+impl BetStrategy for FlatBet {
+    fn name(&self) -> &str {
+        "flat"
+    }
+
+    fn bet(&mut self, context: &BetContext) -> u64 {
+        context.base_bet
+    }
+}
+
+// Doubles the bet after every loss and drops back to the base bet after a
+// win; a push leaves it where it is. Wins a base bet per run, until a run of
+// losses outgrows the bankroll.
+#[derive(Default)]
+pub struct Martingale {
+    losses: u32,
+}
+
+impl BetStrategy for Martingale {
+    fn name(&self) -> &str {
+        "martingale"
+    }
+
+    fn bet(&mut self, context: &BetContext) -> u64 {
+        context.base_bet.saturating_mul(1 << self.losses.min(32))
+    }
+
+    fn record(&mut self, net: i64) {
+        if net < 0 {
+            self.losses += 1;
+        } else if net > 0 {
+            self.losses = 0;
+        }
+    }
+}
+
+// Each true count point is worth about half a percent to the player, from a
+// house edge of about half a percent off the top, and a hand's variance is
+// about 1.3 bets squared.
+const EDGE_OFF_THE_TOP: f64 = -0.005;
+const EDGE_PER_TRUE_COUNT: f64 = 0.005;
+const VARIANCE_PER_HAND: f64 = 1.3;
+
+// Stakes the fraction of the bankroll the Kelly criterion gives for the
+// edge the count implies, and the minimum while the edge is the house's.
+pub struct Kelly {
+    // 1.0 for full Kelly, 0.5 for half.
+    pub fraction: f64,
+}
+
+impl BetStrategy for Kelly {
+    fn name(&self) -> &str {
+        "kelly"
+    }
+
+    fn bet(&mut self, context: &BetContext) -> u64 {
+        let edge = EDGE_OFF_THE_TOP + EDGE_PER_TRUE_COUNT * context.true_count;
+        if edge <= 0.0 {
+            return context.base_bet;
+        }
+        (context.bankroll as f64 * self.fraction * edge / VARIANCE_PER_HAND) as u64
+    }
+}
+
+// A counter's ramp: one unit until the true count reaches 2, then doubling
+// with each point up to `max_units`.
+pub struct CountSpread {
+    pub max_units: u64,
+}
+
+impl BetStrategy for CountSpread {
+    fn name(&self) -> &str {
+        "spread"
+    }
+
+    fn bet(&mut self, context: &BetContext) -> u64 {
+        let units = if context.true_count < 2.0 {
+            1
+        } else {
+            let doublings = (context.true_count.floor() as u32 - 1).min(16);
+            (1u64 << doublings).min(self.max_units)
+        };
+        context.base_bet * units
+    }
+}
+
+// A fixed share of whatever is left, as tournament bots bet.
+pub struct Proportional {
+    pub divisor: u64,
+}
+
+impl BetStrategy for Proportional {
+    fn name(&self) -> &str {
+        "proportional"
+    }
+
+    fn bet(&mut self, context: &BetContext) -> u64 {
+        context.bankroll / self.divisor.max(1)
+    }
+}
+
+pub const BET_STRATEGY_NAMES: [&str; 5] = ["flat", "martingale", "kelly", "spread", "proportional"];
+
+// Kelly bets half of what the formula gives, as most counters do: it gives up
+// a quarter of the growth for half the swings.
+pub fn bet_strategy_from_name(name: &str) -> Option<Box<dyn BetStrategy>> {
+    match name {
+        "flat" => Option::Some(Box::new(FlatBet)),
+        "martingale" => Option::Some(Box::new(Martingale::default())),
+        "kelly" => Option::Some(Box::new(Kelly { fraction: 0.5 })),
+        "spread" => Option::Some(Box::new(CountSpread { max_units: 8 })),
+        "proportional" => Option::Some(Box::new(Proportional { divisor: 10 })),
+        _ => Option::None,
+    }
+}
@@ -0,0 +1,236 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::currency::Currency;
+
+// Bets as a player puts them down: in chips of the usual casino
+// denominations, within the limits the table posts.
+
+pub const DEFAULT_TABLE_MIN: u64 = 5;
+
+pub const DEFAULT_TABLE_MAX: u64 = 500;
+
+// The tallest stack drawn; anything above it is noted over the stack.
+const MAX_STACK_HEIGHT: u64 = 5;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Chip {
+    One,
+    Five,
+    TwentyFive,
+    Hundred,
+    FiveHundred,
+}
+
+impl Chip {
+    // Largest first, the order a bet is broken into chips in.
+    pub const ALL_VALUES: [Chip; 5] = [Chip::FiveHundred, Chip::Hundred, Chip::TwentyFive, Chip::Five, Chip::One];
+
+    pub fn value(&self) -> u64 {
+        match self {
+            Chip::One => 1,
+            Chip::Five => 5,
+            Chip::TwentyFive => 25,
+            Chip::Hundred => 100,
+            Chip::FiveHundred => 500,
+        }
+    }
+
+    pub fn from_value(value: u64) -> Option<Chip> {
+        Chip::ALL_VALUES.iter().find(|chip| chip.value() == value).cloned()
+    }
+
+    // The ANSI color of the chip: white, red, green, black and purple.
+    fn color_code(&self) -> &'static str {
+        match self {
+            Chip::One => "37",
+            Chip::Five => "31",
+            Chip::TwentyFive => "32",
+            Chip::Hundred => "90",
+            Chip::FiveHundred => "35",
+        }
+    }
+}
+
+// The fewest chips that make up an amount, largest first, skipping the
+// denominations it doesn't need.
+pub fn chips_for(amount: u64) -> Vec<(Chip, u64)> {
+    let mut left = amount;
+    let mut chips = Vec::new();
+    for chip in Chip::ALL_VALUES.iter() {
+        let count = left / chip.value();
+        if count > 0 {
+            chips.push((chip.clone(), count));
+            left -= count * chip.value();
+        }
+    }
+    chips
+}
+
+// A bet as typed at the prompt: either an amount, such as "35", or chips,
+// each a denomination on its own or a count of one, such as "25 5 5" or
+// "2x25 1x5". A lone number is always an amount.
+pub fn parse_bet(raw: &str) -> Option<u64> {
+    let terms: Vec<&str> = raw.split(|c: char| c.is_whitespace() || c == ',').filter(|term| !term.is_empty()).collect();
+    match terms.as_slice() {
+        [] => Option::None,
+        [amount] if !amount.contains('x') => amount.parse().ok(),
+        _ => terms.iter().try_fold(0u64, |total, term| total.checked_add(parse_chips(term)?)),
+    }
+}
+
+fn parse_chips(term: &str) -> Option<u64> {
+    let (count, value) = match term.find('x') {
+        Option::Some(position) => (term[..position].parse().ok()?, &term[position + 1..]),
+        Option::None => (1, term),
+    };
+    let chip = value.parse().ok().and_then(Chip::from_value)?;
+    chip.value().checked_mul(count)
+}
+
+// Why a bet, or the extra stake a double or split puts down, was turned
+// away.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BetError {
+    BelowMinimum { bet: u64, minimum: u64 },
+    AboveMaximum { bet: u64, maximum: u64 },
+    OverBankroll { bet: u64, bankroll: u64 },
+    // A split puts down another whole bet, and only `available` is left.
+    CannotAffordSplit { bet: u64, available: u64 },
+    // Nothing at all is left to double with, not even for less.
+    CannotAffordDouble,
+}
+
+impl BetError {
+    // The reason with its amounts as `currency` writes them.
+    pub fn describe_in(&self, currency: &Currency) -> String {
+        let amount = |amount: &u64| currency.format(*amount as i64);
+        match self {
+            BetError::BelowMinimum { bet, minimum } => {
+                format!("a bet of {} is below the table minimum of {}", amount(bet), amount(minimum))
+            },
+            BetError::AboveMaximum { bet, maximum } => {
+                format!("a bet of {} is over the table maximum of {}", amount(bet), amount(maximum))
+            },
+            BetError::OverBankroll { bet, bankroll } => {
+                format!("a bankroll of {} can't cover a bet of {}", amount(bankroll), amount(bet))
+            },
+            BetError::CannotAffordSplit { bet, available } => {
+                format!("splitting needs another {} and only {} is left", amount(bet), amount(available))
+            },
+            BetError::CannotAffordDouble => "nothing is left to double with".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for BetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.describe_in(&Currency::default()))
+    }
+}
+
+impl core::error::Error for BetError {}
+
+// A double puts down up to another bet. With less than that left the
+// player doubles for less, all in, for whatever remains.
+pub fn double_stake(bet: u64, available: u64) -> Result<u64, BetError> {
+    if available == 0 {
+        return Err(BetError::CannotAffordDouble);
+    }
+    Ok(bet.min(available))
+}
+
+pub fn check_split(bet: u64, available: u64) -> Result<(), BetError> {
+    if available < bet {
+        return Err(BetError::CannotAffordSplit { bet, available });
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TableLimits {
+    pub minimum: u64,
+    pub maximum: u64,
+}
+
+impl Default for TableLimits {
+    fn default() -> TableLimits {
+        TableLimits { minimum: DEFAULT_TABLE_MIN, maximum: DEFAULT_TABLE_MAX }
+    }
+}
+
+impl TableLimits {
+    pub fn allows(&self, bet: u64) -> bool {
+        (self.minimum..=self.maximum).contains(&bet)
+    }
+
+    // A bet the table takes and the bankroll covers.
+    pub fn check(&self, bet: u64, bankroll: u64) -> Result<(), BetError> {
+        if bet < self.minimum {
+            return Err(BetError::BelowMinimum { bet, minimum: self.minimum });
+        }
+        if bet > self.maximum {
+            return Err(BetError::AboveMaximum { bet, maximum: self.maximum });
+        }
+        if bet > bankroll {
+            return Err(BetError::OverBankroll { bet, bankroll });
+        }
+        Ok(())
+    }
+
+    // The nearest bet the table takes.
+    pub fn clamp(&self, bet: u64) -> u64 {
+        bet.clamp(self.minimum, self.maximum)
+    }
+}
+
+// The chips of a bet drawn as stacks side by side, one per denomination,
+// with the count and denomination under each.
+pub fn render_stacks(amount: u64, color: bool) -> String {
+    let chips = chips_for(amount);
+    let labels: Vec<String> = chips.iter().map(|(chip, count)| format!("{}x{}", count, chip.value())).collect();
+    let widths: Vec<usize> = chips
+        .iter()
+        .zip(labels.iter())
+        .map(|((chip, _), label)| label.len().max(chip.value().to_string().len() + 2))
+        .collect();
+    let height = chips.iter().map(|(_, count)| *count).max().unwrap_or(0).min(MAX_STACK_HEIGHT);
+    let mut lines = Vec::new();
+    // One row above the stacks for the counts that don't fit.
+    if chips.iter().any(|(_, count)| *count > MAX_STACK_HEIGHT) {
+        let overflow: Vec<String> = chips
+            .iter()
+            .zip(widths.iter())
+            .map(|((_, count), width)| {
+                let extra = if *count > MAX_STACK_HEIGHT { format!("+{}", count - MAX_STACK_HEIGHT) } else { String::new() };
+                format!("{:^width$}", extra, width = width)
+            })
+            .collect();
+        lines.push(overflow.join(" "));
+    }
+    for row in (0..height).rev() {
+        let cells: Vec<String> = chips
+            .iter()
+            .zip(widths.iter())
+            .map(|((chip, count), width)| {
+                if row < *count {
+                    let face = format!("{:^width$}", format!("({})", chip.value()), width = width);
+                    if color {
+                        format!("\x1b[{}m{}\x1b[0m", chip.color_code(), face)
+                    } else {
+                        face
+                    }
+                } else {
+                    " ".repeat(*width)
+                }
+            })
+            .collect();
+        lines.push(cells.join(" "));
+    }
+    let labels: Vec<String> =
+        labels.iter().zip(widths.iter()).map(|(label, width)| format!("{:^width$}", label, width = width)).collect();
+    lines.push(labels.join(" "));
+    lines.iter().map(|line| line.trim_end()).collect::<Vec<&str>>().join("\n")
+}
@@ -0,0 +1,1013 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rust_blackjack::betting::{bet_strategy_from_name, BET_STRATEGY_NAMES};
+use rust_blackjack::chips::TableLimits;
+use rust_blackjack::composition::{parse_rank, ShoeComposition};
+use rust_blackjack::config::{Config, RulesPreset, Speed, MAX_DECKS};
+use rust_blackjack::currency::Currency;
+use rust_blackjack::dealer::DealerProfile;
+use rust_blackjack::export::ExportFormat;
+use rust_blackjack::fixtures::fixture;
+use rust_blackjack::i18n::Language;
+use rust_blackjack::payout::{PayoutRounding, Rounding};
+use rust_blackjack::promotions::promotion_from_name;
+use rust_blackjack::rules::{EmptyShoe, GameRules, DEFAULT_PENETRATION, MAX_SPLIT_HANDS, MIN_CHARLIE};
+use rust_blackjack::shuffle::{ShuffleModel, ShuffleRng};
+use rust_blackjack::session_limits::SessionLimits;
+use rust_blackjack::simulation::{SimulationRng, Wonging};
+use rust_blackjack::strategy::{strategy_from_name, STRATEGY_NAMES};
+use rust_blackjack::strategy_file::CUSTOM_STRATEGY;
+use rust_blackjack::strategy_table::{ChartFormat, TABLE_STRATEGY};
+use rust_blackjack::tournament::TournamentRules;
+use rust_blackjack::CardValue;
+
+pub const USAGE: &str = "Usage: rust-blackjack [COMMAND] [OPTIONS]
+
+Commands:
+    play                     Play an interactive game (the default)
+    simulate                 Play many hands automatically and report results
+    analyze                  Estimate the house edge of a rule set under basic
+                             strategy
+    bankroll                 Estimate risk of ruin, N0 and hourly win for a
+                             bankroll and a Hi-Lo bet spread
+    serve                    Host tables that remote players can connect to
+    train                    Practice basic strategy decisions
+    compose                  Build a custom shoe, then play or simulate from it
+    tournament               Play an elimination tournament against bots
+    hotseat                  Play with friends taking turns at one keyboard,
+                             each hand hidden from the others
+    solve <CARD>... --dealer <CARD>
+                             Work out the exact expected value of each play on
+                             a hand
+    generate-tables          Work out the strategy table for a rule set, which
+                             the advisor and table bots read instead of
+                             solving every hand again
+    stats                    Show totals across stored sessions and each
+                             profile's lifetime stats
+    trends                   Chart progress across stored sessions by week
+    connect <host:port>      Join a remote table
+    leaderboard <PATH>       Rank the players in a server's --store by what
+                             they have won
+    replay <PATH>            Step through the hands in a --history file
+    profiles [...]           Manage local profiles: list, create <NAME> [CHIPS],
+                             show <NAME>, transfer <FROM> <TO> <CHIPS>, ledger
+                             and achievements [NAME]
+    help                     Show this message
+
+Options for play, simulate, serve, train, compose, analyze, bankroll, solve,
+tournament, hotseat and generate-tables (these override the config file):
+    --decks <N>              Number of decks in the shoe (1-8)
+    --seed <N>               Seed the shuffle for a reproducible game
+    --rules <PRESET>         vegas-strip, atlantic-city, european or pontoon
+    --dealer-profile <NAME>  standard, training (the hole card is dealt face
+                             up) or careless (the dealer misplays now and then)
+    --empty-shoe <RULE>      When a shoe runs out mid-round: reshuffle (the
+                             discards go back in; the default) or end-hand
+                             (no more cards are drawn and the hand settles)
+    --charlie <N>            A hand of N cards (3 or more) that hasn't busted
+                             wins outright; 0 plays without a Charlie
+    --h17 / --s17            Dealer hits or stands on soft 17
+    --bankroll <N>           Starting bankroll
+    --bet <N>                Chips wagered on each hand
+    --penetration <PERCENT>  How much of the shoe is dealt before the cut card
+                             comes out and it is reshuffled (50-90, default
+                             75); play keeps one shoe across hands
+    --shoe <NAME>            Deal from a fixed practice shoe (ace-rich,
+                             count-extreme, split-heavy) instead of shuffling;
+                             not used by compose
+    --shuffle <MODEL>        uniform (the default) or casino, which mimics a
+                             dealer's riffles, strip cut, plug and cut
+    --shuffle-rng <RNG>      standard (the default) or secure, which shuffles
+                             every shoe with ChaCha20 under a fresh key from
+                             the operating system; not with --seed, nor
+                             for simulate, analyze or bankroll
+    --promo <NAME>           Run a promotion (blackjack-pays-2-1,
+                             blackjack-pays-6-5, suited-678); repeatable
+    --payout-rounding <DIR>  How a blackjack or surrender that doesn't pay out
+                             whole is rounded: down (the default), nearest
+                             or up
+    --payout-step <N>        Round such payouts to a multiple of N, the
+                             smallest chip the table pays; 0 (the default)
+                             rounds to the smallest unit bets are counted in
+
+Options for play, simulate and train:
+    --summary-json[=<PATH>]  On exit, write a JSON summary of the session to
+                             stdout, or to PATH when given
+
+Options for play and simulate:
+    --export <PATH>          Write a record of every hand (cards, actions, the
+                             Hi-Lo count, bet, outcome and bankroll) to PATH,
+                             as CSV or JSON by its .csv or .json extension;
+                             simulate then plays on one thread
+    --stop-win <N>           End the session once it is N chips up
+    --stop-loss <N>          End the session once it is N chips down
+    --max-hands <N>          End the session after N hands; these three
+                             override the config file, 0 turning one off,
+                             and simulate applies them to every session
+                             --bet-strategy plays
+
+Options for play and train:
+    --profile <NAME>         Play as a local profile: its chips are the
+                             bankroll, and the session adds to its lifetime
+                             stats
+
+Options for play:
+    --count                  Show the running count and log deviation spots;
+                             decisions are graded against the Hi-Lo index
+                             plays (Illustrious 18 and Fab 4)
+    --ascii-cards            Draw the cards as ASCII art, the dealer's hole card
+                             face down
+    --dealer-odds            Show the chance of the dealer busting or making
+                             each total, worked out exactly from the cards you
+                             haven't seen
+    --advisor                Show the book play and the expected value of
+                             every legal action before each decision, from
+                             the strategy table for the rules if there is one
+                             and worked out exactly if not; on too when the
+                             config file sets coach to full
+    --speed <SPEED>          How quickly cards are dealt and the hole card
+                             turned over: instant, fast, normal (the default)
+                             or slow; output that isn't a terminal is instant
+    --lang <LANG>            The table's language: en (English, the default) or
+                             es (Spanish); actions can be typed in it too
+    --bots <N>               Seat N computer players (0-6) ahead of you; they
+                             play from the same shoe
+    --seats <N>              Play N hands of your own each round (1-7 with
+                             the bots, default 1), each with its own bet,
+                             one after another in the seats after the bots'
+    --bot-strategy <NAME>    basic (the default); index, which counts the
+                             shoe and plays the Hi-Lo indices; table, which
+                             plays the best value in the strategy table for
+                             the rules, made first if there isn't one;
+                             custom, which plays --strategy-file; or stand
+    --strategy-file <PATH>   Your own strategy, a CSV chart laid out like the
+                             one generate-tables --chart writes; typing hint
+                             shows its play, and so does the coach when the
+                             config file sets coach to hints
+    --history <PATH>         Write a hand history of the session to PATH, as
+                             text or, for a .jsonl path, JSON lines; see
+                             HAND_HISTORY.md. Not with --bots or --seats
+    --max-minutes <N>        End the session before the first bet once N
+                             minutes have gone by; 0 turns off the config's
+    --trace-state            Print what changed in the game state after every
+                             action to stderr, for debugging the rules
+    --tips                   After every win, offer to tip the dealer out of
+                             it; on too when the config file sets tips
+
+Options for simulate:
+    --hands <N>              Number of hands to simulate (default 10000), per
+                             seed when sweeping
+    --seeds <N>              Sweep N seeds in parallel, starting at --seed, and
+                             report the spread of per-seed results
+    --threads <N>            Worker threads (default: one per CPU); results for
+                             a seed are reproducible for the same count
+    --rng <NAME>             Shuffle with std (the default) or fast, a quicker
+                             generator that isn't cryptographic; results for a
+                             seed are reproducible for the same generator
+    --shoe-stats             Deal each shoe down to the cut card and report
+                             the penetration reached, how often each true
+                             count came up and the return at each count
+    --wong-in <COUNT>        Back-count: watch each shoe and only bet once the
+                             true count reaches COUNT, as --shoe-stats does;
+                             --hands counts the hands watched too
+    --wong-out <COUNT>       Leave for a fresh shoe at another table once the
+                             true count falls below COUNT (default: the
+                             --wong-in count)
+    --bet-strategy <NAME>    Compare betting strategies instead: flat,
+                             martingale, kelly (half Kelly on the count),
+                             spread (a 1-8 Hi-Lo ramp), proportional, or all;
+                             repeatable. Reports risk of ruin and the spread of
+                             final bankrolls, with --hands per session
+    --sessions <N>           Sessions per betting strategy (default 200)
+    --rebuy                  Buy a broke session back in to its starting
+                             bankroll rather than end it; the rebuys are
+                             counted and left out of the results
+
+Options for analyze and bankroll:
+    --hands <N>              Hands to play (default 1000000)
+    --threads <N>            Worker threads (default: one per CPU)
+
+Options for bankroll (--bet is the one-unit bet, --bankroll what is at risk):
+    --spread <UNITS>         Bet up to UNITS units as the true count rises,
+                             doubling per point from a count of 2 (default 8;
+                             1 bets flat)
+    --hands-per-hour <N>     Hands played an hour, for the hourly win
+                             (default 100)
+
+Options for solve:
+    --dealer <CARD>          The dealer's upcard; cards are ranks such as A, 7
+                             or T, and the rest of the shoe is --decks full
+                             decks less the cards on the table
+
+Options for generate-tables:
+    --output <PATH>          Write the table to PATH rather than to the cache
+                             under the data directory that play reads
+    --chart <PATH>           Also write the chart to PATH as a crib sheet to
+                             print, a grid each for hard totals, soft totals
+                             and pairs: CSV or Markdown by its .csv or .md
+                             extension
+
+Options for analyze, bankroll and solve:
+    --surrender / --no-surrender
+                             Allow late surrender or not
+    --peek / --no-peek       Whether a dealer with a hole card checks it for
+                             a natural under a ten or an ace
+    --split-hands <N>        Most hands a pair can be split and resplit into
+                             (1-8, default 4; 1 turns splitting off)
+    --das / --no-das         Allow doubling after a split or not
+    --blackjack-pays <N:M>   Payout for a natural, e.g. 3:2 or 6:5
+
+Options for tournament (everyone starts with --bankroll chips, and bets run
+from --bet up to twenty times it):
+    --bots <N>               Computer players at the table (1-6, default 3)
+    --bot-bets <NAME>        How the bots bet, by a --bet-strategy name
+                             (default proportional, a tenth of the stack)
+    --hands-per-round <N>    Hands between eliminations (default 5)
+    --eliminate <N>          Smallest stacks knocked out each round (default 1)
+
+Options for hotseat (everyone starts with --bankroll chips and bets within
+the table limits):
+    --players <N>            People at the table (2-7, default 2)
+
+Options for serve:
+    --address <HOST:PORT>    Address to listen on (default 127.0.0.1:7878)
+    --decision-time <SECS>   Time each decision at the tables; a player who
+                             runs out of it is played for
+    --on-timeout <NAME>      How: stand (the default) or basic, by a
+                             --bot-strategy name
+    --reconnect-grace <SECS> How long a dropped player's seat is held for
+                             them to resume (default 30)
+    --websocket <HOST:PORT>  Also take WebSocket connections to the tables
+                             on HOST:PORT, as ws://HOST:PORT/
+    --http                   Serve the JSON HTTP API instead of tables,
+                             in builds with --features http
+    --store <PATH>           Keep the tables opened from the lobby in PATH,
+                             to open them again after a restart
+
+Options for trends:
+    --weeks <N>              Only chart the last N weeks with sessions
+    --csv <PATH>             Also write the weekly figures to PATH as CSV";
+
+pub const DEFAULT_SIMULATION_HANDS: u64 = 10000;
+
+pub const DEFAULT_ANALYSIS_HANDS: u64 = 1_000_000;
+
+pub const DEFAULT_SPREAD_UNITS: u64 = 8;
+
+pub const DEFAULT_HANDS_PER_HOUR: u64 = 100;
+
+pub const DEFAULT_BANKROLL_SESSIONS: u64 = 200;
+
+// A cut card shallower than half the shoe is unheard of, and one deeper
+// than nine tenths leaves too few cards to finish a round.
+pub const MIN_PENETRATION: u32 = 50;
+pub const MAX_PENETRATION: u32 = 90;
+
+pub const DEFAULT_SERVER_ADDRESS: &str = "127.0.0.1:7878";
+
+// Seconds a table holds the seat of a player whose connection dropped.
+pub const DEFAULT_RECONNECT_GRACE: u64 = 30;
+
+// A table has seven seats, at least one of them yours.
+pub const TABLE_SEATS: usize = 7;
+pub const MAX_TABLE_BOTS: usize = TABLE_SEATS - 1;
+
+pub const DEFAULT_TOURNAMENT_BOTS: usize = 3;
+pub const DEFAULT_BOT_BETS: &str = "proportional";
+pub const DEFAULT_HOTSEAT_PLAYERS: usize = 2;
+pub const MAX_HOTSEAT_PLAYERS: usize = TABLE_SEATS;
+
+// Flags shared by every command that deals cards. Unset values fall back to
+// the config file and then to the built-in defaults.
+#[derive(Debug, Default)]
+pub struct GameOptions {
+    pub decks: Option<u32>,
+    pub seed: Option<u64>,
+    pub rules: Option<RulesPreset>,
+    pub dealer_profile: Option<DealerProfile>,
+    pub empty_shoe: Option<EmptyShoe>,
+    // 0 turns off a Charlie set in the config file.
+    pub charlie: Option<usize>,
+    pub dealer_hits_soft_17: Option<bool>,
+    pub bankroll: Option<u64>,
+    pub bet: Option<u64>,
+    pub shoe: Option<String>,
+    pub shuffle_model: Option<ShuffleModel>,
+    pub shuffle_rng: Option<ShuffleRng>,
+    pub penetration: Option<u32>,
+    pub promotions: Vec<String>,
+    pub profile: Option<String>,
+    // 0 turns off a limit set in the config file.
+    pub stop_win: Option<u64>,
+    pub stop_loss: Option<u64>,
+    pub max_hands: Option<u64>,
+    pub max_minutes: Option<u64>,
+    pub payout_rounding: Option<Rounding>,
+    // In whole units; 0 rounds to the smallest unit.
+    pub payout_step: Option<u64>,
+}
+
+// The options a command actually runs with, once flags have been laid over
+// the config file.
+#[derive(Clone, Debug)]
+pub struct Settings {
+    pub decks: u32,
+    pub seed: Option<u64>,
+    pub rules: RulesPreset,
+    pub dealer_profile: DealerProfile,
+    pub empty_shoe: EmptyShoe,
+    pub charlie: Option<usize>,
+    // Left to the preset when unset.
+    pub dealer_hits_soft_17: Option<bool>,
+    pub bankroll: u64,
+    pub bet: u64,
+    pub table_limits: TableLimits,
+    pub shoe: Option<String>,
+    pub shuffle_model: ShuffleModel,
+    pub shuffle_rng: ShuffleRng,
+    pub penetration: u32,
+    pub promotions: Vec<String>,
+    // The local profile whose chips are played and whose lifetime stats the
+    // session adds to.
+    pub profile: Option<String>,
+    // Set by the shoe editor; takes precedence over `shoe` and `decks`.
+    pub composition: Option<ShoeComposition>,
+    pub session_limits: SessionLimits,
+    pub currency: Currency,
+    // Payouts round to a multiple of `payout_step`, counted in the same unit
+    // as bets, or to that unit itself at 0.
+    pub payout_rounding: Rounding,
+    pub payout_step: u64,
+}
+
+impl GameOptions {
+    // A secure shuffle can't be replayed, so there is nothing to seed.
+    fn check(&self) -> Result<(), CliError> {
+        if self.seed.is_some() && self.shuffle_rng == Option::Some(ShuffleRng::Secure) {
+            return Err(CliError::Conflict("--seed", "--shuffle-rng secure"));
+        }
+        Ok(())
+    }
+
+    pub fn resolve(&self, config: &Config) -> Settings {
+        let mut promotions = config.promotions.clone();
+        promotions.extend(self.promotions.iter().cloned());
+        Settings {
+            decks: self.decks.unwrap_or(config.decks),
+            seed: self.seed,
+            rules: self.rules.clone().unwrap_or_else(|| config.rules_preset.clone()),
+            dealer_profile: self.dealer_profile.clone().unwrap_or_else(|| config.dealer_profile.clone()),
+            empty_shoe: self.empty_shoe.clone().unwrap_or_else(|| config.empty_shoe.clone()),
+            charlie: Option::Some(self.charlie.unwrap_or(config.charlie)).filter(|&cards| cards > 0),
+            dealer_hits_soft_17: self.dealer_hits_soft_17,
+            bankroll: self.bankroll.unwrap_or(config.bankroll),
+            bet: self.bet.unwrap_or(config.bet),
+            table_limits: config.table_limits(),
+            shoe: self.shoe.clone(),
+            shuffle_model: self.shuffle_model.clone().unwrap_or(ShuffleModel::Uniform),
+            shuffle_rng: self.shuffle_rng.clone().unwrap_or(ShuffleRng::Standard),
+            penetration: self.penetration.unwrap_or(DEFAULT_PENETRATION),
+            promotions,
+            profile: self.profile.clone(),
+            composition: Option::None,
+            session_limits: SessionLimits {
+                stop_win: Option::Some(self.stop_win.unwrap_or(config.stop_win)).filter(|&chips| chips > 0),
+                stop_loss: Option::Some(self.stop_loss.unwrap_or(config.stop_loss)).filter(|&chips| chips > 0),
+                max_hands: Option::Some(self.max_hands.unwrap_or(config.max_hands)).filter(|&hands| hands > 0),
+                max_time: Option::Some(self.max_minutes.unwrap_or(config.max_minutes))
+                    .filter(|&minutes| minutes > 0)
+                    .map(|minutes| Duration::from_secs(minutes * 60)),
+            },
+            currency: config.currency.clone(),
+            payout_rounding: self.payout_rounding.clone().unwrap_or_else(|| config.payout_rounding.clone()),
+            payout_step: self.payout_step.unwrap_or(config.payout_step),
+        }
+    }
+}
+
+impl Settings {
+    // The same settings with every amount, given in whole units, counted in
+    // the currency's smallest unit instead, as play keeps its books.
+    pub fn in_smallest_unit(&self) -> Settings {
+        let unit = self.currency.unit();
+        let limits = &self.session_limits;
+        Settings {
+            bankroll: self.bankroll * unit,
+            bet: self.bet * unit,
+            table_limits: TableLimits { minimum: self.table_limits.minimum * unit, maximum: self.table_limits.maximum * unit },
+            session_limits: SessionLimits {
+                stop_win: limits.stop_win.map(|chips| chips * unit),
+                stop_loss: limits.stop_loss.map(|chips| chips * unit),
+                ..limits.clone()
+            },
+            payout_step: self.payout_step * unit,
+            ..self.clone()
+        }
+    }
+
+    pub fn game_rules(&self) -> GameRules {
+        let preset = self.rules.rules(self.decks);
+        GameRules {
+            dealer_hits_soft_17: self.dealer_hits_soft_17.unwrap_or(preset.dealer_hits_soft_17),
+            dealer: self.dealer_profile.clone(),
+            shuffle_model: self.shuffle_model.clone(),
+            shuffle_rng: self.shuffle_rng.clone(),
+            penetration: self.penetration,
+            empty_shoe: self.empty_shoe.clone(),
+            charlie: self.charlie,
+            payout_rounding: PayoutRounding { rounding: self.payout_rounding.clone(), step: self.payout_step.max(1) },
+            ..preset
+        }
+    }
+}
+
+// Tweaks laid over the chosen preset's rules before analyzing them.
+#[derive(Debug, Default)]
+pub struct RuleOverrides {
+    pub surrender: Option<bool>,
+    pub peek: Option<bool>,
+    pub max_split_hands: Option<usize>,
+    pub double_after_split: Option<bool>,
+    pub blackjack_pays: Option<(u64, u64)>,
+}
+
+impl RuleOverrides {
+    pub fn apply(&self, mut rules: GameRules) -> GameRules {
+        if let Option::Some(surrender) = self.surrender {
+            rules.surrender = surrender;
+        }
+        if let Option::Some(peek) = self.peek {
+            rules.peek = peek;
+        }
+        if let Option::Some(hands) = self.max_split_hands {
+            rules.max_split_hands = hands;
+        }
+        if let Option::Some(double_after_split) = self.double_after_split {
+            rules.double_after_split = double_after_split;
+        }
+        if let Option::Some((numerator, denominator)) = self.blackjack_pays {
+            rules.blackjack_numerator = numerator;
+            rules.blackjack_denominator = denominator;
+        }
+        rules
+    }
+}
+
+#[derive(Debug)]
+pub enum SummaryTarget {
+    Stdout,
+    File(PathBuf),
+}
+
+#[derive(Debug)]
+pub enum Command {
+    Play {
+        options: GameOptions,
+        count: bool,
+        // Draw cards as ASCII art rather than listing them.
+        ascii_cards: bool,
+        // Overrides the config file's speed.
+        speed: Option<Speed>,
+        language: Option<Language>,
+        bots: usize,
+        bot_strategy: String,
+        // Hands the player plays each round, in seats of their own.
+        seats: usize,
+        summary: Option<SummaryTarget>,
+        export: Option<PathBuf>,
+        history: Option<PathBuf>,
+        trace_state: bool,
+        dealer_odds: bool,
+        advisor: bool,
+        strategy_file: Option<PathBuf>,
+        tips: bool,
+    },
+    Simulate {
+        options: GameOptions,
+        hands: u64,
+        seeds: Option<u64>,
+        threads: Option<usize>,
+        // Compared over `sessions` sessions of `hands` hands each when set.
+        bet_strategies: Vec<String>,
+        sessions: u64,
+        // Compared sessions buy back in on going broke.
+        rebuy: bool,
+        shoe_stats: bool,
+        wonging: Option<Wonging>,
+        summary: Option<SummaryTarget>,
+        export: Option<PathBuf>,
+        rng: SimulationRng,
+    },
+    Serve {
+        options: GameOptions,
+        address: String,
+        // Where browsers and other WebSocket clients connect, if anywhere.
+        websocket: Option<String>,
+        decision_time: Option<u64>,
+        on_timeout: String,
+        reconnect_grace: u64,
+        http: bool,
+        // Where the tables opened from the lobby are kept across restarts.
+        store: Option<PathBuf>,
+    },
+    Train { options: GameOptions, summary: Option<SummaryTarget> },
+    Compose { options: GameOptions },
+    Analyze { options: GameOptions, hands: u64, threads: Option<usize>, overrides: RuleOverrides },
+    Bankroll {
+        options: GameOptions,
+        hands: u64,
+        threads: Option<usize>,
+        overrides: RuleOverrides,
+        spread: u64,
+        hands_per_hour: u64,
+    },
+    Solve { options: GameOptions, hand: Vec<CardValue>, dealer: CardValue, overrides: RuleOverrides },
+    Tournament { options: GameOptions, bots: usize, bot_bets: String, hands_per_round: u32, eliminated_per_round: usize },
+    Hotseat { options: GameOptions, players: usize },
+    GenerateTables { options: GameOptions, output: Option<PathBuf>, chart: Option<PathBuf> },
+    Stats,
+    Trends { weeks: Option<usize>, csv: Option<PathBuf> },
+    Connect { address: String },
+    Leaderboard { store: PathBuf },
+    Replay { history: PathBuf },
+    Profiles { args: Vec<String> },
+    Help,
+}
+
+#[derive(Debug)]
+pub enum CliError {
+    UnknownCommand(String),
+    UnknownFlag(String),
+    MissingValue(String),
+    InvalidValue { flag: String, value: String },
+    MissingArgument(&'static str),
+    Conflict(&'static str, &'static str),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::UnknownCommand(command) => write!(f, "unknown command {:?}", command),
+            CliError::UnknownFlag(flag) => write!(f, "unknown option {:?}", flag),
+            CliError::MissingValue(flag) => write!(f, "{} needs a value", flag),
+            CliError::InvalidValue { flag, value } => write!(f, "{:?} is not a valid value for {}", value, flag),
+            CliError::MissingArgument(argument) => write!(f, "missing {}", argument),
+            CliError::Conflict(first, second) => write!(f, "{} cannot be used with {}", first, second),
+        }
+    }
+}
+
+struct Flags<'a> {
+    args: &'a [String],
+    position: usize,
+}
+
+impl<'a> Flags<'a> {
+    fn next_flag(&mut self) -> Option<&'a str> {
+        let flag = self.args.get(self.position)?;
+        self.position += 1;
+        Option::Some(flag)
+    }
+
+    fn value(&mut self, flag: &str) -> Result<&'a str, CliError> {
+        let value = self.args.get(self.position).ok_or_else(|| CliError::MissingValue(flag.to_string()))?;
+        self.position += 1;
+        Ok(value)
+    }
+
+    fn parsed<T, F>(&mut self, flag: &str, parse: F) -> Result<T, CliError>
+    where
+        F: Fn(&str) -> Option<T>,
+    {
+        let value = self.value(flag)?;
+        parse(value).ok_or_else(|| CliError::InvalidValue { flag: flag.to_string(), value: value.to_string() })
+    }
+
+    // Returns false when the flag isn't one of the shared game options.
+    fn game_option(&mut self, flag: &str, options: &mut GameOptions) -> Result<bool, CliError> {
+        match flag {
+            "--decks" => {
+                let decks = self.parsed(flag, |v| v.parse().ok().filter(|&n| n > 0 && n <= MAX_DECKS))?;
+                options.decks = Option::Some(decks)
+            },
+            "--seed" => options.seed = Option::Some(self.parsed(flag, |v| v.parse().ok())?),
+            "--rules" => options.rules = Option::Some(self.parsed(flag, RulesPreset::from_name)?),
+            "--dealer-profile" => options.dealer_profile = Option::Some(self.parsed(flag, DealerProfile::from_name)?),
+            "--empty-shoe" => options.empty_shoe = Option::Some(self.parsed(flag, EmptyShoe::from_name)?),
+            "--charlie" => {
+                let cards = self.parsed(flag, |v| v.parse().ok().filter(|&n| n == 0 || n >= MIN_CHARLIE))?;
+                options.charlie = Option::Some(cards)
+            },
+            "--h17" => options.dealer_hits_soft_17 = Option::Some(true),
+            "--s17" => options.dealer_hits_soft_17 = Option::Some(false),
+            "--bankroll" => options.bankroll = Option::Some(self.parsed(flag, |v| v.parse().ok())?),
+            "--bet" => options.bet = Option::Some(self.parsed(flag, |v| v.parse().ok().filter(|&n| n > 0))?),
+            "--shuffle" => options.shuffle_model = Option::Some(self.parsed(flag, ShuffleModel::from_name)?),
+            "--shuffle-rng" => options.shuffle_rng = Option::Some(self.parsed(flag, ShuffleRng::from_name)?),
+            "--penetration" => {
+                let percent = self.parsed(flag, |v| v.parse().ok().filter(|n| (MIN_PENETRATION..=MAX_PENETRATION).contains(n)))?;
+                options.penetration = Option::Some(percent)
+            },
+            "--shoe" => options.shoe = Option::Some(self.parsed(flag, |v| fixture(v).map(|_| v.to_string()))?),
+            "--payout-rounding" => options.payout_rounding = Option::Some(self.parsed(flag, Rounding::from_name)?),
+            "--payout-step" => options.payout_step = Option::Some(self.parsed(flag, |v| v.parse().ok())?),
+            "--promo" => {
+                let name = self.parsed(flag, |v| promotion_from_name(v).map(|_| v.to_string()))?;
+                options.promotions.push(name)
+            },
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    // Returns false when the flag doesn't tweak the rules.
+    fn rule_override(&mut self, flag: &str, overrides: &mut RuleOverrides) -> Result<bool, CliError> {
+        match flag {
+            "--surrender" => overrides.surrender = Option::Some(true),
+            "--no-surrender" => overrides.surrender = Option::Some(false),
+            "--peek" => overrides.peek = Option::Some(true),
+            "--no-peek" => overrides.peek = Option::Some(false),
+            "--das" => overrides.double_after_split = Option::Some(true),
+            "--no-das" => overrides.double_after_split = Option::Some(false),
+            "--split-hands" => {
+                let hands = self.parsed(flag, |v| v.parse().ok().filter(|n| (1..=MAX_SPLIT_HANDS).contains(n)))?;
+                overrides.max_split_hands = Option::Some(hands)
+            },
+            "--blackjack-pays" => overrides.blackjack_pays = Option::Some(self.parsed(flag, GameRules::parse_payout)?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+// `args` excludes the program name.
+pub fn parse(args: &[String]) -> Result<Command, CliError> {
+    let (command, rest) = match args.first().map(String::as_str) {
+        Option::Some(first) if !first.starts_with("--") => (first, &args[1..]),
+        _ => ("play", args),
+    };
+
+    match command {
+        "connect" => rest
+            .first()
+            .map(|address| Command::Connect { address: address.clone() })
+            .ok_or(CliError::MissingArgument("<host:port>")),
+        "leaderboard" => rest
+            .first()
+            .map(|store| Command::Leaderboard { store: PathBuf::from(store) })
+            .ok_or(CliError::MissingArgument("<PATH>")),
+        "profiles" => Ok(Command::Profiles { args: rest.to_vec() }),
+        "replay" => rest
+            .first()
+            .map(|history| Command::Replay { history: PathBuf::from(history) })
+            .ok_or(CliError::MissingArgument("<PATH>")),
+        "stats" => Ok(Command::Stats),
+        "trends" => {
+            let mut flags = Flags { args: rest, position: 0 };
+            let mut weeks = Option::None;
+            let mut csv = Option::None;
+            while let Option::Some(flag) = flags.next_flag() {
+                match flag {
+                    "--weeks" => weeks = Option::Some(flags.parsed(flag, |v| v.parse().ok().filter(|&n| n > 0))?),
+                    "--csv" => csv = Option::Some(PathBuf::from(flags.value(flag)?)),
+                    "--help" => return Ok(Command::Help),
+                    _ => return Err(CliError::UnknownFlag(flag.to_string())),
+                }
+            }
+            Ok(Command::Trends { weeks, csv })
+        },
+        "help" => Ok(Command::Help),
+        "solve" => {
+            let mut flags = Flags { args: rest, position: 0 };
+            let mut options = GameOptions::default();
+            let mut overrides = RuleOverrides::default();
+            let mut hand = Vec::new();
+            let mut dealer = Option::None;
+            while let Option::Some(flag) = flags.next_flag() {
+                if flag == "--help" {
+                    return Ok(Command::Help);
+                }
+                if flags.game_option(flag, &mut options)? || flags.rule_override(flag, &mut overrides)? {
+                    continue;
+                }
+                match flag {
+                    "--dealer" => dealer = Option::Some(flags.parsed(flag, |v| parse_rank(v).ok())?),
+                    card if !card.starts_with("--") => {
+                        let value = parse_rank(card)
+                            .map_err(|_| CliError::InvalidValue { flag: "<CARD>".to_string(), value: card.to_string() })?;
+                        hand.push(value)
+                    },
+                    _ => return Err(CliError::UnknownFlag(flag.to_string())),
+                }
+            }
+            if hand.len() < 2 {
+                return Err(CliError::MissingArgument("at least two player cards"));
+            }
+            let dealer = dealer.ok_or(CliError::MissingArgument("--dealer <CARD>"))?;
+            Ok(Command::Solve { options, hand, dealer, overrides })
+        },
+        "tournament" => {
+            let mut flags = Flags { args: rest, position: 0 };
+            let mut options = GameOptions::default();
+            let defaults = TournamentRules::default();
+            let mut bots = DEFAULT_TOURNAMENT_BOTS;
+            let mut bot_bets = DEFAULT_BOT_BETS.to_string();
+            let mut hands_per_round = defaults.hands_per_round;
+            let mut eliminated_per_round = defaults.eliminated_per_round;
+            while let Option::Some(flag) = flags.next_flag() {
+                if flag == "--help" {
+                    return Ok(Command::Help);
+                }
+                if flags.game_option(flag, &mut options)? {
+                    continue;
+                }
+                match flag {
+                    "--bots" => bots = flags.parsed(flag, |v| v.parse().ok().filter(|&n| n > 0 && n <= MAX_TABLE_BOTS))?,
+                    "--bot-bets" => {
+                        bot_bets = flags.parsed(flag, |v| bet_strategy_from_name(v).map(|_| v.to_string()))?
+                    },
+                    "--hands-per-round" => hands_per_round = flags.parsed(flag, |v| v.parse().ok().filter(|&n| n > 0))?,
+                    "--eliminate" => eliminated_per_round = flags.parsed(flag, |v| v.parse().ok().filter(|&n| n > 0))?,
+                    _ => return Err(CliError::UnknownFlag(flag.to_string())),
+                }
+            }
+            options.check()?;
+            Ok(Command::Tournament { options, bots, bot_bets, hands_per_round, eliminated_per_round })
+        },
+        "hotseat" => {
+            let mut flags = Flags { args: rest, position: 0 };
+            let mut options = GameOptions::default();
+            let mut players = DEFAULT_HOTSEAT_PLAYERS;
+            while let Option::Some(flag) = flags.next_flag() {
+                if flag == "--help" {
+                    return Ok(Command::Help);
+                }
+                if flags.game_option(flag, &mut options)? {
+                    continue;
+                }
+                match flag {
+                    "--players" => {
+                        players = flags.parsed(flag, |v| v.parse().ok().filter(|n| (2..=MAX_HOTSEAT_PLAYERS).contains(n)))?
+                    },
+                    _ => return Err(CliError::UnknownFlag(flag.to_string())),
+                }
+            }
+            options.check()?;
+            Ok(Command::Hotseat { options, players })
+        },
+        "generate-tables" => {
+            let mut flags = Flags { args: rest, position: 0 };
+            let mut options = GameOptions::default();
+            let mut output = Option::None;
+            let mut chart = Option::None;
+            while let Option::Some(flag) = flags.next_flag() {
+                if flag == "--help" {
+                    return Ok(Command::Help);
+                }
+                if flags.game_option(flag, &mut options)? {
+                    continue;
+                }
+                match flag {
+                    "--output" => output = Option::Some(PathBuf::from(flags.value(flag)?)),
+                    "--chart" => {
+                        chart = Option::Some(flags.parsed(flag, |v| {
+                            let path = PathBuf::from(v);
+                            ChartFormat::from_path(&path).map(|_| path)
+                        })?)
+                    },
+                    _ => return Err(CliError::UnknownFlag(flag.to_string())),
+                }
+            }
+            Ok(Command::GenerateTables { options, output, chart })
+        },
+        "play" | "simulate" | "serve" | "train" | "compose" | "analyze" | "bankroll" => {
+            let mut flags = Flags { args: rest, position: 0 };
+            let mut options = GameOptions::default();
+            let mut count = false;
+            let mut ascii_cards = false;
+            let mut speed = Option::None;
+            let mut language = Option::None;
+            let mut bots = 0;
+            let mut seats = 1;
+            let mut bot_strategy = STRATEGY_NAMES[0].to_string();
+            let analysis = matches!(command, "analyze" | "bankroll");
+            let mut hands = if analysis { DEFAULT_ANALYSIS_HANDS } else { DEFAULT_SIMULATION_HANDS };
+            let mut threads = Option::None;
+            let mut overrides = RuleOverrides::default();
+            let mut seeds = Option::None;
+            let mut bet_strategies = Vec::new();
+            let mut sessions = DEFAULT_BANKROLL_SESSIONS;
+            let mut rebuy = false;
+            let mut shoe_stats = false;
+            let mut wong_in = Option::None;
+            let mut wong_out = Option::None;
+            let mut spread = DEFAULT_SPREAD_UNITS;
+            let mut hands_per_hour = DEFAULT_HANDS_PER_HOUR;
+            let mut address = DEFAULT_SERVER_ADDRESS.to_string();
+            let mut decision_time = Option::None;
+            let mut on_timeout = "stand".to_string();
+            let mut reconnect_grace = DEFAULT_RECONNECT_GRACE;
+            let mut http = false;
+            let mut websocket = Option::None;
+            let mut store = Option::None;
+            let mut summary = Option::None;
+            let mut export = Option::None;
+            let mut history = Option::None;
+            let mut trace_state = false;
+            let mut dealer_odds = false;
+            let mut advisor = false;
+            let mut strategy_file = Option::None;
+            let mut tips = false;
+            let mut rng = SimulationRng::Std;
+            while let Option::Some(flag) = flags.next_flag() {
+                if flag == "--help" {
+                    return Ok(Command::Help);
+                }
+                if flags.game_option(flag, &mut options)? {
+                    continue;
+                }
+                if analysis && flags.rule_override(flag, &mut overrides)? {
+                    continue;
+                }
+                if command != "serve" && command != "compose" && !analysis {
+                    if flag == "--summary-json" {
+                        summary = Option::Some(SummaryTarget::Stdout);
+                        continue;
+                    }
+                    if let Option::Some(path) = flag.strip_prefix("--summary-json=") {
+                        summary = Option::Some(SummaryTarget::File(PathBuf::from(path)));
+                        continue;
+                    }
+                }
+                match (command, flag) {
+                    ("play", "--count") => count = true,
+                    ("play", "--ascii-cards") => ascii_cards = true,
+                    ("play", "--speed") => speed = Option::Some(flags.parsed(flag, Speed::from_name)?),
+                    ("play", "--lang") => language = Option::Some(flags.parsed(flag, Language::from_name)?),
+                    ("play" | "simulate", "--export") => {
+                        export = Option::Some(flags.parsed(flag, |v| {
+                            let path = PathBuf::from(v);
+                            ExportFormat::from_path(&path).map(|_| path)
+                        })?)
+                    },
+                    ("play", "--history") => history = Option::Some(PathBuf::from(flags.value(flag)?)),
+                    ("play", "--trace-state") => trace_state = true,
+                    ("play", "--dealer-odds") => dealer_odds = true,
+                    ("play", "--advisor") => advisor = true,
+                    ("play", "--strategy-file") => strategy_file = Option::Some(PathBuf::from(flags.value(flag)?)),
+                    ("play", "--tips") => tips = true,
+                    ("play" | "simulate", "--stop-win") => options.stop_win = Option::Some(flags.parsed(flag, |v| v.parse().ok())?),
+                    ("play" | "simulate", "--stop-loss") => options.stop_loss = Option::Some(flags.parsed(flag, |v| v.parse().ok())?),
+                    ("play" | "simulate", "--max-hands") => options.max_hands = Option::Some(flags.parsed(flag, |v| v.parse().ok())?),
+                    ("play", "--max-minutes") => options.max_minutes = Option::Some(flags.parsed(flag, |v| v.parse().ok())?),
+                    ("play" | "train", "--profile") => options.profile = Option::Some(flags.value(flag)?.to_string()),
+                    ("play", "--bot-strategy") => {
+                        bot_strategy = flags.parsed(flag, |v| {
+                            Option::Some(v.to_string()).filter(|v| v == TABLE_STRATEGY || v == CUSTOM_STRATEGY || strategy_from_name(v).is_some())
+                        })?
+                    },
+                    ("play", "--bots") => bots = flags.parsed(flag, |v| v.parse().ok().filter(|&n| n <= MAX_TABLE_BOTS))?,
+                    ("play", "--seats") => seats = flags.parsed(flag, |v| v.parse().ok().filter(|&n| n > 0 && n <= TABLE_SEATS))?,
+                    ("simulate", "--hands") => hands = flags.parsed(flag, |v| v.parse().ok())?,
+                    ("simulate", "--bet-strategy") => match flags.value(flag)? {
+                        "all" => bet_strategies.extend(BET_STRATEGY_NAMES.iter().map(|name| name.to_string())),
+                        name if bet_strategy_from_name(name).is_some() => bet_strategies.push(name.to_string()),
+                        name => return Err(CliError::InvalidValue { flag: flag.to_string(), value: name.to_string() }),
+                    },
+                    ("simulate", "--sessions") => sessions = flags.parsed(flag, |v| v.parse().ok().filter(|&n| n > 0))?,
+                    ("simulate", "--rebuy") => rebuy = true,
+                    ("simulate", "--shoe-stats") => shoe_stats = true,
+                    ("simulate", "--wong-in") => {
+                        wong_in = Option::Some(flags.parsed(flag, |v| v.parse().ok().filter(|c: &f64| c.is_finite()))?)
+                    },
+                    ("simulate", "--wong-out") => {
+                        wong_out = Option::Some(flags.parsed(flag, |v| v.parse().ok().filter(|c: &f64| c.is_finite()))?)
+                    },
+                    ("simulate", "--rng") => rng = flags.parsed(flag, SimulationRng::from_name)?,
+                    ("simulate", "--seeds") => seeds = Option::Some(flags.parsed(flag, |v| v.parse().ok().filter(|&n| n > 0))?),
+                    ("serve", "--address") => address = flags.value(flag)?.to_string(),
+                    ("serve", "--decision-time") => {
+                        decision_time = Option::Some(flags.parsed(flag, |v| v.parse().ok().filter(|&n| n > 0))?)
+                    },
+                    ("serve", "--http") => http = true,
+                    ("serve", "--websocket") => websocket = Option::Some(flags.value(flag)?.to_string()),
+                    ("serve", "--store") => store = Option::Some(PathBuf::from(flags.value(flag)?)),
+                    ("serve", "--reconnect-grace") => reconnect_grace = flags.parsed(flag, |v| v.parse().ok())?,
+                    ("serve", "--on-timeout") => {
+                        on_timeout = flags.parsed(flag, |v| strategy_from_name(v).map(|_| v.to_string()))?
+                    },
+                    ("analyze" | "bankroll", "--hands") => hands = flags.parsed(flag, |v| v.parse().ok().filter(|&n| n > 0))?,
+                    ("bankroll", "--spread") => spread = flags.parsed(flag, |v| v.parse().ok().filter(|&n| n > 0))?,
+                    ("bankroll", "--hands-per-hour") => {
+                        hands_per_hour = flags.parsed(flag, |v| v.parse().ok().filter(|&n| n > 0))?
+                    },
+                    ("simulate" | "analyze" | "bankroll", "--threads") => threads = Option::Some(flags.parsed(flag, |v| v.parse().ok().filter(|&n| n > 0))?),
+                    _ => return Err(CliError::UnknownFlag(flag.to_string())),
+                }
+            }
+            options.check()?;
+            if (command == "simulate" || analysis) && options.shuffle_rng == Option::Some(ShuffleRng::Secure) {
+                return Err(CliError::Conflict("--shuffle-rng secure", "simulations"));
+            }
+            if rebuy && bet_strategies.is_empty() {
+                return Err(CliError::MissingArgument("--bet-strategy <NAME> for --rebuy"));
+            }
+            if seeds.is_some() && !bet_strategies.is_empty() {
+                return Err(CliError::Conflict("--seeds", "--bet-strategy"));
+            }
+            if history.is_some() && bots > 0 {
+                return Err(CliError::Conflict("--history", "--bots"));
+            }
+            if history.is_some() && seats > 1 {
+                return Err(CliError::Conflict("--history", "--seats"));
+            }
+            if bots + seats > TABLE_SEATS {
+                return Err(CliError::InvalidValue { flag: "--seats".to_string(), value: seats.to_string() });
+            }
+            if export.is_some() && seeds.is_some() {
+                return Err(CliError::Conflict("--export", "--seeds"));
+            }
+            let wonging = match (wong_in, wong_out) {
+                (Option::Some(enter_at), leave_below) => {
+                    let leave_below = leave_below.unwrap_or(enter_at);
+                    if leave_below > enter_at {
+                        return Err(CliError::InvalidValue { flag: "--wong-out".to_string(), value: leave_below.to_string() });
+                    }
+                    Option::Some(Wonging { enter_at, leave_below })
+                },
+                (Option::None, Option::Some(_)) => return Err(CliError::MissingArgument("--wong-in <COUNT> for --wong-out")),
+                (Option::None, Option::None) => Option::None,
+            };
+            if shoe_stats || wonging.is_some() {
+                let counted = if wonging.is_some() { "--wong-in" } else { "--shoe-stats" };
+                if seeds.is_some() {
+                    return Err(CliError::Conflict(counted, "--seeds"));
+                }
+                if !bet_strategies.is_empty() {
+                    return Err(CliError::Conflict(counted, "--bet-strategy"));
+                }
+                if export.is_some() {
+                    return Err(CliError::Conflict(counted, "--export"));
+                }
+            }
+            if bot_strategy == CUSTOM_STRATEGY && strategy_file.is_none() {
+                return Err(CliError::MissingArgument("--strategy-file <PATH>"));
+            }
+            if http && store.is_some() {
+                return Err(CliError::Conflict("--store", "--http"));
+            }
+            if http && websocket.is_some() {
+                return Err(CliError::Conflict("--websocket", "--http"));
+            }
+            if export.is_some() && !bet_strategies.is_empty() {
+                return Err(CliError::Conflict("--export", "--bet-strategy"));
+            }
+            Ok(match command {
+                "play" => Command::Play {
+                    options,
+                    count,
+                    ascii_cards,
+                    speed,
+                    language,
+                    bots,
+                    bot_strategy,
+                    seats,
+                    summary,
+                    export,
+                    history,
+                    trace_state,
+                    dealer_odds,
+                    advisor,
+                    strategy_file,
+                    tips,
+                },
+                "simulate" => {
+                    Command::Simulate {
+                    options,
+                    hands,
+                    seeds,
+                    threads,
+                    bet_strategies,
+                    sessions,
+                    rebuy,
+                    shoe_stats,
+                    wonging,
+                    summary,
+                    export,
+                    rng,
+                }
+                },
+                "serve" => {
+                    Command::Serve { options, address, websocket, decision_time, on_timeout, reconnect_grace, http, store }
+                },
+                "compose" => Command::Compose { options },
+                "analyze" => Command::Analyze { options, hands, threads, overrides },
+                "bankroll" => Command::Bankroll { options, hands, threads, overrides, spread, hands_per_hour },
+                _ => Command::Train { options, summary },
+            })
+        },
+        other => Err(CliError::UnknownCommand(other.to_string())),
+    }
+}
@@ -0,0 +1,147 @@
+use std::io::{self, BufRead, BufReader, Lines, Write};
+use std::net::TcpStream;
+
+use rust_blackjack::fairness;
+use rust_blackjack::json::JsonValue;
+use rust_blackjack::{action_menu, Action};
+use rust_blackjack::protocol::{ClientMessage, ServerMessage};
+
+use crate::print_hand;
+use crate::store::render_standings;
+use crate::websocket::WebSocket;
+
+// Either transport yields `Ok(None)` once the server goes away. Frames that
+// don't decode are surfaced as `Some(Err(raw))` so they can be reported.
+trait Transport {
+    fn receive(&mut self) -> io::Result<Option<Result<ServerMessage, String>>>;
+
+    fn send(&mut self, message: &ClientMessage) -> io::Result<()>;
+}
+
+struct TcpTransport {
+    lines: Lines<BufReader<TcpStream>>,
+    writer: TcpStream,
+}
+
+impl Transport for TcpTransport {
+    fn receive(&mut self) -> io::Result<Option<Result<ServerMessage, String>>> {
+        match self.lines.next() {
+            Option::Some(line) => {
+                let line = line?;
+                Ok(Option::Some(ServerMessage::parse(&line).ok_or(line)))
+            },
+            Option::None => Ok(Option::None),
+        }
+    }
+
+    fn send(&mut self, message: &ClientMessage) -> io::Result<()> {
+        writeln!(self.writer, "{}", message.to_line())
+    }
+}
+
+struct WebSocketTransport {
+    socket: WebSocket,
+}
+
+impl Transport for WebSocketTransport {
+    fn receive(&mut self) -> io::Result<Option<Result<ServerMessage, String>>> {
+        match self.socket.read_text()? {
+            Option::Some(text) => {
+                let message = JsonValue::parse(&text).and_then(|value| ServerMessage::from_json(&value));
+                Ok(Option::Some(message.ok_or(text)))
+            },
+            Option::None => Ok(Option::None),
+        }
+    }
+
+    fn send(&mut self, message: &ClientMessage) -> io::Result<()> {
+        self.socket.send_text(&message.to_json().to_string())
+    }
+}
+
+fn open_transport(address: &str) -> io::Result<Box<dyn Transport>> {
+    if address.starts_with("ws://") {
+        Ok(Box::new(WebSocketTransport { socket: WebSocket::connect(address)? }))
+    } else {
+        let stream = TcpStream::connect(address)?;
+        let writer = stream.try_clone()?;
+        Ok(Box::new(TcpTransport { lines: BufReader::new(stream).lines(), writer }))
+    }
+}
+
+pub fn run(address: &str) -> io::Result<()> {
+    let mut transport = open_transport(address)?;
+
+    println!("Connected to table at {}", address);
+
+    let stdin = io::stdin();
+    let mut stdin_lines = stdin.lock().lines();
+    // What the server committed the shoe in play to.
+    let mut commitment = Option::None;
+
+    while let Option::Some(message) = transport.receive()? {
+        match message {
+            Ok(ServerMessage::Hand(hand)) => print_hand(&hand),
+            Ok(ServerMessage::Message(text)) => println!("{}", text),
+            Ok(ServerMessage::Prompt) => {
+                println!("Please input what you'd like to do, say something, or leave: {}", action_menu());
+                let message = loop {
+                    let raw_action = match stdin_lines.next() {
+                        Option::Some(raw_action) => raw_action?,
+                        Option::None => return Ok(()),
+                    };
+                    if raw_action.trim() == "leave" {
+                        break ClientMessage::Leave;
+                    }
+                    // Chat doesn't answer the prompt, so the action is
+                    // still to come.
+                    if let Option::Some(ClientMessage::Chat(text)) = ClientMessage::parse(&raw_action) {
+                        transport.send(&ClientMessage::Chat(text))?;
+                        continue;
+                    }
+                    match raw_action.parse::<Action>() {
+                        Ok(action) => break ClientMessage::Action(action),
+                        Err(error) => println!("{}", error),
+                    }
+                };
+                transport.send(&message)?;
+            },
+            Ok(ServerMessage::Outcome(text)) => println!("{}", text),
+            Ok(ServerMessage::Lobby) => {
+                println!("Lobby: tables, create <name> [<rules> [<decks>]], join <name>, name <who>, leaderboard or leave");
+                let message = loop {
+                    let raw = match stdin_lines.next() {
+                        Option::Some(raw) => raw?,
+                        Option::None => return Ok(()),
+                    };
+                    match ClientMessage::parse(&raw) {
+                        Option::Some(message) => break message,
+                        Option::None => println!("Unknown lobby command: {:?}", raw.trim()),
+                    }
+                };
+                transport.send(&message)?;
+            },
+            Ok(ServerMessage::Table(name)) => println!("Seated at the {} table", name),
+            Ok(ServerMessage::Commit(hash)) => {
+                println!("The server has committed to a new shoe: {}", hash);
+                commitment = Option::Some(hash);
+            },
+            Ok(ServerMessage::Reveal(reveal)) => match commitment.take() {
+                Option::Some(hash) => match fairness::verify(&hash, &reveal) {
+                    Ok(cards) => println!("The {} card shoe matches what the server committed to", cards.len()),
+                    Err(error) => println!("Warning: the server changed the shoe: {}", error),
+                },
+                Option::None => println!("The server revealed a shoe it never committed to"),
+            },
+            Ok(ServerMessage::Session(token)) => {
+                println!("If the connection drops, reconnect and type `resume {}` in the lobby to get your seat back", token)
+            },
+            Ok(ServerMessage::Chat { from, text }) => println!("<{}> {}", from, text),
+            Ok(ServerMessage::Leaderboard(standings)) => println!("{}", render_standings(&standings)),
+            Err(raw) => println!("Unrecognized message from server: {:?}", raw),
+        }
+    }
+
+    println!("The server closed the connection");
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+
+use crate::protocol::decode_card_value;
+use crate::shuffle::{ReshuffleKey, Shuffler};
+use crate::{Card, CardSuit, CardValue, Deck};
+
+// A shoe built by hand for studying composition effects: start from whole
+// decks, take ranks out or put extra ones in, and choose how deep the shoe
+// is dealt before the cut card comes out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShoeComposition {
+    cards: Vec<Card>,
+    penetration: f64,
+}
+
+#[derive(Debug)]
+pub enum CompositionError {
+    UnknownRank(String),
+    InvalidPenetration(f64),
+}
+
+impl fmt::Display for CompositionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompositionError::UnknownRank(raw) => write!(f, "unknown rank {:?}, use 2-9, T (or 10), J, Q, K or A", raw),
+            CompositionError::InvalidPenetration(percent) => {
+                write!(f, "penetration must be between 1 and 100 percent, not {}", percent)
+            },
+        }
+    }
+}
+
+// Ranks as they appear in card codes, plus "10" for the ten.
+pub fn parse_rank(raw: &str) -> Result<CardValue, CompositionError> {
+    let upper = raw.trim().to_uppercase();
+    let mut chars = upper.chars();
+    let value = match (upper.as_str(), chars.next(), chars.next()) {
+        ("10", _, _) => Option::Some(CardValue::Ten),
+        (_, Option::Some(c), Option::None) => decode_card_value(c),
+        _ => Option::None,
+    };
+    value.ok_or_else(|| CompositionError::UnknownRank(raw.to_string()))
+}
+
+impl ShoeComposition {
+    pub fn new(decks: u32) -> ShoeComposition {
+        let mut deck = Deck::with_decks(decks);
+        let mut cards = Vec::new();
+        while let Option::Some(card) = deck.draw_card() {
+            cards.push(card);
+        }
+        ShoeComposition { cards, penetration: 1.0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    pub fn count(&self, value: &CardValue) -> usize {
+        self.cards.iter().filter(|card| &card.value == value).count()
+    }
+
+    pub fn penetration(&self) -> f64 {
+        self.penetration
+    }
+
+    // Removes up to `count` cards of the rank, or all of them for None.
+    // Returns how many were actually taken out.
+    pub fn remove(&mut self, value: &CardValue, count: Option<usize>) -> usize {
+        let limit = count.unwrap_or(usize::MAX);
+        let mut removed = 0;
+        while removed < limit {
+            match self.cards.iter().rposition(|card| &card.value == value) {
+                Option::Some(position) => {
+                    self.cards.remove(position);
+                    removed += 1;
+                },
+                Option::None => break,
+            }
+        }
+        removed
+    }
+
+    // Extra cards cycle through the suits so suited side bets stay sane.
+    pub fn add(&mut self, value: &CardValue, count: usize) {
+        for _ in 0..count {
+            let suit = CardSuit::ALL_VALUES[self.count(value) % CardSuit::ALL_VALUES.len()];
+            self.cards.push(Card { suit, value: *value });
+        }
+    }
+
+    pub fn set_penetration(&mut self, percent: f64) -> Result<(), CompositionError> {
+        if !(1.0..=100.0).contains(&percent) {
+            return Err(CompositionError::InvalidPenetration(percent));
+        }
+        self.penetration = percent / 100.0;
+        Ok(())
+    }
+
+    // Shuffles a copy of the shoe and sets aside everything behind the cut
+    // card, so only the first `penetration` of it is ever dealt.
+    pub fn deal<S: Shuffler + ?Sized>(&self, shuffler: &mut S) -> Deck {
+        let mut deck = Deck {
+            remaining_cards: self.cards.clone(),
+            discard_tray: Vec::new(),
+            burned_cards: Vec::new(),
+            cut_card: Option::None,
+            reshuffle_key: ReshuffleKey::default(),
+        };
+        deck.shuffle(shuffler);
+        let dealt = (self.cards.len() as f64 * self.penetration).ceil() as usize;
+        // The deck deals from the back of its vector.
+        let behind_cut = deck.remaining_cards.len() - dealt;
+        deck.remaining_cards.drain(..behind_cut);
+        deck
+    }
+}
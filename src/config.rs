@@ -0,0 +1,405 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::chips::{TableLimits, DEFAULT_TABLE_MAX, DEFAULT_TABLE_MIN};
+use crate::currency::{Currency, MAX_CURRENCY_DECIMALS};
+use crate::dealer::DealerProfile;
+use crate::i18n::Language;
+use crate::payout::Rounding;
+use crate::promotions::promotion_from_name;
+use crate::rules::{DoubleRule, EmptyShoe, GameRules, HoleCard, Variant, MIN_CHARLIE};
+
+pub const DEFAULT_BANKROLL: u64 = 1000;
+
+pub const DEFAULT_BET: u64 = 10;
+
+pub const DEFAULT_DECKS: u32 = 1;
+
+pub use crate::rules::MAX_DECKS;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RulesPreset {
+    VegasStrip,
+    AtlanticCity,
+    European,
+    Pontoon,
+}
+
+impl RulesPreset {
+    pub const ALL_VALUES: [RulesPreset; 4] = [
+        RulesPreset::VegasStrip,
+        RulesPreset::AtlanticCity,
+        RulesPreset::European,
+        RulesPreset::Pontoon,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RulesPreset::VegasStrip => "vegas-strip",
+            RulesPreset::AtlanticCity => "atlantic-city",
+            RulesPreset::European => "european",
+            RulesPreset::Pontoon => "pontoon",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<RulesPreset> {
+        RulesPreset::ALL_VALUES.iter().find(|preset| preset.name() == name).cloned()
+    }
+
+    // Every table stands on soft 17. The Strip plays without surrender,
+    // Atlantic City adds late surrender, and the European table deals no
+    // hole card and only allows doubling on 9 to 11. Pontoon pays 2:1 for a
+    // pontoon.
+    pub fn rules(&self, decks: u32) -> GameRules {
+        let base = GameRules { decks, surrender: false, ..GameRules::default() };
+        match self {
+            RulesPreset::VegasStrip => base,
+            RulesPreset::AtlanticCity => GameRules { surrender: true, ..base },
+            RulesPreset::European => GameRules {
+                hole_card: HoleCard::AfterPlayers,
+                double_on: DoubleRule::NineToEleven,
+                ..base
+            },
+            RulesPreset::Pontoon => GameRules {
+                variant: Variant::Pontoon,
+                blackjack_numerator: 2,
+                blackjack_denominator: 1,
+                ..base
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CoachLevel {
+    Off,
+    Hints,
+    Full,
+}
+
+impl CoachLevel {
+    pub const ALL_VALUES: [CoachLevel; 3] = [
+        CoachLevel::Off,
+        CoachLevel::Hints,
+        CoachLevel::Full,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CoachLevel::Off => "off",
+            CoachLevel::Hints => "hints",
+            CoachLevel::Full => "full",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<CoachLevel> {
+        CoachLevel::ALL_VALUES.iter().find(|level| level.name() == name).cloned()
+    }
+}
+
+// How quickly play deals, one card at a time. Instant prints every card at
+// once, as simulations and piped input always do.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Speed {
+    Instant,
+    Fast,
+    Normal,
+    Slow,
+}
+
+impl Speed {
+    pub const ALL_VALUES: [Speed; 4] = [Speed::Instant, Speed::Fast, Speed::Normal, Speed::Slow];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Speed::Instant => "instant",
+            Speed::Fast => "fast",
+            Speed::Normal => "normal",
+            Speed::Slow => "slow",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Speed> {
+        Speed::ALL_VALUES.iter().find(|speed| speed.name() == name).cloned()
+    }
+
+    // The pause before each card is shown.
+    pub fn card_delay(&self) -> Duration {
+        match self {
+            Speed::Instant => Duration::ZERO,
+            Speed::Fast => Duration::from_millis(150),
+            Speed::Normal => Duration::from_millis(400),
+            Speed::Slow => Duration::from_millis(900),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Config {
+    pub rules_preset: RulesPreset,
+    pub dealer_profile: DealerProfile,
+    pub empty_shoe: EmptyShoe,
+    // 0 plays without a Charlie.
+    pub charlie: usize,
+    pub decks: u32,
+    pub bankroll: u64,
+    pub bet: u64,
+    pub table_min: u64,
+    pub table_max: u64,
+    pub coach: CoachLevel,
+    pub color: bool,
+    pub speed: Speed,
+    pub language: Language,
+    // Cards in words, no color or art, and every update on a line of its
+    // own, for screen readers.
+    pub accessible: bool,
+    // After every win, offer to tip the dealer out of it.
+    pub tips: bool,
+    pub promotions: Vec<String>,
+    // Session limits for play; 0 leaves one unset.
+    pub stop_win: u64,
+    pub stop_loss: u64,
+    pub max_hands: u64,
+    pub max_minutes: u64,
+    // How amounts read in play, and the smallest unit they are counted in.
+    pub currency: Currency,
+    // Which way a payout that doesn't come out whole is rounded, and to a
+    // multiple of how many whole units; 0 rounds to the smallest unit.
+    pub payout_rounding: Rounding,
+    pub payout_step: u64,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            rules_preset: RulesPreset::VegasStrip,
+            dealer_profile: DealerProfile::Standard,
+            empty_shoe: EmptyShoe::Reshuffle,
+            charlie: 0,
+            decks: DEFAULT_DECKS,
+            bankroll: DEFAULT_BANKROLL,
+            bet: DEFAULT_BET,
+            table_min: DEFAULT_TABLE_MIN,
+            table_max: DEFAULT_TABLE_MAX,
+            coach: CoachLevel::Off,
+            color: true,
+            speed: Speed::Normal,
+            language: Language::English,
+            accessible: false,
+            tips: false,
+            promotions: Vec::new(),
+            stop_win: 0,
+            stop_loss: 0,
+            max_hands: 0,
+            max_minutes: 0,
+            currency: Currency::default(),
+            payout_rounding: Rounding::Down,
+            payout_step: 0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Syntax { line: usize, text: String },
+    UnknownKey(String),
+    InvalidValue { key: String, value: String },
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(error) => write!(f, "could not access the config file: {}", error),
+            ConfigError::Syntax { line, text } => write!(f, "line {}: cannot parse {:?}", line, text),
+            ConfigError::UnknownKey(key) => write!(f, "unknown setting {:?}", key),
+            ConfigError::InvalidValue { key, value } => write!(f, "{} cannot be {}", key, value),
+            ConfigError::Invalid(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(error: io::Error) -> ConfigError {
+        ConfigError::Io(error)
+    }
+}
+
+pub fn config_path() -> PathBuf {
+    let config_home = match env::var_os("XDG_CONFIG_HOME") {
+        Option::Some(dir) => PathBuf::from(dir),
+        Option::None => env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".config"),
+    };
+    config_home.join("rust-blackjack").join("config.toml")
+}
+
+fn unquote(raw: &str) -> Option<&str> {
+    raw.strip_prefix('"').and_then(|rest| rest.strip_suffix('"'))
+}
+
+fn parse_string_array(raw: &str) -> Option<Vec<String>> {
+    let inner = raw.strip_prefix('[')?.strip_suffix(']')?.trim();
+    if inner.is_empty() {
+        return Option::Some(Vec::new());
+    }
+    inner
+        .split(',')
+        .map(|item| unquote(item.trim()).map(str::to_string))
+        .collect()
+}
+
+impl Config {
+    // Only the flat subset of TOML the config needs: `key = value` lines with
+    // string, integer, boolean and string array values, plus comments.
+    pub fn from_toml(contents: &str) -> Result<Config, ConfigError> {
+        let mut config = Config::default();
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, raw_value) = match line.find('=') {
+                Option::Some(position) => (line[..position].trim(), line[position + 1..].trim()),
+                Option::None => return Err(ConfigError::Syntax { line: index + 1, text: line.to_string() }),
+            };
+            let invalid = || ConfigError::InvalidValue { key: key.to_string(), value: raw_value.to_string() };
+            match key {
+                "rules_preset" => {
+                    config.rules_preset = unquote(raw_value).and_then(RulesPreset::from_name).ok_or_else(invalid)?
+                },
+                "dealer_profile" => {
+                    config.dealer_profile = unquote(raw_value).and_then(DealerProfile::from_name).ok_or_else(invalid)?
+                },
+                "empty_shoe" => config.empty_shoe = unquote(raw_value).and_then(EmptyShoe::from_name).ok_or_else(invalid)?,
+                "charlie" => config.charlie = raw_value.parse().map_err(|_| invalid())?,
+                "decks" => config.decks = raw_value.parse().map_err(|_| invalid())?,
+                "bankroll" => config.bankroll = raw_value.parse().map_err(|_| invalid())?,
+                "bet" => config.bet = raw_value.parse().map_err(|_| invalid())?,
+                "table_min" => config.table_min = raw_value.parse().map_err(|_| invalid())?,
+                "table_max" => config.table_max = raw_value.parse().map_err(|_| invalid())?,
+                "coach" => config.coach = unquote(raw_value).and_then(CoachLevel::from_name).ok_or_else(invalid)?,
+                "color" => config.color = raw_value.parse().map_err(|_| invalid())?,
+                "language" => config.language = unquote(raw_value).and_then(Language::from_name).ok_or_else(invalid)?,
+                "accessible" => config.accessible = raw_value.parse().map_err(|_| invalid())?,
+                "tips" => config.tips = raw_value.parse().map_err(|_| invalid())?,
+                "speed" => config.speed = unquote(raw_value).and_then(Speed::from_name).ok_or_else(invalid)?,
+                "promotions" => {
+                    config.promotions = parse_string_array(raw_value)
+                        .filter(|names| names.iter().all(|name| promotion_from_name(name).is_some()))
+                        .ok_or_else(invalid)?
+                },
+                "stop_win" => config.stop_win = raw_value.parse().map_err(|_| invalid())?,
+                "stop_loss" => config.stop_loss = raw_value.parse().map_err(|_| invalid())?,
+                "max_hands" => config.max_hands = raw_value.parse().map_err(|_| invalid())?,
+                "max_minutes" => config.max_minutes = raw_value.parse().map_err(|_| invalid())?,
+                "payout_rounding" => {
+                    config.payout_rounding = unquote(raw_value).and_then(Rounding::from_name).ok_or_else(invalid)?
+                },
+                "payout_step" => config.payout_step = raw_value.parse().map_err(|_| invalid())?,
+                "currency_symbol" => config.currency.symbol = unquote(raw_value).ok_or_else(invalid)?.to_string(),
+                "currency_decimals" => config.currency.decimals = raw_value.parse().map_err(|_| invalid())?,
+                "thousands_separator" => {
+                    let separator = unquote(raw_value).ok_or_else(invalid)?;
+                    let mut chars = separator.chars();
+                    config.currency.separator = match (chars.next(), chars.next()) {
+                        (Option::None, _) => Option::None,
+                        (Option::Some(c), Option::None) if !c.is_ascii_digit() && c != '.' => Option::Some(c),
+                        _ => return Err(invalid()),
+                    }
+                },
+                _ => return Err(ConfigError::UnknownKey(key.to_string())),
+            }
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    // Checks the settings that are fine on their own but not together, or
+    // outside what a table can offer.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.decks == 0 || self.decks > MAX_DECKS {
+            return Err(ConfigError::Invalid(format!("decks must be between 1 and {}", MAX_DECKS)));
+        }
+        if self.charlie > 0 && self.charlie < MIN_CHARLIE {
+            return Err(ConfigError::Invalid(format!("charlie must be 0 or at least {}", MIN_CHARLIE)));
+        }
+        if self.currency.decimals > MAX_CURRENCY_DECIMALS {
+            return Err(ConfigError::Invalid(format!("currency_decimals must be at most {}", MAX_CURRENCY_DECIMALS)));
+        }
+        if self.bet == 0 {
+            return Err(ConfigError::Invalid("bet must be at least 1".to_string()));
+        }
+        if self.table_min == 0 || self.table_min > self.table_max {
+            return Err(ConfigError::Invalid(format!(
+                "the table limits of {} to {} aren't a range of bets",
+                self.table_min, self.table_max
+            )));
+        }
+        self.table_limits().check(self.bet, self.bankroll).map_err(|error| ConfigError::Invalid(error.to_string()))
+    }
+
+    pub fn table_limits(&self) -> TableLimits {
+        TableLimits { minimum: self.table_min, maximum: self.table_max }
+    }
+
+    pub fn to_toml(&self) -> String {
+        format!(
+            "rules_preset = \"{}\"\ndealer_profile = \"{}\"\nempty_shoe = \"{}\"\ncharlie = {}\ndecks = {}\nbankroll = {}\nbet = {}\ntable_min = {}\ntable_max = {}\ncoach = \"{}\"\ncolor = {}\nspeed = \"{}\"\nlanguage = \"{}\"\naccessible = {}\ntips = {}\npromotions = [{}]\nstop_win = {}\nstop_loss = {}\nmax_hands = {}\nmax_minutes = {}\ncurrency_symbol = \"{}\"\ncurrency_decimals = {}\nthousands_separator = \"{}\"\npayout_rounding = \"{}\"\npayout_step = {}\n",
+            self.rules_preset.name(),
+            self.dealer_profile.name(),
+            self.empty_shoe.name(),
+            self.charlie,
+            self.decks,
+            self.bankroll,
+            self.bet,
+            self.table_min,
+            self.table_max,
+            self.coach.name(),
+            self.color,
+            self.speed.name(),
+            self.language.name(),
+            self.accessible,
+            self.tips,
+            self.promotions
+                .iter()
+                .map(|name| format!("\"{}\"", name))
+                .collect::<Vec<String>>()
+                .join(", "),
+            self.stop_win,
+            self.stop_loss,
+            self.max_hands,
+            self.max_minutes,
+            self.currency.symbol,
+            self.currency.decimals,
+            self.currency.separator.map(String::from).unwrap_or_default(),
+            self.payout_rounding.name(),
+            self.payout_step
+        )
+    }
+
+    // `Ok(None)` means there is no config file yet.
+    pub fn load() -> Result<Option<Config>, ConfigError> {
+        match fs::read_to_string(config_path()) {
+            Ok(contents) => Config::from_toml(&contents).map(Option::Some),
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => Ok(Option::None),
+            Err(error) => Err(ConfigError::Io(error)),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), ConfigError> {
+        let path = config_path();
+        if let Option::Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_toml())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+use crate::deviation::{DeviationIndex, FAB_4, ILLUSTRIOUS_18};
+use crate::{Card, CardValue, Deck, PlayerState};
+
+const CARDS_PER_DECK: f64 = 52.0;
+
+pub fn hi_lo_value(card_value: &CardValue) -> i32 {
+    match card_value {
+        CardValue::Two
+        | CardValue::Three
+        | CardValue::Four
+        | CardValue::Five
+        | CardValue::Six => 1,
+        CardValue::Seven | CardValue::Eight | CardValue::Nine => 0,
+        CardValue::Ten
+        | CardValue::Jack
+        | CardValue::Queen
+        | CardValue::King
+        | CardValue::Ace => -1,
+    }
+}
+
+// A card counting system: the tag each rank adds to the running count, and
+// the index plays worked out for it. Indices only carry over between
+// systems with the same tags, so each system brings its own.
+pub struct CountingSystem {
+    pub name: &'static str,
+    pub tag: fn(&CardValue) -> i32,
+    // Plays, then surrenders for tables that offer late surrender.
+    pub indices: &'static [DeviationIndex],
+    pub surrender_indices: &'static [DeviationIndex],
+    // Insurance becomes a good bet at this true count.
+    pub insurance_index: i32,
+}
+
+pub const HI_LO: CountingSystem = CountingSystem {
+    name: "hi-lo",
+    tag: hi_lo_value,
+    indices: &ILLUSTRIOUS_18,
+    surrender_indices: &FAB_4,
+    insurance_index: 3,
+};
+
+impl CountingSystem {
+    fn count(&self, cards: &[Card]) -> i32 {
+        cards.iter().map(|card| (self.tag)(&card.value)).sum()
+    }
+
+    fn per_deck(running_count: i32, unseen_cards: usize) -> f64 {
+        let decks_remaining = unseen_cards as f64 / CARDS_PER_DECK;
+        if decks_remaining > 0.0 {
+            running_count as f64 / decks_remaining
+        } else {
+            running_count as f64
+        }
+    }
+
+    // Only cards the player has actually seen count: the discard tray and
+    // whatever is face up on the table.
+    pub fn running_count(&self, state: &PlayerState) -> i32 {
+        self.count(state.deck.discard_tray()) + self.count(&state.player_cards()) + self.count(&state.visible_dealer_cards())
+    }
+
+    pub fn true_count(&self, state: &PlayerState) -> f64 {
+        let unseen_cards = state.deck.remaining() + state.deck.burned().len() + state.hidden_dealer_cards().len();
+        CountingSystem::per_deck(self.running_count(state), unseen_cards)
+    }
+
+    // The counts between hands, when every card dealt so far has been seen
+    // and is in the discard tray.
+    pub fn shoe_running_count(&self, deck: &Deck) -> i32 {
+        self.count(deck.discard_tray())
+    }
+
+    pub fn shoe_true_count(&self, deck: &Deck) -> f64 {
+        CountingSystem::per_deck(self.shoe_running_count(deck), deck.remaining() + deck.burned().len())
+    }
+}
+
+// Hi-Lo counts. Only cards the player has actually seen count, so the
+// dealer's hole card is left out until it is turned over.
+pub fn running_count(state: &PlayerState) -> i32 {
+    HI_LO.running_count(state)
+}
+
+pub fn true_count(state: &PlayerState) -> f64 {
+    HI_LO.true_count(state)
+}
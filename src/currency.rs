@@ -0,0 +1,89 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+// How amounts read at the table. Every amount is a whole number of the
+// currency's smallest unit: with two decimal places a bankroll of 1000.00
+// is 100000, so a 3:2 blackjack on a bet of 5.00 pays exactly 7.50. With no
+// decimal places, the default, that unit is a chip and amounts read just as
+// they always have.
+
+// Beyond this a chip would be worth less than a thousandth of a unit.
+pub const MAX_CURRENCY_DECIMALS: u32 = 3;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Currency {
+    // Written before the amount, such as "$"; empty for none.
+    pub symbol: String,
+    pub decimals: u32,
+    // Between each group of three digits, such as ','.
+    pub separator: Option<char>,
+}
+
+impl Currency {
+    // Smallest units in a whole one.
+    pub fn unit(&self) -> u64 {
+        10u64.pow(self.decimals)
+    }
+
+    pub fn format(&self, amount: i64) -> String {
+        let unit = self.unit();
+        let magnitude = amount.unsigned_abs();
+        let digits = (magnitude / unit).to_string();
+        let mut whole = String::new();
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index).is_multiple_of(3) {
+                if let Option::Some(separator) = self.separator {
+                    whole.push(separator);
+                }
+            }
+            whole.push(digit);
+        }
+        let sign = if amount < 0 { "-" } else { "" };
+        if self.decimals == 0 {
+            format!("{}{}{}", sign, self.symbol, whole)
+        } else {
+            let cents = magnitude % unit;
+            format!("{}{}{}.{:0width$}", sign, self.symbol, whole, cents, width = self.decimals as usize)
+        }
+    }
+
+    // With a sign either way, for wins and losses.
+    pub fn format_signed(&self, amount: i64) -> String {
+        if amount < 0 {
+            self.format(amount)
+        } else {
+            format!("+{}", self.format(amount))
+        }
+    }
+
+    // Whole units to two places or the currency's own, whichever is more,
+    // for amounts such as expected costs that fall between units.
+    pub fn format_approximate(&self, amount: f64) -> String {
+        let places = self.decimals.max(2) as usize;
+        let sign = if amount < 0.0 { "-" } else { "" };
+        format!("{}{}{:.*}", sign, self.symbol, places, amount.abs() / self.unit() as f64)
+    }
+
+    // An amount as typed, such as `12.50`, `$1,000` or `35`, in the smallest
+    // unit. None when it has more decimal places than the currency does or
+    // isn't a plain amount at all.
+    pub fn parse(&self, raw: &str) -> Option<u64> {
+        let raw = raw.trim();
+        let raw = if self.symbol.is_empty() { raw } else { raw.strip_prefix(self.symbol.as_str()).unwrap_or(raw) };
+        let raw: String = raw.chars().filter(|c| Option::Some(*c) != self.separator).collect();
+        let parts: Vec<&str> = raw.split('.').collect();
+        let (whole, fraction) = match parts.as_slice() {
+            [whole] => (*whole, ""),
+            [whole, fraction] if fraction.len() as u32 <= self.decimals => (*whole, *fraction),
+            _ => return Option::None,
+        };
+        let is_digits = |text: &str| text.chars().all(|c| c.is_ascii_digit());
+        if whole.is_empty() || !is_digits(whole) || !is_digits(fraction) {
+            return Option::None;
+        }
+        let padding = 10u64.pow(self.decimals - fraction.len() as u32);
+        let fraction = if fraction.is_empty() { 0 } else { fraction.parse::<u64>().ok()? * padding };
+        whole.parse::<u64>().ok()?.checked_mul(self.unit())?.checked_add(fraction)
+    }
+}
@@ -0,0 +1,126 @@
+use alloc::boxed::Box;
+
+use crate::deviation::upcard_value;
+use crate::rules::GameRules;
+use crate::{Card, CardSuit, CardValue};
+
+// How the dealer runs the hand: when to draw, and what the players get to
+// see. The house rules cover soft 17; a policy can play them straight or not.
+pub trait DealerPolicy {
+    fn name(&self) -> &str;
+    fn must_draw(&self, dealer_cards: &[Card], rules: &GameRules) -> bool;
+    // Whether the hole card is dealt face up.
+    fn shows_hole_card(&self) -> bool {
+        false
+    }
+}
+
+// The dealer's total and whether it is soft, that is whether an ace is
+// counting as 11. However many aces there are, only one of them can: a
+// second would bust the hand, so A-A-5 is a soft 17 and A-6-10 a hard one.
+pub fn dealer_total(dealer_cards: &[Card]) -> (u32, bool) {
+    let hard: u32 = dealer_cards
+        .iter()
+        .map(|card| if card.value == CardValue::Ace { 1 } else { upcard_value(&card.value) })
+        .sum();
+    let has_ace = dealer_cards.iter().any(|card| card.value == CardValue::Ace);
+    if has_ace && hard + 10 <= 21 {
+        (hard + 10, true)
+    } else {
+        (hard, false)
+    }
+}
+
+// Draws to 17, and on a soft 17 as well when the dealer hits it.
+fn draws_by_the_book(dealer_cards: &[Card], rules: &GameRules) -> bool {
+    let (total, soft) = dealer_total(dealer_cards);
+    total < 17 || (total == 17 && soft && rules.dealer_hits_soft_17)
+}
+
+// Draws to 17 exactly as the rules say.
+pub struct StandardDealer;
+
+impl DealerPolicy for StandardDealer {
+    fn name(&self) -> &str {
+        "standard"
+    }
+
+    fn must_draw(&self, dealer_cards: &[Card], rules: &GameRules) -> bool {
+        draws_by_the_book(dealer_cards, rules)
+    }
+}
+
+// Plays straight but deals the hole card face up, so every decision can be
+// checked against the hand the dealer really holds.
+pub struct TrainingDealer;
+
+impl DealerPolicy for TrainingDealer {
+    fn name(&self) -> &str {
+        "training"
+    }
+
+    fn must_draw(&self, dealer_cards: &[Card], rules: &GameRules) -> bool {
+        draws_by_the_book(dealer_cards, rules)
+    }
+
+    fn shows_hole_card(&self) -> bool {
+        true
+    }
+}
+
+// A dealer who slips now and then. The engine has no dice of its own, so the
+// slips follow the last card drawn, which the shuffle has already made
+// random: after a heart an ace is misread as one, so a soft 17 to 20 is hit,
+// and after a club a hard 16 is taken for 17 and stood on.
+pub struct CarelessDealer;
+
+impl DealerPolicy for CarelessDealer {
+    fn name(&self) -> &str {
+        "careless"
+    }
+
+    fn must_draw(&self, dealer_cards: &[Card], rules: &GameRules) -> bool {
+        let (total, soft) = dealer_total(dealer_cards);
+        match dealer_cards.last().map(|card| &card.suit) {
+            Option::Some(CardSuit::Hearts) if soft && (17..=20).contains(&total) => true,
+            Option::Some(CardSuit::Clubs) if !soft && total == 16 => false,
+            _ => draws_by_the_book(dealer_cards, rules),
+        }
+    }
+}
+
+// The dealer profiles a table can be set up with.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DealerProfile {
+    Standard,
+    Training,
+    Careless,
+}
+
+impl DealerProfile {
+    pub const ALL_VALUES: [DealerProfile; 3] = [
+        DealerProfile::Standard,
+        DealerProfile::Training,
+        DealerProfile::Careless,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DealerProfile::Standard => "standard",
+            DealerProfile::Training => "training",
+            DealerProfile::Careless => "careless",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<DealerProfile> {
+        DealerProfile::ALL_VALUES.iter().find(|profile| profile.name() == name).cloned()
+    }
+
+    pub fn policy(&self) -> Box<dyn DealerPolicy> {
+        match self {
+            DealerProfile::Standard => Box::new(StandardDealer),
+            DealerProfile::Training => Box::new(TrainingDealer),
+            DealerProfile::Careless => Box::new(CarelessDealer),
+        }
+    }
+}
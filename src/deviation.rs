@@ -0,0 +1,189 @@
+use alloc::vec::Vec;
+
+use crate::{Action, Card, CardValue, HandTotals};
+
+// A count-based departure from basic strategy: with `player_total` against
+// `dealer_upcard`, take `deviation` instead of `basic` once the true count
+// reaches `index`, or, for the plays marked `below`, once it falls under
+// it. `pair` spots only apply to a pair making up the total.
+pub struct DeviationIndex {
+    pub player_total: u32,
+    pub pair: bool,
+    pub dealer_upcard: u32,
+    pub index: i32,
+    pub below: bool,
+    pub basic: Action,
+    pub deviation: Action,
+}
+
+const fn at_or_above(player_total: u32, dealer_upcard: u32, index: i32, basic: Action, deviation: Action) -> DeviationIndex {
+    DeviationIndex { player_total, pair: false, dealer_upcard, index, below: false, basic, deviation }
+}
+
+const fn under(player_total: u32, dealer_upcard: u32, index: i32, basic: Action, deviation: Action) -> DeviationIndex {
+    DeviationIndex { player_total, pair: false, dealer_upcard, index, below: true, basic, deviation }
+}
+
+const fn pair(player_total: u32, dealer_upcard: u32, index: i32, basic: Action, deviation: Action) -> DeviationIndex {
+    DeviationIndex { player_total, pair: true, dealer_upcard, index, below: false, basic, deviation }
+}
+
+// The Hi-Lo plays that account for most of what indices are worth, for a
+// multi-deck game where the dealer stands on soft 17. Insurance, the first of
+// the eighteen, is a separate bet and lives in `CountingSystem`.
+pub const ILLUSTRIOUS_18: [DeviationIndex; 17] = [
+    at_or_above(16, 10, 0, Action::Hit, Action::Stand),
+    at_or_above(15, 10, 4, Action::Hit, Action::Stand),
+    pair(20, 5, 5, Action::Stand, Action::SplitCards),
+    pair(20, 6, 4, Action::Stand, Action::SplitCards),
+    at_or_above(10, 10, 4, Action::Hit, Action::DoubleDown),
+    at_or_above(12, 3, 2, Action::Hit, Action::Stand),
+    at_or_above(12, 2, 3, Action::Hit, Action::Stand),
+    at_or_above(11, 11, 1, Action::Hit, Action::DoubleDown),
+    at_or_above(9, 2, 1, Action::Hit, Action::DoubleDown),
+    at_or_above(10, 11, 4, Action::Hit, Action::DoubleDown),
+    at_or_above(9, 7, 3, Action::Hit, Action::DoubleDown),
+    at_or_above(16, 9, 5, Action::Hit, Action::Stand),
+    under(13, 2, -1, Action::Stand, Action::Hit),
+    under(12, 4, 0, Action::Stand, Action::Hit),
+    under(12, 5, -2, Action::Stand, Action::Hit),
+    under(12, 6, -1, Action::Stand, Action::Hit),
+    under(13, 3, -2, Action::Stand, Action::Hit),
+];
+
+// The four Hi-Lo late surrender indices, for tables that offer it.
+pub const FAB_4: [DeviationIndex; 4] = [
+    at_or_above(14, 10, 3, Action::Hit, Action::Surrender),
+    at_or_above(15, 10, 0, Action::Hit, Action::Surrender),
+    at_or_above(15, 9, 2, Action::Hit, Action::Surrender),
+    at_or_above(15, 11, 1, Action::Hit, Action::Surrender),
+];
+
+impl DeviationIndex {
+    pub fn is_triggered(&self, true_count: f64) -> bool {
+        if self.below {
+            true_count < self.index as f64
+        } else {
+            true_count >= self.index as f64
+        }
+    }
+
+    fn matches(&self, hand: &[Card], dealer_upcard: u32) -> bool {
+        let total = if self.pair { pair_total(hand) } else { hard_total(hand) };
+        total == Option::Some(self.player_total) && self.dealer_upcard == dealer_upcard
+    }
+}
+
+// The index in `indices` that applies to the hand at this count, if any.
+pub fn triggered<'a>(
+    indices: &'a [DeviationIndex],
+    hand: &[Card],
+    dealer_upcard: &Card,
+    true_count: f64,
+) -> Option<&'a DeviationIndex> {
+    let dealer_upcard = upcard_value(&dealer_upcard.value);
+    indices
+        .iter()
+        .find(|deviation_index| deviation_index.matches(hand, dealer_upcard) && deviation_index.is_triggered(true_count))
+}
+
+pub fn upcard_value(card_value: &CardValue) -> u32 {
+    match card_value {
+        CardValue::Two => 2,
+        CardValue::Three => 3,
+        CardValue::Four => 4,
+        CardValue::Five => 5,
+        CardValue::Six => 6,
+        CardValue::Seven => 7,
+        CardValue::Eight => 8,
+        CardValue::Nine => 9,
+        CardValue::Ten | CardValue::Jack | CardValue::Queen | CardValue::King => 10,
+        CardValue::Ace => 11,
+    }
+}
+
+// Deviations are only defined for hard totals, so soft hands (more than one
+// possible value) never produce a spot.
+fn hard_total(hand: &[Card]) -> Option<u32> {
+    if hand.len() < 2 {
+        return Option::None;
+    }
+    let totals = HandTotals::of(hand);
+    if totals.is_soft() || totals.is_bust() {
+        Option::None
+    } else {
+        Option::Some(totals.hard)
+    }
+}
+
+fn pair_total(hand: &[Card]) -> Option<u32> {
+    match hand {
+        [first, second] if upcard_value(&first.value) == upcard_value(&second.value) => {
+            Option::Some(upcard_value(&first.value) * 2)
+        },
+        _ => Option::None,
+    }
+}
+
+#[derive(Debug)]
+pub struct DeviationEntry {
+    pub player_total: u32,
+    pub dealer_upcard: u32,
+    pub index: i32,
+    pub true_count: f64,
+    pub action_taken: Action,
+    pub basic: Action,
+    pub deviation: Action,
+}
+
+impl DeviationEntry {
+    pub fn took_deviation(&self) -> bool {
+        self.action_taken == self.deviation
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DeviationLog {
+    pub entries: Vec<DeviationEntry>,
+}
+
+impl DeviationLog {
+    pub fn new() -> DeviationLog {
+        DeviationLog { entries: Vec::new() }
+    }
+
+    pub fn record(&mut self, hand: &[Card], dealer_upcard: &Card, true_count: f64, action_taken: &Action) {
+        let deviation_index = match triggered(&ILLUSTRIOUS_18, hand, dealer_upcard, true_count) {
+            Option::Some(deviation_index) => deviation_index,
+            Option::None => return,
+        };
+        self.entries.push(DeviationEntry {
+            player_total: deviation_index.player_total,
+            dealer_upcard: deviation_index.dealer_upcard,
+            index: deviation_index.index,
+            true_count,
+            action_taken: action_taken.clone(),
+            basic: deviation_index.basic.clone(),
+            deviation: deviation_index.deviation.clone(),
+        });
+    }
+
+    #[cfg(feature = "std")]
+    pub fn print_summary(&self) {
+        let taken = self.entries.iter().filter(|entry| entry.took_deviation()).count();
+        println!("Deviation spots: {} (taken {}, missed {})", self.entries.len(), taken, self.entries.len() - taken);
+        for entry in &self.entries {
+            println!(
+                "  {} v {} at TC {:.1} (index {:+}): you chose {:?}, index play is {:?} over {:?}{}",
+                entry.player_total,
+                entry.dealer_upcard,
+                entry.true_count,
+                entry.index,
+                entry.action_taken,
+                entry.deviation,
+                entry.basic,
+                if entry.took_deviation() { "" } else { " (missed)" }
+            );
+        }
+    }
+}
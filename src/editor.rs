@@ -0,0 +1,103 @@
+use std::io::{self, BufRead};
+
+use rust_blackjack::composition::{parse_rank, ShoeComposition};
+use rust_blackjack::protocol::encode_card_value;
+use rust_blackjack::CardValue;
+
+use crate::cli::DEFAULT_SIMULATION_HANDS;
+
+const HELP: &str = "Commands:
+    show                     Print the count of every rank
+    remove <RANK> [N]        Take out N cards of a rank, or all of them
+    add <RANK> [N]           Put in N more cards of a rank (default 1)
+    penetration <PERCENT>    Deal this much of the shoe before reshuffling
+    reset                    Start again from full decks
+    play                     Play from this shoe
+    simulate [HANDS]         Simulate from this shoe
+    quit                     Leave without playing";
+
+pub enum EditorChoice {
+    Play,
+    Simulate(u64),
+}
+
+fn show(composition: &ShoeComposition) {
+    let counts: Vec<String> = CardValue::ALL_VALUES
+        .iter()
+        .map(|value| format!("{}:{}", encode_card_value(value), composition.count(value)))
+        .collect();
+    println!("{}", counts.join(" "));
+    println!(
+        "{} cards, {:.0}% dealt before the cut card",
+        composition.len(),
+        composition.penetration() * 100.0
+    );
+}
+
+// Reads editing commands until the player asks to play or simulate, then
+// hands the finished shoe back. Returns None on quit or end of input.
+pub fn run(decks: u32) -> Option<(ShoeComposition, EditorChoice)> {
+    let stdin = io::stdin();
+    let mut composition = ShoeComposition::new(decks);
+
+    println!("Shoe editor. Type help for the list of commands.");
+    show(&composition);
+
+    for line in stdin.lock().lines() {
+        let line = line.ok()?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let count = words.get(2).map(|raw| raw.parse::<usize>());
+        match (words.first().copied(), words.get(1)) {
+            (Option::Some("show"), _) => show(&composition),
+            (Option::Some("help"), _) => println!("{}", HELP),
+            (Option::Some("reset"), _) => {
+                composition = ShoeComposition::new(decks);
+                show(&composition);
+            },
+            (Option::Some(command @ ("remove" | "add")), Option::Some(rank)) => {
+                let value = match parse_rank(rank) {
+                    Ok(value) => value,
+                    Err(error) => {
+                        println!("{}", error);
+                        continue;
+                    },
+                };
+                let count = match count {
+                    Option::Some(Ok(count)) => Option::Some(count),
+                    Option::Some(Err(_)) => {
+                        println!("{:?} is not a number of cards", words[2]);
+                        continue;
+                    },
+                    Option::None => Option::None,
+                };
+                if command == "remove" {
+                    let removed = composition.remove(&value, count);
+                    println!("Removed {} cards", removed);
+                } else {
+                    composition.add(&value, count.unwrap_or(1));
+                }
+                show(&composition);
+            },
+            (Option::Some("penetration"), Option::Some(raw)) => {
+                match raw.trim_end_matches('%').parse::<f64>() {
+                    Ok(percent) => match composition.set_penetration(percent) {
+                        Ok(()) => show(&composition),
+                        Err(error) => println!("{}", error),
+                    },
+                    Err(_) => println!("{:?} is not a percentage", raw),
+                }
+            },
+            (Option::Some("play" | "simulate"), _) if composition.is_empty() => println!("The shoe is empty; add some cards first"),
+            (Option::Some("play"), _) => return Option::Some((composition, EditorChoice::Play)),
+            (Option::Some("simulate"), hands) => match hands.map(|raw| raw.parse::<u64>()) {
+                Option::None => return Option::Some((composition, EditorChoice::Simulate(DEFAULT_SIMULATION_HANDS))),
+                Option::Some(Ok(hands)) => return Option::Some((composition, EditorChoice::Simulate(hands))),
+                Option::Some(Err(_)) => println!("{:?} is not a number of hands", words[1]),
+            },
+            (Option::Some("quit"), _) => return Option::None,
+            (Option::Some(_), _) => println!("Unknown command {:?}. Type help for the list of commands.", line.trim()),
+            (Option::None, _) => (),
+        }
+    }
+    Option::None
+}
@@ -0,0 +1,56 @@
+use alloc::string::String;
+use core::error::Error;
+use core::fmt;
+
+use crate::chips::BetError;
+use crate::rules::MAX_DECKS;
+use crate::IllegalAction;
+
+// Everything the engine turns down, as one type for library users to match
+// on. The fallible constructors and operations return it where the older,
+// trusting ones would have panicked or quietly done nothing.
+#[derive(Debug)]
+pub enum BlackjackError {
+    // No hand counts more than 21.
+    InvalidHandValue(u32),
+    InvalidDeckCount(u32),
+    // A card or rank that doesn't decode, such as "1S" or "ZZ".
+    InvalidCard(String),
+    InvalidRules(&'static str),
+    // Fewer cards are left than the deal or draw needs.
+    EmptyShoe { needed: usize, remaining: usize },
+    IllegalAction(IllegalAction),
+    InvalidBet(BetError),
+}
+
+impl fmt::Display for BlackjackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BlackjackError::InvalidHandValue(value) => write!(f, "{} is not a hand value; hands count at most 21", value),
+            BlackjackError::InvalidDeckCount(decks) => {
+                write!(f, "a shoe of {} decks is not allowed, only 1 to {}", decks, MAX_DECKS)
+            },
+            BlackjackError::InvalidCard(raw) => write!(f, "{:?} is not a card", raw),
+            BlackjackError::InvalidRules(reason) => write!(f, "invalid rules: {}", reason),
+            BlackjackError::EmptyShoe { needed, remaining } => {
+                write!(f, "the shoe has {} card{} left but {} are needed", remaining, if *remaining == 1 { "" } else { "s" }, needed)
+            },
+            BlackjackError::IllegalAction(illegal) => write!(f, "{}", illegal),
+            BlackjackError::InvalidBet(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for BlackjackError {}
+
+impl From<BetError> for BlackjackError {
+    fn from(error: BetError) -> BlackjackError {
+        BlackjackError::InvalidBet(error)
+    }
+}
+
+impl From<IllegalAction> for BlackjackError {
+    fn from(illegal: IllegalAction) -> BlackjackError {
+        BlackjackError::IllegalAction(illegal)
+    }
+}
@@ -0,0 +1,213 @@
+use std::sync::mpsc::Sender;
+
+use crate::rules::{GameRules, Variant};
+use crate::sound::Sound;
+use crate::{continue_with_game, deal_with_action, Action, Card, Deck, GameState, HandOutcome, IllegalAction, SplitHand};
+
+// What happened at the table, in the order it happened, for a frontend to
+// show, sound or send on. A frontend that follows the events never needs to
+// compare one game state with the next itself.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UiEvent {
+    CardDealt { to: Seat, card: Card, face_up: bool },
+    // A dealer's card dealt face down and turned over in the dealer's turn.
+    HoleCardTurned { card: Card },
+    // `hand` counts the player's hands in play order, from 1; the dealer
+    // only has the one.
+    TurnStarted { seat: Seat, hand: usize },
+    OutcomeDecided { hand: usize, outcome: Outcome },
+    // Sent by whoever settles the bets, which the engine leaves to its caller.
+    BankrollChanged { net: i64, bankroll: i64 },
+}
+
+impl UiEvent {
+    // Whether anyone at the table could see it happen: everything but the
+    // cards the dealer is dealt face down, for spectators and other players.
+    pub fn is_public(&self) -> bool {
+        !matches!(self, UiEvent::CardDealt { face_up: false, .. })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Seat {
+    Player,
+    Dealer,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    Won,
+    Blackjack,
+    Lost,
+    Bust,
+    Surrendered,
+    Push,
+}
+
+impl Outcome {
+    pub fn sound(&self) -> Sound {
+        match self {
+            Outcome::Won => Sound::Won,
+            Outcome::Blackjack => Sound::Blackjack,
+            Outcome::Lost | Outcome::Surrendered => Sound::Lost,
+            Outcome::Bust => Sound::Bust,
+            Outcome::Push => Sound::Push,
+        }
+    }
+}
+
+// Where the events go: a channel to another thread, or a closure.
+pub trait EventSink {
+    fn emit(&mut self, event: UiEvent);
+}
+
+impl EventSink for Sender<UiEvent> {
+    // A receiver that hung up has stopped listening, which is no reason to
+    // stop the game.
+    fn emit(&mut self, event: UiEvent) {
+        let _ = self.send(event);
+    }
+}
+
+impl<F: FnMut(UiEvent)> EventSink for F {
+    fn emit(&mut self, event: UiEvent) {
+        self(event)
+    }
+}
+
+// How a finished hand came out; a hand still in play has no outcome.
+pub fn outcome_of(state: &GameState) -> Option<Outcome> {
+    match state {
+        GameState::BlackjackWin(_) => Option::Some(Outcome::Blackjack),
+        GameState::GameWon(_) => Option::Some(Outcome::Won),
+        GameState::GameLost(player_state) if player_state.hand.is_bust() => Option::Some(Outcome::Bust),
+        GameState::GameLost(_) => Option::Some(Outcome::Lost),
+        GameState::Surrendered(_) => Option::Some(Outcome::Surrendered),
+        GameState::Push(_) => Option::Some(Outcome::Push),
+        GameState::Continuing(_) => Option::None,
+    }
+}
+
+fn split_outcome(split: &SplitHand) -> Option<Outcome> {
+    split.outcome.as_ref().map(|outcome| match outcome {
+        HandOutcome::Won => Outcome::Won,
+        HandOutcome::Blackjack => Outcome::Blackjack,
+        HandOutcome::Lost if split.hand.is_bust() => Outcome::Bust,
+        HandOutcome::Lost => Outcome::Lost,
+        HandOutcome::Push => Outcome::Push,
+    })
+}
+
+// Only the upcard is dealt face up, and in pontoon not even that, unless the
+// dealer plays with the hole card showing.
+fn face_up_when_dealt(rules: &GameRules, position: usize) -> bool {
+    rules.dealer.policy().shows_hole_card() || (position == 0 && rules.variant != Variant::Pontoon)
+}
+
+// The opening deal in the order the cards came out of the shoe: the player,
+// the upcard, the player again and the hole card. A hand settled on the deal,
+// by a natural on either side, goes on to the dealer's turn and the outcome.
+pub fn deal_events(state: &GameState) -> Vec<UiEvent> {
+    let player_state = state.player_state();
+    let rules = &player_state.rules;
+    let mut events = Vec::new();
+    for round in 0..2 {
+        if let Option::Some(card) = player_state.hand.get(round) {
+            events.push(UiEvent::CardDealt { to: Seat::Player, card: *card, face_up: true });
+        }
+        if let Option::Some(dealt) = player_state.dealer_hand.get(round) {
+            let face_up = face_up_when_dealt(rules, round);
+            events.push(UiEvent::CardDealt { to: Seat::Dealer, card: dealt.card, face_up });
+        }
+    }
+    let turned: Vec<&Card> = player_state
+        .dealer_hand
+        .iter()
+        .enumerate()
+        .filter(|(position, dealt)| dealt.face_up && !face_up_when_dealt(rules, *position))
+        .map(|(_, dealt)| &dealt.card)
+        .collect();
+    if !turned.is_empty() {
+        events.push(UiEvent::TurnStarted { seat: Seat::Dealer, hand: 1 });
+        events.extend(turned.into_iter().map(|card| UiEvent::HoleCardTurned { card: *card }));
+    }
+    if continue_with_game(state) {
+        events.push(UiEvent::TurnStarted { seat: Seat::Player, hand: 1 });
+    }
+    if let Option::Some(outcome) = outcome_of(state) {
+        events.push(UiEvent::OutcomeDecided { hand: player_state.hand_count(), outcome });
+    }
+    events
+}
+
+// What an action changed: the cards it dealt the player, a move to the next
+// hand of a split, the dealer's turn once the player is done, and the
+// outcomes it decided.
+pub fn changes(before: &GameState, after: &GameState) -> Vec<UiEvent> {
+    let (old, new) = (before.player_state(), after.player_state());
+    let mut events = Vec::new();
+
+    // A split moves cards between hands, so the new cards are those the
+    // player didn't have before, wherever they ended up.
+    let mut held = old.player_cards();
+    for card in new.player_cards() {
+        match held.iter().position(|old_card| *old_card == card) {
+            Option::Some(position) => {
+                held.remove(position);
+            },
+            Option::None => events.push(UiEvent::CardDealt { to: Seat::Player, card, face_up: true }),
+        }
+    }
+    if continue_with_game(after) && new.split_hands.len() != old.split_hands.len() {
+        events.push(UiEvent::TurnStarted { seat: Seat::Player, hand: new.split_hands.len() + 1 });
+    }
+
+    let turned: Vec<&Card> = new
+        .dealer_hand
+        .iter()
+        .zip(old.dealer_hand.iter())
+        .filter(|(now, then)| now.face_up && !then.face_up)
+        .map(|(now, _)| &now.card)
+        .collect();
+    let drawn = &new.dealer_hand[old.dealer_hand.len().min(new.dealer_hand.len())..];
+    if !turned.is_empty() || !drawn.is_empty() {
+        events.push(UiEvent::TurnStarted { seat: Seat::Dealer, hand: 1 });
+        events.extend(turned.into_iter().map(|card| UiEvent::HoleCardTurned { card: *card }));
+        events.extend(drawn.iter().map(|dealt| UiEvent::CardDealt {
+            to: Seat::Dealer,
+            card: dealt.card,
+            face_up: dealt.face_up,
+        }));
+    }
+
+    for (hand, split) in new.split_hands.iter().enumerate() {
+        let decided_before = old.split_hands.get(hand).is_some_and(|split| split.outcome.is_some());
+        if let (false, Option::Some(outcome)) = (decided_before, split_outcome(split)) {
+            events.push(UiEvent::OutcomeDecided { hand: hand + 1, outcome });
+        }
+    }
+    if let (Option::None, Option::Some(outcome)) = (outcome_of(before), outcome_of(after)) {
+        events.push(UiEvent::OutcomeDecided { hand: new.split_hands.len() + 1, outcome });
+    }
+    events
+}
+
+// Starts a game and sends the deal to `sink`.
+pub fn start(deck: Deck, rules: GameRules, sink: &mut dyn EventSink) -> GameState {
+    let state = GameState::start_with_rules(deck, rules);
+    for event in deal_events(&state) {
+        sink.emit(event);
+    }
+    state
+}
+
+// `deal_with_action`, sending what the action changed to `sink`. An illegal
+// action changes nothing and sends nothing.
+pub fn act(action: &Action, state: GameState, sink: &mut dyn EventSink) -> Result<GameState, IllegalAction> {
+    let before = state.clone();
+    let after = deal_with_action(action, state)?;
+    for event in changes(&before, &after) {
+        sink.emit(event);
+    }
+    Ok(after)
+}
@@ -0,0 +1,148 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::json::JsonValue;
+use crate::protocol::{encode_action, encode_card};
+use crate::{Action, Card, GameState};
+
+// One finished hand, as written to an export file.
+#[derive(Clone, Debug)]
+pub struct HandRecord {
+    pub hand: u64,
+    pub player_cards: Vec<Card>,
+    pub dealer_cards: Vec<Card>,
+    pub actions: Vec<Action>,
+    // Hi-Lo, from the cards seen before the hand was dealt.
+    pub running_count: i32,
+    pub true_count: f64,
+    pub bet: u64,
+    pub outcome: &'static str,
+    pub net: i64,
+    // After the hand was settled.
+    pub bankroll: i64,
+}
+
+pub fn outcome_name(state: &GameState) -> &'static str {
+    match state {
+        GameState::GameWon(_) => "won",
+        GameState::BlackjackWin(_) => "blackjack",
+        GameState::GameLost(_) => "lost",
+        GameState::Push(_) => "push",
+        GameState::Surrendered(_) => "surrendered",
+        GameState::Continuing(_) => "unfinished",
+    }
+}
+
+fn cards_field(cards: &[Card]) -> String {
+    cards.iter().map(encode_card).collect::<Vec<String>>().join(" ")
+}
+
+fn actions_field(actions: &[Action]) -> String {
+    actions.iter().map(encode_action).collect::<Vec<&str>>().join(" ")
+}
+
+impl HandRecord {
+    pub const CSV_HEADER: &'static str =
+        "hand,player_cards,dealer_cards,actions,running_count,true_count,bet,outcome,net,bankroll";
+
+    // Cards and actions are space separated within their column, so no field
+    // ever needs quoting.
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{:.2},{},{},{},{}",
+            self.hand,
+            cards_field(&self.player_cards),
+            cards_field(&self.dealer_cards),
+            actions_field(&self.actions),
+            self.running_count,
+            self.true_count,
+            self.bet,
+            self.outcome,
+            self.net,
+            self.bankroll
+        )
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let cards = |cards: &[Card]| JsonValue::Array(cards.iter().map(|card| JsonValue::String(encode_card(card))).collect());
+        JsonValue::object(vec![
+            ("hand", JsonValue::Number(self.hand as f64)),
+            ("player_cards", cards(&self.player_cards)),
+            ("dealer_cards", cards(&self.dealer_cards)),
+            (
+                "actions",
+                JsonValue::Array(self.actions.iter().map(|action| JsonValue::String(encode_action(action).to_string())).collect()),
+            ),
+            ("running_count", JsonValue::Number(self.running_count as f64)),
+            ("true_count", JsonValue::Number(self.true_count)),
+            ("bet", JsonValue::Number(self.bet as f64)),
+            ("outcome", JsonValue::String(self.outcome.to_string())),
+            ("net", JsonValue::Number(self.net as f64)),
+            ("bankroll", JsonValue::Number(self.bankroll as f64)),
+        ])
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    // Picked by the file's extension.
+    pub fn from_path(path: &Path) -> Option<ExportFormat> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Option::Some("csv") => Option::Some(ExportFormat::Csv),
+            Option::Some("json") => Option::Some(ExportFormat::Json),
+            _ => Option::None,
+        }
+    }
+}
+
+// Writes hand records as they are played, so a long run never holds them
+// all. A JSON export is one array of records, closed by `finish`.
+pub struct Exporter {
+    format: ExportFormat,
+    out: BufWriter<File>,
+    records: u64,
+}
+
+impl Exporter {
+    pub fn create(path: &Path) -> io::Result<Exporter> {
+        let format = ExportFormat::from_path(path).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("{} is neither .csv nor .json", path.display()))
+        })?;
+        let mut out = BufWriter::new(File::create(path)?);
+        match format {
+            ExportFormat::Csv => writeln!(out, "{}", HandRecord::CSV_HEADER)?,
+            ExportFormat::Json => write!(out, "[")?,
+        }
+        Ok(Exporter { format, out, records: 0 })
+    }
+
+    pub fn write(&mut self, record: &HandRecord) -> io::Result<()> {
+        match self.format {
+            ExportFormat::Csv => writeln!(self.out, "{}", record.to_csv())?,
+            ExportFormat::Json => {
+                let separator = if self.records == 0 { "\n" } else { ",\n" };
+                write!(self.out, "{}{}", separator, record.to_json())?;
+            },
+        }
+        self.records += 1;
+        Ok(())
+    }
+
+    // How many records have been written.
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        if self.format == ExportFormat::Json {
+            writeln!(self.out, "\n]")?;
+        }
+        self.out.flush()
+    }
+}
@@ -0,0 +1,177 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use rand::{OsRng, Rng};
+
+use crate::protocol::{decode_card, encode_card};
+use crate::{Card, Deck};
+
+// Commit-reveal fairness for a shoe dealt by a server the players have no
+// reason to trust. Before the first hand the server publishes a commitment:
+// the SHA-256 hash of a secret nonce and the shoe in the order it deals. Once
+// the shoe is done it reveals both, and anyone can check that they hash to
+// the commitment, so the order can't have been changed mid-shoe. The nonce
+// keeps the order from being guessed from the hash before the reveal.
+
+// Who has the hash alone knows nothing of the order.
+const NONCE_BYTES: usize = 16;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FairnessError {
+    // What was revealed hashes to something other than its commitment.
+    Mismatch { committed: String, revealed: String },
+    // A revealed card that doesn't decode.
+    InvalidCard(String),
+    // A step of the joint shuffle taken before its turn.
+    OutOfOrder(&'static str),
+}
+
+impl fmt::Display for FairnessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FairnessError::Mismatch { committed, revealed } => {
+                write!(f, "what was revealed hashes to {} but {} was committed to", revealed, committed)
+            },
+            FairnessError::InvalidCard(raw) => write!(f, "the revealed shoe holds {:?}, which is not a card", raw),
+            FairnessError::OutOfOrder(reason) => write!(f, "out of order: {}", reason),
+        }
+    }
+}
+
+impl Error for FairnessError {}
+
+// What the server reveals at the end of the shoe. `order` is the shoe's
+// card codes, first dealt first, burned cards included.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Reveal {
+    pub nonce: String,
+    pub order: String,
+}
+
+// A shoe, as committed to before its first hand.
+pub struct SealedShoe {
+    reveal: Reveal,
+    commitment: String,
+}
+
+impl SealedShoe {
+    pub fn seal(deck: &Deck, nonce: &str) -> SealedShoe {
+        let order = shoe_order(deck);
+        let commitment = commit(nonce, &order);
+        SealedShoe { reveal: Reveal { nonce: nonce.to_string(), order }, commitment }
+    }
+
+    // Sealed with a nonce from the operating system's generator.
+    pub fn seal_fresh(deck: &Deck) -> io::Result<SealedShoe> {
+        Ok(SealedShoe::seal(deck, &new_nonce()?))
+    }
+
+    pub fn commitment(&self) -> &str {
+        &self.commitment
+    }
+
+    pub fn reveal(&self) -> &Reveal {
+        &self.reveal
+    }
+}
+
+pub fn new_nonce() -> io::Result<String> {
+    let mut bytes = [0; NONCE_BYTES];
+    OsRng::new()?.fill_bytes(&mut bytes);
+    Ok(hex(&bytes))
+}
+
+// The shoe as it deals: whatever was burned off the top, then the cards
+// still to come, next card first.
+pub fn shoe_order(deck: &Deck) -> String {
+    let cards: Vec<String> = deck.burned_cards.iter().chain(deck.remaining_cards.iter().rev()).map(encode_card).collect();
+    cards.join(" ")
+}
+
+pub fn commit(nonce: &str, order: &str) -> String {
+    hex(&sha256(format!("{}:{}", nonce, order).as_bytes()))
+}
+
+// Checks a reveal against the commitment made for it, returning the shoe's
+// cards in dealing order for a client to compare with what it was dealt.
+pub fn verify(commitment: &str, reveal: &Reveal) -> Result<Vec<Card>, FairnessError> {
+    let revealed = commit(&reveal.nonce, &reveal.order);
+    if !revealed.eq_ignore_ascii_case(commitment.trim()) {
+        return Err(FairnessError::Mismatch { committed: commitment.trim().to_string(), revealed });
+    }
+    reveal
+        .order
+        .split_whitespace()
+        .map(|code| decode_card(code).ok_or_else(|| FairnessError::InvalidCard(code.to_string())))
+        .collect()
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+const ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+// SHA-256, as in FIPS 180-4. A shoe is a few hundred bytes, so the message
+// is padded in one piece rather than streamed.
+pub fn sha256(input: &[u8]) -> [u8; 32] {
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+    ];
+    let mut message = input.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((input.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut words = [0u32; 64];
+        for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for index in 16..64 {
+            let (early, late) = (words[index - 15], words[index - 2]);
+            let s0 = early.rotate_right(7) ^ early.rotate_right(18) ^ (early >> 3);
+            let s1 = late.rotate_right(17) ^ late.rotate_right(19) ^ (late >> 10);
+            words[index] = words[index - 16].wrapping_add(s0).wrapping_add(words[index - 7]).wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for (constant, word) in ROUND_CONSTANTS.iter().zip(words.iter()) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let choice = (e & f) ^ (!e & g);
+            let t1 = h.wrapping_add(s1).wrapping_add(choice).wrapping_add(*constant).wrapping_add(*word);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let majority = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(majority);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (value, added) in state.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+            *value = value.wrapping_add(*added);
+        }
+    }
+
+    let mut digest = [0u8; 32];
+    for (bytes, value) in digest.chunks_mut(4).zip(state.iter()) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
@@ -0,0 +1,104 @@
+#[cfg(feature = "std")]
+use rand::{Rng, SeedableRng};
+
+#[cfg(not(feature = "std"))]
+use crate::shuffle::{ReshuffleKey, Shuffler};
+#[cfg(not(feature = "std"))]
+use crate::Card;
+
+// xoshiro256**, a small, fast generator for simulations: a few shifts and
+// rotations a number, against ISAAC's table behind `StdRng`. It is not
+// cryptographic and must never shuffle for a real table. Its output is
+// fixed by the algorithm, so a seed plays the same hands in every version.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Xoshiro256 {
+    state: [u64; 4],
+}
+
+// Spreads a seed over the whole state, as the algorithm's authors advise,
+// so that nearby seeds start far apart.
+fn split_mix(seed: &mut u64) -> u64 {
+    *seed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *seed;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+impl Xoshiro256 {
+    pub fn seed_from_u64(mut seed: u64) -> Xoshiro256 {
+        let mut state = [0; 4];
+        for word in state.iter_mut() {
+            *word = split_mix(&mut seed);
+        }
+        Xoshiro256 { state }
+    }
+
+    // The state as it is, for known-answer tests. All zeros is the one
+    // state that never leaves zero.
+    pub fn from_state(state: [u64; 4]) -> Xoshiro256 {
+        Xoshiro256 { state }
+    }
+
+    fn advance(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    // A Fisher-Yates shuffle for builds without `rand`. The modulo leans a
+    // little towards low slots, far too little to see in a shoe.
+    #[cfg(not(feature = "std"))]
+    pub fn shuffle_cards(&mut self, cards: &mut [Card]) {
+        for index in (1..cards.len()).rev() {
+            let other = (self.advance() % (index as u64 + 1)) as usize;
+            cards.swap(index, other);
+        }
+    }
+}
+
+// What shuffles without `rand`.
+#[cfg(not(feature = "std"))]
+impl Shuffler for Xoshiro256 {
+    fn shuffle(&mut self, cards: &mut [Card]) {
+        self.shuffle_cards(cards)
+    }
+
+    fn reshuffle_key(&mut self) -> ReshuffleKey {
+        let mut key = [0; 8];
+        for word in key.iter_mut() {
+            *word = (self.advance() >> 32) as u32;
+        }
+        key
+    }
+}
+
+#[cfg(feature = "std")]
+impl Rng for Xoshiro256 {
+    fn next_u32(&mut self) -> u32 {
+        // The high bits are the strongest.
+        (self.advance() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.advance()
+    }
+}
+
+#[cfg(feature = "std")]
+impl SeedableRng<u64> for Xoshiro256 {
+    fn reseed(&mut self, seed: u64) {
+        *self = Xoshiro256::seed_from_u64(seed);
+    }
+
+    fn from_seed(seed: u64) -> Xoshiro256 {
+        Xoshiro256::seed_from_u64(seed)
+    }
+}
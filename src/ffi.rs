@@ -0,0 +1,166 @@
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+
+use crate::wasm::{apply_action, new_game, state_json, Game};
+use crate::{calculate_current_hand_value, Card, CardSuit, CardValue, GameState};
+
+// C view of the engine. The matching declarations live in
+// include/rust_blackjack.h; keep the two in sync.
+
+// Hands longer than this are cut short in `BlackjackState`; the JSON keeps
+// every card.
+pub const BLACKJACK_MAX_CARDS: usize = 12;
+
+pub const BLACKJACK_CONTINUING: i32 = 0;
+pub const BLACKJACK_WON: i32 = 1;
+pub const BLACKJACK_LOST: i32 = 2;
+pub const BLACKJACK_PUSH: i32 = 3;
+pub const BLACKJACK_SURRENDERED: i32 = 4;
+pub const BLACKJACK_BLACKJACK_WIN: i32 = 5;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BlackjackCard {
+    // 2-10 for pip cards, 11 jack, 12 queen, 13 king, 14 ace.
+    pub rank: u8,
+    // 0 clubs, 1 hearts, 2 diamonds, 3 spades.
+    pub suit: u8,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BlackjackState {
+    pub status: i32,
+    // Highest hand value that doesn't exceed 21, or 0 once busted.
+    pub best_value: u32,
+    pub hand_len: u32,
+    pub hand: [BlackjackCard; BLACKJACK_MAX_CARDS],
+    // Face-up dealer cards only; the hole card shows up once the round is over.
+    pub dealer_len: u32,
+    pub dealer: [BlackjackCard; BLACKJACK_MAX_CARDS],
+}
+
+fn to_c_card(card: &Card) -> BlackjackCard {
+    let rank = match card.value {
+        CardValue::Two => 2,
+        CardValue::Three => 3,
+        CardValue::Four => 4,
+        CardValue::Five => 5,
+        CardValue::Six => 6,
+        CardValue::Seven => 7,
+        CardValue::Eight => 8,
+        CardValue::Nine => 9,
+        CardValue::Ten => 10,
+        CardValue::Jack => 11,
+        CardValue::Queen => 12,
+        CardValue::King => 13,
+        CardValue::Ace => 14,
+    };
+    let suit = match card.suit {
+        CardSuit::Clubs => 0,
+        CardSuit::Hearts => 1,
+        CardSuit::Diamonds => 2,
+        CardSuit::Spades => 3,
+    };
+    BlackjackCard { rank, suit }
+}
+
+fn copy_cards(cards: &[Card], out: &mut [BlackjackCard; BLACKJACK_MAX_CARDS]) -> u32 {
+    for (slot, card) in out.iter_mut().zip(cards.iter()) {
+        *slot = to_c_card(card);
+    }
+    cards.len().min(BLACKJACK_MAX_CARDS) as u32
+}
+
+#[no_mangle]
+pub extern "C" fn blackjack_game_new(seed: u32) -> *mut Game {
+    Box::into_raw(Box::new(new_game(seed)))
+}
+
+/// # Safety
+///
+/// `game` must come from `blackjack_game_new` and not have been freed, and
+/// `action` must be a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn blackjack_game_apply_action(game: *mut Game, action: *const c_char) -> i32 {
+    if game.is_null() || action.is_null() {
+        return -1;
+    }
+    let action = match CStr::from_ptr(action).to_str() {
+        Ok(action) => action,
+        Err(_) => return -1,
+    };
+    if apply_action(&mut *game, action) { 0 } else { -1 }
+}
+
+/// # Safety
+///
+/// `game` must come from `blackjack_game_new` and not have been freed, and
+/// `out` must point to writable memory for one `BlackjackState`.
+#[no_mangle]
+pub unsafe extern "C" fn blackjack_game_state(game: *const Game, out: *mut BlackjackState) -> i32 {
+    if game.is_null() || out.is_null() {
+        return -1;
+    }
+    let state = match (*game).state() {
+        Option::Some(state) => state,
+        Option::None => return -1,
+    };
+    let player_state = state.player_state();
+    let mut result = BlackjackState {
+        status: match state {
+            GameState::Continuing(_) => BLACKJACK_CONTINUING,
+            GameState::GameWon(_) => BLACKJACK_WON,
+            GameState::GameLost(_) => BLACKJACK_LOST,
+            GameState::Push(_) => BLACKJACK_PUSH,
+            GameState::Surrendered(_) => BLACKJACK_SURRENDERED,
+            GameState::BlackjackWin(_) => BLACKJACK_BLACKJACK_WIN,
+        },
+        best_value: calculate_current_hand_value(&player_state.create_hand_values())
+            .iter()
+            .map(|hand_value| hand_value.value)
+            .max()
+            .unwrap_or(0),
+        ..BlackjackState::default()
+    };
+    result.hand_len = copy_cards(&player_state.hand, &mut result.hand);
+    result.dealer_len = copy_cards(&player_state.visible_dealer_cards(), &mut result.dealer);
+    *out = result;
+    0
+}
+
+/// # Safety
+///
+/// `game` must come from `blackjack_game_new` and not have been freed. The
+/// returned string must be released with `blackjack_string_free`.
+#[no_mangle]
+pub unsafe extern "C" fn blackjack_game_state_json(game: *const Game) -> *mut c_char {
+    if game.is_null() {
+        return ptr::null_mut();
+    }
+    match CString::new(state_json(&*game)) {
+        Ok(json) => json.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// # Safety
+///
+/// `json` must come from `blackjack_game_state_json` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn blackjack_string_free(json: *mut c_char) {
+    if !json.is_null() {
+        drop(CString::from_raw(json));
+    }
+}
+
+/// # Safety
+///
+/// `game` must come from `blackjack_game_new` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn blackjack_game_free(game: *mut Game) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
@@ -0,0 +1,65 @@
+use crate::counting::hi_lo_value;
+use crate::scripted::ScriptedCardSource;
+use crate::{Card, CardValue, Deck};
+
+// Curated shoes for tests, drills and documentation, addressable by name.
+// Each is a fixed arrangement of real decks, so the card counts stay honest.
+
+pub const FIXTURE_NAMES: [&str; 3] = ["ace-rich", "count-extreme", "split-heavy"];
+
+fn unshuffled_cards(decks: u32) -> Vec<Card> {
+    let mut deck = Deck::with_decks(decks);
+    let mut cards = Vec::new();
+    while let Option::Some(card) = deck.draw_card() {
+        cards.push(card);
+    }
+    cards
+}
+
+fn is_low(card: &Card) -> bool {
+    hi_lo_value(&card.value) > 0
+}
+
+// Two decks with an ace in every third position until the aces run out.
+fn ace_rich() -> Vec<Card> {
+    let (mut aces, mut others): (Vec<Card>, Vec<Card>) =
+        unshuffled_cards(2).into_iter().partition(|card| card.value == CardValue::Ace);
+    let mut result = Vec::new();
+    while !others.is_empty() || !aces.is_empty() {
+        for _ in 0..2 {
+            if let Option::Some(card) = others.pop() {
+                result.push(card);
+            }
+        }
+        if let Option::Some(card) = aces.pop() {
+            result.push(card);
+        }
+    }
+    result
+}
+
+// Two decks with every low card first, driving the Hi-Lo count to +40 before
+// the tens and aces arrive.
+fn count_extreme() -> Vec<Card> {
+    let (mut low, rest): (Vec<Card>, Vec<Card>) = unshuffled_cards(2).into_iter().partition(is_low);
+    low.extend(rest);
+    low
+}
+
+// Two decks arranged as runs of equal ranks, so almost every pair of
+// consecutive cards is a splittable pair.
+fn split_heavy() -> Vec<Card> {
+    let mut cards = unshuffled_cards(2);
+    cards.sort_by_key(|card| CardValue::ALL_VALUES.iter().position(|value| *value == card.value));
+    cards
+}
+
+pub fn fixture(name: &str) -> Option<ScriptedCardSource> {
+    let cards = match name {
+        "ace-rich" => ace_rich(),
+        "count-extreme" => count_extreme(),
+        "split-heavy" => split_heavy(),
+        _ => return Option::None,
+    };
+    Option::Some(ScriptedCardSource::new(cards))
+}
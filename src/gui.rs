@@ -0,0 +1,211 @@
+use rand::{SeedableRng, StdRng};
+
+use crate::chips::TableLimits;
+use crate::events::{self, Seat, UiEvent};
+use crate::protocol::encode_card;
+use crate::rules::GameRules;
+use crate::settlement::settle;
+use crate::{continue_with_game, game_message, Action, Card, Deck, GameState};
+
+// The desktop table, without the window: what a frame shows and what its
+// buttons and slider do. An eframe app keeps a `TableWindow`, draws `view()`
+// each frame and calls `deal`, `press` and `set_bet` from the widgets, so the
+// window code never touches the engine itself. Everything here goes through
+// the engine's public API alone, which keeps the engine honest about having
+// no terminal in it.
+pub struct TableWindow {
+    rules: GameRules,
+    limits: TableLimits,
+    rng: StdRng,
+    state: Option<GameState>,
+    bankroll: i64,
+    bet: u64,
+    // What happened in the last hand, oldest first, for the log pane.
+    log: Vec<String>,
+}
+
+pub struct Button {
+    pub label: &'static str,
+    pub action: Action,
+    pub enabled: bool,
+}
+
+pub struct Slider {
+    pub minimum: u64,
+    pub maximum: u64,
+    pub step: u64,
+    pub value: u64,
+}
+
+pub struct View {
+    pub dealer: String,
+    pub hands: Vec<String>,
+    // One per action, in menu order; those not legal right now are greyed
+    // out rather than hidden, so the buttons don't jump about.
+    pub buttons: Vec<Button>,
+    pub bet: Slider,
+    // The bet can only change, and a hand only be dealt, between hands.
+    pub can_deal: bool,
+    pub message: String,
+    pub bankroll: i64,
+    pub log: Vec<String>,
+}
+
+fn cards_text(cards: &[Card]) -> String {
+    cards.iter().map(encode_card).collect::<Vec<String>>().join(" ")
+}
+
+fn describe(event: &UiEvent) -> Option<String> {
+    match event {
+        UiEvent::CardDealt { to: Seat::Player, card, .. } => Option::Some(format!("You: {}", encode_card(card))),
+        UiEvent::CardDealt { card, face_up: true, .. } | UiEvent::HoleCardTurned { card } => {
+            Option::Some(format!("Dealer: {}", encode_card(card)))
+        },
+        UiEvent::CardDealt { face_up: false, .. } => Option::Some("Dealer: face down".to_string()),
+        UiEvent::OutcomeDecided { hand, outcome } => Option::Some(format!("Hand {}: {:?}", hand, outcome)),
+        UiEvent::BankrollChanged { net, bankroll } => Option::Some(format!("Net {:+}, bankroll {}", net, bankroll)),
+        UiEvent::TurnStarted { .. } => Option::None,
+    }
+}
+
+impl TableWindow {
+    // A seeded window deals the same shoe every time, like `play --seed`.
+    pub fn new(rules: GameRules, limits: TableLimits, bankroll: u64, seed: u32) -> TableWindow {
+        let bet = limits.minimum;
+        TableWindow {
+            rules,
+            limits,
+            rng: SeedableRng::from_seed(&[seed as usize][..]),
+            state: Option::None,
+            bankroll: bankroll as i64,
+            bet,
+            log: Vec::new(),
+        }
+    }
+
+    fn in_hand(&self) -> bool {
+        self.state.as_ref().is_some_and(continue_with_game)
+    }
+
+    fn available(&self) -> u64 {
+        self.bankroll.max(0) as u64
+    }
+
+    fn record(&mut self, events: Vec<UiEvent>) {
+        self.log.extend(events.iter().filter_map(describe));
+    }
+
+    // Slides the bet to within the table limits and the bankroll, on the
+    // slider's steps. Ignored mid-hand.
+    pub fn set_bet(&mut self, bet: u64) {
+        if !self.in_hand() {
+            let step = self.limits.minimum.max(1);
+            let bet = self.limits.clamp(bet).min(self.available());
+            self.bet = (bet / step * step).max(self.limits.minimum);
+        }
+    }
+
+    // Deals the next hand from the shoe, shuffling a new one when the cut
+    // card is out. Returns false mid-hand, or when the bankroll can't cover
+    // the bet.
+    pub fn deal(&mut self) -> bool {
+        if self.in_hand() || self.limits.check(self.bet, self.available()).is_err() {
+            return false;
+        }
+        // Before the first hand there is no shoe yet, which is as good as
+        // one that needs shuffling.
+        let mut shoe = self.state.take().map(GameState::discard_hand).unwrap_or_else(|| Deck::with_decks(0));
+        if shoe.needs_shuffle() {
+            shoe = Deck::with_decks(self.rules.decks);
+            shoe.shuffle(&mut self.rng);
+            shoe.burn();
+            shoe.place_cut_card(self.rules.penetration);
+        }
+        let mut events = Vec::new();
+        let state = events::start(shoe, self.rules.clone(), &mut |event| events.push(event));
+        self.log.clear();
+        self.record(events);
+        self.state = Option::Some(state);
+        self.settle_if_over();
+        true
+    }
+
+    // Plays a button's action. Returns false when it isn't legal, which a
+    // greyed-out button never sends.
+    pub fn press(&mut self, action: &Action) -> bool {
+        let state = match self.state.take() {
+            Option::Some(state) if continue_with_game(&state) => state,
+            other => {
+                self.state = other;
+                return false;
+            },
+        };
+        let mut events = Vec::new();
+        let (state, played) = match events::act(action, state, &mut |event| events.push(event)) {
+            Ok(state) => (state, true),
+            Err(illegal) => (*illegal.state, false),
+        };
+        self.record(events);
+        self.state = Option::Some(state);
+        self.settle_if_over();
+        played
+    }
+
+    fn settle_if_over(&mut self) {
+        match &self.state {
+            Option::Some(state) if !continue_with_game(state) => {
+                let settlement = settle(state, self.bet, &[]);
+                self.bankroll += settlement.net;
+                let event = UiEvent::BankrollChanged { net: settlement.net, bankroll: self.bankroll };
+                self.record(vec![event]);
+            },
+            _ => (),
+        }
+    }
+
+    pub fn view(&self) -> View {
+        let legal = self.state.as_ref().filter(|state| continue_with_game(state)).map(GameState::legal_actions);
+        let buttons = Action::ALL_VALUES
+            .iter()
+            .map(|action| Button {
+                label: self.rules.variant.action_name(action),
+                action: action.clone(),
+                enabled: legal.as_ref().is_some_and(|legal| legal.contains(action)),
+            })
+            .collect();
+        let bet = Slider {
+            minimum: self.limits.minimum,
+            maximum: self.limits.maximum.min(self.available()).max(self.limits.minimum),
+            step: self.limits.minimum.max(1),
+            value: self.bet,
+        };
+        let (dealer, hands, message) = match &self.state {
+            Option::Some(state) => {
+                let player_state = state.player_state();
+                let hidden = player_state.hidden_dealer_cards().len();
+                let mut dealer = cards_text(&player_state.visible_dealer_cards());
+                dealer.push_str(&" ##".repeat(hidden));
+                let hands = player_state
+                    .split_hands
+                    .iter()
+                    .map(|split| cards_text(&split.hand))
+                    .chain(std::iter::once(cards_text(&player_state.hand)))
+                    .chain(player_state.waiting_hands.iter().rev().map(|hand| cards_text(hand)))
+                    .collect();
+                let message = if continue_with_game(state) { String::new() } else { game_message(state).to_string() };
+                (dealer.trim().to_string(), hands, message)
+            },
+            Option::None => (String::new(), Vec::new(), "Place your bet and deal".to_string()),
+        };
+        View {
+            dealer,
+            hands,
+            buttons,
+            bet,
+            can_deal: !self.in_hand(),
+            message,
+            bankroll: self.bankroll,
+            log: self.log.clone(),
+        }
+    }
+}
@@ -0,0 +1,491 @@
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::dealer::DealerProfile;
+use crate::events::{self, UiEvent};
+use crate::json::JsonValue;
+use crate::payout::{PayoutRounding, Rounding};
+use crate::protocol::{decode_card, encode_action, encode_card};
+use crate::rules::{DoubleRule, EmptyShoe, GameRules, HoleCard, Variant};
+use crate::scripted::ScriptedCardSource;
+use crate::shuffle::ShuffleModel;
+use crate::{continue_with_game, parse_action, Action, Card, Deck, GameState};
+
+// Hand histories: a record of every round played, with enough in it to deal
+// the round again, for analysis tools and the replayer. HAND_HISTORY.md
+// documents the format. Files start with a line naming the format and its
+// version, and a reader turns down versions it doesn't know rather than
+// guessing at them.
+
+pub const HISTORY_VERSION: u32 = 1;
+const TEXT_HEADER: &str = "# rust-blackjack hand history v";
+const JSON_FORMAT: &str = "rust-blackjack hand history";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum HistoryError {
+    // The first line names neither form of the format.
+    UnknownFormat,
+    UnsupportedVersion(String),
+    // Line numbers count from 1, the header included.
+    Malformed { line: usize, reason: String },
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HistoryError::UnknownFormat => write!(f, "not a rust-blackjack hand history"),
+            HistoryError::UnsupportedVersion(version) => {
+                write!(f, "hand history version {} is not supported, only version {}", version, HISTORY_VERSION)
+            },
+            HistoryError::Malformed { line, reason } => write!(f, "line {}: {}", line, reason),
+        }
+    }
+}
+
+impl Error for HistoryError {}
+
+// One round, from the player's seat.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryHand {
+    pub hand: u64,
+    pub rules: GameRules,
+    pub bet: u64,
+    // Every card the round took from the shoe, in the order it came out.
+    // None when the shoe ran out and was reshuffled mid-round, which loses
+    // the order of the cards drawn before it.
+    pub shoe: Option<Vec<Card>>,
+    pub actions: Vec<Action>,
+    // The player left mid-hand, after `actions`, surrendering it.
+    pub left: bool,
+    // In the order they were played, one unless the player split.
+    pub player_hands: Vec<Vec<Card>>,
+    pub dealer_cards: Vec<Card>,
+    pub outcome: String,
+    pub net: i64,
+    pub bankroll: i64,
+}
+
+// The cards taken from `before` to leave `after`, first drawn first, unless
+// the discards were shuffled back in along the way.
+pub fn cards_drawn(before: &Deck, after: &Deck) -> Option<Vec<Card>> {
+    let (remaining, left) = (before.remaining_cards.len(), after.remaining_cards.len());
+    if left > remaining || after.discard_tray.len() < before.discard_tray.len() {
+        return Option::None;
+    }
+    Option::Some(before.remaining_cards[left..].iter().rev().cloned().collect())
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
+
+// Every rule, as space separated `key=value` pairs.
+pub fn encode_rules(rules: &GameRules) -> String {
+    [
+        format!("variant={}", if rules.variant == Variant::Pontoon { "pontoon" } else { "blackjack" }),
+        format!("decks={}", rules.decks),
+        format!("soft17={}", if rules.dealer_hits_soft_17 { "hit" } else { "stand" }),
+        format!("dealer={}", rules.dealer.name()),
+        format!("surrender={}", yes_no(rules.surrender)),
+        format!("payout={}:{}", rules.blackjack_numerator, rules.blackjack_denominator),
+        format!("hole-card={}", if rules.hole_card == HoleCard::Dealt { "dealt" } else { "after-players" }),
+        format!("peek={}", yes_no(rules.peek)),
+        format!("double={}", if rules.double_on == DoubleRule::AnyTwoCards { "any" } else { "9-11" }),
+        format!("shuffle={}", rules.shuffle_model.name()),
+        format!("penetration={}", rules.penetration),
+        format!("empty-shoe={}", rules.empty_shoe.name()),
+        format!("charlie={}", rules.charlie.map_or("none".to_string(), |cards| cards.to_string())),
+        format!("split-hands={}", rules.max_split_hands),
+        format!("split-aces-one-card={}", yes_no(rules.split_aces_one_card)),
+        format!("blackjack-after-split={}", yes_no(rules.blackjack_after_split)),
+        format!("double-after-split={}", yes_no(rules.double_after_split)),
+        format!("rounding={}:{}", rules.payout_rounding.rounding.name(), rules.payout_rounding.step),
+    ]
+    .join(" ")
+}
+
+// Keys left out keep their default, so that a rule added in a later version
+// reads as the game was before it.
+pub fn decode_rules(raw: &str) -> Result<GameRules, String> {
+    let mut rules = GameRules::default();
+    for pair in raw.split_whitespace() {
+        let (key, value) = pair.split_once('=').ok_or_else(|| format!("{:?} is not a key=value rule", pair))?;
+        let flag = || match value {
+            "yes" => Option::Some(true),
+            "no" => Option::Some(false),
+            _ => Option::None,
+        };
+        let number = || value.parse().ok();
+        let known = match key {
+            "variant" => match value {
+                "blackjack" => Option::Some(Variant::Blackjack),
+                "pontoon" => Option::Some(Variant::Pontoon),
+                _ => Option::None,
+            }
+            .map(|variant| rules.variant = variant),
+            "decks" => number().map(|decks| rules.decks = decks),
+            "soft17" => match value {
+                "hit" => Option::Some(true),
+                "stand" => Option::Some(false),
+                _ => Option::None,
+            }
+            .map(|hits| rules.dealer_hits_soft_17 = hits),
+            "dealer" => DealerProfile::from_name(value).map(|dealer| rules.dealer = dealer),
+            "surrender" => flag().map(|surrender| rules.surrender = surrender),
+            "payout" => GameRules::parse_payout(value).map(|(numerator, denominator)| {
+                rules.blackjack_numerator = numerator;
+                rules.blackjack_denominator = denominator;
+            }),
+            "hole-card" => match value {
+                "dealt" => Option::Some(HoleCard::Dealt),
+                "after-players" => Option::Some(HoleCard::AfterPlayers),
+                _ => Option::None,
+            }
+            .map(|hole_card| rules.hole_card = hole_card),
+            "peek" => flag().map(|peek| rules.peek = peek),
+            "double" => match value {
+                "any" => Option::Some(DoubleRule::AnyTwoCards),
+                "9-11" => Option::Some(DoubleRule::NineToEleven),
+                _ => Option::None,
+            }
+            .map(|double_on| rules.double_on = double_on),
+            "shuffle" => ShuffleModel::from_name(value).map(|model| rules.shuffle_model = model),
+            "penetration" => number().map(|penetration| rules.penetration = penetration),
+            "empty-shoe" => EmptyShoe::from_name(value).map(|empty_shoe| rules.empty_shoe = empty_shoe),
+            "charlie" if value == "none" => {
+                rules.charlie = Option::None;
+                Option::Some(())
+            },
+            "charlie" => value.parse().ok().map(|cards| rules.charlie = Option::Some(cards)),
+            "split-hands" => value.parse().ok().map(|hands| rules.max_split_hands = hands),
+            "split-aces-one-card" => flag().map(|one_card| rules.split_aces_one_card = one_card),
+            "blackjack-after-split" => flag().map(|natural| rules.blackjack_after_split = natural),
+            "double-after-split" => flag().map(|double| rules.double_after_split = double),
+            "rounding" => value
+                .split_once(':')
+                .and_then(|(rounding, step)| {
+                    Option::Some(PayoutRounding { rounding: Rounding::from_name(rounding)?, step: step.parse().ok()? })
+                })
+                .map(|payout_rounding| rules.payout_rounding = payout_rounding),
+            _ => return Err(format!("unknown rule {:?}", key)),
+        };
+        known.ok_or_else(|| format!("{:?} is not a value for {}", value, key))?;
+    }
+    rules.validate().map_err(|error| error.to_string())?;
+    Ok(rules)
+}
+
+fn cards_text(cards: &[Card]) -> String {
+    cards.iter().map(encode_card).collect::<Vec<String>>().join(" ")
+}
+
+fn decode_cards(raw: &str) -> Result<Vec<Card>, String> {
+    raw.split_whitespace().map(|code| decode_card(code).ok_or_else(|| format!("{:?} is not a card", code))).collect()
+}
+
+// The actions as typed, with `leave` last for a player who left mid-hand.
+fn decode_actions(words: &[&str]) -> Result<(Vec<Action>, bool), String> {
+    let (words, left) = match words.split_last() {
+        Option::Some((&"leave", played)) => (played, true),
+        _ => (words, false),
+    };
+    let actions = words.iter().map(|word| parse_action(word).ok_or_else(|| format!("{:?} is not an action", word))).collect::<Result<_, _>>()?;
+    Ok((actions, left))
+}
+
+impl HistoryHand {
+    fn action_words(&self) -> Vec<&'static str> {
+        let mut words: Vec<&'static str> = self.actions.iter().map(encode_action).collect();
+        if self.left {
+            words.push("leave");
+        }
+        words
+    }
+
+    // A block of `Key: value` lines, the shoe's left out when it is unknown.
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![
+            format!("Hand #{}", self.hand),
+            format!("Rules: {}", encode_rules(&self.rules)),
+            format!("Bet: {}", self.bet),
+        ];
+        if let Option::Some(shoe) = &self.shoe {
+            lines.push(format!("Shoe: {}", cards_text(shoe)));
+        }
+        let hands: Vec<String> = self.player_hands.iter().map(|hand| cards_text(hand)).collect();
+        lines.extend(vec![
+            format!("Actions: {}", self.action_words().join(" ")),
+            format!("Player: {}", hands.join(" | ")),
+            format!("Dealer: {}", cards_text(&self.dealer_cards)),
+            format!("Outcome: {}", self.outcome),
+            format!("Net: {:+}", self.net),
+            format!("Bankroll: {}", self.bankroll),
+        ]);
+        // Nothing trails a line with nothing in it, such as a hand settled
+        // before any action.
+        lines.iter().map(|line| line.trim_end()).collect::<Vec<&str>>().join("\n")
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let cards = |cards: &[Card]| JsonValue::Array(cards.iter().map(|card| JsonValue::String(encode_card(card))).collect());
+        let mut fields = vec![
+            ("hand", JsonValue::Number(self.hand as f64)),
+            ("rules", JsonValue::String(encode_rules(&self.rules))),
+            ("bet", JsonValue::Number(self.bet as f64)),
+            ("actions", JsonValue::Array(self.action_words().into_iter().map(|word| JsonValue::String(word.to_string())).collect())),
+            ("player", JsonValue::Array(self.player_hands.iter().map(|hand| cards(hand)).collect())),
+            ("dealer", cards(&self.dealer_cards)),
+            ("outcome", JsonValue::String(self.outcome.clone())),
+            ("net", JsonValue::Number(self.net as f64)),
+            ("bankroll", JsonValue::Number(self.bankroll as f64)),
+        ];
+        if let Option::Some(shoe) = &self.shoe {
+            fields.push(("shoe", cards(shoe)));
+        }
+        JsonValue::object(fields)
+    }
+
+    fn from_fields(fields: &[(String, String)]) -> Result<HistoryHand, String> {
+        let field = |key: &str| {
+            fields.iter().find(|(name, _)| name == key).map(|(_, value)| value.as_str()).ok_or_else(|| format!("no {} line", key))
+        };
+        let number = |key: &str| field(key)?.parse::<i64>().map_err(|_| format!("{} is not a number", key));
+        let hand = field("Hand")?.strip_prefix('#').and_then(|hand| hand.parse().ok()).ok_or("Hand is not #<number>")?;
+        let (actions, left) = decode_actions(&field("Actions")?.split_whitespace().collect::<Vec<&str>>())?;
+        Ok(HistoryHand {
+            hand,
+            rules: decode_rules(field("Rules")?)?,
+            bet: field("Bet")?.parse().map_err(|_| "Bet is not a number of chips".to_string())?,
+            shoe: match field("Shoe") {
+                Ok(shoe) => Option::Some(decode_cards(shoe)?),
+                Err(_) => Option::None,
+            },
+            actions,
+            left,
+            player_hands: field("Player")?.split('|').map(decode_cards).collect::<Result<_, _>>()?,
+            dealer_cards: decode_cards(field("Dealer")?)?,
+            outcome: field("Outcome")?.to_string(),
+            net: number("Net")?,
+            bankroll: number("Bankroll")?,
+        })
+    }
+
+    fn from_json(value: &JsonValue) -> Result<HistoryHand, String> {
+        let field = |key: &str| value.get(key).ok_or_else(|| format!("no {:?} field", key));
+        let number = |key: &str| field(key)?.as_number().ok_or_else(|| format!("{:?} is not a number", key));
+        let string = |key: &str| field(key)?.as_str().ok_or_else(|| format!("{:?} is not a string", key));
+        let cards = |value: &JsonValue| match value.as_array() {
+            Option::Some(cards) => cards.iter().map(|card| card.as_str().and_then(decode_card).ok_or("not a card")).collect(),
+            Option::None => Err("not a list of cards"),
+        };
+        let words: Vec<&str> = match field("actions")?.as_array() {
+            Option::Some(words) => words.iter().map(|word| word.as_str().ok_or("an action is not a string")).collect::<Result<_, _>>()?,
+            Option::None => return Err("\"actions\" is not a list".to_string()),
+        };
+        let (actions, left) = decode_actions(&words)?;
+        let hands = field("player")?.as_array().ok_or("\"player\" is not a list of hands")?;
+        Ok(HistoryHand {
+            hand: number("hand")? as u64,
+            rules: decode_rules(string("rules")?)?,
+            bet: number("bet")? as u64,
+            shoe: match value.get("shoe") {
+                Option::Some(shoe) => Option::Some(cards(shoe)?),
+                Option::None => Option::None,
+            },
+            actions,
+            left,
+            player_hands: hands.iter().map(cards).collect::<Result<_, _>>()?,
+            dealer_cards: cards(field("dealer")?)?,
+            outcome: string("outcome")?.to_string(),
+            net: number("net")? as i64,
+            bankroll: number("bankroll")? as i64,
+        })
+    }
+}
+
+fn check_version(version: &str) -> Result<(), HistoryError> {
+    match version.parse::<u32>() {
+        Ok(HISTORY_VERSION) => Ok(()),
+        _ => Err(HistoryError::UnsupportedVersion(version.to_string())),
+    }
+}
+
+// Reads either form of the format, told apart by the header.
+pub fn parse_history(input: &str) -> Result<Vec<HistoryHand>, HistoryError> {
+    let mut lines = input.lines().enumerate().map(|(index, line)| (index + 1, line.trim()));
+    let header = lines.next().map(|(_, line)| line).unwrap_or("");
+    if let Option::Some(version) = header.strip_prefix(TEXT_HEADER) {
+        check_version(version)?;
+        return parse_text(lines);
+    }
+    let header = JsonValue::parse(header).ok_or(HistoryError::UnknownFormat)?;
+    if header.get("format").and_then(JsonValue::as_str) != Option::Some(JSON_FORMAT) {
+        return Err(HistoryError::UnknownFormat);
+    }
+    let version = match header.get("version").and_then(JsonValue::as_number) {
+        Option::Some(version) => version.to_string(),
+        Option::None => return Err(HistoryError::UnsupportedVersion("none".to_string())),
+    };
+    check_version(&version)?;
+    lines
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(line, text)| {
+            let malformed = |reason: String| HistoryError::Malformed { line, reason };
+            let value = JsonValue::parse(text).ok_or_else(|| malformed("not a JSON object".to_string()))?;
+            HistoryHand::from_json(&value).map_err(malformed)
+        })
+        .collect()
+}
+
+// Hands are blocks of lines separated by blank ones; `#` lines are comments.
+fn parse_text<'a>(lines: impl Iterator<Item = (usize, &'a str)>) -> Result<Vec<HistoryHand>, HistoryError> {
+    let mut hands = Vec::new();
+    let mut block: Vec<(String, String)> = Vec::new();
+    let mut started = 0;
+    let finish = |block: &mut Vec<(String, String)>, started: usize, hands: &mut Vec<HistoryHand>| {
+        if !block.is_empty() {
+            let hand = HistoryHand::from_fields(block).map_err(|reason| HistoryError::Malformed { line: started, reason })?;
+            hands.push(hand);
+            block.clear();
+        }
+        Ok(())
+    };
+    for (number, line) in lines {
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.is_empty() {
+            finish(&mut block, started, &mut hands)?;
+            continue;
+        }
+        if block.is_empty() {
+            started = number;
+        }
+        let (key, value) = match (line.starts_with("Hand #"), line.split_once(':')) {
+            (true, _) => ("Hand", &line["Hand ".len()..]),
+            (false, Option::Some((key, value))) => (key.trim(), value.trim()),
+            (false, Option::None) => {
+                return Err(HistoryError::Malformed { line: number, reason: format!("{:?} is not a Key: value line", line) })
+            },
+        };
+        block.push((key.to_string(), value.to_string()));
+    }
+    finish(&mut block, started, &mut hands)?;
+    Ok(hands)
+}
+
+// Writes hands as they are played, in the text form or, for a path ending
+// in .jsonl, a JSON object per line.
+pub struct HistoryWriter {
+    json: bool,
+    out: BufWriter<File>,
+    hands: u64,
+}
+
+impl HistoryWriter {
+    pub fn create(path: &Path) -> io::Result<HistoryWriter> {
+        let json = path.extension().and_then(|extension| extension.to_str()) == Option::Some("jsonl");
+        let mut out = BufWriter::new(File::create(path)?);
+        if json {
+            let header = JsonValue::object(vec![
+                ("format", JsonValue::String(JSON_FORMAT.to_string())),
+                ("version", JsonValue::Number(HISTORY_VERSION as f64)),
+            ]);
+            writeln!(out, "{}", header)?;
+        } else {
+            writeln!(out, "{}{}", TEXT_HEADER, HISTORY_VERSION)?;
+        }
+        Ok(HistoryWriter { json, out, hands: 0 })
+    }
+
+    // Flushed hand by hand, so a session that ends badly keeps its history.
+    pub fn write(&mut self, hand: &HistoryHand) -> io::Result<()> {
+        if self.json {
+            writeln!(self.out, "{}", hand.to_json())?;
+        } else {
+            writeln!(self.out, "\n{}", hand.to_text())?;
+        }
+        self.hands += 1;
+        self.out.flush()
+    }
+
+    pub fn hands(&self) -> u64 {
+        self.hands
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReplayError {
+    // The hand was written without its shoe, so there is nothing to deal.
+    NoShoe,
+    // The engine turned down the action at this index of `actions`.
+    IllegalAction(usize),
+    // The actions ran out while the hand was still being played, or the
+    // hand came out other than it was written.
+    Diverged,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::NoShoe => write!(f, "the shoe was reshuffled mid-hand, so the hand can't be dealt again"),
+            ReplayError::IllegalAction(index) => write!(f, "action {} can't be played there", index + 1),
+            ReplayError::Diverged => write!(f, "the hand doesn't play out as it was written"),
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReplayMove {
+    Deal,
+    Act(Action),
+    Leave,
+}
+
+// A move in the hand, what the engine reported of it and the hand after it.
+#[derive(Clone, Debug)]
+pub struct ReplayStep {
+    pub played: ReplayMove,
+    pub events: Vec<UiEvent>,
+    pub state: GameState,
+}
+
+// Deals the hand again from its shoe and plays its actions through the
+// engine, a step for the deal and one for each action, checking that it
+// ends with the cards it was written with.
+pub fn replay(hand: &HistoryHand) -> Result<Vec<ReplayStep>, ReplayError> {
+    let shoe = hand.shoe.clone().ok_or(ReplayError::NoShoe)?;
+    let deck = ScriptedCardSource::new(shoe).into_deck();
+    let mut events = Vec::new();
+    let state = events::start(deck, hand.rules.clone(), &mut |event| events.push(event));
+    let mut steps = vec![ReplayStep { played: ReplayMove::Deal, events, state }];
+    for (index, action) in hand.actions.iter().enumerate() {
+        let before = steps.last().expect("the deal is a step").state.clone();
+        let mut events = Vec::new();
+        let state = events::act(action, before, &mut |event| events.push(event)).map_err(|_| ReplayError::IllegalAction(index))?;
+        steps.push(ReplayStep { played: ReplayMove::Act(action.clone()), events, state });
+    }
+    let last = steps.last().expect("the deal is a step").state.clone();
+    match (hand.left, continue_with_game(&last)) {
+        (true, true) => {
+            let state = last.clone().abandon();
+            let events = events::changes(&last, &state);
+            steps.push(ReplayStep { played: ReplayMove::Leave, events, state });
+        },
+        (false, false) => {},
+        _ => return Err(ReplayError::Diverged),
+    }
+
+    let player_state = steps.last().expect("the deal is a step").state.player_state();
+    let mut player_hands: Vec<Vec<Card>> = player_state.split_hands.iter().map(|split| split.hand.to_vec()).collect();
+    player_hands.push(player_state.hand.to_vec());
+    if player_hands != hand.player_hands || player_state.dealer_cards() != hand.dealer_cards {
+        return Err(ReplayError::Diverged);
+    }
+    Ok(steps)
+}
@@ -0,0 +1,67 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rust_blackjack::summary::SessionSummary;
+use rust_blackjack::trends::SessionRecord;
+
+use crate::profile::data_dir;
+
+const SESSIONS_FILE: &str = "sessions.txt";
+
+// One tab separated line per finished session, next to the profiles and
+// ledger. Malformed lines are skipped when reading.
+pub fn record(kind: &str, summary: &SessionSummary) -> io::Result<()> {
+    let dir = data_dir();
+    fs::create_dir_all(&dir)?;
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0);
+    let mut file = OpenOptions::new().create(true).append(true).open(dir.join(SESSIONS_FILE))?;
+    writeln!(
+        file,
+        "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+        timestamp,
+        kind,
+        summary.hands,
+        summary.wins,
+        summary.losses,
+        summary.pushes,
+        summary.mistakes,
+        summary.deviation_spots,
+        summary.deviations_taken,
+        summary.seconds,
+        summary.starting_bankroll,
+        summary.net
+    )
+}
+
+fn parse_record(line: &str) -> Option<SessionRecord> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    if fields.len() != 12 {
+        return Option::None;
+    }
+    Option::Some(SessionRecord {
+        timestamp: fields[0].parse().ok()?,
+        kind: fields[1].to_string(),
+        summary: SessionSummary {
+            hands: fields[2].parse().ok()?,
+            wins: fields[3].parse().ok()?,
+            losses: fields[4].parse().ok()?,
+            pushes: fields[5].parse().ok()?,
+            mistakes: fields[6].parse().ok()?,
+            deviation_spots: fields[7].parse().ok()?,
+            deviations_taken: fields[8].parse().ok()?,
+            seconds: fields[9].parse().ok()?,
+            starting_bankroll: fields[10].parse().ok()?,
+            net: fields[11].parse().ok()?,
+            ..SessionSummary::default()
+        },
+    })
+}
+
+pub fn load() -> io::Result<Vec<SessionRecord>> {
+    match fs::read_to_string(data_dir().join(SESSIONS_FILE)) {
+        Ok(contents) => Ok(contents.lines().filter_map(parse_record).collect()),
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(error) => Err(error),
+    }
+}
@@ -0,0 +1,223 @@
+use std::io::{self, Write};
+
+use rust_blackjack::chips::{parse_bet, TableLimits};
+use rust_blackjack::protocol::encode_card;
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::settlement::{settle, staked};
+use rust_blackjack::table::{clear_table, Round};
+use rust_blackjack::{
+    describe_actions, describe_dealer, game_message, insurance_answer, insurance_prompt, outcome_message, Action, Deck,
+};
+
+use crate::input::{Input, InputLines};
+use crate::{cards_text, total_text};
+
+// Two or more people taking turns at one keyboard, on the seats of one
+// table. Bets are placed in the open, but each turn is played on a cleared
+// screen that the player only sees once they have pressed Enter, and it is
+// cleared again before the keyboard goes round, so nobody sees another's
+// cards until the dealer has played.
+
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+struct Player {
+    name: String,
+    bankroll: u64,
+    bet: u64,
+}
+
+// None when someone has had enough.
+fn read_line(input: &InputLines) -> Option<String> {
+    match input.next() {
+        Input::Line(line) if !matches!(line.trim(), "quit" | "q") => Option::Some(line),
+        _ => Option::None,
+    }
+}
+
+fn clear_screen() {
+    print!("{}", CLEAR_SCREEN);
+    let _ = io::stdout().flush();
+}
+
+// Clears the screen and waits for `name` to take the keyboard.
+fn hand_over(name: &str, input: &InputLines) -> Option<()> {
+    clear_screen();
+    println!("Pass the keyboard to {}.", name);
+    println!("{}, press Enter when nobody else can see the screen.", name);
+    read_line(input).map(|_| ())
+}
+
+fn ask_names(players: usize, input: &InputLines) -> Option<Vec<String>> {
+    let mut names = Vec::new();
+    for seat in 1..=players {
+        let default = format!("Player {}", seat);
+        println!("Name for seat {} (enter for {}):", seat, default);
+        let name = read_line(input)?.trim().to_string();
+        names.push(if name.is_empty() { default } else { name });
+    }
+    Option::Some(names)
+}
+
+fn ask_bet(player: &Player, limits: &TableLimits, input: &InputLines) -> Option<u64> {
+    loop {
+        println!(
+            "{}, your bet ({}-{}, you have {}; enter for {}):",
+            player.name, limits.minimum, limits.maximum, player.bankroll, player.bet
+        );
+        let line = read_line(input)?;
+        let bet = if line.trim().is_empty() { Option::Some(player.bet) } else { parse_bet(&line) };
+        match bet.map(|bet| (bet, limits.check(bet, player.bankroll))) {
+            Option::Some((bet, Ok(()))) => return Option::Some(bet),
+            Option::Some((_, Err(error))) => println!("Cannot bet: {}", error),
+            Option::None => println!("{:?} is not a bet", line.trim()),
+        }
+    }
+}
+
+// The dealer's cards as the seat sees them, and the seat's own hands only.
+fn print_seat(round: &Round, seat: usize, name: &str) {
+    let player_state = round.seats()[seat].player_state();
+    let shown: Vec<String> = player_state.visible_dealer_cards().iter().map(encode_card).collect();
+    println!("{}", describe_dealer(&shown.join(" "), player_state.hidden_dealer_cards().len()));
+    for split in player_state.split_hands.iter() {
+        println!("  {:<10} {:<20} {}", name, cards_text(&split.hand), total_text(&split.hand));
+    }
+    let hand = &player_state.hand;
+    let playing = if player_state.has_split() { " <- playing" } else { "" };
+    println!("  {:<10} {:<20} {}{}", name, cards_text(hand), total_text(hand), playing);
+}
+
+// A double or a split puts down another bet, which has to be there.
+fn afford(action: &Action, round: &Round, seat: usize, player: &Player) -> Result<(), String> {
+    if !matches!(action, Action::DoubleDown | Action::SplitCards) {
+        return Ok(());
+    }
+    let behind = player.bankroll.saturating_sub(staked(&round.seats()[seat], player.bet, Option::None));
+    if behind < player.bet {
+        return Err(format!("{} more is needed and {} has {}", player.bet, player.name, behind));
+    }
+    Ok(())
+}
+
+// Plays one seat's turn. Returns false when the player quit.
+fn play_turn(round: &mut Round, seat: usize, player: &Player, input: &InputLines) -> bool {
+    if !round.is_playing(seat) {
+        return true;
+    }
+    if hand_over(&player.name, input).is_none() {
+        return false;
+    }
+    while round.is_playing(seat) {
+        print_seat(round, seat, &player.name);
+        let state = &round.seats()[seat];
+        if let Option::Some(prompt) = insurance_prompt(state) {
+            println!("{} (y/n)", prompt);
+        }
+        println!("You can: {}", describe_actions(&state.legal_actions(), &state.player_state().rules));
+        let line = match read_line(input) {
+            Option::Some(line) => line,
+            Option::None => return false,
+        };
+        let action = match insurance_answer(state, &line) {
+            Option::Some(action) => Ok(action),
+            Option::None => line.parse::<Action>().map_err(|error| error.to_string()),
+        };
+        let played = action.and_then(|action| {
+            afford(&action, round, seat, player)?;
+            round.act(seat, &action)
+        });
+        if let Err(error) = played {
+            println!("{}", error);
+        }
+    }
+    print_seat(round, seat, &player.name);
+    println!("{}, press Enter to hide your cards.", player.name);
+    let stayed = read_line(input).is_some();
+    clear_screen();
+    stayed
+}
+
+// Plays one hand for every seat and shows how it went. Returns the shoe
+// and whether everyone is still at the table; a player who quits ends the
+// game, and any hand still in play is surrendered.
+fn play_hand(players: &mut [&mut Player], rules: &GameRules, limits: &TableLimits, shoe: Deck, input: &InputLines) -> (Deck, bool) {
+    for player in players.iter_mut() {
+        match ask_bet(player, limits, input) {
+            Option::Some(bet) => player.bet = bet,
+            Option::None => return (shoe, false),
+        }
+    }
+
+    let mut round = Round::deal(shoe, players.len(), rules);
+    let mut stayed = true;
+    for (seat, player) in players.iter().enumerate() {
+        if stayed {
+            stayed = play_turn(&mut round, seat, player, input);
+        }
+        if !stayed && round.is_playing(seat) {
+            round.abandon(seat);
+        }
+    }
+
+    let (states, deck) = round.finish();
+    let dealer_cards = states[0].player_state().dealer_cards();
+    println!("{:<12}{:<20}{}", "Dealer", cards_text(&dealer_cards), total_text(&dealer_cards));
+    for (state, player) in states.iter().zip(players.iter_mut()) {
+        let settlement = settle(state, player.bet, &[]);
+        for split in state.player_state().split_hands.iter() {
+            let message = split.outcome.as_ref().map(outcome_message).unwrap_or("");
+            println!("{:<12}{:<20}{:<10}{}", player.name, cards_text(&split.hand), total_text(&split.hand), message);
+        }
+        let hand = &state.player_state().hand;
+        println!("{:<12}{:<20}{:<10}{:<36}{:+}", player.name, cards_text(hand), total_text(hand), game_message(state), settlement.net);
+        player.bankroll = (player.bankroll as i64 + settlement.net).max(0) as u64;
+        if player.bankroll < limits.minimum {
+            println!("{} can no longer cover the table minimum of {} and leaves the table.", player.name, limits.minimum);
+        }
+    }
+    (clear_table(states, deck), stayed)
+}
+
+// Seats `players` people, each with `bankroll` chips, and deals hand after
+// hand from shoes made by `new_deck` until someone quits or nobody can
+// cover the table minimum any more.
+pub fn run<F>(rules: &GameRules, players: usize, bankroll: u64, limits: &TableLimits, bet: u64, mut new_deck: F)
+where
+    F: FnMut() -> Deck,
+{
+    println!("Hotseat blackjack for {} players. Rules: {}", players, rules.describe());
+    println!("Type quit at any prompt to end the game; hands still in play are surrendered.");
+    let input = InputLines::new();
+    let names = match ask_names(players, &input) {
+        Option::Some(names) => names,
+        Option::None => return,
+    };
+    let bet = limits.clamp(bet);
+    let mut players: Vec<Player> = names.into_iter().map(|name| Player { name, bankroll, bet }).collect();
+    let mut shoe = new_deck();
+
+    loop {
+        let mut seated: Vec<&mut Player> = players.iter_mut().filter(|player| player.bankroll >= limits.minimum).collect();
+        if seated.is_empty() {
+            println!("Nobody can cover the table minimum any more.");
+            break;
+        }
+        for player in seated.iter_mut() {
+            player.bet = player.bet.min(player.bankroll).max(limits.minimum);
+        }
+        if shoe.needs_shuffle() {
+            println!("Shuffling a new shoe.");
+            shoe = new_deck();
+        }
+        let (deck, stayed) = play_hand(&mut seated, rules, limits, shoe, &input);
+        shoe = deck;
+        if !stayed {
+            break;
+        }
+    }
+
+    println!("Final bankrolls:");
+    for player in &players {
+        println!("  {:<12}{} ({:+})", player.name, player.bankroll, player.bankroll as i64 - bankroll as i64);
+    }
+}
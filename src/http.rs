@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+use rand::{OsRng, Rng};
+
+use rust_blackjack::json::JsonValue;
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::wasm::game_state_json;
+use rust_blackjack::{continue_with_game, deal_with_action, parse_action, Deck, GameState};
+
+// A JSON API over plain HTTP, for scripts and web pages that would rather
+// not hold a socket open. Each session is one player's shoe and the hand in
+// play from it:
+//
+//     POST   /sessions              start a session, dealing its first hand
+//     GET    /sessions/<id>         the hand as it stands
+//     POST   /sessions/<id>/actions play {"action": "hit"}
+//     POST   /sessions/<id>/deal    deal the next hand once this one is over
+//     DELETE /sessions/<id>         end the session
+//
+// Every answer is a JSON object: the session's id and state, or an error.
+// Requests are answered one at a time, each on a connection of its own.
+
+// Sessions nobody ends would otherwise pile up for as long as the server
+// runs, so one left alone for `SESSION_IDLE` is ended, and once there are
+// `MAX_SESSIONS` a new one ends whichever has been left alone longest.
+const MAX_SESSIONS: usize = 1000;
+const SESSION_IDLE: Duration = Duration::from_secs(30 * 60);
+// Bodies are a line of JSON; anything longer isn't meant for this API, and
+// nor are longer or more header lines.
+const MAX_BODY: usize = 4096;
+const MAX_LINE: u64 = 8192;
+const MAX_HEADERS: usize = 64;
+// The time a whole request has to arrive in, however it trickles in.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+struct Response {
+    status: u16,
+    body: JsonValue,
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        _ => "Service Unavailable",
+    }
+}
+
+fn error(status: u16, message: &str) -> Response {
+    Response { status, body: JsonValue::object(vec![("error", JsonValue::String(message.to_string()))]) }
+}
+
+fn session(id: &str, state: &GameState, status: u16) -> Response {
+    let body = JsonValue::object(vec![("id", JsonValue::String(id.to_string())), ("state", game_state_json(state))]);
+    Response { status, body }
+}
+
+// Reads from a connection until `deadline`: each read waits only for what
+// is left of the time, so a client sending a byte at a time can't stretch it.
+struct Deadline<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left == Duration::from_secs(0) {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "the request took too long"));
+        }
+        self.stream.set_read_timeout(Option::Some(left))?;
+        let mut stream = self.stream;
+        stream.read(buffer)
+    }
+}
+
+// A line of the request, refused when it runs past `MAX_LINE`.
+fn read_line<R: BufRead>(reader: &mut R) -> Result<String, Response> {
+    let mut line = String::new();
+    reader.take(MAX_LINE).read_line(&mut line).map_err(|_| error(400, "Could not read the request"))?;
+    if line.len() as u64 == MAX_LINE && !line.ends_with('\n') {
+        return Err(error(400, "A request line is too long"));
+    }
+    Ok(line)
+}
+
+// A request that can't be read is answered with the error straight away.
+fn read_request(stream: &TcpStream) -> Result<Request, Response> {
+    let unreadable = |_| error(400, "Could not read the request");
+    let mut reader = BufReader::new(Deadline { stream, deadline: Instant::now() + REQUEST_TIMEOUT });
+    let line = read_line(&mut reader)?;
+    let mut words = line.split_whitespace();
+    let (method, path) = match (words.next(), words.next()) {
+        (Option::Some(method), Option::Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Err(error(400, "Not an HTTP request")),
+    };
+    let mut length = 0;
+    for headers in 0.. {
+        let header = read_line(&mut reader)?;
+        if header.trim().is_empty() {
+            break;
+        }
+        if headers == MAX_HEADERS {
+            return Err(error(400, "The request has too many headers"));
+        }
+        if let Option::Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse().map_err(|_| error(400, "Bad Content-Length"))?;
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return Err(error(413, "The request body is too large"));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(unreadable)?;
+    let body = String::from_utf8(body).map_err(|_| error(400, "The request body is not UTF-8"))?;
+    Ok(Request { method, path, body })
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response) -> io::Result<()> {
+    let body = response.body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason(response.status),
+        body.len(),
+        body
+    )
+}
+
+fn new_id() -> io::Result<String> {
+    Ok(format!("{:016x}", OsRng::new()?.next_u64()))
+}
+
+// A session's hand, and when a request last came for it.
+struct Held {
+    state: GameState,
+    used: Instant,
+}
+
+struct Sessions<F> {
+    rules: GameRules,
+    new_deck: F,
+    hands: HashMap<String, Held>,
+}
+
+impl<F: FnMut() -> Deck> Sessions<F> {
+    fn take(&mut self, id: &str) -> Option<GameState> {
+        self.hands.remove(id).map(|held| held.state)
+    }
+
+    fn put(&mut self, id: &str, state: GameState) {
+        self.hands.insert(id.to_string(), Held { state, used: Instant::now() });
+    }
+
+    // Ends the sessions left alone too long, and the longest left alone
+    // of the rest if there's still no room for another.
+    fn make_room(&mut self) {
+        self.hands.retain(|_, held| held.used.elapsed() < SESSION_IDLE);
+        if self.hands.len() >= MAX_SESSIONS {
+            let oldest = self.hands.iter().min_by_key(|(_, held)| held.used).map(|(id, _)| id.clone());
+            if let Option::Some(oldest) = oldest {
+                self.hands.remove(&oldest);
+            }
+        }
+    }
+
+    fn deal(&mut self, shoe: Deck) -> GameState {
+        let shoe = if shoe.needs_shuffle() { (self.new_deck)() } else { shoe };
+        GameState::start_with_rules(shoe, self.rules.clone())
+    }
+
+    fn create(&mut self) -> Response {
+        self.make_room();
+        let id = match new_id() {
+            Ok(id) => id,
+            Err(_) => return error(503, "Could not make a session id"),
+        };
+        // A shoe with no cards in it needs shuffling, so the first hand
+        // comes from a fresh one.
+        let state = self.deal(Deck::with_decks(0));
+        let response = session(&id, &state, 201);
+        self.put(&id, state);
+        response
+    }
+
+    fn act(&mut self, id: &str, body: &str) -> Response {
+        let action = match JsonValue::parse(body).as_ref().and_then(|body| body.get("action")).and_then(JsonValue::as_str) {
+            Option::Some(name) => match parse_action(name) {
+                Option::Some(action) => action,
+                Option::None => return error(400, &format!("Unknown action {:?}", name)),
+            },
+            Option::None => return error(400, "Send {\"action\": \"<name>\"}"),
+        };
+        let state = match self.take(id) {
+            Option::Some(state) => state,
+            Option::None => return error(404, "No such session"),
+        };
+        let (state, response) = match deal_with_action(&action, state) {
+            Ok(state) => {
+                let response = session(id, &state, 200);
+                (state, response)
+            },
+            Err(illegal) => {
+                let response = error(409, &illegal.to_string());
+                (*illegal.state, response)
+            },
+        };
+        self.put(id, state);
+        response
+    }
+
+    fn deal_next(&mut self, id: &str) -> Response {
+        let state = match self.take(id) {
+            Option::Some(state) => state,
+            Option::None => return error(404, "No such session"),
+        };
+        if continue_with_game(&state) {
+            self.put(id, state);
+            return error(409, "The hand in play isn't over yet");
+        }
+        let state = self.deal(state.discard_hand());
+        let response = session(id, &state, 200);
+        self.put(id, state);
+        response
+    }
+
+    fn handle(&mut self, request: &Request) -> Response {
+        let path: Vec<&str> = request.path.split('/').filter(|part| !part.is_empty()).collect();
+        match (request.method.as_str(), path.as_slice()) {
+            ("POST", ["sessions"]) => self.create(),
+            ("GET", ["sessions", id]) => match self.hands.get_mut(*id) {
+                Option::Some(held) => {
+                    held.used = Instant::now();
+                    session(id, &held.state, 200)
+                },
+                Option::None => error(404, "No such session"),
+            },
+            ("DELETE", ["sessions", id]) => match self.hands.remove(*id) {
+                Option::Some(_) => Response { status: 200, body: JsonValue::object(vec![("id", JsonValue::String(id.to_string()))]) },
+                Option::None => error(404, "No such session"),
+            },
+            ("POST", ["sessions", id, "actions"]) => self.act(id, &request.body),
+            ("POST", ["sessions", id, "deal"]) => self.deal_next(id),
+            (_, ["sessions"]) | (_, ["sessions", _]) | (_, ["sessions", _, "actions"]) | (_, ["sessions", _, "deal"]) => {
+                error(405, "That method isn't allowed here")
+            },
+            _ => error(404, "No such endpoint"),
+        }
+    }
+}
+
+// Answers requests on `address` until the process is stopped, dealing each
+// session's shoes with `new_deck`.
+pub fn run<F>(address: &str, rules: &GameRules, new_deck: F) -> io::Result<()>
+where
+    F: FnMut() -> Deck,
+{
+    let listener = TcpListener::bind(address)?;
+    println!("Serving the blackjack HTTP API on http://{}", listener.local_addr()?);
+    let mut sessions = Sessions { rules: rules.clone(), new_deck, hands: HashMap::new() };
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(error) => {
+                println!("Could not take a connection: {}", error);
+                continue;
+            },
+        };
+        // A request has `REQUEST_TIMEOUT` in all, so one slow client holds
+        // up everyone else's requests for no longer than that.
+        let response = match read_request(&stream) {
+            Ok(request) => sessions.handle(&request),
+            Err(response) => response,
+        };
+        let _ = write_response(&mut stream, &response);
+    }
+    Ok(())
+}
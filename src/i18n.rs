@@ -0,0 +1,258 @@
+use alloc::string::{String, ToString};
+use core::fmt::Display;
+
+use crate::rules::Variant;
+use crate::{Action, Card, CardSuit, CardValue};
+
+// The languages the table can talk in. Messages are written in English in
+// the code and looked up in a catalog for the others, so a message missing
+// from a catalog still reads, in English, rather than disappearing.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Language {
+    English,
+    Spanish,
+}
+
+// English message, then its Spanish translation. `{}` marks where `fill`
+// puts each value, in order.
+const SPANISH: &[(&str, &str)] = &[
+    // Outcomes.
+    ("Even money, your blackjack was paid 1:1", "Pago igual, tu blackjack se ha pagado 1:1"),
+    ("Five card trick! You won", "¡Cinco cartas! Has ganado"),
+    ("Charlie! You won", "¡Charlie! Has ganado"),
+    ("You won", "Has ganado"),
+    ("Pontoon! You won", "¡Pontoon! Has ganado"),
+    ("Blackjack! You won", "¡Blackjack! Has ganado"),
+    ("You lost", "Has perdido"),
+    ("Push, your bet is returned", "Empate, se te devuelve la apuesta"),
+    ("You surrendered half your bet", "Te has rendido y pierdes la mitad de la apuesta"),
+    ("The game is still going", "La mano sigue en juego"),
+    // Actions, as typed and as listed.
+    ("hit", "pedir"),
+    ("stand", "plantarse"),
+    ("double-down", "doblar"),
+    ("split", "separar"),
+    ("surrender", "rendirse"),
+    ("insurance", "seguro"),
+    ("even-money", "pago-igual"),
+    ("no-insurance", "sin-seguro"),
+    ("twist", "carta"),
+    ("stick", "me-planto"),
+    ("buy", "comprar"),
+    // Prompts and the hand in play.
+    (
+        "You have blackjack against an ace. Take even money, a sure 1:1 win, or decline and risk a push for the full payout?",
+        "Tienes blackjack contra un as. ¿Aceptas el pago igual, una ganancia segura de 1:1, o lo rechazas y te arriesgas a un empate por el pago completo?",
+    ),
+    (
+        "The dealer shows an ace. Insurance costs half your bet and pays 2:1 if the dealer has blackjack. Take it?",
+        "El crupier muestra un as. El seguro cuesta la mitad de tu apuesta y paga 2:1 si el crupier tiene blackjack. ¿Lo tomas?",
+    ),
+    ("Dealer has a face-down card", "El crupier tiene una carta boca abajo"),
+    ("Dealer has {} face-down cards", "El crupier tiene {} cartas boca abajo"),
+    ("Dealer shows {}", "El crupier muestra {}"),
+    ("Dealer shows {} and a face-down card", "El crupier muestra {} y una carta boca abajo"),
+    ("Dealer shows {} and {} face-down cards", "El crupier muestra {} y {} cartas boca abajo"),
+    ("Your hand is {}", "Tu mano es {}"),
+    ("Your hand value is {}", "Tu mano vale {}"),
+    ("You can: {}", "Puedes: {}"),
+    ("Hand {} of {}", "Mano {} de {}"),
+    ("You wanted to: {}", "Has elegido: {}"),
+    ("You cannot {}: {}", "No puedes {}: {}"),
+    ("Doubling for less: {} more, all in", "Doblas por menos: {} más, todo lo que te queda"),
+    ("Running count: {}, true count: {}", "Cuenta corrida: {}, cuenta real: {}"),
+    ("Dealer odds: {}", "Probabilidades del crupier: {}"),
+    ("bust {}", "pasarse {}"),
+    ("blackjack {}", "blackjack {}"),
+    ("The book says: {}", "El libro dice: {}"),
+    ("Your strategy says: {}", "Tu estrategia dice: {}"),
+    ("Expected value per bet: {}", "Valor esperado por apuesta: {}"),
+    ("With these cards left, {} is worth {} more", "Con las cartas que quedan, {} vale {} más"),
+    ("Expected value per bet from a full shoe: {}", "Valor esperado por apuesta con el sabot completo: {}"),
+    ("By the table, {} is worth {} more", "Según la tabla, {} vale {} más"),
+    ("bust ({})", "pasado ({})"),
+    ("You, {}", "Tú, {}"),
+    ("Dealer, {}", "Crupier, {}"),
+    // The session.
+    ("Play blackjack!", "¡A jugar al blackjack!"),
+    ("Rules: {}, bankroll: {}", "Reglas: {}, fondos: {}"),
+    ("Please input what you'd like to do: {}", "Escribe lo que quieres hacer: {}"),
+    (
+        "Type stats for the session so far, hint for the play to make, or quit to leave the table; a hand still in play is surrendered.",
+        "Escribe stats para ver la sesión hasta ahora, hint para ver la jugada que toca, o quit para dejar la mesa; una mano en juego se rinde.",
+    ),
+    (
+        "Place your bet of {} to {}, as an amount or chips such as 2x25 1x5 (enter bets {}), or type stats or quit",
+        "Haz tu apuesta de {} a {}, como cantidad o en fichas como 2x25 1x5 (intro apuesta {}), o escribe stats o quit",
+    ),
+    ("{} is neither an amount nor chips of 1, 5, 25, 100 or 500", "{} no es ni una cantidad ni fichas de 1, 5, 25, 100 o 500"),
+    ("Cannot bet: {}", "No se puede apostar: {}"),
+    ("Your bankroll can no longer cover the table minimum of {}", "Tus fondos ya no cubren el mínimo de la mesa, {}"),
+    (
+        "Up {}, reaching your stop-win of {}: the session ends here",
+        "Ganas {}, tu límite de ganancia es {}: la sesión termina aquí",
+    ),
+    (
+        "Down {}, reaching your stop-loss of {}: the session ends here",
+        "Pierdes {}, tu límite de pérdida es {}: la sesión termina aquí",
+    ),
+    ("That was hand {}, the last of the session", "Esa fue la mano {}, la última de la sesión"),
+    (
+        "Buy back in with an amount, or enter for {} to bring the bankroll to {}, or type quit",
+        "Vuelve a comprar fichas con una cantidad, o pulsa enter por {} para dejar los fondos en {}, o escribe quit",
+    ),
+    (
+        "{} more still leaves the bankroll short of the table minimum of {}",
+        "Con {} más los fondos siguen sin cubrir el mínimo de la mesa, {}",
+    ),
+    ("Bought back in for {}, bankroll {}", "Compraste {} en fichas, fondos {}"),
+    (
+        "Tip the dealer out of your {}? Type an amount or chips, or press enter for no tip",
+        "¿Das propina al crupier de tus {}? Escribe una cantidad o fichas, o pulsa enter para no dar nada",
+    ),
+    ("A tip of {} is more than the {} you won", "Una propina de {} es más de los {} que ganaste"),
+    ("You tip the dealer {}, bankroll {}", "Das {} de propina al crupier, fondos {}"),
+    ("Your bankroll covers {} of your {} seats this round", "Tus fondos cubren {} de tus {} sitios en esta ronda"),
+    ("Seat {}:", "Sitio {}:"),
+    ("Your hand in seat {}, bet {}", "Tu mano en el sitio {}, apuesta {}"),
+    ("The session's {} minutes are up: it ends here", "Se acabaron los {} minutos de la sesión: termina aquí"),
+    ("The table takes bets of {} to {}, so the bet starts at {}", "La mesa acepta apuestas de {} a {}, así que empiezas apostando {}"),
+    ("The cut card is out, shuffling a new shoe.", "Ha salido la carta de corte, se baraja un sabot nuevo."),
+    ("The shoe is used up, starting a new one.", "El sabot se ha agotado, se empieza uno nuevo."),
+    ("Leaving the table", "Dejas la mesa"),
+    ("Input closed, leaving the table", "Se ha cerrado la entrada, dejas la mesa"),
+    ("Interrupted, leaving the table", "Interrumpido, dejas la mesa"),
+    // The result.
+    ("----- Result -----", "--- Resultado ---"),
+    ("Dealer", "Crupier"),
+    ("Seat {}", "Sitio {}"),
+    ("Bet {}, even money {}", "Apuesta {}, pago igual {}"),
+    ("Bet {} (doubled), payout {}", "Apuesta {} (doblada), pago {}"),
+    ("Bet {}, payout {}", "Apuesta {}, pago {}"),
+    ("Insurance {}", "Seguro {}"),
+    ("Net {}, bankroll {}", "Neto {}, fondos {}"),
+    // Cards spelled out.
+    ("{} of {}", "{} de {}"),
+    ("two", "dos"),
+    ("three", "tres"),
+    ("four", "cuatro"),
+    ("five", "cinco"),
+    ("six", "seis"),
+    ("seven", "siete"),
+    ("eight", "ocho"),
+    ("nine", "nueve"),
+    ("ten", "diez"),
+    ("jack", "jota"),
+    ("queen", "reina"),
+    ("king", "rey"),
+    ("ace", "as"),
+    ("clubs", "tréboles"),
+    ("hearts", "corazones"),
+    ("diamonds", "diamantes"),
+    ("spades", "picas"),
+    ("Your hand: {}, total {}", "Tu mano: {}, total {}"),
+    ("{}: {}, total {}", "{}: {}, total {}"),
+    ("Chips: {}", "Fichas: {}"),
+    // Dealing.
+    ("You are dealt {}", "Recibes {}"),
+    ("The dealer takes {}", "El crupier toma {}"),
+    ("The dealer takes a card face down", "El crupier toma una carta boca abajo"),
+    ("The dealer turns over {}", "El crupier descubre {}"),
+    ("The dealer draws {}", "El crupier saca {}"),
+];
+
+impl Language {
+    pub const ALL_VALUES: [Language; 2] = [Language::English, Language::Spanish];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Spanish => "es",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Language> {
+        Language::ALL_VALUES.iter().find(|language| language.name() == name).cloned()
+    }
+
+    fn catalog(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => &[],
+            Language::Spanish => SPANISH,
+        }
+    }
+
+    pub fn translate<'a>(&self, english: &'a str) -> &'a str {
+        self.catalog()
+            .iter()
+            .find(|(key, _)| *key == english)
+            .map(|(_, translated)| *translated)
+            .unwrap_or(english)
+    }
+
+    // The English word behind a translated one, so a player can type the
+    // actions in their own language.
+    pub fn untranslate<'a>(&self, word: &'a str) -> &'a str {
+        self.catalog()
+            .iter()
+            .find(|(_, translated)| *translated == word)
+            .map(|(key, _)| *key)
+            .unwrap_or(word)
+    }
+
+    pub fn action_name(&self, variant: &Variant, action: &Action) -> &'static str {
+        self.translate(variant.action_name(action))
+    }
+
+    // A card in words, such as "queen of hearts", for screen readers.
+    pub fn card_name(&self, card: &Card) -> String {
+        let rank = self.translate(rank_word(&card.value));
+        let suit = self.translate(suit_word(&card.suit));
+        fill(self.translate("{} of {}"), &[&rank, &suit])
+    }
+}
+
+fn rank_word(value: &CardValue) -> &'static str {
+    match value {
+        CardValue::Two => "two",
+        CardValue::Three => "three",
+        CardValue::Four => "four",
+        CardValue::Five => "five",
+        CardValue::Six => "six",
+        CardValue::Seven => "seven",
+        CardValue::Eight => "eight",
+        CardValue::Nine => "nine",
+        CardValue::Ten => "ten",
+        CardValue::Jack => "jack",
+        CardValue::Queen => "queen",
+        CardValue::King => "king",
+        CardValue::Ace => "ace",
+    }
+}
+
+fn suit_word(suit: &CardSuit) -> &'static str {
+    match suit {
+        CardSuit::Clubs => "clubs",
+        CardSuit::Hearts => "hearts",
+        CardSuit::Diamonds => "diamonds",
+        CardSuit::Spades => "spades",
+    }
+}
+
+// Puts the values into a message's `{}` markers in order. Markers left
+// without a value stay as they are.
+pub fn fill(template: &str, values: &[&dyn Display]) -> String {
+    let mut filled = String::new();
+    let mut values = values.iter();
+    let mut rest = template;
+    while let Option::Some(position) = rest.find("{}") {
+        filled.push_str(&rest[..position]);
+        match values.next() {
+            Option::Some(value) => filled.push_str(&value.to_string()),
+            Option::None => filled.push_str("{}"),
+        }
+        rest = &rest[position + 2..];
+    }
+    filled.push_str(rest);
+    filled
+}
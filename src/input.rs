@@ -0,0 +1,70 @@
+use std::io::{self, BufRead};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+pub enum Input {
+    Line(String),
+    // Stdin was closed or could not be read.
+    Closed,
+    // Ctrl-C was pressed while waiting for a line.
+    Interrupted,
+}
+
+#[cfg(unix)]
+extern "C" fn on_interrupt(_signal: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+// Lines from stdin, read on a separate thread so a Ctrl-C can be noticed
+// while a line is still being waited for. Ctrl-C is only caught while this
+// is alive; dropping it restores the default behaviour of exiting.
+pub struct InputLines {
+    receiver: Receiver<io::Result<String>>,
+}
+
+impl InputLines {
+    pub fn new() -> InputLines {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        #[cfg(unix)]
+        unsafe {
+            libc::signal(libc::SIGINT, on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t);
+        }
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        InputLines { receiver }
+    }
+
+    pub fn next(&self) -> Input {
+        loop {
+            if INTERRUPTED.swap(false, Ordering::SeqCst) {
+                return Input::Interrupted;
+            }
+            match self.receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(Ok(line)) => return Input::Line(line),
+                Ok(Err(_)) | Err(RecvTimeoutError::Disconnected) => return Input::Closed,
+                Err(RecvTimeoutError::Timeout) => (),
+            }
+        }
+    }
+}
+
+impl Drop for InputLines {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_DFL);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt;
+
+use crate::{calculate_current_hand_value, Card, CardSuit, CardValue, Deck, GameState};
+
+const CARDS_PER_DECK: usize = 52;
+
+// What the engine promises to keep true of a shoe and the hands dealt from
+// it. The checks are for tests, and for embedders who would rather catch a
+// corrupted state than play on from it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Violation {
+    // The shoe doesn't hold whole decks.
+    PartialShoe { cards: usize },
+    // Some card turns up more or less often than once per deck.
+    CardCount { card: Card, expected: usize, found: usize },
+    CutCardPastEnd { cut_card: usize, cards: usize },
+    HandOverTwentyOne { total: u32 },
+    // A busted hand is still being played.
+    PlayingBust,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::PartialShoe { cards } => write!(f, "a shoe of {} cards isn't a whole number of decks", cards),
+            Violation::CardCount { card, expected, found } => {
+                write!(f, "the shoe holds {} of {:?} rather than {}", found, card, expected)
+            },
+            Violation::CutCardPastEnd { cut_card, cards } => {
+                write!(f, "the cut card sits {} cards deep in a shoe of {}", cut_card, cards)
+            },
+            Violation::HandOverTwentyOne { total } => write!(f, "a hand that hasn't busted counts {}", total),
+            Violation::PlayingBust => write!(f, "a busted hand is still in play"),
+        }
+    }
+}
+
+fn card_counts<'a, I: Iterator<Item = &'a Card>>(cards: I) -> Vec<usize> {
+    let mut counts = vec![0; CARDS_PER_DECK];
+    for card in cards {
+        counts[usize::from(card.to_index())] += 1;
+    }
+    counts
+}
+
+fn check_whole_decks<'a, I: Iterator<Item = &'a Card>>(deck: &'a Deck, table: I) -> Result<(), Violation> {
+    let all_cards: Vec<&Card> =
+        deck.remaining_cards.iter().chain(deck.discard_tray()).chain(deck.burned()).chain(table).collect();
+    let cards = all_cards.len();
+    if cards == 0 || !cards.is_multiple_of(CARDS_PER_DECK) {
+        return Err(Violation::PartialShoe { cards });
+    }
+    let expected = cards / CARDS_PER_DECK;
+    let counts = card_counts(all_cards.into_iter());
+    for suit in CardSuit::ALL_VALUES.iter() {
+        for value in CardValue::ALL_VALUES.iter() {
+            let card = Card { suit: *suit, value: *value };
+            let found = counts[usize::from(card.to_index())];
+            if found != expected {
+                return Err(Violation::CardCount { card, expected, found });
+            }
+        }
+    }
+    match deck.cut_card() {
+        Option::Some(cut_card) if cut_card > cards => Err(Violation::CutCardPastEnd { cut_card, cards }),
+        _ => Ok(()),
+    }
+}
+
+// Every card of the decks the shoe started with is still accounted for,
+// whether waiting to be dealt, in the discard tray or burned. Holds between
+// hands, once the table is cleared, and only for shoes made of whole decks,
+// not for composed or practice shoes.
+pub fn check_deck(deck: &Deck) -> Result<(), Violation> {
+    check_whole_decks(deck, std::iter::empty())
+}
+
+// The same in the middle of a hand, counting the cards on the table too:
+// each card is in the shoe, the tray or a hand, and only in one of them.
+pub fn check_cards_dealt(state: &GameState) -> Result<(), Violation> {
+    let player_state = state.player_state();
+    let player_cards = player_state.player_cards();
+    let dealer_cards = player_state.dealer_hand.iter().map(|dealt| &dealt.card);
+    check_whole_decks(&player_state.deck, player_cards.iter().chain(dealer_cards))
+}
+
+// Every total a hand reports is one it can stand on: nothing over 21 is
+// offered, and a hand with no total left has busted.
+pub fn check_hand_value(hand: &[Card]) -> Result<(), Violation> {
+    let card_values: Vec<CardValue> = hand.iter().map(|card| card.value).collect();
+    match calculate_current_hand_value(&card_values).iter().find(|hand_value| hand_value.value > 21) {
+        Option::Some(hand_value) => Err(Violation::HandOverTwentyOne { total: hand_value.value }),
+        Option::None => Ok(()),
+    }
+}
+
+// All of the above for a hand in any state, dealt from a shoe of whole
+// decks.
+pub fn check_state(state: &GameState) -> Result<(), Violation> {
+    let player_state = state.player_state();
+    check_cards_dealt(state)?;
+    check_hand_value(&player_state.hand)?;
+    for split in player_state.split_hands.iter() {
+        check_hand_value(&split.hand)?;
+    }
+    let dealer_cards: Vec<Card> = player_state.dealer_hand.iter().map(|dealt| dealt.card).collect();
+    check_hand_value(&dealer_cards)?;
+    if matches!(state, GameState::Continuing(_)) && player_state.hand.is_bust() {
+        return Err(Violation::PlayingBust);
+    }
+    Ok(())
+}
+
+impl Deck {
+    pub fn is_consistent(&self) -> bool {
+        check_deck(self).is_ok()
+    }
+}
@@ -0,0 +1,153 @@
+use std::io;
+
+use crate::fairness::{self, FairnessError};
+use crate::shuffle::Shuffler;
+use crate::{Card, Deck};
+
+// Experimental: a shuffle two players make together, so that neither the
+// dealer's side nor the player's can stack the shoe. Each side picks a
+// secret and sends a commitment to it, the hash of the secret. Once both
+// commitments are in, both reveal their secrets, check the other's against
+// its commitment and shuffle with a seed hashed from the two. Neither side
+// learns the other's secret before it has committed to its own, so neither
+// can pick one that steers the shuffle. What this doesn't stop is the side
+// that reveals last walking away once it has seen the shoe it would get.
+//
+// The shuffle is Fisher-Yates driven by SHA-256 of the seed and a counter,
+// rather than by a random number generator, so that any implementation of
+// the protocol deals the same shoe from the same secrets.
+
+// Which of the two contributions comes first in the combined seed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Role {
+    Dealer,
+    Player,
+}
+
+enum Step {
+    // Our commitment is out; theirs isn't in yet.
+    Committed,
+    // Both commitments are in, so our secret can be revealed.
+    BothCommitted { theirs: String },
+    Revealed { theirs: String },
+    Done,
+}
+
+// One side's half of the protocol. Send `commitment()`, pass the other
+// side's to `receive_commitment`, send `reveal()` and pass the other side's
+// secret to `receive_secret` for the shoe.
+pub struct JointShuffle {
+    role: Role,
+    secret: String,
+    step: Step,
+}
+
+impl JointShuffle {
+    pub fn new(role: Role) -> io::Result<JointShuffle> {
+        Ok(JointShuffle::with_secret(role, &fairness::new_nonce()?))
+    }
+
+    pub fn with_secret(role: Role, secret: &str) -> JointShuffle {
+        JointShuffle { role, secret: secret.to_string(), step: Step::Committed }
+    }
+
+    pub fn commitment(&self) -> String {
+        commitment_to(&self.secret)
+    }
+
+    pub fn receive_commitment(&mut self, theirs: &str) -> Result<(), FairnessError> {
+        match self.step {
+            Step::Committed => {
+                self.step = Step::BothCommitted { theirs: theirs.trim().to_lowercase() };
+                Ok(())
+            },
+            _ => Err(FairnessError::OutOfOrder("the other side has already committed")),
+        }
+    }
+
+    // Refused until the other side has committed, since revealing first
+    // would let them choose their secret knowing ours.
+    pub fn reveal(&mut self) -> Result<String, FairnessError> {
+        match &self.step {
+            Step::BothCommitted { theirs } => {
+                self.step = Step::Revealed { theirs: theirs.clone() };
+                Ok(self.secret.clone())
+            },
+            Step::Revealed { .. } => Ok(self.secret.clone()),
+            Step::Committed => Err(FairnessError::OutOfOrder("the other side must commit before we reveal")),
+            Step::Done => Err(FairnessError::OutOfOrder("the shuffle is already done")),
+        }
+    }
+
+    // Checks the other side's secret and shuffles `decks` decks with the
+    // combined seed.
+    pub fn receive_secret(&mut self, theirs: &str, decks: u32) -> Result<Deck, FairnessError> {
+        let committed = match &self.step {
+            Step::Revealed { theirs } => theirs.clone(),
+            _ => return Err(FairnessError::OutOfOrder("secrets are exchanged once both are committed and ours is revealed")),
+        };
+        let revealed = commitment_to(theirs);
+        if revealed != committed {
+            return Err(FairnessError::Mismatch { committed, revealed });
+        }
+        self.step = Step::Done;
+        let seed = match self.role {
+            Role::Dealer => combined_seed(&self.secret, theirs),
+            Role::Player => combined_seed(theirs, &self.secret),
+        };
+        let mut deck = Deck::with_decks(decks);
+        deck.shuffle(&mut HashShuffle::new(seed));
+        Ok(deck)
+    }
+}
+
+pub fn commitment_to(secret: &str) -> String {
+    fairness::hex(&fairness::sha256(secret.as_bytes()))
+}
+
+pub fn combined_seed(dealer_secret: &str, player_secret: &str) -> [u8; 32] {
+    fairness::sha256(format!("{}:{}", dealer_secret, player_secret).as_bytes())
+}
+
+// Fisher-Yates with its randomness from SHA-256 in counter mode.
+pub struct HashShuffle {
+    seed: [u8; 32],
+    counter: u64,
+}
+
+impl HashShuffle {
+    pub fn new(seed: [u8; 32]) -> HashShuffle {
+        HashShuffle { seed, counter: 0 }
+    }
+
+    fn next_word(&mut self) -> u64 {
+        let mut block = self.seed.to_vec();
+        block.extend_from_slice(&self.counter.to_be_bytes());
+        self.counter += 1;
+        let digest = fairness::sha256(&block);
+        let mut word = [0; 8];
+        word.copy_from_slice(&digest[..8]);
+        u64::from_be_bytes(word)
+    }
+
+    // Uniform in 0..bound: words past the last whole multiple of `bound`
+    // are drawn again rather than folded in, which would favour the low end.
+    fn below(&mut self, bound: u64) -> u64 {
+        let limit = u64::MAX - u64::MAX % bound;
+        loop {
+            let word = self.next_word();
+            if word < limit {
+                return word % bound;
+            }
+        }
+    }
+}
+
+impl Shuffler for HashShuffle {
+    fn shuffle(&mut self, cards: &mut [Card]) {
+        for index in (1..cards.len()).rev() {
+            let other = self.below(index as u64 + 1) as usize;
+            cards.swap(index, other);
+        }
+    }
+}
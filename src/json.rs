@@ -0,0 +1,246 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(BTreeMap<String, JsonValue>),
+}
+
+impl JsonValue {
+    pub fn object(fields: Vec<(&str, JsonValue)>) -> JsonValue {
+        JsonValue::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect()
+        )
+    }
+
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(fields) => fields.get(key),
+            _ => Option::None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(s) => Option::Some(s),
+            _ => Option::None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(n) => Option::Some(*n),
+            _ => Option::None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(values) => Option::Some(values),
+            _ => Option::None,
+        }
+    }
+
+    pub fn parse(input: &str) -> Option<JsonValue> {
+        let mut parser = Parser { chars: input.chars().collect(), position: 0 };
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+        if parser.position == parser.chars.len() {
+            Option::Some(value)
+        } else {
+            Option::None
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool(b) => write!(f, "{}", b),
+            JsonValue::Number(n) => write!(f, "{}", n),
+            JsonValue::String(s) => write_string(f, s),
+            JsonValue::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            },
+            JsonValue::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            },
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).cloned()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.position += 1;
+        c
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Option::Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn expect_word(&mut self, word: &str, value: JsonValue) -> Option<JsonValue> {
+        for expected in word.chars() {
+            if self.next()? != expected {
+                return Option::None;
+            }
+        }
+        Option::Some(value)
+    }
+
+    fn parse_value(&mut self) -> Option<JsonValue> {
+        self.skip_whitespace();
+        match self.peek()? {
+            'n' => self.expect_word("null", JsonValue::Null),
+            't' => self.expect_word("true", JsonValue::Bool(true)),
+            'f' => self.expect_word("false", JsonValue::Bool(false)),
+            '"' => self.parse_string().map(JsonValue::String),
+            '[' => self.parse_array(),
+            '{' => self.parse_object(),
+            _ => self.parse_number(),
+        }
+    }
+
+    fn parse_number(&mut self) -> Option<JsonValue> {
+        let start = self.position;
+        while let Option::Some(c) = self.peek() {
+            if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' || c == 'e' || c == 'E' {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+        let raw: String = self.chars[start..self.position].iter().collect();
+        raw.parse().ok().map(JsonValue::Number)
+    }
+
+    fn parse_string(&mut self) -> Option<String> {
+        if self.next()? != '"' {
+            return Option::None;
+        }
+        let mut result = String::new();
+        loop {
+            match self.next()? {
+                '"' => return Option::Some(result),
+                '\\' => match self.next()? {
+                    '"' => result.push('"'),
+                    '\\' => result.push('\\'),
+                    '/' => result.push('/'),
+                    'b' => result.push('\u{8}'),
+                    'f' => result.push('\u{c}'),
+                    'n' => result.push('\n'),
+                    'r' => result.push('\r'),
+                    't' => result.push('\t'),
+                    'u' => {
+                        let hex: String = (0..4).filter_map(|_| self.next()).collect();
+                        let code = u32::from_str_radix(&hex, 16).ok()?;
+                        result.push(std::char::from_u32(code)?);
+                    },
+                    _ => return Option::None,
+                },
+                c => result.push(c),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Option<JsonValue> {
+        self.next();
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.peek()? == ']' {
+            self.next();
+            return Option::Some(JsonValue::Array(values));
+        }
+        loop {
+            values.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.next()? {
+                ',' => continue,
+                ']' => return Option::Some(JsonValue::Array(values)),
+                _ => return Option::None,
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Option<JsonValue> {
+        self.next();
+        let mut fields = BTreeMap::new();
+        self.skip_whitespace();
+        if self.peek()? == '}' {
+            self.next();
+            return Option::Some(JsonValue::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            if self.next()? != ':' {
+                return Option::None;
+            }
+            let value = self.parse_value()?;
+            fields.insert(key, value);
+            self.skip_whitespace();
+            match self.next()? {
+                ',' => continue,
+                '}' => return Option::Some(JsonValue::Object(fields)),
+                _ => return Option::None,
+            }
+        }
+    }
+}
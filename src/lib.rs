@@ -0,0 +1,1323 @@
+// Without `std` the rules engine builds on its own, with an allocator, and
+// everything that needs an operating system is left out.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+use core::ops::Deref;
+use core::str::FromStr;
+
+use crate::deviation::upcard_value;
+use crate::error::BlackjackError;
+use crate::i18n::{fill, Language};
+use crate::rules::{GameRules, HoleCard, Variant, MAX_DECKS, PONTOON_MINIMUM_STICK};
+use crate::shoe::RankCounts;
+use crate::shuffle::{ReshuffleKey, Shuffler};
+
+#[cfg(feature = "std")]
+pub mod achievements;
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "std")]
+pub mod ascii;
+#[cfg(feature = "std")]
+pub mod async_game;
+#[cfg(feature = "std")]
+pub mod betting;
+pub mod chips;
+#[cfg(feature = "std")]
+pub mod composition;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
+pub mod counting;
+pub mod currency;
+pub mod dealer;
+pub mod deviation;
+pub mod error;
+#[cfg(feature = "std")]
+pub mod events;
+#[cfg(feature = "std")]
+pub mod export;
+#[cfg(feature = "std")]
+pub mod fairness;
+pub mod fast_rng;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod fixtures;
+#[cfg(feature = "gui")]
+pub mod gui;
+#[cfg(feature = "std")]
+pub mod hand_history;
+pub mod i18n;
+#[cfg(feature = "std")]
+pub mod invariants;
+#[cfg(feature = "std")]
+pub mod joint_shuffle;
+#[cfg(feature = "std")]
+pub mod json;
+#[cfg(feature = "std")]
+pub mod mistakes;
+pub mod payout;
+pub mod promotions;
+pub mod protocol;
+pub mod rules;
+#[cfg(feature = "std")]
+pub mod scripted;
+#[cfg(feature = "std")]
+pub mod service;
+pub mod session_limits;
+pub mod settlement;
+pub mod shoe;
+pub mod shuffle;
+#[cfg(feature = "std")]
+pub mod shuffle_tracking;
+#[cfg(feature = "std")]
+pub mod simulation;
+#[cfg(feature = "std")]
+pub mod solver;
+#[cfg(feature = "std")]
+pub mod sound;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "std")]
+pub mod strategy;
+#[cfg(feature = "std")]
+pub mod strategy_file;
+#[cfg(feature = "std")]
+pub mod strategy_table;
+#[cfg(feature = "std")]
+pub mod summary;
+#[cfg(feature = "std")]
+pub mod table;
+#[cfg(feature = "std")]
+pub mod tournament;
+#[cfg(feature = "std")]
+pub mod trace;
+#[cfg(feature = "std")]
+pub mod trends;
+#[cfg(feature = "std")]
+pub mod wasm;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Action {
+    Hit,
+    Stand,
+    DoubleDown,
+    SplitCards,
+    Surrender,
+    Insurance,
+    EvenMoney,
+    DeclineInsurance
+}
+
+// Against a dealer ace the hand opens with an insurance decision (even money
+// when the player holds a natural) before normal play starts.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Phase {
+    Insurance,
+    Playing,
+    // At a table with other seats the player has finished but the dealer
+    // only plays once every seat has; see `table::Round`.
+    AwaitingDealer,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GameState {
+    GameWon(PlayerState),
+    BlackjackWin(PlayerState),
+    GameLost(PlayerState),
+    Push(PlayerState),
+    Surrendered(PlayerState),
+    Continuing(PlayerState)
+}
+
+impl GameState {
+    // Cards go out in casino order: player, dealer upcard, player, dealer
+    // hole card. A player natural is settled straight away unless the dealer
+    // shows an ace, in which case even money is offered first.
+    pub fn start(deck: Deck) -> GameState {
+        GameState::start_with_rules(deck, GameRules::default())
+    }
+
+    // As `start_with_rules`, but refuses rules that can't be played and a
+    // shoe too short for the opening deal, rather than dealing short hands.
+    pub fn try_start_with_rules(deck: Deck, rules: GameRules) -> Result<GameState, BlackjackError> {
+        rules.validate()?;
+        let needed = match rules.hole_card {
+            HoleCard::Dealt => 4,
+            HoleCard::AfterPlayers => 3,
+        };
+        if deck.remaining() < needed {
+            return Err(BlackjackError::EmptyShoe { needed, remaining: deck.remaining() });
+        }
+        Ok(GameState::start_with_rules(deck, rules))
+    }
+
+    pub fn start_with_rules(mut deck: Deck, rules: GameRules) -> GameState {
+        let empty_shoe = &rules.empty_shoe;
+        let first_card = deck.draw_with(empty_shoe);
+        // Neither of a pontoon dealer's cards is shown.
+        let face_up = rules.variant != Variant::Pontoon;
+        let upcard = deck.draw_with(empty_shoe).map(|card| DealtCard { card, face_up });
+        let second_card = deck.draw_with(empty_shoe);
+        let hole_card = match rules.hole_card {
+            HoleCard::Dealt => deck.draw_with(empty_shoe).map(|card| DealtCard { card, face_up: false }),
+            HoleCard::AfterPlayers => Option::None,
+        };
+        let internal_state = PlayerState {
+            deck,
+            hand: first_card.into_iter().chain(second_card).collect(),
+            split_hands: Vec::new(),
+            waiting_hands: Vec::new(),
+            dealer_hand: upcard.into_iter().chain(hole_card).collect(),
+            doubled: false,
+            phase: Phase::Playing,
+            insured: false,
+            even_money: false,
+            shares_dealer: false,
+            rules
+        };
+        GameState::open(internal_state)
+    }
+
+    fn open(mut player_state: PlayerState) -> GameState {
+        if player_state.rules.dealer.policy().shows_hole_card() {
+            player_state.reveal_dealer_hand();
+        }
+        if player_state.dealer_upcard().map(|card| &card.value) == Option::Some(&CardValue::Ace) {
+            player_state.phase = Phase::Insurance;
+            GameState::Continuing(player_state)
+        } else if player_state.hand.is_blackjack() || player_state.dealer_peeks_natural() {
+            settle_against_dealer(player_state)
+        } else {
+            GameState::Continuing(player_state)
+        }
+    }
+
+    pub fn player_state(&self) -> &PlayerState {
+        match self {
+            GameState::GameLost(p) => p,
+            GameState::GameWon(p) => p,
+            GameState::BlackjackWin(p) => p,
+            GameState::Push(p) => p,
+            GameState::Surrendered(p) => p,
+            GameState::Continuing(p) => p,
+        }
+    }
+
+    pub fn player_state_mut(&mut self) -> &mut PlayerState {
+        match self {
+            GameState::GameLost(p) => p,
+            GameState::GameWon(p) => p,
+            GameState::BlackjackWin(p) => p,
+            GameState::Push(p) => p,
+            GameState::Surrendered(p) => p,
+            GameState::Continuing(p) => p,
+        }
+    }
+
+    pub fn into_player_state(self) -> PlayerState {
+        match self {
+            GameState::GameLost(p) => p,
+            GameState::GameWon(p) => p,
+            GameState::BlackjackWin(p) => p,
+            GameState::Push(p) => p,
+            GameState::Surrendered(p) => p,
+            GameState::Continuing(p) => p,
+        }
+    }
+
+    // Clears the table once the hand is settled: the player's and dealer's
+    // cards go to the discard tray, and the shoe is handed back for the next
+    // hand.
+    pub fn discard_hand(self) -> Deck {
+        let player_state = self.into_player_state();
+        let player_cards = player_state.player_cards();
+        let mut deck = player_state.deck;
+        deck.discard(player_cards);
+        deck.discard(player_state.dealer_hand.into_iter().map(|dealt| dealt.card));
+        deck
+    }
+
+    // Walking away from a hand that is still being played gives it up as a
+    // surrender, and forfeits any other hands split from it; a finished hand
+    // is left as it is.
+    pub fn abandon(self) -> GameState {
+        match self {
+            GameState::Continuing(mut player_state) if player_state.phase != Phase::AwaitingDealer => {
+                let waiting = mem::take(&mut player_state.waiting_hands);
+                player_state.split_hands.extend(waiting.into_iter().map(|hand| SplitHand {
+                    hand,
+                    doubled: false,
+                    outcome: Option::None,
+                }));
+                for split in player_state.split_hands.iter_mut() {
+                    split.outcome = Option::Some(HandOutcome::Lost);
+                }
+                player_state.reveal_dealer_hand();
+                GameState::Surrendered(player_state)
+            },
+            finished => finished,
+        }
+    }
+
+    // Doubling, splitting and surrendering are only offered as the first
+    // decision on a hand, and surrendering not at all after a split. A pair
+    // splits until the player has as many hands as the rules allow, and a
+    // split hand doubles only where the rules allow doubling after a split.
+    pub fn legal_actions(&self) -> Vec<Action> {
+        match self {
+            GameState::Continuing(player_state) if player_state.phase == Phase::Insurance => {
+                if player_state.hand.is_blackjack() {
+                    vec![Action::EvenMoney, Action::DeclineInsurance]
+                } else {
+                    vec![Action::Insurance, Action::DeclineInsurance]
+                }
+            },
+            GameState::Continuing(player_state) if player_state.phase == Phase::AwaitingDealer => Vec::new(),
+            GameState::Continuing(player_state) => {
+                let mut actions = vec![Action::Hit];
+                let total = player_state.hand.total().unwrap_or(0);
+                if player_state.rules.variant != Variant::Pontoon || total >= PONTOON_MINIMUM_STICK {
+                    actions.push(Action::Stand);
+                }
+                if player_state.hand.card_count() == 2 {
+                    let may_double = !player_state.has_split() || player_state.rules.double_after_split;
+                    if may_double && player_state.rules.double_on.allows(total) {
+                        actions.push(Action::DoubleDown);
+                    }
+                    if player_state.hand.can_split() && player_state.hand_count() < player_state.rules.max_split_hands {
+                        actions.push(Action::SplitCards);
+                    }
+                    if player_state.rules.surrender && !player_state.has_split() {
+                        actions.push(Action::Surrender);
+                    }
+                }
+                actions
+            },
+            _ => Vec::new(),
+        }
+    }
+}
+
+// Returned by `deal_with_action` for an action that isn't legal right now.
+// The game is handed back untouched in `state`; it is boxed to keep the
+// error small.
+#[derive(Debug)]
+pub struct IllegalAction {
+    pub action: Action,
+    pub state: Box<GameState>,
+}
+
+impl fmt::Display for IllegalAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let legal_actions = self.state.legal_actions();
+        if legal_actions.is_empty() {
+            write!(f, "cannot {}, the game is over", protocol::encode_action(&self.action))
+        } else {
+            write!(
+                f,
+                "cannot {} right now, choose one of: {}",
+                protocol::encode_action(&self.action),
+                describe_actions(&legal_actions, &self.state.player_state().rules)
+            )
+        }
+    }
+}
+
+// Names the actions as the rules' variant calls them.
+pub fn describe_actions(actions: &[Action], rules: &GameRules) -> String {
+    describe_actions_in(&Language::English, actions, rules)
+}
+
+pub fn describe_actions_in(language: &Language, actions: &[Action], rules: &GameRules) -> String {
+    actions.iter().map(|action| language.action_name(&rules.variant, action)).collect::<Vec<&str>>().join(", ")
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CardSuit {
+    Clubs,
+    Hearts,
+    Diamonds,
+    Spades
+}
+
+impl CardSuit {
+    pub const ALL_VALUES: [CardSuit; 4] = [
+        CardSuit::Clubs,
+        CardSuit::Hearts,
+        CardSuit::Diamonds,
+        CardSuit::Spades
+    ];
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HandValue {
+    pub value: u32
+}
+
+impl TryFrom<u32> for HandValue {
+    type Error = BlackjackError;
+
+    fn try_from(value: u32) -> Result<HandValue, BlackjackError> {
+        if value <= 21 {
+            Ok(HandValue { value })
+        } else {
+            Err(BlackjackError::InvalidHandValue(value))
+        }
+    }
+}
+
+impl HandValue {
+    pub fn from_u32(x: u32) -> Option<HandValue> {
+        HandValue::try_from(x).ok()
+    }
+
+    pub fn combine_with_separate_value(&self, other_value: &HandValue) -> Option<HandValue> {
+        let raw_result = self.value + other_value.value;
+        if raw_result <= 21 {
+            Option::Some(
+                HandValue {
+                    value: raw_result
+                }
+            )
+        } else {
+            Option::None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CardValue {
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+    Ace
+}
+
+impl CardValue {
+    pub const ALL_VALUES: [CardValue; 13] = [
+        CardValue::Two,
+        CardValue::Three,
+        CardValue::Four,
+        CardValue::Five,
+        CardValue::Six,
+        CardValue::Seven,
+        CardValue::Eight,
+        CardValue::Nine,
+        CardValue::Ten,
+        CardValue::Jack,
+        CardValue::Queen,
+        CardValue::King,
+        CardValue::Ace,
+    ];
+}
+
+// Ranks are read as the protocol writes them: 2-9, T, J, Q, K and A.
+impl TryFrom<char> for CardValue {
+    type Error = BlackjackError;
+
+    fn try_from(c: char) -> Result<CardValue, BlackjackError> {
+        protocol::decode_card_value(c).ok_or_else(|| BlackjackError::InvalidCard(c.to_string()))
+    }
+}
+
+// A rank then a suit, such as "AS" or "TD".
+impl TryFrom<&str> for Card {
+    type Error = BlackjackError;
+
+    fn try_from(raw: &str) -> Result<Card, BlackjackError> {
+        protocol::decode_card(raw).ok_or_else(|| BlackjackError::InvalidCard(raw.to_string()))
+    }
+}
+
+// Built directly rather than through `HandValue::from_u32`: a single card
+// is always a valid total, so there is nothing to check or panic on.
+pub fn card_value_to_hand_value(card_value: &CardValue) -> Vec<HandValue> {
+    let total = |value| HandValue { value };
+    match card_value {
+        CardValue::Two => vec![total(2)],
+        CardValue::Three=> vec![total(3)],
+        CardValue::Four => vec![total(4)],
+        CardValue::Five => vec![total(5)],
+        CardValue::Six => vec![total(6)],
+        CardValue::Seven => vec![total(7)],
+        CardValue::Eight => vec![total(8)],
+        CardValue::Nine => vec![total(9)],
+        CardValue::Ten => vec![total(10)],
+        CardValue::Jack => vec![total(10)],
+        CardValue::Queen => vec![total(10)],
+        CardValue::King => vec![total(10)],
+        CardValue::Ace => vec![total(1), total(11)],
+    }
+}
+
+pub fn combine_possible_values(values_0: &[HandValue], values_1: &[HandValue]) -> Vec<HandValue> {
+    values_0
+        .iter()
+        .flat_map::<Vec<Option<HandValue>>, _>(
+            |x: &HandValue| {
+                let result = values_1
+                    .iter()
+                    .map(|y: &HandValue| x.combine_with_separate_value(y))
+                    .collect();
+                result
+            }
+        )
+        .flatten()
+        .collect()
+}
+
+// A hand's value without listing every way of counting it: the total with
+// each ace as one and the number of aces. Only one ace can ever count as
+// eleven, since two would make 22, so a hand has at most two totals.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HandTotals {
+    pub hard: u32,
+    pub aces: u32,
+}
+
+impl HandTotals {
+    pub fn of_values<'a, I: IntoIterator<Item = &'a CardValue>>(values: I) -> HandTotals {
+        let mut totals = HandTotals { hard: 0, aces: 0 };
+        for value in values {
+            totals.add(value);
+        }
+        totals
+    }
+
+    pub fn of(hand: &[Card]) -> HandTotals {
+        HandTotals::of_values(hand.iter().map(|card| &card.value))
+    }
+
+    // One more card in the hand.
+    pub fn add(&mut self, value: &CardValue) {
+        self.hard += match value {
+            CardValue::Two => 2,
+            CardValue::Three => 3,
+            CardValue::Four => 4,
+            CardValue::Five => 5,
+            CardValue::Six => 6,
+            CardValue::Seven => 7,
+            CardValue::Eight => 8,
+            CardValue::Nine => 9,
+            CardValue::Ten | CardValue::Jack | CardValue::Queen | CardValue::King => 10,
+            CardValue::Ace => {
+                self.aces += 1;
+                1
+            },
+        };
+    }
+
+    // An ace still counting as eleven.
+    pub fn is_soft(&self) -> bool {
+        self.aces > 0 && self.hard + 10 <= 21
+    }
+
+    pub fn is_bust(&self) -> bool {
+        self.hard > 21
+    }
+
+    // The highest total that doesn't bust, if there is one.
+    pub fn best(&self) -> Option<u32> {
+        if self.is_soft() {
+            Option::Some(self.hard + 10)
+        } else if self.is_bust() {
+            Option::None
+        } else {
+            Option::Some(self.hard)
+        }
+    }
+}
+
+// Every total the hand can count as without busting, lowest first.
+pub fn calculate_current_hand_value(hand: &[CardValue]) -> Vec<HandValue> {
+    let totals = HandTotals::of_values(hand);
+    let mut values = Vec::with_capacity(2);
+    if !totals.is_bust() {
+        values.push(HandValue { value: totals.hard });
+    }
+    if totals.is_soft() {
+        values.push(HandValue { value: totals.hard + 10 });
+    }
+    values
+}
+
+pub fn cartesian_product<'a, 'b, A, B>(xs: &'a [A], ys: &'b [B]) -> Vec<(&'a A, &'b B)> {
+    xs
+        .iter()
+        .flat_map::<Vec<(&A, &B)>, _>(|x| ys.iter().map(|y| (x, y)).collect())
+        .collect()
+}
+
+// Every total the hand can count as, busted or not, lowest first: one for
+// each number of aces counted as eleven.
+pub fn raw_calculate_current_hand_value(hand: &[CardValue]) -> Vec<u32> {
+    let totals = HandTotals::of_values(hand);
+    (0..=totals.aces).map(|elevens| totals.hard + 10 * elevens).collect()
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Card {
+    pub suit: CardSuit,
+    pub value: CardValue
+}
+
+impl Card {
+    // Every card of a deck as one of 52 numbers: the suit's place in
+    // `CardSuit::ALL_VALUES` times 13, plus the rank's in
+    // `CardValue::ALL_VALUES`, so `index % 13` is the rank.
+    pub fn to_index(&self) -> u8 {
+        self.suit as u8 * 13 + self.value as u8
+    }
+
+    pub fn from_index(index: u8) -> Option<Card> {
+        let suit = *CardSuit::ALL_VALUES.get(usize::from(index / 13))?;
+        Option::Some(Card { suit, value: CardValue::ALL_VALUES[usize::from(index % 13)] })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DealtCard {
+    pub card: Card,
+    pub face_up: bool
+}
+
+// The player's cards. It derefs to the cards themselves, so a hand goes
+// wherever a slice of cards does.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Hand {
+    cards: Vec<Card>,
+}
+
+impl Hand {
+    pub fn new() -> Hand {
+        Hand { cards: Vec::new() }
+    }
+
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    pub fn card_count(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn push_card(&mut self, card: Card) {
+        self.cards.push(card);
+    }
+
+    // Splits a pair: the first card stays and the second becomes a hand of
+    // its own.
+    pub fn split(&mut self) -> Hand {
+        Hand { cards: self.cards.split_off(1) }
+    }
+
+    // The highest total that doesn't bust, or none once the hand has.
+    pub fn total(&self) -> Option<u32> {
+        best_hand_value(&self.cards)
+    }
+
+    // An ace can still count as 11 or as 1.
+    pub fn is_soft(&self) -> bool {
+        HandTotals::of(&self.cards).is_soft()
+    }
+
+    pub fn is_bust(&self) -> bool {
+        is_hand_too_large(&self.cards)
+    }
+
+    // A natural: 21 in the first two cards.
+    pub fn is_blackjack(&self) -> bool {
+        is_natural(&self.cards)
+    }
+
+    // Two cards of the same rank, such as two kings.
+    pub fn is_pair(&self) -> bool {
+        match &self.cards[..] {
+            [first, second] => first.value == second.value,
+            _ => false,
+        }
+    }
+
+    // Two cards of the same value, which may be split even when they differ
+    // in rank, such as a king and a queen.
+    pub fn can_split(&self) -> bool {
+        match &self.cards[..] {
+            [first, second] => upcard_value(&first.value) == upcard_value(&second.value),
+            _ => false,
+        }
+    }
+}
+
+impl Deref for Hand {
+    type Target = [Card];
+
+    fn deref(&self) -> &[Card] {
+        &self.cards
+    }
+}
+
+impl From<Vec<Card>> for Hand {
+    fn from(cards: Vec<Card>) -> Hand {
+        Hand { cards }
+    }
+}
+
+impl FromIterator<Card> for Hand {
+    fn from_iter<I: IntoIterator<Item = Card>>(cards: I) -> Hand {
+        Hand { cards: cards.into_iter().collect() }
+    }
+}
+
+impl IntoIterator for Hand {
+    type Item = Card;
+    type IntoIter = vec::IntoIter<Card>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.cards.into_iter()
+    }
+}
+
+// How one of several hands came out. The hand being played when the round
+// ends has its outcome in the game state instead.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum HandOutcome {
+    Won,
+    Blackjack,
+    Lost,
+    Push,
+}
+
+// A hand split off earlier in the round and already played. Its outcome is
+// set once the dealer has played.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SplitHand {
+    pub hand: Hand,
+    pub doubled: bool,
+    pub outcome: Option<HandOutcome>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlayerState {
+    pub deck: Deck,
+    // The hand being played, or the last one played once the round is over.
+    pub hand: Hand,
+    // After a split, the hands played before `hand`, in order...
+    pub split_hands: Vec<SplitHand>,
+    // ...and those still to come, one card each, the next one last.
+    pub waiting_hands: Vec<Hand>,
+    pub dealer_hand: Vec<DealtCard>,
+    pub doubled: bool,
+    pub phase: Phase,
+    // Half the bet is staked on the dealer having a natural.
+    pub insured: bool,
+    // A natural against an ace was paid 1:1 before the hole card was seen.
+    pub even_money: bool,
+    // Set for a seat at a shared table, where a hand that needs the dealer
+    // waits in `Phase::AwaitingDealer` rather than being settled.
+    pub shares_dealer: bool,
+    pub rules: GameRules
+}
+
+impl PlayerState {
+    pub fn create_hand_values(&self) -> Vec<CardValue> {
+        self.hand.iter().map(|card| card.value).collect()
+    }
+
+    pub fn dealer_upcard(&self) -> Option<&Card> {
+        self.dealer_hand.iter().find(|dealt| dealt.face_up).map(|dealt| &dealt.card)
+    }
+
+    // What the player is allowed to see of the dealer's hand right now.
+    pub fn visible_dealer_cards(&self) -> Vec<Card> {
+        self.dealer_hand
+            .iter()
+            .filter(|dealt| dealt.face_up)
+            .map(|dealt| dealt.card)
+            .collect()
+    }
+
+    // Every card the player hasn't seen: the rest of the shoe, the burned
+    // cards and the dealer's hole card.
+    pub fn unseen_cards(&self) -> RankCounts {
+        let mut unseen = self.hidden_dealer_cards();
+        unseen.extend_from_slice(&self.deck.remaining_cards);
+        unseen.extend_from_slice(&self.deck.burned_cards);
+        RankCounts::of(&unseen)
+    }
+
+    pub fn hidden_dealer_cards(&self) -> Vec<Card> {
+        self.dealer_hand
+            .iter()
+            .filter(|dealt| !dealt.face_up)
+            .map(|dealt| dealt.card)
+            .collect()
+    }
+
+    pub fn dealer_cards(&self) -> Vec<Card> {
+        self.dealer_hand.iter().map(|dealt| dealt.card).collect()
+    }
+
+    // Five cards without busting, which pontoon pays and stands on
+    // automatically.
+    pub fn five_card_trick(&self) -> bool {
+        self.rules.is_five_card_trick(&self.hand)
+    }
+
+    // Whether the peek finds a dealer natural, which settles the round
+    // before the player acts.
+    fn dealer_peeks_natural(&self) -> bool {
+        self.rules.peek
+            && self.rules.variant == Variant::Blackjack
+            && self.dealer_hand.len() == 2
+            && self.dealer_upcard().is_some_and(|card| upcard_value(&card.value) >= 10)
+            && is_natural(&self.dealer_cards())
+    }
+
+    // A blackjack hand that has reached the Charlie without busting.
+    pub fn charlie(&self) -> bool {
+        self.rules.is_charlie(&self.hand)
+    }
+
+    pub fn has_split(&self) -> bool {
+        !self.split_hands.is_empty() || !self.waiting_hands.is_empty()
+    }
+
+    // Counting the one being played.
+    pub fn hand_count(&self) -> usize {
+        1 + self.split_hands.len() + self.waiting_hands.len()
+    }
+
+    // Every card the player has on the table, across all their hands.
+    pub fn player_cards(&self) -> Vec<Card> {
+        self.split_hands
+            .iter()
+            .flat_map(|split| split.hand.iter())
+            .chain(self.hand.iter())
+            .chain(self.waiting_hands.iter().flat_map(|hand| hand.iter()))
+            .cloned()
+            .collect()
+    }
+
+    // A natural only counts as one on the unsplit hand, unless the rules
+    // pay blackjack after a split.
+    fn is_natural_hand(&self, hand: &Hand) -> bool {
+        hand.is_blackjack() && (!self.has_split() || self.rules.blackjack_after_split)
+    }
+
+    // Without a hole card the dealer's second card only comes once the
+    // player is done.
+    fn complete_dealer_deal(&mut self) {
+        if self.dealer_hand.len() == 1 {
+            if let Option::Some(card) = self.deck.draw_with(&self.rules.empty_shoe) {
+                self.dealer_hand.push(DealtCard { card, face_up: true });
+            }
+        }
+    }
+
+    pub fn reveal_dealer_hand(&mut self) {
+        for dealt in self.dealer_hand.iter_mut() {
+            dealt.face_up = true;
+        }
+    }
+}
+
+// Numbered menu entries count from 1 in this order.
+impl Action {
+    pub const ALL_VALUES: [Action; 8] = [
+        Action::Hit,
+        Action::Stand,
+        Action::DoubleDown,
+        Action::SplitCards,
+        Action::Surrender,
+        Action::Insurance,
+        Action::EvenMoney,
+        Action::DeclineInsurance,
+    ];
+
+    pub fn alias(&self) -> &'static str {
+        match self {
+            Action::Hit => "h",
+            Action::Stand => "s",
+            Action::DoubleDown => "d",
+            Action::SplitCards => "p",
+            Action::Surrender => "r",
+            Action::Insurance => "i",
+            Action::EvenMoney => "e",
+            Action::DeclineInsurance => "n",
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParseActionError {
+    Empty,
+    Unknown(String),
+}
+
+impl fmt::Display for ParseActionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<&str> = Action::ALL_VALUES.iter().map(protocol::encode_action).collect();
+        match self {
+            ParseActionError::Empty => write!(f, "no command given, try: {}", names.join("/")),
+            ParseActionError::Unknown(raw) => write!(f, "unknown command {:?}, try: {}", raw, names.join("/")),
+        }
+    }
+}
+
+// Accepts the full name, the one-letter alias or the menu number, in any
+// case: "stand", "S" and "2" are all the same action.
+impl FromStr for Action {
+    type Err = ParseActionError;
+
+    fn from_str(raw: &str) -> Result<Action, ParseActionError> {
+        let word = raw.trim().to_lowercase();
+        if word.is_empty() {
+            return Err(ParseActionError::Empty);
+        }
+        let by_number = word
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| number.checked_sub(1))
+            .and_then(|index| Action::ALL_VALUES.get(index));
+        let by_name = Action::ALL_VALUES.iter().find(|action| {
+            protocol::encode_action(action) == word || action.alias() == word || Variant::Pontoon.action_name(action) == word
+        });
+        by_number
+            .or(by_name)
+            .cloned()
+            .ok_or_else(|| ParseActionError::Unknown(raw.trim().to_string()))
+    }
+}
+
+pub fn parse_action(str: &str) -> Option<Action> {
+    str.parse().ok()
+}
+
+// The numbered menu shown at the prompt, e.g. "1) hit (h)".
+pub fn action_menu() -> String {
+    action_menu_in(&Language::English)
+}
+
+pub fn action_menu_in(language: &Language) -> String {
+    Action::ALL_VALUES
+        .iter()
+        .enumerate()
+        .map(|(index, action)| format!("{}) {} ({})", index + 1, language.translate(protocol::encode_action(action)), action.alias()))
+        .collect::<Vec<String>>()
+        .join("  ")
+}
+
+// The shoe and everything that comes back to it. A card is in exactly one
+// place at a time: waiting in the shoe, on the table in someone's hand, in
+// the discard tray once its round is over, or burned.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Deck {
+    pub(crate) remaining_cards: Vec<Card>,
+    pub(crate) discard_tray: Vec<Card>,
+    pub(crate) burned_cards: Vec<Card>,
+    pub(crate) cut_card: Option<usize>,
+    // Drawn from the table's shuffler each time the shoe is shuffled.
+    pub(crate) reshuffle_key: ReshuffleKey,
+}
+
+impl Deck {
+    pub fn new() -> Deck {
+        Deck::with_decks(1)
+    }
+
+    pub fn with_decks(decks: u32) -> Deck {
+        let mut result = Vec::new();
+        for _ in 0..decks {
+            for suit in CardSuit::ALL_VALUES.iter() {
+                for value in CardValue::ALL_VALUES.iter() {
+                    result.push(
+                        Card {
+                            suit: *suit,
+                            value: *value
+                        }
+                    )
+                }
+            }
+        }
+        Deck {
+            remaining_cards: result,
+            discard_tray: Vec::new(),
+            burned_cards: Vec::new(),
+            cut_card: Option::None,
+            reshuffle_key: ReshuffleKey::default(),
+        }
+    }
+
+    pub fn try_with_decks(decks: u32) -> Result<Deck, BlackjackError> {
+        if decks == 0 || decks > MAX_DECKS {
+            return Err(BlackjackError::InvalidDeckCount(decks));
+        }
+        Ok(Deck::with_decks(decks))
+    }
+
+    pub fn shuffle<S: Shuffler + ?Sized>(&mut self, shuffler: &mut S) {
+        shuffler.shuffle(&mut self.remaining_cards);
+        self.reshuffle_key = shuffler.reshuffle_key();
+    }
+
+    pub fn draw_card(&mut self) -> Option<Card> {
+        draw_card(self)
+    }
+
+    pub fn try_draw_card(&mut self) -> Result<Card, BlackjackError> {
+        draw_card(self).ok_or(BlackjackError::EmptyShoe { needed: 1, remaining: 0 })
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.remaining_cards.len()
+    }
+
+    // Cards from finished rounds, in the order they were discarded.
+    pub fn discard_tray(&self) -> &[Card] {
+        &self.discard_tray
+    }
+
+    pub fn discard<I: IntoIterator<Item = Card>>(&mut self, cards: I) {
+        self.discard_tray.extend(cards);
+    }
+
+    // Counts of each rank still to be dealt.
+    pub fn composition(&self) -> RankCounts {
+        RankCounts::of(&self.remaining_cards)
+    }
+
+    pub fn probability_of_rank(&self, value: &CardValue) -> f64 {
+        self.composition().probability_of_rank(value)
+    }
+}
+
+impl Default for Deck {
+    fn default() -> Deck {
+        Deck::new()
+    }
+}
+
+// The card leaves the shoe for whoever drew it; it comes back through
+// `Deck::discard` once its round is over.
+pub fn draw_card(deck: &mut Deck) -> Option<Card> {
+    deck.remaining_cards.pop()
+}
+
+pub fn is_hand_too_large(hand: &[Card]) -> bool {
+    HandTotals::of(hand).is_bust()
+}
+
+// The highest total that doesn't bust, if there is one.
+pub fn best_hand_value(hand: &[Card]) -> Option<u32> {
+    HandTotals::of(hand).best()
+}
+
+pub fn is_natural(hand: &[Card]) -> bool {
+    hand.len() == 2 && best_hand_value(hand) == Option::Some(21)
+}
+
+// How a hand comes out without the dealer drawing: against a dealer
+// natural, which beats any other 21, or when it has busted, is a natural or
+// has won on its number of cards. None while it still needs the dealer's
+// total.
+fn outcome_before_dealer(player_state: &PlayerState, hand: &Hand, dealer_natural: bool) -> Option<HandOutcome> {
+    let natural = player_state.is_natural_hand(hand);
+    if dealer_natural {
+        let push = natural && player_state.rules.variant != Variant::Pontoon;
+        Option::Some(if push { HandOutcome::Push } else { HandOutcome::Lost })
+    } else if hand.is_bust() {
+        Option::Some(HandOutcome::Lost)
+    } else if natural {
+        Option::Some(HandOutcome::Blackjack)
+    } else if player_state.rules.is_five_card_trick(hand) || player_state.rules.is_charlie(hand) {
+        Option::Some(HandOutcome::Won)
+    } else {
+        Option::None
+    }
+}
+
+fn outcome_against_dealer(player_state: &PlayerState, hand: &Hand, dealer_natural: bool) -> HandOutcome {
+    if let Option::Some(outcome) = outcome_before_dealer(player_state, hand, dealer_natural) {
+        return outcome;
+    }
+    let player_value = hand.total().unwrap_or(0);
+    match best_hand_value(&player_state.dealer_cards()) {
+        Option::None => HandOutcome::Won,
+        Option::Some(dealer_value) if player_value > dealer_value => HandOutcome::Won,
+        Option::Some(dealer_value) if player_value == dealer_value && player_state.rules.variant != Variant::Pontoon => {
+            HandOutcome::Push
+        },
+        Option::Some(_) => HandOutcome::Lost,
+    }
+}
+
+// The player is done: turn over the hole card, let the dealer draw as its
+// policy says (to 17, by the book) and compare each hand. The dealer
+// doesn't draw when every hand is settled without it: against a dealer
+// natural, or when each one has busted or won outright.
+fn settle_against_dealer(mut player_state: PlayerState) -> GameState {
+    if player_state.shares_dealer {
+        player_state.phase = Phase::AwaitingDealer;
+        return GameState::Continuing(player_state);
+    }
+    player_state.complete_dealer_deal();
+    player_state.reveal_dealer_hand();
+    let dealer_natural = is_natural(&player_state.dealer_cards());
+    let dealer_needed = !dealer_natural
+        && player_state
+            .split_hands
+            .iter()
+            .map(|split| &split.hand)
+            .chain(core::iter::once(&player_state.hand))
+            .any(|hand| outcome_before_dealer(&player_state, hand, false).is_none());
+
+    if dealer_needed {
+        let dealer = player_state.rules.dealer.policy();
+        while dealer.must_draw(&player_state.dealer_cards(), &player_state.rules) {
+            match player_state.deck.draw_with(&player_state.rules.empty_shoe) {
+                Option::Some(card) => player_state.dealer_hand.push(DealtCard { card, face_up: true }),
+                Option::None => break,
+            }
+        }
+    }
+
+    let outcomes: Vec<HandOutcome> = player_state
+        .split_hands
+        .iter()
+        .map(|split| outcome_against_dealer(&player_state, &split.hand, dealer_natural))
+        .collect();
+    for (split, outcome) in player_state.split_hands.iter_mut().zip(outcomes) {
+        split.outcome = Option::Some(outcome);
+    }
+    match outcome_against_dealer(&player_state, &player_state.hand, dealer_natural) {
+        HandOutcome::Won => GameState::GameWon(player_state),
+        HandOutcome::Blackjack => GameState::BlackjackWin(player_state),
+        HandOutcome::Lost => GameState::GameLost(player_state),
+        HandOutcome::Push => GameState::Push(player_state),
+    }
+}
+
+// The hand being played is done. After a split the next waiting hand takes
+// its second card and is played; once there are none left the round is
+// settled. A lone hand that busts is lost without the dealer playing.
+fn finish_hand(mut player_state: PlayerState) -> GameState {
+    match player_state.waiting_hands.pop() {
+        Option::Some(next_hand) => {
+            let played = mem::replace(&mut player_state.hand, next_hand);
+            player_state.split_hands.push(SplitHand { hand: played, doubled: player_state.doubled, outcome: Option::None });
+            player_state.doubled = false;
+            play_split_hand(player_state)
+        },
+        Option::None if player_state.hand.is_bust() && !player_state.has_split() => GameState::GameLost(player_state),
+        Option::None => settle_against_dealer(player_state),
+    }
+}
+
+// A hand fresh from a split takes its second card. Split aces stand on it
+// when the rules say so, and any hand stands on 21.
+fn play_split_hand(mut player_state: PlayerState) -> GameState {
+    let split_ace = player_state.hand.first().map(|card| &card.value) == Option::Some(&CardValue::Ace);
+    if !draw_to_hand(&mut player_state)
+        || (split_ace && player_state.rules.split_aces_one_card)
+        || player_state.hand.total() == Option::Some(21)
+    {
+        finish_hand(player_state)
+    } else {
+        GameState::Continuing(player_state)
+    }
+}
+
+// The hole card is turned over as soon as the round is decided.
+pub fn deal_with_action(action: &Action, state: GameState) -> Result<GameState, IllegalAction> {
+    if !state.legal_actions().contains(action) {
+        return Err(IllegalAction { action: action.clone(), state: Box::new(state) });
+    }
+    let mut state = resolve_action(action, state);
+    if !continue_with_game(&state) && state.player_state().phase != Phase::AwaitingDealer {
+        let player_state = state.player_state_mut();
+        // An insurance bet still needs the dealer's second card to settle
+        // after the player busts.
+        if player_state.insured && !player_state.shares_dealer {
+            player_state.complete_dealer_deal();
+        }
+        player_state.reveal_dealer_hand();
+    }
+    Ok(state)
+}
+
+// False when the shoe ran out and the rules end the hand instead.
+fn draw_to_hand(player_state: &mut PlayerState) -> bool {
+    match player_state.deck.draw_with(&player_state.rules.empty_shoe) {
+        Option::Some(card) => {
+            player_state.hand.push_card(card);
+            true
+        },
+        Option::None => false,
+    }
+}
+
+// A player natural that turned down even money is settled right away, as
+// is any hand once the peek finds a dealer natural.
+fn finish_insurance_phase(mut player_state: PlayerState) -> GameState {
+    player_state.phase = Phase::Playing;
+    if player_state.hand.is_blackjack() || player_state.dealer_peeks_natural() {
+        settle_against_dealer(player_state)
+    } else {
+        GameState::Continuing(player_state)
+    }
+}
+
+fn resolve_action(action: &Action, state: GameState) -> GameState {
+    match state {
+        GameState::Continuing(mut player_state) =>
+            match action {
+                Action::Surrender => GameState::Surrendered(player_state),
+                Action::Hit => {
+                    // Out of cards, the hand stands as it is.
+                    if !draw_to_hand(&mut player_state)
+                        || player_state.hand.is_bust()
+                        || player_state.hand.total() == Option::Some(21)
+                        || player_state.five_card_trick()
+                        || player_state.charlie()
+                    {
+                        finish_hand(player_state)
+                    } else {
+                        GameState::Continuing(player_state)
+                    }
+                },
+                Action::Stand => finish_hand(player_state),
+                Action::DoubleDown => {
+                    player_state.doubled = true;
+                    let drew = draw_to_hand(&mut player_state);
+                    let keeps_playing = drew
+                        && player_state.rules.variant == Variant::Pontoon
+                        && player_state.hand.total().is_some_and(|total| total < 21);
+                    if keeps_playing {
+                        // A pontoon buy raises the stake and the hand goes on.
+                        GameState::Continuing(player_state)
+                    } else {
+                        finish_hand(player_state)
+                    }
+                },
+                Action::SplitCards => {
+                    let second_hand = player_state.hand.split();
+                    player_state.waiting_hands.push(second_hand);
+                    play_split_hand(player_state)
+                },
+                // Even money is paid out at 1:1 before the hole card is
+                // looked at, so it is simply a win.
+                Action::EvenMoney => {
+                    player_state.even_money = true;
+                    GameState::GameWon(player_state)
+                },
+                Action::Insurance => {
+                    player_state.insured = true;
+                    finish_insurance_phase(player_state)
+                },
+                Action::DeclineInsurance => finish_insurance_phase(player_state),
+            },
+        finished => finished,
+    }
+}
+
+pub fn continue_with_game(game_state: &GameState) -> bool {
+    match game_state {
+        GameState::GameWon(_) => false,
+        GameState::BlackjackWin(_) => false,
+        GameState::GameLost(_) => false,
+        GameState::Push(_) => false,
+        GameState::Surrendered(_) => false,
+        GameState::Continuing(player_state) => player_state.phase != Phase::AwaitingDealer,
+    }
+}
+
+// "Dealer shows ... and a face-down card", worded for however many of the
+// dealer's cards are showing and hidden. `shown` is empty when none are.
+pub fn describe_dealer(shown: &str, face_down: usize) -> String {
+    describe_dealer_in(&Language::English, shown, face_down)
+}
+
+pub fn describe_dealer_in(language: &Language, shown: &str, face_down: usize) -> String {
+    let message = |english| language.translate(english);
+    match (shown.is_empty(), face_down) {
+        (true, 1) => message("Dealer has a face-down card").to_string(),
+        (true, count) => fill(message("Dealer has {} face-down cards"), &[&count]),
+        (false, 0) => fill(message("Dealer shows {}"), &[&shown]),
+        (false, 1) => fill(message("Dealer shows {} and a face-down card"), &[&shown]),
+        (false, count) => fill(message("Dealer shows {} and {} face-down cards"), &[&shown, &count]),
+    }
+}
+
+// The question put to the player while the insurance decision is open. A
+// natural is offered even money instead of insurance, which comes to the
+// same thing but reads very differently at the table.
+pub fn insurance_prompt(game_state: &GameState) -> Option<&'static str> {
+    match game_state {
+        GameState::Continuing(player_state) if player_state.phase == Phase::Insurance => {
+            if player_state.hand.is_blackjack() {
+                Option::Some("You have blackjack against an ace. Take even money, a sure 1:1 win, or decline and risk a push for the full payout?")
+            } else {
+                Option::Some("The dealer shows an ace. Insurance costs half your bet and pays 2:1 if the dealer has blackjack. Take it?")
+            }
+        },
+        _ => Option::None,
+    }
+}
+
+// A yes or no answer to `insurance_prompt`.
+pub fn insurance_answer(game_state: &GameState, answer: &str) -> Option<Action> {
+    insurance_prompt(game_state)?;
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" if game_state.player_state().hand.is_blackjack() => Option::Some(Action::EvenMoney),
+        "y" | "yes" => Option::Some(Action::Insurance),
+        "no" => Option::Some(Action::DeclineInsurance),
+        _ => Option::None,
+    }
+}
+
+pub fn outcome_message(outcome: &HandOutcome) -> &'static str {
+    match outcome {
+        HandOutcome::Won => "You won",
+        HandOutcome::Blackjack => "Blackjack! You won",
+        HandOutcome::Lost => "You lost",
+        HandOutcome::Push => "Push, your bet is returned",
+    }
+}
+
+pub fn game_message(game_state: &GameState) -> &'static str {
+    match game_state {
+        GameState::GameWon(player_state) if player_state.even_money => "Even money, your blackjack was paid 1:1",
+        GameState::GameWon(player_state) if player_state.five_card_trick() => "Five card trick! You won",
+        GameState::GameWon(player_state) if player_state.charlie() => "Charlie! You won",
+        GameState::GameWon(_) => "You won",
+        GameState::BlackjackWin(player_state) if player_state.rules.variant == Variant::Pontoon => "Pontoon! You won",
+        GameState::BlackjackWin(_) => "Blackjack! You won",
+        GameState::GameLost(_) => "You lost",
+        GameState::Push(_) => "Push, your bet is returned",
+        GameState::Surrendered(_) => "You surrendered half your bet",
+        GameState::Continuing(_) => "The game is still going",
+    }
+}
+
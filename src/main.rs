@@ -1,380 +1,1894 @@
-use std::io::{self, BufRead};
-use rand::{thread_rng, Rng};
-use std::convert::identity;
-
-#[derive(Debug)]
-enum Action {
-    Hit,
-    Stand,
-    DoubleDown,
-    SplitCards,
-    Surrender
-}
-
-#[derive(Debug, Eq, PartialEq)]
-enum GameState<'a> {
-    GameWon(PlayerState<'a>),
-    GameLost(PlayerState<'a>),
-    Continuing(PlayerState<'a>)
-}
-
-impl<'a> GameState<'a> {
-    fn start<'b>(deck: &'b mut Deck) -> GameState<'b> {
-        let internal_state = PlayerState {
-            deck: deck,
-            hand: Vec::new()
-        };
-        GameState::Continuing(internal_state)
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::mem;
+use std::process;
+use std::thread;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use rand::{thread_rng, Rng, SeedableRng, StdRng};
+
+use rust_blackjack::achievements::{self, Achievement};
+use rust_blackjack::analysis::{self, BankrollAnalysis};
+use rust_blackjack::ascii::render_cards;
+use rust_blackjack::betting::bet_strategy_from_name;
+use rust_blackjack::chips::{check_split, chips_for, double_stake, parse_bet, render_stacks, BetError, TableLimits};
+use rust_blackjack::config::{CoachLevel, Config, Speed};
+use rust_blackjack::currency::Currency;
+use rust_blackjack::fixtures::fixture;
+use rust_blackjack::i18n::{fill, Language};
+use rust_blackjack::json::JsonValue;
+use rust_blackjack::mistakes::{Mistake, MistakeLog};
+use rust_blackjack::promotions::{promotion_from_name, RoundModifier, Timed, PROMOTION_NAMES};
+use rust_blackjack::composition::ShoeComposition;
+use rust_blackjack::protocol::{encode_action, encode_card};
+use rust_blackjack::rules::{GameRules, Variant};
+use rust_blackjack::session_limits::{SessionLimits, StopReason};
+use rust_blackjack::settlement::{max_tip, settle_with_short_double, staked, Settlement, ShortDouble};
+use rust_blackjack::simulation::{self, SimulationRng, Stakes, SweepReport, WorkerRng, Wonging};
+use rust_blackjack::shoe::RankCounts;
+use rust_blackjack::shuffle::Shuffler;
+use rust_blackjack::solver::{self, DealerOutcomes, DecisionValues};
+use rust_blackjack::sound;
+use rust_blackjack::strategy::{basic_strategy_for, index_strategy_for, strategy_from_name, BasicStrategy, Strategy};
+use rust_blackjack::strategy_file::{CustomStrategy, CUSTOM_STRATEGY};
+use rust_blackjack::strategy_table::{self, ChartFormat, StrategyTable, TableError, TableStrategy, TABLE_STRATEGY};
+use rust_blackjack::table::{self, Round};
+use rust_blackjack::stats::SessionStats;
+use rust_blackjack::summary::SessionSummary;
+use rust_blackjack::tournament::TournamentRules;
+use rust_blackjack::trace;
+use rust_blackjack::trends;
+use rust_blackjack::deviation::DeviationLog;
+use rust_blackjack::events::{self, EventSink, Seat, UiEvent};
+use rust_blackjack::export::{outcome_name, Exporter, HandRecord};
+use rust_blackjack::fast_rng::Xoshiro256;
+use rust_blackjack::hand_history::{cards_drawn, HistoryHand, HistoryWriter};
+use rust_blackjack::{
+    action_menu_in, best_hand_value, calculate_current_hand_value, continue_with_game, counting,
+    describe_actions_in, describe_dealer_in, game_message, outcome_message, insurance_answer, insurance_prompt, is_natural, raw_calculate_current_hand_value, Action, Card, CardSuit, CardValue, Deck,
+    GameState, Phase, PlayerState,
+};
+
+mod cli;
+mod client;
+mod editor;
+mod history;
+mod hotseat;
+#[cfg(feature = "http")]
+mod http;
+mod input;
+mod pacing;
+mod profile;
+mod replay;
+mod server;
+mod store;
+mod tournament_play;
+mod train;
+mod websocket;
+mod wizard;
+
+use crate::cli::{Command, Settings, SummaryTarget};
+use crate::editor::EditorChoice;
+use crate::input::{Input, InputLines};
+use crate::pacing::Presentation;
+use crate::store::{render_standings, FileStore, MemoryStore, SessionStore};
+
+fn print_hand(hand: &[Card]) {
+    print_hand_in(&Language::English, hand)
+}
+
+fn print_hand_in(language: &Language, hand: &[Card]) {
+    let card_values: Vec<CardValue> = hand.iter().map(|card| card.value).collect();
+    println!("{}", fill(language.translate("Your hand is {}"), &[&format!("{:?}", hand)]));
+    let values = format!("{:?}", calculate_current_hand_value(&card_values));
+    println!("{}", fill(language.translate("Your hand value is {}"), &[&values]));
+}
+
+// Flags over the config file. Dealing is paced only for someone watching;
+// piped output gets every card at once. The accessible mode has neither art
+// nor pauses, whatever the flags say.
+fn presentation(
+    ascii_cards: bool,
+    dealer_odds: bool,
+    advisor: bool,
+    speed: Option<Speed>,
+    language: Option<Language>,
+    config: &Config,
+) -> Presentation {
+    let watched = io::stdout().is_terminal() && !config.accessible;
+    let speed = if watched { speed.unwrap_or_else(|| config.speed.clone()) } else { Speed::Instant };
+    Presentation {
+        ascii_cards: ascii_cards && !config.accessible,
+        speed,
+        language: language.unwrap_or_else(|| config.language.clone()),
+        accessible: config.accessible,
+        dealer_odds,
+        advisor: advisor || config.coach == CoachLevel::Full,
+        hints: config.coach == CoachLevel::Hints,
+        own_strategy: Option::None,
+        currency: config.currency.clone(),
+        sounds: sound::default_player(),
+        dealers_turn: false,
     }
+}
 
-    fn player_state(&self) -> &PlayerState<'a> {
-        match self {
-            GameState::GameLost(p) => p,
-            GameState::GameWon(p) => p,
-            GameState::Continuing(p) => p,
-        }
+// First run: ask for preferences when someone is at the keyboard, otherwise
+// fall back to the defaults without writing anything.
+fn load_config() -> Config {
+    match Config::load() {
+        Ok(Option::Some(config)) => config,
+        Ok(Option::None) if io::stdin().is_terminal() => wizard::run().unwrap_or_else(|error| {
+            eprintln!("Could not save your preferences: {}", error);
+            Config::default()
+        }),
+        Ok(Option::None) => Config::default(),
+        Err(error) => {
+            eprintln!("Ignoring config file: {}", error);
+            Config::default()
+        },
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-enum CardSuit {
-    Clubs,
-    Hearts,
-    Diamonds,
-    Spades
+// For commands that aren't interactive: never start the wizard.
+fn load_config_quietly() -> Config {
+    match Config::load() {
+        Ok(config) => config.unwrap_or_default(),
+        Err(error) => {
+            eprintln!("Ignoring config file: {}", error);
+            Config::default()
+        },
+    }
 }
 
-impl CardSuit {
-    const ALL_VALUES: [CardSuit; 4] = [
-        CardSuit::Clubs,
-        CardSuit::Hearts,
-        CardSuit::Diamonds,
-        CardSuit::Spades
-    ];
+fn colored(text: &str, color_code: &str, config: &Config) -> String {
+    if config.color {
+        format!("\x1b[{}m{}\x1b[0m", color_code, text)
+    } else {
+        text.to_string()
+    }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-struct HandValue {
-    value: u32
+// A seeded generator replays the same game every time; otherwise each run
+// draws from the thread's generator.
+fn rng(settings: &Settings) -> Box<dyn Rng> {
+    match settings.seed {
+        Option::Some(seed) => {
+            let rng: StdRng = SeedableRng::from_seed(&[seed as usize][..]);
+            Box::new(rng)
+        },
+        Option::None => Box::new(thread_rng()),
+    }
 }
 
-impl HandValue {
-    pub fn from_u32(x: u32) -> Option<HandValue> {
-        if x < 21 {
-            Option::Some(
-                HandValue {
-                    value: x,
-                }
-            )
-        } else {
-            Option::None
+fn shuffler(settings: &Settings) -> Box<dyn Shuffler> {
+    settings.game_rules().shuffler(rng(settings)).unwrap_or_else(|error| exit_with_error(error))
+}
+
+fn new_deck(settings: &Settings, shuffler: &mut dyn Shuffler) -> Deck {
+    if let Option::Some(composition) = &settings.composition {
+        return composition.deal(shuffler);
+    }
+    if let Option::Some(source) = settings.shoe.as_deref().and_then(fixture) {
+        return source.into_deck();
+    }
+    let mut deck = Deck::with_decks(settings.decks);
+    deck.shuffle(shuffler);
+    deck.burn();
+    deck.place_cut_card(settings.penetration);
+    deck
+}
+
+#[cfg(feature = "http")]
+fn serve_http<F: FnMut() -> Deck>(address: &str, rules: &GameRules, new_deck: F) {
+    if let Err(error) = http::run(address, rules, new_deck) {
+        exit_with_error(error);
+    }
+}
+
+#[cfg(not(feature = "http"))]
+fn serve_http<F: FnMut() -> Deck>(_: &str, _: &GameRules, _: F) {
+    exit_with_error("this build has no HTTP API; rebuild with --features http");
+}
+
+fn exit_with_error<E: Display>(error: E) -> ! {
+    eprintln!("Error: {}", error);
+    process::exit(1);
+}
+
+fn active_promotions(settings: &Settings) -> Vec<Box<dyn RoundModifier>> {
+    settings.promotions
+        .iter()
+        .filter_map(|name| promotion_from_name(name))
+        .collect()
+}
+
+// Table admin command: `promo <name> [minutes]`.
+fn activate_promotion(command: &str, modifiers: &mut Vec<Box<dyn RoundModifier>>) {
+    let mut words = command.split_whitespace();
+    let promotion = words.next().and_then(promotion_from_name);
+    let minutes = words.next().map(|raw| raw.parse::<u64>());
+    match (promotion, minutes) {
+        (Option::Some(promotion), Option::None) => {
+            println!("Promotion {} is now running", promotion.name());
+            modifiers.push(promotion);
+        },
+        (Option::Some(promotion), Option::Some(Ok(minutes))) => {
+            println!("Promotion {} is running for the next {} minutes", promotion.name(), minutes);
+            modifiers.push(Box::new(Timed::for_duration(promotion, Duration::from_secs(minutes * 60))));
+        },
+        _ => println!("Usage: promo <{}> [minutes]", PROMOTION_NAMES.join("|")),
+    }
+}
+
+fn print_sweep(sweep: &SweepReport, hands: u64, bet: u64) {
+    println!("Seeds: {} x {} hands at a bet of {}", sweep.results.len(), hands, bet);
+    println!(
+        "Net per hand: mean {:.3}, std dev across seeds {:.3}, standard error {:.4}",
+        sweep.mean(),
+        sweep.standard_deviation(),
+        sweep.standard_error()
+    );
+    println!(
+        "Percentiles: 5% {:.3}, 25% {:.3}, median {:.3}, 75% {:.3}, 95% {:.3}",
+        sweep.percentile(0.05),
+        sweep.percentile(0.25),
+        sweep.percentile(0.5),
+        sweep.percentile(0.75),
+        sweep.percentile(0.95)
+    );
+    if let (Option::Some(worst), Option::Some(best)) = (sweep.results.first(), sweep.results.last()) {
+        println!(
+            "Worst seed {} ({:.3} per hand), best seed {} ({:.3} per hand)",
+            worst.seed,
+            worst.report.net_per_hand(),
+            best.seed,
+            best.report.net_per_hand()
+        );
+    }
+}
+
+fn write_summary(session: &SessionSummary, target: &Option<SummaryTarget>) {
+    match target {
+        Option::Some(SummaryTarget::Stdout) => println!("{}", session.to_json()),
+        Option::Some(SummaryTarget::File(path)) => {
+            if let Err(error) = fs::write(path, format!("{}\n", session.to_json())) {
+                exit_with_error(format!("could not write {}: {}", path.display(), error));
+            }
+        },
+        Option::None => (),
+    }
+}
+
+fn record_session(kind: &str, session: &SessionSummary, profile: Option<&str>) {
+    if let Err(error) = history::record(kind, session) {
+        eprintln!("Could not save this session to your history: {}", error);
+    }
+    if let Option::Some(name) = profile {
+        let recorded = profile::Profiles::load().and_then(|mut profiles| profiles.record_session(name, kind, session));
+        if let Err(error) = recorded {
+            eprintln!("Could not update profile {}: {}", name, error);
         }
     }
+}
 
-    pub fn unsafe_from_u32(x: u32) -> HandValue {
-        HandValue::from_u32(x).unwrap()
+// A profile plays with its own chips.
+fn apply_profile(settings: &mut Settings) {
+    if let Option::Some(name) = &settings.profile {
+        let profiles = profile::Profiles::load().unwrap_or_else(|error| exit_with_error(error));
+        settings.bankroll = profiles.get(name).unwrap_or_else(|error| exit_with_error(error)).bankroll;
     }
+}
 
-    pub fn combine_with_separate_value(&self, other_value: &HandValue) -> Option<HandValue> {
-        let raw_result = self.value + other_value.value;
-        if raw_result <= 21 {
-            Option::Some(
-                HandValue {
-                    value: raw_result
-                }
-            )
-        } else {
-            Option::None
+fn unlocked_achievements(profile: Option<&str>) -> Vec<Achievement> {
+    match profile::read_achievements(profile) {
+        Ok(unlocks) => unlocks.into_iter().map(|unlock| unlock.achievement).collect(),
+        Err(error) => {
+            eprintln!("Could not read your achievements: {}", error);
+            Vec::new()
+        },
+    }
+}
+
+// Announces and saves whatever the hand earned that wasn't unlocked before.
+fn announce_achievements(game_state: &GameState, table: &mut Table) {
+    // Chips bought back in for don't count towards doubling the bankroll.
+    let bankroll = table.session.final_bankroll() - table.session.rebought as i64;
+    for achievement in achievements::earned(game_state, &table.stats, bankroll, table.session.starting_bankroll) {
+        if table.achievements.contains(&achievement) {
+            continue;
         }
+        println!("*** Achievement unlocked: {} ({}) ***", achievement.title(), achievement.description());
+        if let Err(error) = profile::unlock_achievement(table.profile.as_deref(), &achievement) {
+            eprintln!("Could not save the achievement: {}", error);
+        }
+        table.achievements.push(achievement);
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-enum CardValue {
-    Two,
-    Three,
-    Four,
-    Five,
-    Six,
-    Seven,
-    Eight,
-    Nine,
-    Ten,
-    Jack,
-    Queen,
-    King,
-    Ace
-}
-
-impl CardValue {
-    const ALL_VALUES: [CardValue; 13] = [
-        CardValue::Two,
-        CardValue::Three,
-        CardValue::Four,
-        CardValue::Five,
-        CardValue::Six,
-        CardValue::Seven,
-        CardValue::Eight,
-        CardValue::Nine,
-        CardValue::Ten,
-        CardValue::Jack,
-        CardValue::Queen,
-        CardValue::King,
-        CardValue::Ace,
-    ];
-}
-
-fn card_value_to_hand_value(card_value: &CardValue) -> Vec<HandValue> {
-    match card_value {
-        CardValue::Two => vec![HandValue::unsafe_from_u32(2)],
-        CardValue::Three=> vec![HandValue::unsafe_from_u32(3)],
-        CardValue::Four => vec![HandValue::unsafe_from_u32(4)],
-        CardValue::Five => vec![HandValue::unsafe_from_u32(5)],
-        CardValue::Six => vec![HandValue::unsafe_from_u32(6)],
-        CardValue::Seven => vec![HandValue::unsafe_from_u32(7)],
-        CardValue::Eight => vec![HandValue::unsafe_from_u32(8)],
-        CardValue::Nine => vec![HandValue::unsafe_from_u32(9)],
-        CardValue::Ten => vec![HandValue::unsafe_from_u32(10)],
-        CardValue::Jack => vec![HandValue::unsafe_from_u32(10)],
-        CardValue::Queen => vec![HandValue::unsafe_from_u32(10)],
-        CardValue::King => vec![HandValue::unsafe_from_u32(10)],
-        CardValue::Ace => vec![HandValue::unsafe_from_u32(1), HandValue::unsafe_from_u32(11)],
-    }
-}
-
-fn combine_possible_values(values_0: &Vec<HandValue>, values_1: &Vec<HandValue>) -> Vec<HandValue> {
-    values_0
-        .iter()
-        .flat_map::<Vec<Option<HandValue>>, _>(
-            |x: &HandValue| {
-                let result = values_1
-                    .iter()
-                    .map(|y: &HandValue| x.combine_with_separate_value(y))
-                    .collect();
-                result
+// Totals across every stored session, then each profile's lifetime stats.
+// As in a profile, only play sessions count towards the hands and chips.
+fn print_stats() {
+    let records = history::load().unwrap_or_else(|error| exit_with_error(error));
+    if records.is_empty() {
+        println!("No sessions have been recorded yet. Play or train to start tracking.");
+    } else {
+        let played: Vec<&SessionSummary> =
+            records.iter().filter(|record| record.kind == "play").map(|record| &record.summary).collect();
+        let total = |field: fn(&SessionSummary) -> u64| played.iter().map(|summary| field(summary)).sum::<u64>();
+        let mistakes: u64 = records.iter().map(|record| record.summary.mistakes).sum();
+        let seconds: u64 = records.iter().map(|record| record.summary.seconds).sum();
+        println!("Sessions:        {} ({} played, {} trained)", records.len(), played.len(), records.len() - played.len());
+        println!("Hands played:    {}", total(|summary| summary.hands));
+        println!(
+            "Won/lost/pushed: {}/{}/{}",
+            total(|summary| summary.wins),
+            total(|summary| summary.losses),
+            total(|summary| summary.pushes)
+        );
+        println!("Net result:      {:+}", played.iter().map(|summary| summary.net).sum::<i64>());
+        println!("Mistakes:        {}", mistakes);
+        println!("Time at tables:  {}h {:02}m", seconds / 3600, seconds / 60 % 60);
+    }
+    let profiles = profile::Profiles::load().unwrap_or_else(|error| exit_with_error(error));
+    for (name, profile) in profiles.profiles() {
+        println!("\nProfile {}: {} chips\n{}", name, profile.bankroll, profile.lifetime.render());
+    }
+}
+
+fn print_trends(weeks: Option<usize>, csv: Option<PathBuf>) {
+    let records = history::load().unwrap_or_else(|error| exit_with_error(error));
+    let mut trend = trends::weekly_trends(&records);
+    if let Option::Some(weeks) = weeks {
+        trend.drain(..trend.len().saturating_sub(weeks));
+    }
+    if trend.is_empty() {
+        println!("No sessions have been recorded yet. Play or train to start tracking.");
+        return;
+    }
+    println!("{}", trends::render_charts(&trend));
+    if let Option::Some(path) = csv {
+        if let Err(error) = fs::write(&path, trends::to_csv(&trend)) {
+            exit_with_error(format!("could not write {}: {}", path.display(), error));
+        }
+        println!("Wrote weekly figures to {}", path.display());
+    }
+}
+
+fn print_bankroll_analysis(analysis: &BankrollAnalysis, bankroll: u64, unit: u64, hands_per_hour: u64) {
+    let win_rate = analysis.win_rate();
+    println!("Average bet:                 {:.2} ({:.2} units)", analysis.average_bet(), analysis.average_bet() / unit as f64);
+    println!("Win rate per hand:           {:+.3} ({:+.3}% of the average bet)", win_rate, 100.0 * win_rate / analysis.average_bet());
+    println!(
+        "Standard deviation per hand: {:.2} ({:.2} units)",
+        analysis.standard_deviation(),
+        analysis.standard_deviation() / unit as f64
+    );
+    println!("Expected hourly win:         {:+.2} at {} hands an hour", analysis.hourly_win(hands_per_hour), hands_per_hour);
+    match analysis.n0() {
+        Option::Some(n0) => println!(
+            "N0:                          {:.0} hands ({:.0} hours)",
+            n0,
+            n0 / hands_per_hour as f64
+        ),
+        Option::None => println!("N0:                          never; the spread loses on average"),
+    }
+    println!("Risk of ruin:                {:.2}% for a bankroll of {}", 100.0 * analysis.risk_of_ruin(bankroll), bankroll);
+    if let Option::Some(needed) = analysis.bankroll_for_risk(0.05) {
+        println!("Bankroll for 5% risk:        {:.0} ({:.0} units)", needed, needed / unit as f64);
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let command = cli::parse(&args).unwrap_or_else(|error| {
+        eprintln!("Error: {}\n\n{}", error, cli::USAGE);
+        process::exit(2);
+    });
+
+    match command {
+        Command::Play { options, count, ascii_cards, speed, language, bots, bot_strategy, seats, summary, export, history, trace_state, dealer_odds, advisor, strategy_file, tips } => {
+            let mut config = load_config();
+            // Outcomes are spelled out already, so color adds nothing for a
+            // screen reader but escape codes.
+            config.color = config.color && !config.accessible;
+            config.tips = config.tips || tips;
+            let mut settings = options.resolve(&config);
+            apply_profile(&mut settings);
+            let own_strategy = strategy_file.map(|path| {
+                CustomStrategy::load(&path).unwrap_or_else(|error| exit_with_error(format!("{}: {}", path.display(), error)))
+            });
+            let mut bot_strategy: Box<dyn Strategy> = if bot_strategy == TABLE_STRATEGY {
+                Box::new(TableStrategy { table: strategy_table(&settings.game_rules()) })
+            } else if let (CUSTOM_STRATEGY, Option::Some(own_strategy)) = (bot_strategy.as_str(), &own_strategy) {
+                Box::new(own_strategy.clone())
+            } else {
+                strategy_from_name(&bot_strategy).unwrap_or_else(|| Box::new(BasicStrategy))
+            };
+            let mut presentation = presentation(ascii_cards, dealer_odds, advisor, speed, language, &config);
+            presentation.own_strategy = own_strategy;
+            let records = Records { export: export.as_deref(), history: history.as_deref(), trace_state };
+            let seating = Seating { bots, bot_strategy: bot_strategy.as_mut(), seats };
+            let session = play(&settings, &config, count, presentation, seating, records);
+            record_session("play", &session, settings.profile.as_deref());
+            write_summary(&session, &summary);
+        },
+        Command::Simulate { options, hands, seeds: Option::Some(seeds), threads, summary, rng, .. } => {
+            let settings = options.resolve(&load_config_quietly());
+            let modifiers = active_promotions(&settings);
+            let threads = threads.unwrap_or_else(default_threads);
+            let first_seed = settings.seed.unwrap_or(0);
+            let rules = settings.game_rules();
+            let sweep = match rng {
+                SimulationRng::Std => simulation::seed_sweep::<StdRng>(first_seed, seeds, hands, &rules, settings.bet, &modifiers, threads),
+                SimulationRng::Fast => {
+                    simulation::seed_sweep::<Xoshiro256>(first_seed, seeds, hands, &rules, settings.bet, &modifiers, threads)
+                },
+            };
+            print_sweep(&sweep, hands, settings.bet);
+            let mut session = SessionSummary::new(settings.bankroll);
+            for result in &sweep.results {
+                session.hands += result.report.hands;
+                session.wins += result.report.wins;
+                session.losses += result.report.losses;
+                session.pushes += result.report.pushes;
+                session.net += result.report.net;
             }
-        )
-        .filter_map(identity)
-        .collect()
+            write_summary(&session, &summary);
+        },
+        Command::Simulate { options, hands, threads, bet_strategies, sessions, rebuy, summary, rng, .. } if !bet_strategies.is_empty() => {
+            let settings = options.resolve(&load_config_quietly());
+            let threads = threads.unwrap_or_else(default_threads);
+            let stakes = Stakes { bankroll: settings.bankroll, base_bet: settings.bet, rebuy };
+            let session = match rng {
+                SimulationRng::Std => compare_bet_strategies::<StdRng>(&settings, &bet_strategies, &stakes, sessions, hands, threads),
+                SimulationRng::Fast => {
+                    compare_bet_strategies::<Xoshiro256>(&settings, &bet_strategies, &stakes, sessions, hands, threads)
+                },
+            };
+            write_summary(&session, &summary);
+        },
+        Command::Simulate { options, hands, seeds: Option::None, threads, shoe_stats, wonging, summary, export, rng, .. } => {
+            let settings = options.resolve(&load_config_quietly());
+            let threads = threads.unwrap_or_else(default_threads);
+            let (export, wonging) = (export.as_deref(), wonging.as_ref());
+            let session = match rng {
+                SimulationRng::Std => simulate::<StdRng>(&settings, hands, threads, export, shoe_stats, wonging),
+                SimulationRng::Fast => simulate::<Xoshiro256>(&settings, hands, threads, export, shoe_stats, wonging),
+            };
+            write_summary(&session, &summary);
+        },
+        Command::Serve { options, address, websocket, decision_time, on_timeout, reconnect_grace, http, store } => {
+            let settings = options.resolve(&load_config_quietly());
+            let mut shuffler = shuffler(&settings);
+            let rules = settings.game_rules();
+            if http {
+                serve_http(&address, &rules, || new_deck(&settings, shuffler.as_mut()));
+                return;
+            }
+            let clock = server::DecisionClock {
+                limit: decision_time.map(Duration::from_secs),
+                on_timeout,
+                grace: Duration::from_secs(reconnect_grace),
+            };
+            let store: Box<dyn SessionStore> = match store {
+                Option::Some(path) => Box::new(FileStore::open(&path).unwrap_or_else(|error| exit_with_error(error))),
+                Option::None => Box::new(MemoryStore::default()),
+            };
+            if let Err(error) = server::run(&address, websocket.as_deref(), &rules, clock, store, settings.bet, || new_deck(&settings, shuffler.as_mut())) {
+                exit_with_error(error);
+            }
+        },
+        Command::Train { options, summary } => {
+            let mut settings = options.resolve(&load_config_quietly());
+            apply_profile(&mut settings);
+            let started = Instant::now();
+            let (correct, total) = train::run(rng(&settings));
+            let mut session = SessionSummary::new(settings.bankroll);
+            session.hands = total;
+            session.decisions = total;
+            session.mistakes = total - correct;
+            session.seconds = started.elapsed().as_secs();
+            record_session("train", &session, settings.profile.as_deref());
+            write_summary(&session, &summary);
+        },
+        Command::Compose { options } => {
+            let config = load_config();
+            let mut settings = options.resolve(&config);
+            if let Option::Some((composition, choice)) = editor::run(settings.decks) {
+                settings.composition = Option::Some(composition);
+                match choice {
+                    EditorChoice::Play => {
+                        let presentation = presentation(false, false, false, Option::None, Option::None, &config);
+                        let seating = Seating { bots: 0, bot_strategy: &mut BasicStrategy, seats: 1 };
+                        record_session("play", &play(&settings, &config, false, presentation, seating, Records::default()), Option::None)
+                    },
+                    EditorChoice::Simulate(hands) => {
+                        simulate::<StdRng>(&settings, hands, default_threads(), Option::None, false, Option::None);
+                    },
+                }
+            }
+        },
+        Command::Analyze { options, hands, threads, overrides } => {
+            let settings = options.resolve(&load_config_quietly());
+            let rules = overrides.apply(settings.game_rules());
+            let threads = threads.unwrap_or_else(default_threads);
+            println!("Rules: {}", rules.describe());
+            println!(
+                "Playing {} hands of basic strategy on {} thread{}...",
+                hands,
+                threads,
+                if threads == 1 { "" } else { "s" }
+            );
+            let report = analysis::analyze(&rules, hands, settings.seed.unwrap_or(0), threads);
+            let (low, high) = report.confidence_interval();
+            println!(
+                "House edge: {:.3}% (95% confidence interval {:.3}% to {:.3}%)",
+                report.house_edge() * 100.0,
+                low * 100.0,
+                high * 100.0
+            );
+            println!("Standard deviation per hand: {:.3} bets", report.standard_deviation());
+        },
+        Command::Bankroll { options, hands, threads, overrides, spread, hands_per_hour } => {
+            let settings = options.resolve(&load_config_quietly());
+            let rules = overrides.apply(settings.game_rules());
+            let threads = threads.unwrap_or_else(default_threads);
+            println!("Rules: {}", rules.describe());
+            let betting = if spread == 1 {
+                "flat".to_string()
+            } else {
+                format!("spreading 1-{} units on the Hi-Lo true count", spread)
+            };
+            println!("Betting {} a unit, {}, from a bankroll of {}", settings.bet, betting, settings.bankroll);
+            println!(
+                "Playing {} hands of basic strategy on {} thread{}...",
+                hands,
+                threads,
+                if threads == 1 { "" } else { "s" }
+            );
+            let analysis =
+                analysis::analyze_bankroll(&rules, hands, settings.bet, spread, settings.seed.unwrap_or(0), threads);
+            print_bankroll_analysis(&analysis, settings.bankroll, settings.bet, hands_per_hour);
+        },
+        Command::Solve { options, hand, dealer, overrides } => {
+            let settings = options.resolve(&load_config_quietly());
+            let rules = overrides.apply(settings.game_rules());
+            solve_hand(&rules, &hand, &dealer);
+        },
+        Command::Tournament { options, bots, bot_bets, hands_per_round, eliminated_per_round } => {
+            let settings = options.resolve(&load_config());
+            let rules = settings.game_rules();
+            let tournament_rules = TournamentRules {
+                starting_chips: settings.bankroll,
+                hands_per_round,
+                eliminated_per_round,
+                minimum_bet: settings.bet,
+                maximum_bet: settings.bet * 20,
+            };
+            let mut shuffler = shuffler(&settings);
+            let bot_bets = (0..bots).filter_map(|_| bet_strategy_from_name(&bot_bets)).collect();
+            tournament_play::run(&rules, tournament_rules, bot_bets, || new_deck(&settings, shuffler.as_mut()));
+        },
+        Command::GenerateTables { options, output, chart } => {
+            let settings = options.resolve(&load_config_quietly());
+            let rules = settings.game_rules();
+            if rules.variant != Variant::Blackjack {
+                exit_with_error("strategy tables are only made for blackjack rules");
+            }
+            let path = output.unwrap_or_else(|| strategy_table_path(&rules));
+            let table = StrategyTable::generate(&rules);
+            print!("{}", table.chart(ChartFormat::Text, &rules));
+            table.save(&path).unwrap_or_else(|error| exit_with_error(format!("could not write {}: {}", path.display(), error)));
+            println!("Wrote the strategy table to {}", path.display());
+            if let Option::Some(chart) = chart {
+                let format = ChartFormat::from_path(&chart).expect("the extension was checked when parsing");
+                fs::write(&chart, table.chart(format, &rules))
+                    .unwrap_or_else(|error| exit_with_error(format!("could not write {}: {}", chart.display(), error)));
+                println!("Wrote the chart to {}", chart.display());
+            }
+        },
+        Command::Hotseat { options, players } => {
+            let settings = options.resolve(&load_config());
+            let rules = settings.game_rules();
+            let mut shuffler = shuffler(&settings);
+            let limits = &settings.table_limits;
+            hotseat::run(&rules, players, settings.bankroll, limits, settings.bet, || new_deck(&settings, shuffler.as_mut()));
+        },
+        Command::Stats => print_stats(),
+        Command::Trends { weeks, csv } => print_trends(weeks, csv),
+        Command::Connect { address } => {
+            if let Err(error) = client::run(&address) {
+                exit_with_error(error);
+            }
+        },
+        Command::Leaderboard { store } => match FileStore::open(&store) {
+            Ok(store) => println!("{}", render_standings(&store.standings())),
+            Err(error) => exit_with_error(error),
+        },
+        Command::Replay { history } => replay::run(&history),
+        Command::Profiles { args } => {
+            if let Err(error) = profile::run_command(&args) {
+                exit_with_error(error);
+            }
+        },
+        Command::Help => println!("{}", cli::USAGE),
+    }
 }
 
-fn calculate_current_hand_value(hand: &Vec<CardValue>) -> Vec<HandValue> {
-    hand
-        .iter()
-        .map(card_value_to_hand_value)
-        .fold(
-            vec![HandValue::unsafe_from_u32(0)],
-            |x, y| combine_possible_values(&x, &y)
-        )
+fn default_threads() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
 }
 
-fn cartesian_product<'a, 'b, A, B>(xs: &'a Vec<A>, ys: &'b Vec<B>) -> Vec<(&'a A, &'b B)> {
-    xs
-        .iter()
-        .flat_map::<Vec<(&A, &B)>, _>(|x| ys.iter().map(|y| (x, y)).collect())
-        .collect()
+// Where a session's hands are written as they are played, if anywhere.
+#[derive(Default)]
+struct Records<'a> {
+    export: Option<&'a Path>,
+    history: Option<&'a Path>,
+    // The state's changes after every step, on stderr.
+    trace_state: bool,
 }
 
-fn raw_calculate_current_hand_value(hand: &Vec<CardValue>) -> Vec<u32> {
-    hand
-        .iter()
-        .map(card_value_to_hand_value)
-        .fold(
-            vec![0],
-            |x, y|
-                cartesian_product(&x, &y)
-                    .iter()
-                    .map(|x_and_y| x_and_y.0 + x_and_y.1.value)
-                    .collect()
-        )
+fn create_exporter(path: &Path) -> Exporter {
+    Exporter::create(path).unwrap_or_else(|error| exit_with_error(format!("could not write {}: {}", path.display(), error)))
+}
+
+fn finish_export(exporter: Exporter, path: &Path) {
+    let records = exporter.records();
+    match exporter.finish() {
+        Ok(()) => println!("Wrote {} hand{} to {}", records, if records == 1 { "" } else { "s" }, path.display()),
+        Err(error) => eprintln!("Could not finish writing {}: {}", path.display(), error),
+    }
 }
 
-#[derive(Debug, Eq, PartialEq)]
-struct Card {
-    suit: CardSuit,
-    value: CardValue
+// An export is written as the hands are played, so it plays them all on
+// this thread, in order.
+// With `shoe_stats` or `wonging`, shoes are dealt to the cut card and what
+// a counter would see in them is printed after the results.
+fn simulate<G: WorkerRng>(
+    settings: &Settings,
+    hands: u64,
+    threads: usize,
+    export: Option<&Path>,
+    shoe_stats: bool,
+    wonging: Option<&Wonging>,
+) -> SessionSummary {
+    let modifiers = active_promotions(settings);
+    let bet = settings.bet;
+    let rules = settings.game_rules();
+    let first_seed = settings.seed.unwrap_or_else(|| thread_rng().gen());
+    let started = Instant::now();
+    let mut counted = Option::None;
+    let (report, threads) = match export {
+        Option::Some(path) => {
+            let mut exporter = create_exporter(path);
+            let mut shuffler = rules.shuffle_model.shuffler(G::for_seed(first_seed));
+            let mut failed = Option::None;
+            let report = simulation::simulate_recorded(
+                hands,
+                || new_deck(settings, shuffler.as_mut()),
+                &rules,
+                bet,
+                &modifiers,
+                settings.bankroll,
+                |record| {
+                    if failed.is_none() {
+                        failed = exporter.write(&record).err();
+                    }
+                },
+            );
+            if let Option::Some(error) = failed {
+                exit_with_error(format!("could not write {}: {}", path.display(), error));
+            }
+            finish_export(exporter, path);
+            (report, 1)
+        },
+        Option::None if shoe_stats || wonging.is_some() => {
+            let (report, stats) = simulation::parallel_batch(hands, threads, first_seed, |share, rng: &mut G| {
+                let mut shuffler = rules.shuffle_model.shuffler(rng);
+                simulation::simulate_shoes(share, || new_deck(settings, shuffler.as_mut()), &rules, bet, &modifiers, wonging)
+            });
+            counted = Option::Some(stats);
+            (report, threads)
+        },
+        Option::None => {
+            let report = simulation::parallel_batch(hands, threads, first_seed, |share, rng: &mut G| {
+                let mut shuffler = rules.shuffle_model.shuffler(rng);
+                simulation::simulate(share, || new_deck(settings, shuffler.as_mut()), &rules, bet, &modifiers)
+            });
+            (report, threads)
+        },
+    };
+    let seconds = started.elapsed().as_secs_f64();
+    println!("Hands played: {}", report.hands);
+    println!("Won: {}", report.wins);
+    println!("Lost: {}", report.losses);
+    println!("Pushed: {}", report.pushes);
+    println!("Net: {} ({:.3} per hand at a bet of {})", report.net, report.net_per_hand(), bet);
+    if seconds > 0.0 {
+        println!(
+            "Throughput: {:.0} hands/sec on {} thread{}",
+            report.hands as f64 / seconds,
+            threads,
+            if threads == 1 { "" } else { "s" }
+        );
+    }
+    if let Option::Some(stats) = counted {
+        println!("{}", stats.render());
+    }
+    let mut session = SessionSummary::new(settings.bankroll);
+    session.hands = report.hands;
+    session.wins = report.wins;
+    session.losses = report.losses;
+    session.pushes = report.pushes;
+    session.net = report.net;
+    session
 }
 
-#[derive(Debug, Eq, PartialEq)]
-struct PlayerState<'a> {
-    deck: &'a mut Deck,
-    hand: Vec<Card>
+// Runs every strategy over the same number of sessions and prints them side
+// by side. The summary adds all of them up.
+fn compare_bet_strategies<G: WorkerRng>(
+    settings: &Settings,
+    names: &[String],
+    stakes: &Stakes,
+    sessions: u64,
+    hands: u64,
+    threads: usize,
+) -> SessionSummary {
+    let rules = settings.game_rules();
+    let first_seed = settings.seed.unwrap_or_else(|| thread_rng().gen());
+    // --hands caps every session, as does a lower hand limit.
+    let limits = SessionLimits {
+        max_hands: Option::Some(settings.session_limits.max_hands.map_or(hands, |max_hands| max_hands.min(hands))),
+        ..settings.session_limits.clone()
+    };
+    println!("Rules: {}", rules.describe());
+    println!(
+        "{} sessions of up to {} hands from a bankroll of {}, base bet {}",
+        sessions,
+        limits.max_hands.unwrap_or(hands),
+        settings.bankroll,
+        settings.bet
+    );
+    if let Option::Some(stop_win) = limits.stop_win {
+        println!("Each session stops once it is {} up", stop_win);
+    }
+    if let Option::Some(stop_loss) = limits.stop_loss {
+        println!("Each session stops once it is {} down", stop_loss);
+    }
+    if stakes.rebuy {
+        println!("A session that goes broke buys back in and plays on; ruin counts the sessions that did");
+    }
+    println!(
+        "{:<14}{:>10}{:>14}{:>16}{:>14}{:>12}",
+        "Strategy", "Ruin", "Mean final", "Std dev final", "Per chip bet", "Hands"
+    );
+    let mut session = SessionSummary::new(settings.bankroll);
+    for name in names {
+        let report = simulation::parallel_batch(sessions, threads, first_seed, |share, rng: &mut G| {
+            let new_strategy = || bet_strategy_from_name(name).expect("names are checked when parsing");
+            simulation::bankroll_sessions(share, &limits, &rules, stakes, new_strategy, rng)
+        });
+        println!(
+            "{:<14}{:>9.1}%{:>14.0}{:>16.0}{:>+14.4}{:>12}",
+            name,
+            report.risk_of_ruin() * 100.0,
+            report.mean_final_bankroll(),
+            report.final_bankroll_variance().sqrt(),
+            report.return_on_wagers(),
+            report.hands
+        );
+        if stakes.rebuy {
+            println!("{:<14}{} rebuys for {} chips", "", report.rebuys, report.rebought);
+        }
+        session.hands += report.hands;
+        session.net += report.net;
+        session.rebuys += report.rebuys;
+        session.rebought += report.rebought;
+    }
+    session
 }
 
-impl<'a> PlayerState<'a> {
-    fn create_hand_values(&self) -> Vec<CardValue> {
-        self.hand.iter().map(|card| card.value.clone()).collect()
+// Takes the cards on the table out of a fresh shoe and prints the value of
+// every play available on the hand.
+fn solve_hand(rules: &GameRules, hand: &[CardValue], dealer: &CardValue) {
+    if rules.variant != Variant::Blackjack {
+        exit_with_error("the solver only knows blackjack rules");
+    }
+    let mut shoe = ShoeComposition::new(rules.decks);
+    let mut take = |value: &CardValue| {
+        if shoe.remove(value, Option::Some(1)) == 0 {
+            exit_with_error(format!("a {}-deck shoe doesn't hold that many {:?}s", rules.decks, value));
+        }
+        Card { suit: CardSuit::Spades, value: *value }
+    };
+    let cards: Vec<Card> = hand.iter().map(&mut take).collect();
+    let upcard = take(dealer);
+    println!("Rules: {}", rules.describe());
+    println!("Hand: {} ({}) against a dealer {}", cards_text(&cards), total_text(&cards), encode_card(&upcard));
+    let values = solver::solve(&cards, &upcard, &RankCounts::of(shoe.cards()), rules);
+    for (action, value) in values.all() {
+        println!("  {:<12} {:+.4}", encode_action(&action), value);
     }
+    let (action, value) = values.best();
+    println!("Best play: {} ({:+.4} bets per hand)", encode_action(&action), value);
+}
+
+fn cards_text(cards: &[Card]) -> String {
+    cards.iter().map(encode_card).collect::<Vec<String>>().join(" ")
+}
+
+// The best total, or the lowest one when every way of counting the hand
+// busts.
+fn total_text(cards: &[Card]) -> String {
+    total_text_in(&Language::English, cards)
 }
 
-fn parse_action(str: &String) -> Option<Action> {
-    match str.trim() {
-        "hit" => Option::Some(Action::Hit),
-        "stand" => Option::Some(Action::Stand),
-        "double-down" => Option::Some(Action::DoubleDown),
-        "split" => Option::Some(Action::SplitCards),
-        "surrender" => Option::Some(Action::Surrender),
-        _ => Option::None,
+fn total_text_in(language: &Language, cards: &[Card]) -> String {
+    let card_values: Vec<CardValue> = cards.iter().map(|card| card.value).collect();
+    match best_hand_value(cards) {
+        Option::Some(total) => total.to_string(),
+        Option::None => {
+            let lowest = raw_calculate_current_hand_value(&card_values).into_iter().min().unwrap_or(0);
+            fill(language.translate("bust ({})"), &[&lowest])
+        },
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
-struct Deck {
-    remaining_cards: Vec<Card>,
-    drawn_cards: Vec<Card>,
+// One of the player's hands once the dealer has played, as it settled.
+struct SettledHand {
+    state: GameState,
+    bet: u64,
+    actions: Vec<Action>,
+    settlement: Settlement,
+    outcome_color: &'static str,
 }
 
-impl Deck {
-    fn new() -> Deck {
-        let mut result = Vec::new();
-        for suit in CardSuit::ALL_VALUES.iter() {
-            for value in CardValue::ALL_VALUES.iter() {
-                result.push(
-                    Card {
-                        suit: suit.clone(),
-                        value: value.clone()
-                    }
-                )
+// `others` are the bots' seats, in front of the player's.
+fn print_result(hands: &[SettledHand], others: &[GameState], bankroll: i64, config: &Config, presentation: &Presentation) {
+    let language = &presentation.language;
+    let text = |english| language.translate(english);
+    let seat_name = |seat: usize| fill(text("Seat {}"), &[&seat]);
+    let total = |cards: &[Card]| total_text_in(language, cards);
+    // A row of the table: lined up in columns, or read out as one sentence
+    // in the accessible mode.
+    let row = |name: &str, cards: &[Card], message: &str| {
+        if presentation.accessible {
+            let line = fill(text("{}: {}, total {}"), &[&name, &presentation.cards_text(cards), &total(cards)]);
+            if message.is_empty() {
+                println!("{}", line);
+            } else {
+                println!("{}. {}", line, message);
             }
+        } else {
+            println!("{:<8}{:<24}{:<10}{}", name, cards_text(cards), total(cards), message);
+        }
+    };
+    let dealer_cards = hands[0].state.player_state().dealer_cards();
+    println!("{}", text("----- Result -----"));
+    row(text("Dealer"), &dealer_cards, "");
+    let print_split_hands = |seat: usize, state: &GameState| {
+        for split in state.player_state().split_hands.iter() {
+            let message = split.outcome.as_ref().map(|outcome| text(outcome_message(outcome))).unwrap_or("");
+            row(&seat_name(seat), &split.hand, message);
+        }
+    };
+    for (seat, other) in others.iter().enumerate() {
+        print_split_hands(seat + 1, other);
+        row(&seat_name(seat + 1), &other.player_state().hand, text(game_message(other)));
+    }
+    for (index, hand) in hands.iter().enumerate() {
+        let seat = others.len() + index + 1;
+        let (player_state, settlement) = (hand.state.player_state(), &hand.settlement);
+        print_split_hands(seat, &hand.state);
+        let message = colored(text(game_message(&hand.state)), hand.outcome_color, config);
+        row(&seat_name(seat), &player_state.hand, &message);
+        let payout = presentation.money_signed(settlement.net - settlement.insurance);
+        let staked = presentation.money(settlement.bet as i64);
+        if player_state.even_money {
+            println!("{}", fill(text("Bet {}, even money {}"), &[&staked, &presentation.money_signed(settlement.net)]));
+        } else if player_state.doubled {
+            println!("{}", fill(text("Bet {} (doubled), payout {}"), &[&staked, &payout]));
+        } else {
+            println!("{}", fill(text("Bet {}, payout {}"), &[&staked, &payout]));
         }
-        Deck {
-            remaining_cards: result,
-            drawn_cards: Vec::new()
+        if player_state.insured {
+            println!("{}", fill(text("Insurance {}"), &[&presentation.money_signed(settlement.insurance)]));
         }
     }
+    let net = presentation.money_signed(hands.iter().map(|hand| hand.settlement.net).sum());
+    println!("{}", fill(text("Net {}, bankroll {}"), &[&net, &presentation.money(bankroll)]));
+    println!("------------------");
+}
 
-    fn shuffle<R: Rng>(&mut self, rng: &mut R) {
-        rng.shuffle(&mut self.remaining_cards);
+// Why play stopped before the next bet, in the table's language.
+fn stop_message(reason: &StopReason, limits: &SessionLimits, table: &Table) -> String {
+    let text = |english| table.presentation.text(english);
+    let money = |amount: i64| table.presentation.money(amount);
+    let net = table.session.net;
+    match reason {
+        StopReason::StopWin => {
+            let stop_win = money(limits.stop_win.unwrap_or(0) as i64);
+            fill(text("Up {}, reaching your stop-win of {}: the session ends here"), &[&money(net), &stop_win])
+        },
+        StopReason::StopLoss => {
+            let stop_loss = money(limits.stop_loss.unwrap_or(0) as i64);
+            fill(text("Down {}, reaching your stop-loss of {}: the session ends here"), &[&money(-net), &stop_loss])
+        },
+        StopReason::MaxHands => fill(text("That was hand {}, the last of the session"), &[&table.session.hands]),
+        StopReason::MaxTime => {
+            let minutes = limits.max_time.map_or(0, |max_time| max_time.as_secs() / 60);
+            fill(text("The session's {} minutes are up: it ends here"), &[&minutes])
+        },
+    }
+}
+
+// Everything that carries over from one hand to the next while sitting at
+// the table.
+struct Table {
+    session: SessionSummary,
+    stats: SessionStats,
+    deviation_log: DeviationLog,
+    modifiers: Vec<Box<dyn RoundModifier>>,
+    counting_enabled: bool,
+    presentation: Presentation,
+    // Unlocked in this session or an earlier one, by the profile playing.
+    achievements: Vec<Achievement>,
+    profile: Option<String>,
+    history: Option<HistoryWriter>,
+    trace_state: bool,
+    // Every hand is written here when the session is being exported, with
+    // the actions the player took in it.
+    export: Option<Exporter>,
+    hand_actions: Vec<Action>,
+    // The bet on the hand being played, and the double made for less in it,
+    // if any.
+    bet: u64,
+    short_double: Option<ShortDouble>,
+    // Which of the player's seats is being played, counting from 0, and the
+    // chips down on their others this round, which the bankroll can't cover
+    // twice.
+    seat: usize,
+    staked_elsewhere: u64,
+    // The advisor's values for a full shoe, when a table has been generated
+    // for the rules.
+    strategy_table: Option<StrategyTable>,
+    // Kept only when no hints are shown, for the report after the session.
+    mistake_log: Option<MistakeLog>,
+}
+
+enum Reply {
+    Command(String),
+    Leave,
+}
+
+// Waits for a line, handling the commands that can be typed at any prompt.
+// None means the line was one of those commands and nothing else is needed.
+fn read_reply(input: &InputLines, table: &mut Table) -> Option<Reply> {
+    let line = match input.next() {
+        Input::Line(line) => line,
+        Input::Closed => {
+            println!("{}", table.presentation.text("Input closed, leaving the table"));
+            return Option::Some(Reply::Leave);
+        },
+        Input::Interrupted => {
+            println!("{}", table.presentation.text("Interrupted, leaving the table"));
+            return Option::Some(Reply::Leave);
+        },
+    };
+    let command = line.trim();
+    if matches!(command.to_lowercase().as_str(), "quit" | "q") {
+        println!("{}", table.presentation.text("Leaving the table"));
+        return Option::Some(Reply::Leave);
+    }
+    if command == "stats" {
+        println!("{}", table.stats.render_in(&table.presentation.currency));
+        return Option::None;
     }
+    if let Option::Some(command) = command.strip_prefix("promo") {
+        activate_promotion(command, &mut table.modifiers);
+        return Option::None;
+    }
+    Option::Some(Reply::Command(line))
+}
 
-    fn draw_card(&mut self) -> Option<Card> {
-        draw_card(self)
+// What the player is graded against: the chart, which never takes
+// insurance or even money, or with the count on, the chart with the Hi-Lo
+// index plays laid over it.
+fn advised_action(game_state: &GameState, table: &Table) -> Action {
+    if table.counting_enabled {
+        index_strategy_for(game_state, &counting::HI_LO)
+    } else {
+        basic_strategy_for(game_state)
     }
 }
 
-fn draw_card(deck: &mut Deck) -> Option<Card> {
-    let card_opt = deck.remaining_cards.pop();
-    match card_opt {
-        Option::Some(card) => {
-            let card_ref = &card;
-            let value = card_ref.value.clone();
-            let suit = card_ref.suit.clone();
-            let new_card = Card {
-                value: value,
-                suit: suit
+enum Turn {
+    Act(Action),
+    Leave,
+    // Nothing to play yet: a table command, or a line that didn't parse.
+    Again,
+}
+
+// Shows the hand, asks for a decision and scores it against basic strategy
+// and the count.
+fn next_action(game_state: &GameState, input: &InputLines, table: &mut Table) -> Turn {
+    if let GameState::Continuing(continuing_game_state) = game_state {
+        let language = &table.presentation.language;
+        let visible = continuing_game_state.visible_dealer_cards();
+        let hidden = continuing_game_state.hidden_dealer_cards().len();
+        if table.presentation.ascii_cards {
+            println!("{}\n{}", language.translate("Dealer"), render_cards(&visible, hidden));
+        } else {
+            let shown = if visible.is_empty() {
+                String::new()
+            } else if table.presentation.accessible {
+                table.presentation.cards_text(&visible)
+            } else {
+                format!("{:?}", visible)
             };
-            deck.drawn_cards.push(card);
-            Option::Some(new_card)
+            println!("{}", describe_dealer_in(language, &shown, hidden));
+        }
+        if continuing_game_state.has_split() {
+            let hand = continuing_game_state.split_hands.len() + 1;
+            println!("{}", fill(language.translate("Hand {} of {}"), &[&hand, &continuing_game_state.hand_count()]));
+        }
+        if table.presentation.ascii_cards {
+            let hand = &continuing_game_state.hand;
+            let total = total_text_in(language, hand);
+            println!("{}\n{}", fill(language.translate("You, {}"), &[&total]), render_cards(hand, 0));
+        } else if table.presentation.accessible {
+            let hand = &continuing_game_state.hand;
+            let cards = table.presentation.cards_text(hand);
+            println!("{}", fill(language.translate("Your hand: {}, total {}"), &[&cards, &total_text_in(language, hand)]));
+        } else {
+            print_hand_in(language, &continuing_game_state.hand);
+        }
+        if let Option::Some(prompt) = insurance_prompt(game_state) {
+            println!("{} (y/n)", language.translate(prompt));
+        }
+        if table.presentation.dealer_odds && continuing_game_state.phase == Phase::Playing {
+            if let Option::Some(upcard) = continuing_game_state.dealer_upcard() {
+                let rules = &continuing_game_state.rules;
+                let outcomes = solver::dealer_outcomes(upcard, &continuing_game_state.unseen_cards(), rules);
+                println!("{}", fill(language.translate("Dealer odds: {}"), &[&dealer_odds_text(&outcomes, language)]));
+            }
+        }
+        if table.presentation.advisor && continuing_game_state.phase == Phase::Playing {
+            print_advice(game_state, table);
+        } else if table.presentation.hints && continuing_game_state.phase == Phase::Playing {
+            print_hint(game_state, table);
+        }
+        let actions = describe_actions_in(language, &game_state.legal_actions(), &game_state.player_state().rules);
+        println!("{}", fill(language.translate("You can: {}"), &[&actions]));
+        if table.counting_enabled {
+            let running_count = counting::running_count(continuing_game_state);
+            let true_count = format!("{:.1}", counting::true_count(continuing_game_state));
+            println!("{}", fill(language.translate("Running count: {}, true count: {}"), &[&running_count, &true_count]));
+        }
+    }
+
+    let raw_action = match read_reply(input, table) {
+        Option::Some(Reply::Command(line)) => line,
+        Option::Some(Reply::Leave) => return Turn::Leave,
+        Option::None => return Turn::Again,
+    };
+
+    let language = &table.presentation.language;
+    let raw_action = language.untranslate(raw_action.trim().to_lowercase().as_str()).to_string();
+    if raw_action == "hint" {
+        print_hint(game_state, table);
+        return Turn::Again;
+    }
+    let action = match insurance_answer(game_state, &raw_action) {
+        Option::Some(action) => Ok(action),
+        Option::None => raw_action.parse::<Action>(),
+    };
+
+    let action = match action {
+        Ok(action) => {
+            let action_name = language.action_name(&game_state.player_state().rules.variant, &action);
+            println!("{}", fill(language.translate("You wanted to: {}"), &[&action_name]));
+            action
+        },
+        Err(error) => {
+            println!("{}", error);
+            return Turn::Again;
+        },
+    };
+
+    let player_state = game_state.player_state();
+    let legal = game_state.legal_actions().contains(&action);
+    if legal {
+        if let Err(error) = afford(&action, game_state, table) {
+            let language = &table.presentation.language;
+            let action_name = language.action_name(&player_state.rules.variant, &action);
+            let reason = error.describe_in(&table.presentation.currency);
+            println!("{}", fill(language.translate("You cannot {}: {}"), &[&action_name, &reason]));
+            return Turn::Again;
+        }
+    }
+    if let (true, Option::Some(upcard)) = (legal, player_state.dealer_upcard()) {
+        if player_state.phase == Phase::Insurance {
+            table.session.decisions += 1;
+            if advised_action(game_state, table) != action {
+                table.session.mistakes += 1;
+                record_mistake(game_state, &action, table);
+            }
+        } else {
+            if table.counting_enabled {
+                let true_count = counting::true_count(player_state);
+                table.deviation_log.record(&player_state.hand, upcard, true_count, &action);
+            }
+            if player_state.hand.card_count() >= 2 {
+                table.session.decisions += 1;
+                if advised_action(game_state, table) != action {
+                    table.session.mistakes += 1;
+                    record_mistake(game_state, &action, table);
+                }
+            }
+        }
+    }
+    Turn::Act(action)
+}
+
+// Tables are cached under the data directory by the hash of the rules they
+// were made for, so each rule set has its own.
+fn strategy_table_path(rules: &GameRules) -> PathBuf {
+    profile::data_dir().join("tables").join(format!("{:016x}.bin", strategy_table::rules_hash(rules)))
+}
+
+// None when no table has been generated for the rules. A damaged or stale
+// file is reported rather than used.
+fn cached_strategy_table(rules: &GameRules) -> Option<StrategyTable> {
+    let path = strategy_table_path(rules);
+    match StrategyTable::load(&path, rules) {
+        Ok(table) => Option::Some(table),
+        Err(TableError::Io(error)) if error.kind() == io::ErrorKind::NotFound => Option::None,
+        Err(error) => {
+            eprintln!("Ignoring {}: {}", path.display(), error);
+            Option::None
+        },
+    }
+}
+
+// The cached table, or a new one, worked out and cached on the spot.
+fn strategy_table(rules: &GameRules) -> StrategyTable {
+    if rules.variant != Variant::Blackjack {
+        exit_with_error("the table bot strategy needs blackjack rules");
+    }
+    cached_strategy_table(rules).unwrap_or_else(|| {
+        println!("Working out the strategy table for these rules...");
+        let table = StrategyTable::generate(rules);
+        let path = strategy_table_path(rules);
+        if let Err(error) = table.save(&path) {
+            eprintln!("Could not cache the strategy table in {}: {}", path.display(), error);
+        }
+        table
+    })
+}
+
+// The bust chance first, then each total the dealer can finish on, and a
+// natural when the dealer hasn't peeked for one.
+fn dealer_odds_text(outcomes: &DealerOutcomes, language: &Language) -> String {
+    let percent = |chance: f64| format!("{:.1}%", chance * 100.0);
+    let mut parts = vec![fill(language.translate("bust {}"), &[&percent(outcomes.bust)])];
+    for (total, chance) in (17..).zip(outcomes.totals.iter()) {
+        parts.push(format!("{} {}", total, percent(*chance)));
+    }
+    if outcomes.natural > 0.0 {
+        parts.push(fill(language.translate("blackjack {}"), &[&percent(outcomes.natural)]));
+    }
+    parts.join(", ")
+}
+
+// The play from the player's own strategy file when there is one, and
+// otherwise the one they are graded against.
+fn print_hint(game_state: &GameState, table: &Table) {
+    let language = &table.presentation.language;
+    let variant = &game_state.player_state().rules.variant;
+    let (message, action) = match &table.presentation.own_strategy {
+        Option::Some(own_strategy) => ("Your strategy says: {}", own_strategy.action_for(game_state)),
+        Option::None => ("The book says: {}", advised_action(game_state, table)),
+    };
+    println!("{}", fill(language.translate(message), &[&language.action_name(variant, &action)]));
+}
+
+// The play the player is graded against, then what every legal action is
+// worth, best first, so the size of a mistake shows and not only that it is
+// one. The values come from the strategy table when there is one for the
+// rules, and are otherwise worked out from the cards not yet seen. Where
+// they pick a different play, the book's is the one the count of the
+// mistakes goes by.
+fn print_advice(game_state: &GameState, table: &Table) {
+    let player_state = game_state.player_state();
+    let rules = &player_state.rules;
+    let upcard = match player_state.dealer_upcard() {
+        Option::Some(upcard) if rules.variant == Variant::Blackjack && player_state.hand.card_count() >= 2 => upcard,
+        _ => return,
+    };
+    let language = &table.presentation.language;
+    let book = advised_action(game_state, table);
+    println!("{}", fill(language.translate("The book says: {}"), &[&language.action_name(&rules.variant, &book)]));
+
+    let legal = game_state.legal_actions();
+    let (values, from_table) = decision_values(player_state, upcard, table);
+    let mut values: Vec<(Action, f64)> = values.all().into_iter().filter(|(action, _)| legal.contains(action)).collect();
+    values.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let listed: Vec<String> = values
+        .iter()
+        .map(|(action, value)| format!("{} {:+.4}", language.action_name(&rules.variant, action), value))
+        .collect();
+    let heading = if from_table { "Expected value per bet from a full shoe: {}" } else { "Expected value per bet: {}" };
+    println!("{}", fill(language.translate(heading), &[&listed.join(", ")]));
+
+    let book_value = values.iter().find(|(action, _)| *action == book).map(|(_, value)| *value);
+    if let (Option::Some((best, best_value)), Option::Some(book_value)) = (values.first(), book_value) {
+        if *best != book && best_value - book_value > 1e-9 {
+            let best_name = language.action_name(&rules.variant, best);
+            let gain = format!("{:.4}", best_value - book_value);
+            let message = if from_table { "By the table, {} is worth {} more" } else { "With these cards left, {} is worth {} more" };
+            println!("{}", fill(language.translate(message), &[&best_name, &gain]));
         }
-        Option::None => Option::None
     }
 }
 
-fn is_hand_too_large(hand: &Vec<Card>) -> bool {
-    let card_values = &hand.iter().map(|card| card.value.clone()).collect();
-    if !hand.is_empty() {
-        calculate_current_hand_value(card_values).is_empty()
+// What each play on the hand is worth, from the strategy table when there is
+// one for the rules and otherwise from the cards not yet seen, and whether
+// the table was used.
+fn decision_values(player_state: &PlayerState, upcard: &Card, table: &Table) -> (DecisionValues, bool) {
+    let looked_up = table.strategy_table.as_ref().and_then(|strategy_table| strategy_table.values(&player_state.hand, upcard));
+    let from_table = looked_up.is_some();
+    let values =
+        looked_up.unwrap_or_else(|| solver::solve(&player_state.hand, upcard, &player_state.unseen_cards(), &player_state.rules));
+    (values, from_table)
+}
+
+// Notes a decision that went against the book, with what it gave up. Only
+// blackjack hands are priced, so a pontoon mistake is counted but not kept.
+fn record_mistake(game_state: &GameState, action: &Action, table: &mut Table) {
+    let player_state = game_state.player_state();
+    let upcard = match player_state.dealer_upcard() {
+        Option::Some(upcard) if table.mistake_log.is_some() && player_state.rules.variant == Variant::Blackjack => upcard,
+        _ => return,
+    };
+    let values = if player_state.phase == Phase::Insurance {
+        solver::insurance_values(is_natural(&player_state.hand), &player_state.unseen_cards(), &player_state.rules)
     } else {
-        false
+        decision_values(player_state, upcard, table).0.all()
+    };
+    let advised = advised_action(game_state, table);
+    let value_of = |wanted: &Action| values.iter().find(|(action, _)| action == wanted).map(|(_, value)| *value);
+    if let (Option::Some(advised_value), Option::Some(taken_value)) = (value_of(&advised), value_of(action)) {
+        let mistake = Mistake {
+            hand_number: table.session.hands + 1 + table.seat as u64,
+            hand: player_state.hand.to_vec(),
+            dealer_upcard: *upcard,
+            action_taken: action.clone(),
+            advised,
+            cost: advised_value - taken_value,
+            bet: table.bet,
+        };
+        if let Option::Some(log) = table.mistake_log.as_mut() {
+            log.record(mistake);
+        }
     }
 }
 
-fn deal_with_action<'a>(action: &Action, state: GameState<'a>) -> GameState<'a> {
-    match state {
-        x @ GameState::GameLost(_) => x,
-        x @ GameState::GameWon(_) => x,
-        GameState::Continuing(mut player_state) =>
-            match action {
-                Action::Surrender => GameState::GameLost(player_state),
-                Action::Hit => {
-                    let card_opt = player_state.deck.draw_card();
-                    if let Option::Some(card) = card_opt {
-                        player_state.hand.push(card);
-                    }
-                    if is_hand_too_large(&player_state.hand) {
-                        GameState::GameLost(player_state)
-                    } else {
-                        let card_values = &player_state.create_hand_values();
-                        let possible_hand_values = calculate_current_hand_value(card_values);
-                        let are_any_hand_values_21 =
-                            possible_hand_values.iter().find(|x| x.value == 21).is_some();
-                        if are_any_hand_values_21 {
-                            GameState::GameWon(player_state)
-                        } else {
-                            GameState::Continuing(player_state)
-                        }
-                    }
-                },
-                Action::Stand => GameState::GameLost(player_state),
-                Action::DoubleDown => GameState::GameLost(player_state),
-                Action::SplitCards => GameState::GameLost(player_state),
+// Checks the bankroll covers what a double or a split puts down. A double it
+// only partly covers is made for less, all in.
+fn afford(action: &Action, game_state: &GameState, table: &mut Table) -> Result<(), BetError> {
+    let bankroll = (table.session.final_bankroll().max(0) as u64).saturating_sub(table.staked_elsewhere);
+    let available = bankroll.saturating_sub(staked(game_state, table.bet, table.short_double.as_ref()));
+    match action {
+        Action::SplitCards => check_split(table.bet, available),
+        Action::DoubleDown => {
+            let extra = double_stake(table.bet, available)?;
+            if extra < table.bet {
+                let extra_text = table.presentation.money(extra as i64);
+                println!("{}", fill(table.presentation.text("Doubling for less: {} more, all in"), &[&extra_text]));
+                let hand = game_state.player_state().split_hands.len();
+                table.short_double = Option::Some(ShortDouble { hand, extra });
             }
+            Ok(())
+        },
+        _ => Ok(()),
     }
 }
 
-fn continue_with_game(game_state: &GameState) -> bool {
-    match game_state {
-        GameState::GameWon(_) => false,
-        GameState::GameLost(_) => false,
-        GameState::Continuing(_) => true,
+// The state as --trace-state shows it, with the stakes on the table taken
+// out of the bankroll.
+fn table_snapshot(state: &GameState, table: &Table) -> JsonValue {
+    let staked = staked(state, table.bet, table.short_double.as_ref());
+    trace::snapshot(state, staked, table.session.final_bankroll() - (staked + table.staked_elsewhere) as i64)
+}
+
+// What a step changed, on stderr so the table reads as it always does.
+fn trace_step(played: &str, before: &GameState, after: &GameState, table: &Table) {
+    if table.trace_state {
+        print_trace(played, &table_snapshot(before, table), &table_snapshot(after, table));
     }
 }
 
-fn game_message(game_state: &GameState) -> &'static str {
-    match game_state {
-        GameState::GameWon(_) => "You won",
-        GameState::GameLost(_) => "You lost",
-        GameState::Continuing(_) => "The game is still going",
+fn print_trace(played: &str, before: &JsonValue, after: &JsonValue) {
+    eprintln!("trace: {}", played);
+    for change in trace::diff(before, after) {
+        eprintln!("trace:   {}", change);
     }
 }
 
-fn main() {
-    println!("Play blackjack!");
+fn trace_deal(state: &GameState, table: &Table) {
+    if table.trace_state {
+        eprintln!("trace: deal {}", table_snapshot(state, table));
+    }
+}
 
-    println!("Please input what you'd like to do (hit/stand/double-down/split/surrender):");
+// Pauses before an action that takes a card, as the dealer reaches for it.
+// Plays one hand to the end. The flag is set when the player left the table
+// mid-hand, in which case the hand has been surrendered.
+fn play_hand(mut game_state: GameState, input: &InputLines, table: &mut Table) -> (GameState, bool) {
+    while continue_with_game(&game_state) {
+        match next_action(&game_state, input, table) {
+            Turn::Act(action) => {
+                let before = game_state.clone();
+                game_state = match events::act(&action, game_state, &mut table.presentation) {
+                    Ok(next_state) => {
+                        trace_step(encode_action(&action), &before, &next_state, table);
+                        table.hand_actions.push(action);
+                        next_state
+                    },
+                    Err(illegal) => {
+                        println!("You {}", illegal);
+                        *illegal.state
+                    },
+                }
+            },
+            Turn::Leave => {
+                let abandoned = game_state.clone().abandon();
+                for event in events::changes(&game_state, &abandoned) {
+                    table.presentation.emit(event);
+                }
+                trace_step("leave", &game_state, &abandoned, table);
+                return (abandoned, true);
+            },
+            Turn::Again => (),
+        }
+    }
+    (game_state, false)
+}
 
-    let mut raw_action = String::new();
+// Who sits at the table: `bots` computer players playing `bot_strategy`,
+// then the player, in `seats` seats of their own.
+struct Seating<'a> {
+    bots: usize,
+    bot_strategy: &'a mut dyn Strategy,
+    seats: usize,
+}
 
-    let mut deck = Deck::new();
+// What the player did in one of their seats, for settling it.
+struct Played {
+    actions: Vec<Action>,
+    short_double: Option<ShortDouble>,
+}
 
-    deck.shuffle(&mut thread_rng());
+// The chips down on the player's hands other than the one at `index`.
+fn staked_elsewhere(hands: &[GameState], bets: &[u64], played: &[Played], index: usize) -> u64 {
+    hands
+        .iter()
+        .zip(bets)
+        .enumerate()
+        .filter(|(other, _)| *other != index)
+        .map(|(other, (state, bet))| staked(state, *bet, played.get(other).and_then(|played| played.short_double.as_ref())))
+        .sum()
+}
 
-    let mut game_state = GameState::start(&mut deck);
+// Plays one round with bots in the first seats and the player in the rest,
+// a seat for each of `bets`, so every other hand has been played out in
+// front of them before they decide. The player's seats are played one after
+// another, and leaving surrenders every hand of theirs still in play.
+// Returns every seat once the dealer has played, the shoe, what the player
+// did in each of theirs and whether they left.
+fn play_round(
+    shoe: Deck,
+    rules: &GameRules,
+    seating: &mut Seating,
+    bets: &[u64],
+    input: &InputLines,
+    table: &mut Table,
+) -> (Vec<GameState>, Deck, Vec<Played>, bool) {
+    let bots = seating.bots;
+    let strategy = &mut *seating.bot_strategy;
+    let mut round = Round::deal(shoe, bots + bets.len(), rules);
+    for seat in 0..bots {
+        let actions = round.play_with(seat, strategy);
+        let hand = &round.seats()[seat].player_state().hand;
+        let played: Vec<&str> = actions
+            .iter()
+            .filter(|action| !matches!(action, Action::DeclineInsurance))
+            .map(|action| rules.variant.action_name(action))
+            .collect();
+        let played = if played.is_empty() { "nothing to play".to_string() } else { played.join(", ") };
+        println!("Seat {} ({}): {}; {} {}", seat + 1, strategy.name(), played, cards_text(hand), total_text(hand));
+    }
 
-    let stdin = io::stdin();
+    let mut left = false;
+    let mut played = Vec::new();
+    for (index, bet) in bets.iter().enumerate() {
+        let seat = bots + index;
+        table.bet = *bet;
+        table.seat = index;
+        table.staked_elsewhere = staked_elsewhere(&round.seats()[bots..], bets, &played, index);
+        if left {
+            if round.is_playing(seat) {
+                round.abandon(seat);
+            }
+        } else {
+            if bets.len() > 1 {
+                let message = table.presentation.text("Your hand in seat {}, bet {}");
+                println!("{}", fill(message, &[&(seat + 1), &table.presentation.money(*bet as i64)]));
+            }
+            // The dealer's cards are dealt once, with the first of the
+            // player's hands.
+            if index == 0 {
+                table.presentation.deal(&round.view(seat));
+            } else {
+                for event in events::deal_events(&round.view(seat)) {
+                    if matches!(event, UiEvent::CardDealt { to: Seat::Player, .. }) {
+                        table.presentation.emit(event);
+                    }
+                }
+            }
+            trace_deal(&round.view(seat), table);
+        }
+        while round.is_playing(seat) {
+            let view = round.view(seat);
+            match next_action(&view, input, table) {
+                Turn::Act(action) => match round.act(seat, &action) {
+                    Ok(()) => {
+                        for event in events::changes(&view, &round.view(seat)) {
+                            table.presentation.emit(event);
+                        }
+                        trace_step(encode_action(&action), &view, &round.view(seat), table);
+                        table.hand_actions.push(action);
+                    },
+                    Err(error) => println!("You {}", error),
+                },
+                Turn::Leave => {
+                    round.abandon(seat);
+                    trace_step("leave", &view, &round.view(seat), table);
+                    left = true;
+                },
+                Turn::Again => (),
+            }
+        }
+        played.push(Played { actions: mem::take(&mut table.hand_actions), short_double: table.short_double.take() });
+    }
+    let before: Vec<GameState> = (bots..bots + bets.len()).map(|seat| round.view(seat)).collect();
+    let (seats, deck) = round.finish();
+    for (index, before) in before.iter().enumerate() {
+        let after = &seats[bots + index];
+        for event in events::changes(before, after) {
+            // The dealer's turn is shown once, and each hand's outcome.
+            if index == 0 || matches!(event, UiEvent::OutcomeDecided { .. }) {
+                table.presentation.emit(event);
+            }
+        }
+        table.bet = bets[index];
+        table.seat = index;
+        table.short_double = played[index].short_double.clone();
+        table.staked_elsewhere = staked_elsewhere(&seats[bots..], bets, &played, index);
+        trace_step("dealer", before, after, table);
+    }
+    table.short_double = Option::None;
+    table.staked_elsewhere = 0;
+    table.seat = 0;
+    (seats, deck, played, left)
+}
 
-    let mut stdin_lines = stdin.lock().lines();
+// Asks for the next bet until one is within the table limits and the
+// bankroll left after the player's other seats, then shows it as chips. An empty line bets `last` again. None
+// means the player left.
+fn ask_bet(last: u64, limits: &TableLimits, input: &InputLines, table: &mut Table, color: bool) -> Option<u64> {
+    let prompt = "Place your bet of {} to {}, as an amount or chips such as 2x25 1x5 (enter bets {}), or type stats or quit";
+    let money = |amount: u64| table.presentation.money(amount as i64);
+    println!("{}", fill(table.presentation.text(prompt), &[&money(limits.minimum), &money(limits.maximum), &money(last)]));
+    loop {
+        let line = match read_reply(input, table) {
+            Option::Some(Reply::Command(line)) => line,
+            Option::Some(Reply::Leave) => return Option::None,
+            Option::None => continue,
+        };
+        let bet = if line.trim().is_empty() { Option::Some(last) } else { parse_amount(&line, &table.presentation.currency) };
+        let bankroll = (table.session.final_bankroll().max(0) as u64).saturating_sub(table.staked_elsewhere);
+        match bet.map(|bet| (bet, limits.check(bet, bankroll))) {
+            Option::None => {
+                let message = table.presentation.text("{} is neither an amount nor chips of 1, 5, 25, 100 or 500");
+                println!("{}", fill(message, &[&format!("{:?}", line.trim())]))
+            },
+            Option::Some((_, Err(error))) => {
+                let reason = error.describe_in(&table.presentation.currency);
+                println!("{}", fill(table.presentation.text("Cannot bet: {}"), &[&reason]))
+            },
+            Option::Some((bet, Ok(()))) => {
+                // Chips come in whole units; anything under one is left off
+                // the stacks.
+                let whole = bet / table.presentation.currency.unit();
+                if table.presentation.accessible {
+                    let chips: Vec<String> = chips_for(whole).iter().map(|(chip, count)| format!("{}x{}", count, chip.value())).collect();
+                    println!("{}", fill(table.presentation.text("Chips: {}"), &[&chips.join(", ")]));
+                } else {
+                    println!("{}", render_stacks(whole, color));
+                }
+                return Option::Some(bet);
+            },
+        }
+    }
+}
 
-    while continue_with_game(&game_state) {
-        if let GameState::Continuing(continuing_game_state) = &game_state {
-            println!("Your hand is {:?}", &continuing_game_state.hand);
-            println!("Your hand value is {:?}", calculate_current_hand_value(&continuing_game_state.hand.iter().map(|x| x.value.clone()).collect()));
+// An amount as the currency writes it, or chips such as `2x25 1x5`, each
+// chip a whole unit; in the currency's smallest unit either way.
+fn parse_amount(raw: &str, currency: &Currency) -> Option<u64> {
+    currency.parse(raw).or_else(|| parse_bet(raw).and_then(|chips| chips.checked_mul(currency.unit())))
+}
 
+// Asks for the chips to buy back in with once the bankroll is short of the
+// table minimum: enter tops it back up to `top_up`, or any amount that
+// covers the minimum will do. None when the player leaves instead.
+fn ask_rebuy(top_up: u64, limits: &TableLimits, input: &InputLines, table: &mut Table) -> Option<u64> {
+    let left = table.session.final_bankroll().max(0) as u64;
+    let prompt = "Buy back in with an amount, or enter for {} to bring the bankroll to {}, or type quit";
+    let money = |amount: u64| table.presentation.money(amount as i64);
+    println!("{}", fill(table.presentation.text(prompt), &[&money(top_up - left), &money(top_up)]));
+    loop {
+        let line = match read_reply(input, table) {
+            Option::Some(Reply::Command(line)) => line,
+            Option::Some(Reply::Leave) => return Option::None,
+            Option::None => continue,
+        };
+        let chips = if line.trim().is_empty() { Option::Some(top_up - left) } else { parse_amount(&line, &table.presentation.currency) };
+        match chips {
+            Option::Some(chips) if left + chips >= limits.minimum => return Option::Some(chips),
+            Option::Some(chips) => {
+                let message = table.presentation.text("{} more still leaves the bankroll short of the table minimum of {}");
+                println!("{}", fill(message, &[&table.presentation.money(chips as i64), &table.presentation.money(limits.minimum as i64)]))
+            },
+            Option::None => {
+                let message = table.presentation.text("{} is neither an amount nor chips of 1, 5, 25, 100 or 500");
+                println!("{}", fill(message, &[&format!("{:?}", line.trim())]))
+            },
         }
+    }
+}
 
-        if let Option::Some(line) = stdin_lines.next() {
-            raw_action = line.expect("Failed to read line!")
+// Offers to tip the dealer out of a win of `won`. None means the player is
+// leaving, tipping nothing.
+fn ask_tip(won: u64, input: &InputLines, table: &mut Table) -> Option<u64> {
+    let prompt = "Tip the dealer out of your {}? Type an amount or chips, or press enter for no tip";
+    println!("{}", fill(table.presentation.text(prompt), &[&table.presentation.money(won as i64)]));
+    loop {
+        let line = match read_reply(input, table) {
+            Option::Some(Reply::Command(line)) => line,
+            Option::Some(Reply::Leave) => return Option::None,
+            Option::None => continue,
+        };
+        if line.trim().is_empty() {
+            return Option::Some(0);
+        }
+        match parse_amount(&line, &table.presentation.currency) {
+            Option::Some(tip) if tip <= won => return Option::Some(tip),
+            Option::Some(tip) => {
+                let message = table.presentation.text("A tip of {} is more than the {} you won");
+                println!("{}", fill(message, &[&table.presentation.money(tip as i64), &table.presentation.money(won as i64)]))
+            },
+            Option::None => {
+                let message = table.presentation.text("{} is neither an amount nor chips of 1, 5, 25, 100 or 500");
+                println!("{}", fill(message, &[&format!("{:?}", line.trim())]))
+            },
         }
+    }
+}
+
+// Deals hand after hand until the player leaves, a session limit is
+// reached or the bankroll can no longer cover the table minimum and the
+// player doesn't buy back in. Each of the player's seats is a hand of the
+// session, with a bet of its own.
+fn play(
+    settings: &Settings,
+    config: &Config,
+    counting_enabled: bool,
+    presentation: Presentation,
+    mut seating: Seating,
+    records: Records,
+) -> SessionSummary {
+    // The books are kept in the currency's smallest unit, and the session
+    // is cashed out in whole units at the end.
+    let settings = &settings.in_smallest_unit();
+    let unit = settings.currency.unit();
+    let money = |amount: u64| presentation.money(amount as i64);
+    let rules = &settings.rules;
+    let bankroll = settings.bankroll;
+    let limits = &settings.table_limits;
+    let bet = limits.clamp(settings.bet);
+    if bet != settings.bet {
+        let message = presentation.text("The table takes bets of {} to {}, so the bet starts at {}");
+        println!("{}", fill(message, &[&money(limits.minimum), &money(limits.maximum), &money(bet)]));
+    }
 
-        println!("raw_action: {:?}", raw_action);
+    let started = Instant::now();
 
-        let action = parse_action(&raw_action);
+    println!("{}", presentation.text("Play blackjack!"));
 
-        println!("You wanted to: {:?}", action);
+    println!("{}", fill(presentation.text("Rules: {}, bankroll: {}"), &[&rules.name(), &money(bankroll)]));
 
-        match action {
-            Option::Some(action) =>
-                game_state = deal_with_action(&action, game_state),
-            Option::None=>
-                ()
+    let menu = action_menu_in(&presentation.language);
+    println!("{}", fill(presentation.text("Please input what you'd like to do: {}"), &[&menu]));
+    println!(
+        "{}",
+        presentation.text(
+            "Type stats for the session so far, hint for the play to make, or quit to leave the table; a hand still in play is surrendered."
+        )
+    );
+
+    let mut shuffler = shuffler(settings);
+
+    let input = InputLines::new();
+
+    // Mistakes are priced from the same table the advisor reads, when one
+    // has been made.
+    let hinted = presentation.advisor || presentation.hints;
+    let advice_table = cached_strategy_table(&settings.game_rules());
+    let mut table = Table {
+        session: SessionSummary::new(bankroll),
+        stats: SessionStats::new(),
+        deviation_log: DeviationLog::new(),
+        modifiers: active_promotions(settings),
+        counting_enabled,
+        presentation,
+        achievements: unlocked_achievements(settings.profile.as_deref()),
+        profile: settings.profile.clone(),
+        export: records.export.map(create_exporter),
+        history: records.history.map(|path| {
+            HistoryWriter::create(path).unwrap_or_else(|error| exit_with_error(format!("could not write {}: {}", path.display(), error)))
+        }),
+        trace_state: records.trace_state,
+        hand_actions: Vec::new(),
+        bet,
+        short_double: Option::None,
+        seat: 0,
+        staked_elsewhere: 0,
+        strategy_table: advice_table,
+        mistake_log: if hinted { Option::None } else { Option::Some(MistakeLog::new()) },
+    };
+
+    // One shoe lasts until the cut card comes out; the hand it appears in is
+    // finished before the reshuffle.
+    let mut shoe = new_deck(settings, shuffler.as_mut());
+    // The last bet in each of the player's seats.
+    let mut bets = vec![bet; seating.seats];
+
+    loop {
+        let reached = settings.session_limits.reached(table.session.net, table.session.hands, started.elapsed());
+        if let Option::Some(reason) = reached {
+            println!("{}", stop_message(&reason, &settings.session_limits, &table));
+            break;
+        }
+        if table.session.final_bankroll() < limits.minimum as i64 {
+            let message = table.presentation.text("Your bankroll can no longer cover the table minimum of {}");
+            println!("{}", fill(message, &[&table.presentation.money(limits.minimum as i64)]));
+            match ask_rebuy(bankroll.max(limits.minimum), limits, &input, &mut table) {
+                Option::Some(chips) => {
+                    table.session.rebuys += 1;
+                    table.session.rebought += chips;
+                    let message = table.presentation.text("Bought back in for {}, bankroll {}");
+                    let bought = table.presentation.money(chips as i64);
+                    println!("{}", fill(message, &[&bought, &table.presentation.money(table.session.final_bankroll())]));
+                },
+                Option::None => break,
+            }
         }
+        // A seat the bankroll can't cover on top of the others sits this
+        // round out.
+        let mut round_bets: Vec<u64> = Vec::new();
+        let mut quit = false;
+        for (index, last) in bets.iter().take(seating.seats).enumerate() {
+            table.staked_elsewhere = round_bets.iter().sum();
+            let available = (table.session.final_bankroll().max(0) as u64).saturating_sub(table.staked_elsewhere);
+            // Once the bets already down leave less than the minimum, this
+            // seat and the ones after it sit the round out.
+            if index > 0 && available < limits.minimum {
+                let message = table.presentation.text("Your bankroll covers {} of your {} seats this round");
+                println!("{}", fill(message, &[&index, &seating.seats]));
+                break;
+            }
+            if seating.seats > 1 {
+                println!("{}", fill(table.presentation.text("Seat {}:"), &[&(seating.bots + index + 1)]));
+            }
+            match ask_bet((*last).min(available).max(limits.minimum), limits, &input, &mut table, config.color) {
+                Option::Some(bet) => round_bets.push(bet),
+                Option::None => {
+                    quit = true;
+                    break;
+                },
+            }
+        }
+        table.staked_elsewhere = 0;
+        if quit {
+            break;
+        }
+        let seats = round_bets.len();
+        bets[..seats].copy_from_slice(&round_bets);
 
+        if shoe.needs_shuffle() {
+            match shoe.cut_card() {
+                Option::Some(_) => println!("{}", table.presentation.text("The cut card is out, shuffling a new shoe.")),
+                Option::None => println!("{}", table.presentation.text("The shoe is used up, starting a new one.")),
+            }
+            shoe = new_deck(settings, shuffler.as_mut());
+        }
+        let shoe_before = table.history.as_ref().map(|_| shoe.clone());
+        let running_count = counting::HI_LO.shoe_running_count(&shoe);
+        let true_count = counting::HI_LO.shoe_true_count(&shoe);
+        // The shoe comes back in the last of the player's hands.
+        let (hands, others, played, mut left) = if seating.bots == 0 && seats == 1 {
+            table.bet = round_bets[0];
+            let game_state = GameState::start_with_rules(shoe, settings.game_rules());
+            table.presentation.deal(&game_state);
+            trace_deal(&game_state, &table);
+            let (game_state, left) = play_hand(game_state, &input, &mut table);
+            let played = Played { actions: mem::take(&mut table.hand_actions), short_double: table.short_double.take() };
+            (vec![game_state], Vec::new(), vec![played], left)
+        } else {
+            let (mut others, deck, played, left) =
+                play_round(shoe, &settings.game_rules(), &mut seating, &round_bets, &input, &mut table);
+            let mut hands = others.split_off(seating.bots);
+            hands.last_mut().expect("the player has a seat").player_state_mut().deck = deck;
+            (hands, others, played, left)
+        };
 
+        let mut settled = Vec::new();
+        for ((game_state, played), bet) in hands.into_iter().zip(played).zip(round_bets) {
+            table.session.hands += 1;
+            let outcome_color = match game_state {
+                GameState::GameWon(_) | GameState::BlackjackWin(_) => {
+                    table.session.wins += 1;
+                    "32"
+                },
+                GameState::GameLost(_) | GameState::Surrendered(_) => {
+                    table.session.losses += 1;
+                    "31"
+                },
+                GameState::Push(_) => {
+                    table.session.pushes += 1;
+                    "33"
+                },
+                GameState::Continuing(_) => "0",
+            };
+            table.bet = bet;
+            table.short_double = played.short_double;
+            let unsettled = Option::Some(&game_state).filter(|_| table.trace_state).map(|state| table_snapshot(state, &table));
+            let short_double = table.short_double.take();
+            let settlement = settle_with_short_double(&game_state, bet, short_double.as_ref(), &table.modifiers);
+            table.session.net += settlement.net;
+            if let Option::Some(unsettled) = unsettled {
+                print_trace("settle", &unsettled, &trace::snapshot(&game_state, 0, table.session.final_bankroll()));
+            }
+            table.stats.record(&game_state, &settlement);
+            let bankroll = table.session.final_bankroll();
+            table.presentation.emit(UiEvent::BankrollChanged { net: settlement.net, bankroll });
+            settled.push(SettledHand { state: game_state, bet, actions: played.actions, settlement, outcome_color });
+        }
+        if table.presentation.ascii_cards {
+            let presentation = &table.presentation;
+            let dealer_cards = settled[0].state.player_state().dealer_cards();
+            let dealer_total = total_text_in(&presentation.language, &dealer_cards);
+            println!("{}\n{}", fill(presentation.text("Dealer, {}"), &[&dealer_total]), render_cards(&dealer_cards, 0));
+            for hand in &settled {
+                let player_hand = &hand.state.player_state().hand;
+                let total = total_text_in(&presentation.language, player_hand);
+                println!("{}\n{}", fill(presentation.text("You, {}"), &[&total]), render_cards(player_hand, 0));
+            }
+        }
+        print_result(&settled, &others, table.session.final_bankroll(), config, &table.presentation);
+        for hand in &settled {
+            announce_achievements(&hand.state, &mut table);
+        }
+        let won: u64 = settled.iter().map(|hand| max_tip(&hand.settlement)).sum();
+        if config.tips && won > 0 && !left {
+            match ask_tip(won, &input, &mut table) {
+                Option::Some(0) => (),
+                Option::Some(tip) => {
+                    table.session.net -= tip as i64;
+                    table.stats.record_tip(tip);
+                    let message = table.presentation.text("You tip the dealer {}, bankroll {}");
+                    let bankroll = table.presentation.money(table.session.final_bankroll());
+                    println!("{}", fill(message, &[&table.presentation.money(tip as i64), &bankroll]));
+                },
+                Option::None => left = true,
+            }
+        }
+        let bankroll = table.session.final_bankroll();
+        let first_hand = table.session.hands + 1 - settled.len() as u64;
+        for (number, hand) in (first_hand..).zip(&settled) {
+            let game_state = &hand.state;
+            if let Option::Some(exporter) = &mut table.export {
+                let record = HandRecord {
+                    hand: number,
+                    player_cards: game_state.player_state().hand.to_vec(),
+                    dealer_cards: game_state.player_state().dealer_cards(),
+                    actions: hand.actions.clone(),
+                    running_count,
+                    true_count,
+                    bet: hand.bet,
+                    outcome: outcome_name(game_state),
+                    net: hand.settlement.net,
+                    bankroll,
+                };
+                if let Err(error) = exporter.write(&record) {
+                    eprintln!("Could not export the hand, so the export stops here: {}", error);
+                    table.export = Option::None;
+                }
+            }
+            // Only ever the one hand, as the history doesn't take several seats.
+            if let (Option::Some(history), Option::Some(before)) = (&mut table.history, &shoe_before) {
+                let player_state = game_state.player_state();
+                let mut player_hands: Vec<Vec<Card>> = player_state.split_hands.iter().map(|split| split.hand.to_vec()).collect();
+                player_hands.push(player_state.hand.to_vec());
+                let history_hand = HistoryHand {
+                    hand: number,
+                    rules: settings.game_rules(),
+                    bet: hand.bet,
+                    shoe: cards_drawn(before, &player_state.deck),
+                    actions: hand.actions.clone(),
+                    left,
+                    player_hands,
+                    dealer_cards: player_state.dealer_cards(),
+                    outcome: outcome_name(game_state).to_string(),
+                    net: hand.settlement.net,
+                    bankroll,
+                };
+                if let Err(error) = history.write(&history_hand) {
+                    eprintln!("Could not write the hand history, so it stops here: {}", error);
+                    table.history = Option::None;
+                }
+            }
+        }
+        let mut seats = others;
+        seats.extend(settled.into_iter().map(|hand| hand.state));
+        let deck = mem::replace(&mut seats.last_mut().expect("the player has a seat").player_state_mut().deck, Deck::with_decks(0));
+        shoe = table::clear_table(seats, deck);
+
+        if left {
+            break;
+        }
+    }
+
+    let currency = &table.presentation.currency;
+    println!("Session statistics:\n{}", table.stats.render_in(currency));
+    if table.session.rebuys > 0 {
+        let rebought = currency.format(table.session.rebought as i64);
+        println!("Rebuys:          {} for {}, not in the net result", table.session.rebuys, rebought);
     }
 
-    println!("{}", game_message(&game_state));
+    if let Option::Some(mistake_log) = &table.mistake_log {
+        println!("{}", mistake_log.render_in(currency));
+    }
 
-    println!("Final hand: {:?}", game_state.player_state().hand);
-    println!("Final hand value: {:?}", raw_calculate_current_hand_value(&game_state.player_state().create_hand_values()));
+    if counting_enabled {
+        table.deviation_log.print_summary();
+    }
+    if let (Option::Some(exporter), Option::Some(path)) = (table.export.take(), records.export) {
+        finish_export(exporter, path);
+    }
+    if let (Option::Some(history), Option::Some(path)) = (table.history.take(), records.history) {
+        let hands = history.hands();
+        println!("Wrote the history of {} hand{} to {}", hands, if hands == 1 { "" } else { "s" }, path.display());
+    }
+    let mut session = table.session;
+    session.deviation_spots = table.deviation_log.entries.len() as u64;
+    session.deviations_taken = table.deviation_log.entries.iter().filter(|entry| entry.took_deviation()).count() as u64;
+    session.seconds = started.elapsed().as_secs();
 
+    session.in_whole_units(unit)
 }
@@ -0,0 +1,85 @@
+use crate::currency::Currency;
+use crate::protocol::{encode_action, encode_card};
+use crate::{Action, Card, HandTotals};
+
+// A decision that went against the one graded as correct, with what it
+// cost in expected value, as a fraction of the bet. The cost is negative
+// when the cards left made the player's choice the better one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mistake {
+    // The hand of the session it came in, counting from one.
+    pub hand_number: u64,
+    pub hand: Vec<Card>,
+    pub dealer_upcard: Card,
+    pub action_taken: Action,
+    pub advised: Action,
+    pub cost: f64,
+    pub bet: u64,
+}
+
+impl Mistake {
+    // The cost in chips at the stake that was riding on it.
+    pub fn chips(&self) -> f64 {
+        self.cost * self.bet as f64
+    }
+}
+
+// Mistakes kept quietly through a session, for a report once it is over
+// rather than a correction on the spot.
+#[derive(Debug, Default)]
+pub struct MistakeLog {
+    pub entries: Vec<Mistake>,
+}
+
+impl MistakeLog {
+    pub fn new() -> MistakeLog {
+        MistakeLog { entries: Vec::new() }
+    }
+
+    pub fn record(&mut self, mistake: Mistake) {
+        self.entries.push(mistake);
+    }
+
+    pub fn total_cost(&self) -> f64 {
+        self.entries.iter().map(|entry| entry.cost).sum()
+    }
+
+    pub fn total_chips(&self) -> f64 {
+        self.entries.iter().map(Mistake::chips).sum()
+    }
+
+    pub fn render(&self) -> String {
+        self.render_in(&Currency::default())
+    }
+
+    // Chips as `currency` writes them.
+    pub fn render_in(&self, currency: &Currency) -> String {
+        if self.entries.is_empty() {
+            return "Mistakes: none".to_string();
+        }
+        let mut lines = vec![format!(
+            "Mistakes: {}, costing {:.4} bets, or {} at the stakes played",
+            self.entries.len(),
+            self.total_cost(),
+            currency.format_approximate(self.total_chips())
+        )];
+        for entry in &self.entries {
+            let cards: Vec<String> = entry.hand.iter().map(encode_card).collect();
+            let totals = HandTotals::of(&entry.hand);
+            lines.push(format!(
+                "  hand {}: {} ({}{}) v {}: you chose {}, the book plays {}, cost {:.4} ({}){}",
+                entry.hand_number,
+                cards.join(" "),
+                if totals.is_soft() { "soft " } else { "" },
+                totals.best().unwrap_or(totals.hard),
+                encode_card(&entry.dealer_upcard),
+                encode_action(&entry.action_taken),
+                encode_action(&entry.advised),
+                entry.cost,
+                currency.format_approximate(entry.chips()),
+                if entry.cost < 0.0 { ", though the cards left favoured your play" } else { "" }
+            ));
+        }
+        lines.join("\n")
+    }
+}
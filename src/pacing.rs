@@ -0,0 +1,125 @@
+use std::io::{self, Write};
+use std::thread;
+
+use rust_blackjack::config::Speed;
+use rust_blackjack::currency::Currency;
+use rust_blackjack::i18n::{fill, Language};
+use rust_blackjack::protocol::encode_card;
+use rust_blackjack::events::{deal_events, EventSink, Seat, UiEvent};
+use rust_blackjack::sound::{Sound, SoundPlayer};
+use rust_blackjack::strategy_file::CustomStrategy;
+use rust_blackjack::{Card, GameState};
+
+// How the table is shown: the cards as text or as ASCII art, how quickly
+// they arrive, what they sound like and the language it all reads in. Only
+// the printing waits; by the time anything is shown the engine has already
+// dealt it.
+pub struct Presentation {
+    pub ascii_cards: bool,
+    pub speed: Speed,
+    pub language: Language,
+    // Cards in words and every update narrated, with no art, color or
+    // pauses.
+    pub accessible: bool,
+    // The dealer's chances from the upcard, before every decision.
+    pub dealer_odds: bool,
+    // The book play and what each legal action is worth.
+    pub advisor: bool,
+    // The play to make, shown before every decision rather than on asking.
+    pub hints: bool,
+    // Where hints come from when the player has a strategy of their own.
+    pub own_strategy: Option<CustomStrategy>,
+    // Amounts are counted in its smallest unit.
+    pub currency: Currency,
+    pub sounds: Box<dyn SoundPlayer>,
+    // Cards coming to the dealer now are drawn rather than dealt.
+    pub dealers_turn: bool,
+}
+
+impl Presentation {
+    pub fn text<'a>(&self, english: &'a str) -> &'a str {
+        self.language.translate(english)
+    }
+
+    pub fn money(&self, amount: i64) -> String {
+        self.currency.format(amount)
+    }
+
+    pub fn money_signed(&self, amount: i64) -> String {
+        self.currency.format_signed(amount)
+    }
+
+    pub fn card_text(&self, card: &Card) -> String {
+        if self.accessible {
+            self.language.card_name(card)
+        } else {
+            encode_card(card)
+        }
+    }
+
+    pub fn cards_text(&self, cards: &[Card]) -> String {
+        let separator = if self.accessible { ", " } else { " " };
+        cards.iter().map(|card| self.card_text(card)).collect::<Vec<String>>().join(separator)
+    }
+
+    // Whether the deal and the dealer's turn are told card by card.
+    fn narrated(&self) -> bool {
+        self.accessible || self.speed != Speed::Instant
+    }
+
+    pub fn pause(&self) {
+        let delay = self.speed.card_delay();
+        if !delay.is_zero() {
+            let _ = io::stdout().flush();
+            thread::sleep(delay);
+        }
+    }
+
+    // The opening deal as it came out of the shoe, a card at a time.
+    pub fn deal(&mut self, game_state: &GameState) {
+        self.dealers_turn = false;
+        for event in deal_events(game_state) {
+            self.emit(event);
+        }
+    }
+}
+
+// The table shows what the engine reports, a line per card when the deal is
+// narrated, with a pause and a sound before each. The outcome is only
+// sounded; the result table says the rest.
+impl EventSink for Presentation {
+    fn emit(&mut self, event: UiEvent) {
+        match event {
+            UiEvent::CardDealt { to, card, face_up } => {
+                if !self.narrated() {
+                    return;
+                }
+                self.pause();
+                self.sounds.play(&Sound::CardDealt);
+                let line = match (to, face_up) {
+                    (Seat::Player, _) => fill(self.text("You are dealt {}"), &[&self.card_text(&card)]),
+                    (Seat::Dealer, false) => self.text("The dealer takes a card face down").to_string(),
+                    (Seat::Dealer, true) if self.dealers_turn => fill(self.text("The dealer draws {}"), &[&self.card_text(&card)]),
+                    (Seat::Dealer, true) => fill(self.text("The dealer takes {}"), &[&self.card_text(&card)]),
+                };
+                println!("{}", line);
+            },
+            UiEvent::HoleCardTurned { card } => {
+                if !self.narrated() {
+                    return;
+                }
+                self.pause();
+                self.sounds.play(&Sound::HoleCardTurned);
+                println!("{}", fill(self.text("The dealer turns over {}"), &[&self.card_text(&card)]));
+            },
+            UiEvent::TurnStarted { seat, .. } => {
+                self.dealers_turn = seat == Seat::Dealer;
+                if self.narrated() {
+                    self.pause();
+                }
+            },
+            UiEvent::OutcomeDecided { outcome, .. } => self.sounds.play(&outcome.sound()),
+            UiEvent::BankrollChanged { .. } => (),
+        }
+    }
+}
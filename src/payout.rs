@@ -0,0 +1,85 @@
+// Payouts worked out exactly, as a ratio of the stake, and rounded once at
+// the end by the table's rule. A 3:2 blackjack on a bet of 5 is worth 7.5
+// whatever the table does with the half; the rule decides whether it pays
+// 7, 8 or, at a table that only pays in chips of 5, 5.
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Odds {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+impl Odds {
+    pub const EVEN_MONEY: Odds = Odds { numerator: 1, denominator: 1 };
+    // Insurance pays 2:1 on its own stake.
+    pub const INSURANCE: Odds = Odds { numerator: 2, denominator: 1 };
+    // What a surrender hands back, and what insurance costs.
+    pub const HALF: Odds = Odds { numerator: 1, denominator: 2 };
+
+    // The payout on `stake` as the table pays it.
+    pub fn pay(&self, stake: u64, rounding: &PayoutRounding) -> u64 {
+        rounding.round(stake as u128 * self.numerator as u128, self.denominator as u128)
+    }
+}
+
+// Which way a payout that doesn't come out whole goes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Rounding {
+    // The house keeps the difference, as most tables do.
+    Down,
+    // Halves go to the player.
+    Nearest,
+    Up,
+}
+
+impl Rounding {
+    pub const ALL_VALUES: [Rounding; 3] = [Rounding::Down, Rounding::Nearest, Rounding::Up];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rounding::Down => "down",
+            Rounding::Nearest => "nearest",
+            Rounding::Up => "up",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Rounding> {
+        Rounding::ALL_VALUES.iter().find(|rounding| rounding.name() == name).cloned()
+    }
+}
+
+// A payout that doesn't come out whole is rounded to a multiple of `step`,
+// the smallest chip the table pays with; one that does is paid as it is.
+// The default rounds down to the unit bets are counted in, which is what
+// settlement always did.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutRounding {
+    pub rounding: Rounding,
+    pub step: u64,
+}
+
+impl Default for PayoutRounding {
+    fn default() -> PayoutRounding {
+        PayoutRounding { rounding: Rounding::Down, step: 1 }
+    }
+}
+
+impl PayoutRounding {
+    // `numerator / denominator` as the table pays it. A step of 0 is taken
+    // as 1.
+    pub fn round(&self, numerator: u128, denominator: u128) -> u64 {
+        if numerator.is_multiple_of(denominator) {
+            return (numerator / denominator) as u64;
+        }
+        let step = denominator * self.step.max(1) as u128;
+        let (steps, left) = (numerator / step, numerator % step);
+        let steps = match self.rounding {
+            Rounding::Down => steps,
+            Rounding::Nearest if left * 2 >= step => steps + 1,
+            Rounding::Nearest => steps,
+            Rounding::Up if left > 0 => steps + 1,
+            Rounding::Up => steps,
+        };
+        (steps * self.step.max(1) as u128) as u64
+    }
+}
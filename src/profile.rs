@@ -0,0 +1,385 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rust_blackjack::achievements::Achievement;
+use rust_blackjack::config::DEFAULT_BANKROLL;
+use rust_blackjack::summary::SessionSummary;
+
+const PROFILES_FILE: &str = "profiles.txt";
+const LEDGER_FILE: &str = "ledger.txt";
+const ACHIEVEMENTS_FILE: &str = "achievements.txt";
+
+// Everything is kept as tab separated text under the data directory so a
+// household sharing a machine shares the same profiles and ledger.
+pub fn data_dir() -> PathBuf {
+    if let Option::Some(dir) = env::var_os("BLACKJACK_DATA_DIR") {
+        return PathBuf::from(dir);
+    }
+    if let Option::Some(dir) = env::var_os("XDG_DATA_HOME") {
+        return PathBuf::from(dir).join("rust-blackjack");
+    }
+    let home = env::var_os("HOME").map(PathBuf::from).unwrap_or_else(|| PathBuf::from("."));
+    home.join(".local").join("share").join("rust-blackjack")
+}
+
+#[derive(Debug)]
+pub enum ProfileError {
+    Io(io::Error),
+    UnknownProfile(String),
+    ProfileExists(String),
+    InvalidName(String),
+    InvalidAmount(String),
+    // A line of the profiles file that doesn't read as a profile.
+    Unreadable(String),
+    SameProfile,
+    InsufficientFunds { name: String, bankroll: u64, requested: u64 },
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProfileError::Io(error) => write!(f, "could not access profile data: {}", error),
+            ProfileError::UnknownProfile(name) => write!(f, "no profile named {:?}", name),
+            ProfileError::ProfileExists(name) => write!(f, "a profile named {:?} already exists", name),
+            ProfileError::InvalidName(name) => write!(f, "{:?} is not a valid profile name", name),
+            ProfileError::InvalidAmount(raw) => write!(f, "{:?} is not a valid number of chips", raw),
+            ProfileError::Unreadable(line) => write!(f, "could not read the profile {:?}", line),
+            ProfileError::SameProfile => write!(f, "cannot transfer chips from a profile to itself"),
+            ProfileError::InsufficientFunds { name, bankroll, requested } =>
+                write!(f, "{} only has {} chips, cannot transfer {}", name, bankroll, requested),
+        }
+    }
+}
+
+impl From<io::Error> for ProfileError {
+    fn from(error: io::Error) -> ProfileError {
+        ProfileError::Io(error)
+    }
+}
+
+#[derive(Debug)]
+pub struct Transfer {
+    pub timestamp: u64,
+    pub from: String,
+    pub to: String,
+    pub amount: u64,
+}
+
+// Totals across every session a profile has played.
+#[derive(Clone, Debug, Default)]
+pub struct LifetimeStats {
+    pub sessions: u64,
+    pub hands: u64,
+    pub net: i64,
+    // Decisions scored against basic strategy, in play and training.
+    pub decisions: u64,
+    pub mistakes: u64,
+}
+
+impl LifetimeStats {
+    // The share of decisions that matched basic strategy.
+    pub fn accuracy(&self) -> Option<f64> {
+        match self.decisions {
+            0 => Option::None,
+            decisions => Option::Some(1.0 - self.mistakes as f64 / decisions as f64),
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let accuracy = match self.accuracy() {
+            Option::Some(accuracy) => format!("{:.1}%", accuracy * 100.0),
+            Option::None => "no decisions yet".to_string(),
+        };
+        [
+            format!("Sessions:        {}", self.sessions),
+            format!("Hands played:    {}", self.hands),
+            format!("Net result:      {:+}", self.net),
+            format!("Decisions:       {} ({} mistakes)", self.decisions, self.mistakes),
+            format!("Accuracy:        {}", accuracy),
+        ]
+        .join("\n")
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Profile {
+    pub bankroll: u64,
+    pub lifetime: LifetimeStats,
+}
+
+// A line is the name, the bankroll and then the lifetime totals; files from
+// before the totals were kept have just the first two fields.
+fn parse_profile(line: &str) -> Option<(String, Profile)> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    let number = |index: usize| fields.get(index).map_or(Option::Some(0), |field| field.parse().ok());
+    let profile = Profile {
+        bankroll: fields.get(1)?.parse().ok()?,
+        lifetime: LifetimeStats {
+            sessions: number(2)?,
+            hands: number(3)?,
+            net: fields.get(4).map_or(Option::Some(0), |field| field.parse().ok())?,
+            decisions: number(5)?,
+            mistakes: number(6)?,
+        },
+    };
+    Option::Some((fields[0].to_string(), profile))
+}
+
+#[derive(Debug)]
+pub struct Profiles {
+    dir: PathBuf,
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl Profiles {
+    // A line that can't be read fails the load, rather than dropping a
+    // profile the next save would then write out without.
+    pub fn load() -> Result<Profiles, ProfileError> {
+        let dir = data_dir();
+        let profiles = match fs::read_to_string(dir.join(PROFILES_FILE)) {
+            Ok(contents) => contents
+                .lines()
+                .map(|line| parse_profile(line).ok_or_else(|| ProfileError::Unreadable(line.to_string())))
+                .collect::<Result<_, _>>()?,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(error) => return Err(ProfileError::Io(error)),
+        };
+        Ok(Profiles { dir, profiles })
+    }
+
+    // Written beside the profiles and renamed over them, so a write cut
+    // short leaves the old profiles rather than half of the new ones.
+    fn save(&self) -> Result<(), ProfileError> {
+        fs::create_dir_all(&self.dir)?;
+        let contents: String = self.profiles
+            .iter()
+            .map(|(name, profile)| {
+                let lifetime = &profile.lifetime;
+                format!(
+                    "{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                    name,
+                    profile.bankroll,
+                    lifetime.sessions,
+                    lifetime.hands,
+                    lifetime.net,
+                    lifetime.decisions,
+                    lifetime.mistakes
+                )
+            })
+            .collect();
+        let staged = self.dir.join(PROFILES_FILE).with_extension("tmp");
+        fs::write(&staged, contents)?;
+        fs::rename(&staged, self.dir.join(PROFILES_FILE))?;
+        Ok(())
+    }
+
+    pub fn profiles(&self) -> &BTreeMap<String, Profile> {
+        &self.profiles
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Profile, ProfileError> {
+        self.profiles.get(name).ok_or_else(|| ProfileError::UnknownProfile(name.to_string()))
+    }
+
+    // Folds a finished session into the profile. Training sessions add to
+    // the decisions but not the hands or chips. The session's net result is
+    // added to the bankroll as loaded, so chips transferred in or out while
+    // it was played stay moved.
+    pub fn record_session(&mut self, name: &str, kind: &str, session: &SessionSummary) -> Result<(), ProfileError> {
+        let profile = self.profiles.get_mut(name).ok_or_else(|| ProfileError::UnknownProfile(name.to_string()))?;
+        let lifetime = &mut profile.lifetime;
+        lifetime.sessions += 1;
+        lifetime.decisions += session.decisions;
+        lifetime.mistakes += session.mistakes;
+        if kind == "play" {
+            lifetime.hands += session.hands;
+            lifetime.net += session.net;
+            profile.bankroll = (profile.bankroll as i64 + session.net).max(0) as u64;
+        }
+        self.save()
+    }
+
+    pub fn create(&mut self, name: &str, bankroll: u64) -> Result<(), ProfileError> {
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(ProfileError::InvalidName(name.to_string()));
+        }
+        if self.profiles.contains_key(name) {
+            return Err(ProfileError::ProfileExists(name.to_string()));
+        }
+        self.profiles.insert(name.to_string(), Profile { bankroll, lifetime: LifetimeStats::default() });
+        self.save()
+    }
+
+    pub fn transfer(&mut self, from: &str, to: &str, amount: u64) -> Result<Transfer, ProfileError> {
+        if from == to {
+            return Err(ProfileError::SameProfile);
+        }
+        let from_bankroll = self.get(from)?.bankroll;
+        self.get(to)?;
+        if from_bankroll < amount {
+            return Err(ProfileError::InsufficientFunds {
+                name: from.to_string(),
+                bankroll: from_bankroll,
+                requested: amount,
+            });
+        }
+
+        let transfer = Transfer {
+            timestamp: now(),
+            from: from.to_string(),
+            to: to.to_string(),
+            amount,
+        };
+        // The ledger entry is written first so a crash never leaves balances
+        // that moved without a record of why.
+        append_to_ledger(&self.dir, &transfer)?;
+        if let Option::Some(profile) = self.profiles.get_mut(from) {
+            profile.bankroll -= amount;
+        }
+        if let Option::Some(profile) = self.profiles.get_mut(to) {
+            profile.bankroll += amount;
+        }
+        self.save()?;
+        Ok(transfer)
+    }
+}
+
+fn append_to_ledger(dir: &Path, transfer: &Transfer) -> Result<(), ProfileError> {
+    fs::create_dir_all(dir)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(dir.join(LEDGER_FILE))?;
+    writeln!(file, "{}\t{}\t{}\t{}", transfer.timestamp, transfer.from, transfer.to, transfer.amount)?;
+    Ok(())
+}
+
+pub fn read_ledger() -> Result<Vec<Transfer>, ProfileError> {
+    let contents = match fs::read_to_string(data_dir().join(LEDGER_FILE)) {
+        Ok(contents) => contents,
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(ProfileError::Io(error)),
+    };
+    Ok(
+        contents
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split('\t').collect();
+                if fields.len() != 4 {
+                    return Option::None;
+                }
+                Option::Some(Transfer {
+                    timestamp: fields[0].parse().ok()?,
+                    from: fields[1].to_string(),
+                    to: fields[2].to_string(),
+                    amount: fields[3].parse().ok()?,
+                })
+            })
+            .collect()
+    )
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[derive(Debug)]
+pub struct Unlock {
+    pub timestamp: u64,
+    pub achievement: Achievement,
+}
+
+// Achievements unlocked so far by a profile, or outside any profile for
+// `None`, oldest first. Lines naming an achievement this build doesn't know
+// are skipped.
+pub fn read_achievements(profile: Option<&str>) -> Result<Vec<Unlock>, ProfileError> {
+    let contents = match fs::read_to_string(data_dir().join(ACHIEVEMENTS_FILE)) {
+        Ok(contents) => contents,
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(ProfileError::Io(error)),
+    };
+    Ok(
+        contents
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let unlock = Unlock {
+                    timestamp: fields.next()?.parse().ok()?,
+                    achievement: Achievement::from_name(fields.next()?)?,
+                };
+                let owner = fields.next().unwrap_or("");
+                if owner == profile.unwrap_or("") { Option::Some(unlock) } else { Option::None }
+            })
+            .collect()
+    )
+}
+
+pub fn unlock_achievement(profile: Option<&str>, achievement: &Achievement) -> Result<Unlock, ProfileError> {
+    let dir = data_dir();
+    fs::create_dir_all(&dir)?;
+    let unlock = Unlock { timestamp: now(), achievement: achievement.clone() };
+    let mut file = OpenOptions::new().create(true).append(true).open(dir.join(ACHIEVEMENTS_FILE))?;
+    writeln!(file, "{}\t{}\t{}", unlock.timestamp, unlock.achievement.name(), profile.unwrap_or(""))?;
+    Ok(unlock)
+}
+
+pub fn run_command(args: &[String]) -> Result<(), ProfileError> {
+    let mut profiles = Profiles::load()?;
+    match args.first().map(String::as_str) {
+        Option::None | Option::Some("list") => {
+            for (name, profile) in profiles.profiles() {
+                let lifetime = &profile.lifetime;
+                let accuracy = lifetime.accuracy().map(|accuracy| format!(", {:.1}% accurate", accuracy * 100.0));
+                println!(
+                    "{}: {} chips, {} hands, net {:+}{}",
+                    name,
+                    profile.bankroll,
+                    lifetime.hands,
+                    lifetime.net,
+                    accuracy.unwrap_or_default()
+                );
+            }
+        },
+        Option::Some("show") => {
+            let name = args.get(1).map(String::as_str).unwrap_or("");
+            let profile = profiles.get(name)?;
+            println!("{}: {} chips\n{}", name, profile.bankroll, profile.lifetime.render());
+        },
+        Option::Some("create") => {
+            let name = args.get(1).map(String::as_str).unwrap_or("");
+            let bankroll = match args.get(2) {
+                Option::Some(raw) => raw.parse().map_err(|_| ProfileError::InvalidAmount(raw.clone()))?,
+                Option::None => DEFAULT_BANKROLL,
+            };
+            profiles.create(name, bankroll)?;
+            println!("Created profile {} with {} chips", name, bankroll);
+        },
+        Option::Some("transfer") => match (args.get(1), args.get(2), args.get(3).and_then(|raw| raw.parse().ok())) {
+            (Option::Some(from), Option::Some(to), Option::Some(amount)) => {
+                let transfer = profiles.transfer(from, to, amount)?;
+                println!("Transferred {} chips from {} to {}", transfer.amount, transfer.from, transfer.to);
+            },
+            _ => println!("Usage: rust-blackjack profiles transfer <from> <to> <amount>"),
+        },
+        Option::Some("ledger") => {
+            for transfer in read_ledger()? {
+                println!("{}: {} -> {}: {} chips", transfer.timestamp, transfer.from, transfer.to, transfer.amount);
+            }
+        },
+        Option::Some("achievements") => {
+            let name = args.get(1).map(String::as_str);
+            if let Option::Some(name) = name {
+                profiles.get(name)?;
+            }
+            let unlocked = read_achievements(name)?;
+            for achievement in Achievement::ALL_VALUES.iter() {
+                let mark = if unlocked.iter().any(|unlock| &unlock.achievement == achievement) { "x" } else { " " };
+                println!("[{}] {}: {}", mark, achievement.title(), achievement.description());
+            }
+        },
+        Option::Some(other) => println!("Unknown profiles command: {}", other),
+    }
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime};
+
+use crate::deviation::upcard_value;
+use crate::settlement::RoundResult;
+
+// A casino promotion layered on top of the normal payouts. Modifiers are
+// applied one after another, so they can be stacked freely.
+pub trait RoundModifier: Debug + Send + Sync {
+    fn name(&self) -> String;
+
+    // `net` is the result so far; return the adjusted result.
+    fn apply(&self, round: &RoundResult, net: i64) -> i64;
+}
+
+// Winning naturals pay `numerator:denominator` instead of 3:2.
+#[derive(Debug)]
+pub struct BlackjackPays {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+impl RoundModifier for BlackjackPays {
+    fn name(&self) -> String {
+        format!("blackjack-pays-{}-{}", self.numerator, self.denominator)
+    }
+
+    fn apply(&self, round: &RoundResult, net: i64) -> i64 {
+        if round.won && round.natural {
+            (round.bet * self.numerator / self.denominator) as i64
+        } else {
+            net
+        }
+    }
+}
+
+// Pays `bonus` times the bet on top of the result when the first cards are
+// exactly `ranks`, all of one suit, in any order.
+#[derive(Debug)]
+pub struct SuitedBonus {
+    pub ranks: Vec<u32>,
+    pub bonus: u64,
+}
+
+impl RoundModifier for SuitedBonus {
+    fn name(&self) -> String {
+        let ranks: Vec<String> = self.ranks.iter().map(u32::to_string).collect();
+        format!("suited-{}", ranks.concat())
+    }
+
+    fn apply(&self, round: &RoundResult, net: i64) -> i64 {
+        if round.hand.len() < self.ranks.len() {
+            return net;
+        }
+        let first_cards = &round.hand[..self.ranks.len()];
+        let mut ranks: Vec<u32> = first_cards.iter().map(|card| upcard_value(&card.value)).collect();
+        ranks.sort_unstable();
+        let mut wanted = self.ranks.clone();
+        wanted.sort_unstable();
+        let suited = first_cards.iter().all(|card| card.suit == first_cards[0].suit);
+        if suited && ranks == wanted {
+            net + (round.bet * self.bonus) as i64
+        } else {
+            net
+        }
+    }
+}
+
+// Wraps another modifier so it only applies until `expires_at`, by the
+// system clock.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct Timed {
+    pub inner: Box<dyn RoundModifier>,
+    pub expires_at: SystemTime,
+}
+
+#[cfg(feature = "std")]
+impl Timed {
+    pub fn for_duration(inner: Box<dyn RoundModifier>, duration: Duration) -> Timed {
+        Timed { inner, expires_at: SystemTime::now() + duration }
+    }
+}
+
+#[cfg(feature = "std")]
+impl RoundModifier for Timed {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn apply(&self, round: &RoundResult, net: i64) -> i64 {
+        if SystemTime::now() < self.expires_at {
+            self.inner.apply(round, net)
+        } else {
+            net
+        }
+    }
+}
+
+pub const PROMOTION_NAMES: [&str; 3] = ["blackjack-pays-2-1", "blackjack-pays-6-5", "suited-678"];
+
+pub fn promotion_from_name(name: &str) -> Option<Box<dyn RoundModifier>> {
+    match name {
+        "blackjack-pays-2-1" => Option::Some(Box::new(BlackjackPays { numerator: 2, denominator: 1 })),
+        "blackjack-pays-6-5" => Option::Some(Box::new(BlackjackPays { numerator: 6, denominator: 5 })),
+        "suited-678" => Option::Some(Box::new(SuitedBonus { ranks: vec![6, 7, 8], bonus: 5 })),
+        _ => Option::None,
+    }
+}
@@ -0,0 +1,438 @@
+use alloc::format;
+use alloc::string::String;
+
+#[cfg(feature = "std")]
+use crate::fairness::Reveal;
+#[cfg(feature = "std")]
+use crate::json::JsonValue;
+#[cfg(feature = "std")]
+use crate::parse_action;
+use crate::{Action, Card, CardSuit, CardValue};
+
+// The remote table protocol is line oriented over raw TCP. The server sends
+// one of the messages below per line and the client answers every PROMPT with
+// a single line holding the action name, exactly as it would be typed locally,
+// and every LOBBY with a lobby command: `tables`, `create <name> [<rules>
+// [<decks>]]`, `join <name>`, `watch <name>`, `resume <token>`, `name <who>`
+// or `leaderboard`. A seated player can also send `say <text>` at any time,
+// which the server relays to the table as CHAT.
+//
+//     HAND 2C TH AS
+//     MESSAGE <free text>
+//     PROMPT
+//     OUTCOME <free text>
+//     LOBBY
+//     TABLE <name>
+//     CHAT <from> <text>
+//     SESSION <token>
+//     COMMIT <hash>
+//     REVEAL <nonce> 2C TH AS ...
+//     LEADERBOARD <name> <hands> <net> ...
+//
+// Over WebSocket the same messages travel as one JSON object per text frame;
+// the schema is documented in PROTOCOL.md.
+
+// A player's lifetime results on a server, each hand counted as if it
+// had been played for the server's bet.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub hands: u64,
+    pub net: i64,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Eq, PartialEq)]
+pub enum ServerMessage {
+    Hand(Vec<Card>),
+    Message(String),
+    Prompt,
+    Outcome(String),
+    // Waiting for a lobby command.
+    Lobby,
+    // Seated at the named table.
+    Table(String),
+    // Something said at the table; `from` is the player's name, which has
+    // no spaces in it.
+    Chat { from: String, text: String },
+    // The token that takes the seat back after a lost connection.
+    Session(String),
+    // The hash of the shoe about to be dealt, checked against its reveal
+    // once the shoe is done; see the fairness module.
+    Commit(String),
+    Reveal(Reveal),
+    // Every player the server has results for, best first.
+    Leaderboard(Vec<Standing>),
+}
+
+#[cfg(feature = "std")]
+fn split_command(line: &str) -> (&str, &str) {
+    let trimmed = line.trim();
+    match trimmed.find(' ') {
+        Option::Some(index) => (&trimmed[..index], trimmed[index + 1..].trim()),
+        Option::None => (trimmed, ""),
+    }
+}
+
+#[cfg(feature = "std")]
+impl ServerMessage {
+    pub fn parse(line: &str) -> Option<ServerMessage> {
+        let (command, rest) = split_command(line);
+        match command {
+            "HAND" => rest
+                .split_whitespace()
+                .map(decode_card)
+                .collect::<Option<Vec<Card>>>()
+                .map(ServerMessage::Hand),
+            "MESSAGE" => Option::Some(ServerMessage::Message(rest.to_string())),
+            "PROMPT" => Option::Some(ServerMessage::Prompt),
+            "OUTCOME" => Option::Some(ServerMessage::Outcome(rest.to_string())),
+            "LOBBY" => Option::Some(ServerMessage::Lobby),
+            "TABLE" if !rest.is_empty() => Option::Some(ServerMessage::Table(rest.to_string())),
+            "COMMIT" if !rest.is_empty() => Option::Some(ServerMessage::Commit(rest.to_string())),
+            "REVEAL" => {
+                let (nonce, order) = split_command(rest);
+                if nonce.is_empty() {
+                    Option::None
+                } else {
+                    Option::Some(ServerMessage::Reveal(Reveal { nonce: nonce.to_string(), order: order.to_string() }))
+                }
+            },
+            "SESSION" if !rest.is_empty() => Option::Some(ServerMessage::Session(rest.to_string())),
+            "LEADERBOARD" => {
+                let fields: Vec<&str> = rest.split_whitespace().collect();
+                if !fields.len().is_multiple_of(3) {
+                    return Option::None;
+                }
+                fields
+                    .chunks(3)
+                    .map(|fields| {
+                        Option::Some(Standing { name: fields[0].to_string(), hands: fields[1].parse().ok()?, net: fields[2].parse().ok()? })
+                    })
+                    .collect::<Option<Vec<Standing>>>()
+                    .map(ServerMessage::Leaderboard)
+            },
+            "CHAT" => {
+                let (from, text) = split_command(rest);
+                if from.is_empty() {
+                    Option::None
+                } else {
+                    Option::Some(ServerMessage::Chat { from: from.to_string(), text: text.to_string() })
+                }
+            },
+            _ => Option::None,
+        }
+    }
+
+    pub fn to_line(&self) -> String {
+        match self {
+            ServerMessage::Hand(hand) => {
+                let cards: Vec<String> = hand.iter().map(encode_card).collect();
+                format!("HAND {}", cards.join(" "))
+            },
+            ServerMessage::Message(text) => format!("MESSAGE {}", text),
+            ServerMessage::Prompt => "PROMPT".to_string(),
+            ServerMessage::Outcome(text) => format!("OUTCOME {}", text),
+            ServerMessage::Lobby => "LOBBY".to_string(),
+            ServerMessage::Table(name) => format!("TABLE {}", name),
+            ServerMessage::Chat { from, text } => format!("CHAT {} {}", from, text),
+            ServerMessage::Session(token) => format!("SESSION {}", token),
+            ServerMessage::Commit(hash) => format!("COMMIT {}", hash),
+            ServerMessage::Reveal(reveal) => format!("REVEAL {} {}", reveal.nonce, reveal.order),
+            ServerMessage::Leaderboard(standings) => {
+                let fields: Vec<String> =
+                    standings.iter().map(|standing| format!(" {} {} {}", standing.name, standing.hands, standing.net)).collect();
+                format!("LEADERBOARD{}", fields.concat())
+            },
+        }
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let kind = |name: &str| ("type", JsonValue::String(name.to_string()));
+        let string = |text: &str| JsonValue::String(text.to_string());
+        match self {
+            ServerMessage::Hand(hand) => JsonValue::object(vec![
+                kind("hand"),
+                ("cards", JsonValue::Array(hand.iter().map(|card| JsonValue::String(encode_card(card))).collect())),
+            ]),
+            ServerMessage::Message(text) => JsonValue::object(vec![kind("message"), ("text", string(text))]),
+            ServerMessage::Prompt => JsonValue::object(vec![kind("prompt")]),
+            ServerMessage::Outcome(text) => JsonValue::object(vec![kind("outcome"), ("text", string(text))]),
+            ServerMessage::Lobby => JsonValue::object(vec![kind("lobby")]),
+            ServerMessage::Table(name) => JsonValue::object(vec![kind("table"), ("name", string(name))]),
+            ServerMessage::Chat { from, text } => JsonValue::object(vec![kind("chat"), ("from", string(from)), ("text", string(text))]),
+            ServerMessage::Session(token) => JsonValue::object(vec![kind("session"), ("token", string(token))]),
+            ServerMessage::Commit(hash) => JsonValue::object(vec![kind("commit"), ("hash", string(hash))]),
+            ServerMessage::Reveal(reveal) => {
+                JsonValue::object(vec![kind("reveal"), ("nonce", string(&reveal.nonce)), ("order", string(&reveal.order))])
+            },
+            ServerMessage::Leaderboard(standings) => {
+                let players = standings
+                    .iter()
+                    .map(|standing| {
+                        JsonValue::object(vec![
+                            ("name", string(&standing.name)),
+                            ("hands", JsonValue::Number(standing.hands as f64)),
+                            ("net", JsonValue::Number(standing.net as f64)),
+                        ])
+                    })
+                    .collect();
+                JsonValue::object(vec![kind("leaderboard"), ("players", JsonValue::Array(players))])
+            },
+        }
+    }
+
+    pub fn from_json(value: &JsonValue) -> Option<ServerMessage> {
+        let text = || value.get("text").and_then(JsonValue::as_str).map(str::to_string);
+        match value.get("type")?.as_str()? {
+            "hand" => value
+                .get("cards")?
+                .as_array()?
+                .iter()
+                .map(|card| card.as_str().and_then(decode_card))
+                .collect::<Option<Vec<Card>>>()
+                .map(ServerMessage::Hand),
+            "message" => text().map(ServerMessage::Message),
+            "prompt" => Option::Some(ServerMessage::Prompt),
+            "outcome" => text().map(ServerMessage::Outcome),
+            "lobby" => Option::Some(ServerMessage::Lobby),
+            "table" => value.get("name").and_then(JsonValue::as_str).map(|name| ServerMessage::Table(name.to_string())),
+            "commit" => value.get("hash").and_then(JsonValue::as_str).map(|hash| ServerMessage::Commit(hash.to_string())),
+            "reveal" => Option::Some(ServerMessage::Reveal(Reveal {
+                nonce: value.get("nonce")?.as_str()?.to_string(),
+                order: value.get("order")?.as_str()?.to_string(),
+            })),
+            "session" => value.get("token").and_then(JsonValue::as_str).map(|token| ServerMessage::Session(token.to_string())),
+            "chat" => Option::Some(ServerMessage::Chat { from: value.get("from")?.as_str()?.to_string(), text: text()? }),
+            "leaderboard" => value
+                .get("players")?
+                .as_array()?
+                .iter()
+                .map(|player| {
+                    Option::Some(Standing {
+                        name: player.get("name")?.as_str()?.to_string(),
+                        hands: player.get("hands")?.as_number()? as u64,
+                        net: player.get("net")?.as_number()? as i64,
+                    })
+                })
+                .collect::<Option<Vec<Standing>>>()
+                .map(ServerMessage::Leaderboard),
+            _ => Option::None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Eq, PartialEq)]
+pub enum ClientMessage {
+    Action(Action),
+    ListTables,
+    // A new table, on a rules preset and deck count when given and the
+    // server's own rules otherwise.
+    CreateTable { name: String, rules: Option<String>, decks: Option<u32> },
+    JoinTable(String),
+    Leave,
+    // A line for everyone at the table.
+    Chat(String),
+    // Back to the seat the token was issued for.
+    Resume(String),
+    // Follow a table's play without a seat at it.
+    Watch(String),
+    // The name to play under, in place of the connection's address; the
+    // leaderboard keeps results by it.
+    Name(String),
+    Leaderboard,
+}
+
+#[cfg(feature = "std")]
+impl ClientMessage {
+    pub fn to_line(&self) -> String {
+        match self {
+            ClientMessage::Action(action) => encode_action(action).to_string(),
+            ClientMessage::ListTables => "tables".to_string(),
+            ClientMessage::CreateTable { name, rules, decks } => {
+                let mut line = format!("create {}", name);
+                if let Option::Some(rules) = rules {
+                    line.push_str(&format!(" {}", rules));
+                    if let Option::Some(decks) = decks {
+                        line.push_str(&format!(" {}", decks));
+                    }
+                }
+                line
+            },
+            ClientMessage::JoinTable(name) => format!("join {}", name),
+            ClientMessage::Leave => "leave".to_string(),
+            ClientMessage::Chat(text) => format!("say {}", text),
+            ClientMessage::Resume(token) => format!("resume {}", token),
+            ClientMessage::Watch(name) => format!("watch {}", name),
+            ClientMessage::Name(name) => format!("name {}", name),
+            ClientMessage::Leaderboard => "leaderboard".to_string(),
+        }
+    }
+
+    // Actions are tried first, so "s" is always a stand.
+    pub fn parse(line: &str) -> Option<ClientMessage> {
+        if let Option::Some(action) = parse_action(line) {
+            return Option::Some(ClientMessage::Action(action));
+        }
+        if let ("say", text) = split_command(line) {
+            return Option::Some(ClientMessage::Chat(text.to_string())).filter(|_| !text.is_empty());
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["tables"] => Option::Some(ClientMessage::ListTables),
+            ["create", name] => Option::Some(ClientMessage::CreateTable { name: name.to_string(), rules: Option::None, decks: Option::None }),
+            ["create", name, rules] => {
+                Option::Some(ClientMessage::CreateTable { name: name.to_string(), rules: Option::Some(rules.to_string()), decks: Option::None })
+            },
+            ["create", name, rules, decks] => Option::Some(ClientMessage::CreateTable {
+                name: name.to_string(),
+                rules: Option::Some(rules.to_string()),
+                decks: Option::Some(decks.parse().ok()?),
+            }),
+            ["join", name] => Option::Some(ClientMessage::JoinTable(name.to_string())),
+            ["resume", token] => Option::Some(ClientMessage::Resume(token.to_string())),
+            ["watch", name] => Option::Some(ClientMessage::Watch(name.to_string())),
+            ["name", name] => Option::Some(ClientMessage::Name(name.to_string())),
+            ["leaderboard"] => Option::Some(ClientMessage::Leaderboard),
+            ["leave"] => Option::Some(ClientMessage::Leave),
+            _ => Option::None,
+        }
+    }
+
+    pub fn from_json(value: &JsonValue) -> Option<ClientMessage> {
+        let field = |key: &str| value.get(key).and_then(JsonValue::as_str).map(str::to_string);
+        match value.get("type")?.as_str()? {
+            "action" => parse_action(value.get("action")?.as_str()?).map(ClientMessage::Action),
+            "tables" => Option::Some(ClientMessage::ListTables),
+            "create" => Option::Some(ClientMessage::CreateTable {
+                name: field("name")?,
+                rules: field("rules"),
+                decks: match value.get("decks") {
+                    Option::Some(decks) => Option::Some(decks.as_number()? as u32),
+                    Option::None => Option::None,
+                },
+            }),
+            "join" => field("name").map(ClientMessage::JoinTable),
+            "leave" => Option::Some(ClientMessage::Leave),
+            "say" => field("text").filter(|text| !text.is_empty()).map(ClientMessage::Chat),
+            "resume" => field("token").map(ClientMessage::Resume),
+            "watch" => field("name").map(ClientMessage::Watch),
+            "name" => field("name").map(ClientMessage::Name),
+            "leaderboard" => Option::Some(ClientMessage::Leaderboard),
+            _ => Option::None,
+        }
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let kind = |name: &str| ("type", JsonValue::String(name.to_string()));
+        match self {
+            ClientMessage::Action(action) => JsonValue::object(vec![
+                kind("action"),
+                ("action", JsonValue::String(encode_action(action).to_string())),
+            ]),
+            ClientMessage::ListTables => JsonValue::object(vec![kind("tables")]),
+            ClientMessage::CreateTable { name, rules, decks } => {
+                let mut fields = vec![kind("create"), ("name", JsonValue::String(name.clone()))];
+                if let Option::Some(rules) = rules {
+                    fields.push(("rules", JsonValue::String(rules.clone())));
+                }
+                if let Option::Some(decks) = decks {
+                    fields.push(("decks", JsonValue::Number(*decks as f64)));
+                }
+                JsonValue::object(fields)
+            },
+            ClientMessage::JoinTable(name) => JsonValue::object(vec![kind("join"), ("name", JsonValue::String(name.clone()))]),
+            ClientMessage::Leave => JsonValue::object(vec![kind("leave")]),
+            ClientMessage::Chat(text) => JsonValue::object(vec![kind("say"), ("text", JsonValue::String(text.clone()))]),
+            ClientMessage::Resume(token) => JsonValue::object(vec![kind("resume"), ("token", JsonValue::String(token.clone()))]),
+            ClientMessage::Watch(name) => JsonValue::object(vec![kind("watch"), ("name", JsonValue::String(name.clone()))]),
+            ClientMessage::Name(name) => JsonValue::object(vec![kind("name"), ("name", JsonValue::String(name.clone()))]),
+            ClientMessage::Leaderboard => JsonValue::object(vec![kind("leaderboard")]),
+        }
+    }
+}
+
+pub fn encode_action(action: &Action) -> &'static str {
+    match action {
+        Action::Hit => "hit",
+        Action::Stand => "stand",
+        Action::DoubleDown => "double-down",
+        Action::SplitCards => "split",
+        Action::Surrender => "surrender",
+        Action::Insurance => "insurance",
+        Action::EvenMoney => "even-money",
+        Action::DeclineInsurance => "no-insurance",
+    }
+}
+
+pub fn encode_card_value(card_value: &CardValue) -> char {
+    match card_value {
+        CardValue::Two => '2',
+        CardValue::Three => '3',
+        CardValue::Four => '4',
+        CardValue::Five => '5',
+        CardValue::Six => '6',
+        CardValue::Seven => '7',
+        CardValue::Eight => '8',
+        CardValue::Nine => '9',
+        CardValue::Ten => 'T',
+        CardValue::Jack => 'J',
+        CardValue::Queen => 'Q',
+        CardValue::King => 'K',
+        CardValue::Ace => 'A',
+    }
+}
+
+fn encode_card_suit(card_suit: &CardSuit) -> char {
+    match card_suit {
+        CardSuit::Clubs => 'C',
+        CardSuit::Hearts => 'H',
+        CardSuit::Diamonds => 'D',
+        CardSuit::Spades => 'S',
+    }
+}
+
+pub fn encode_card(card: &Card) -> String {
+    format!("{}{}", encode_card_value(&card.value), encode_card_suit(&card.suit))
+}
+
+pub fn decode_card_value(c: char) -> Option<CardValue> {
+    match c {
+        '2' => Option::Some(CardValue::Two),
+        '3' => Option::Some(CardValue::Three),
+        '4' => Option::Some(CardValue::Four),
+        '5' => Option::Some(CardValue::Five),
+        '6' => Option::Some(CardValue::Six),
+        '7' => Option::Some(CardValue::Seven),
+        '8' => Option::Some(CardValue::Eight),
+        '9' => Option::Some(CardValue::Nine),
+        'T' => Option::Some(CardValue::Ten),
+        'J' => Option::Some(CardValue::Jack),
+        'Q' => Option::Some(CardValue::Queen),
+        'K' => Option::Some(CardValue::King),
+        'A' => Option::Some(CardValue::Ace),
+        _ => Option::None,
+    }
+}
+
+fn decode_card_suit(c: char) -> Option<CardSuit> {
+    match c {
+        'C' => Option::Some(CardSuit::Clubs),
+        'H' => Option::Some(CardSuit::Hearts),
+        'D' => Option::Some(CardSuit::Diamonds),
+        'S' => Option::Some(CardSuit::Spades),
+        _ => Option::None,
+    }
+}
+
+pub fn decode_card(raw: &str) -> Option<Card> {
+    let mut chars = raw.chars();
+    let value = chars.next().and_then(decode_card_value)?;
+    let suit = chars.next().and_then(decode_card_suit)?;
+    if chars.next().is_some() {
+        return Option::None;
+    }
+    Option::Some(Card { suit, value })
+}
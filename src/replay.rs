@@ -0,0 +1,178 @@
+use std::fs;
+use std::path::Path;
+
+use rust_blackjack::events::{Outcome, Seat, UiEvent};
+use rust_blackjack::hand_history::{parse_history, replay, HistoryHand, ReplayError, ReplayMove, ReplayStep};
+use rust_blackjack::protocol::{encode_action, encode_card};
+use rust_blackjack::GameState;
+
+use crate::input::{Input, InputLines};
+use crate::{cards_text, exit_with_error, total_text};
+
+// Steps through a hand history, dealing each hand again through the engine
+// from the cards it was written with: the deal, then each action with what
+// it changed. A hand written without its shoe is shown as it was written.
+
+const CONTROLS: &str = "Enter or f: forward, b: back, n: next hand, p: previous hand, a number: that hand, q: quit";
+
+struct Recorded {
+    hand: HistoryHand,
+    steps: Result<Vec<ReplayStep>, ReplayError>,
+}
+
+impl Recorded {
+    // A hand that can't be dealt again is one step, its record.
+    fn step_count(&self) -> usize {
+        self.steps.as_ref().map_or(1, |steps| steps.len())
+    }
+}
+
+fn outcome_text(outcome: &Outcome) -> &'static str {
+    match outcome {
+        Outcome::Won => "won",
+        Outcome::Blackjack => "blackjack",
+        Outcome::Lost => "lost",
+        Outcome::Bust => "bust",
+        Outcome::Surrendered => "surrendered",
+        Outcome::Push => "push",
+    }
+}
+
+// The events of one step, in the words the table uses for them.
+fn describe(events: &[UiEvent]) -> Vec<String> {
+    let mut dealers_turn = false;
+    let mut lines = Vec::new();
+    for event in events {
+        let line = match event {
+            UiEvent::CardDealt { to: Seat::Player, card, .. } => format!("You are dealt {}", encode_card(card)),
+            UiEvent::CardDealt { to: Seat::Dealer, face_up: false, .. } => "The dealer takes a card face down".to_string(),
+            UiEvent::CardDealt { to: Seat::Dealer, card, .. } if dealers_turn => format!("The dealer draws {}", encode_card(card)),
+            UiEvent::CardDealt { to: Seat::Dealer, card, .. } => format!("The dealer takes {}", encode_card(card)),
+            UiEvent::HoleCardTurned { card } => format!("The dealer turns over {}", encode_card(card)),
+            UiEvent::TurnStarted { seat: Seat::Dealer, .. } => {
+                dealers_turn = true;
+                continue;
+            },
+            UiEvent::TurnStarted { seat: Seat::Player, hand } if *hand > 1 => format!("On to hand {}", hand),
+            UiEvent::TurnStarted { .. } | UiEvent::BankrollChanged { .. } => continue,
+            UiEvent::OutcomeDecided { hand, outcome } => format!("Hand {}: {}", hand, outcome_text(outcome)),
+        };
+        lines.push(line);
+    }
+    lines
+}
+
+// The table as the player saw it after the step: the dealer's hole card
+// stays hidden until it was turned over.
+fn print_table(state: &GameState) {
+    let player_state = state.player_state();
+    let hidden = player_state.hidden_dealer_cards().len();
+    let visible = cards_text(&player_state.visible_dealer_cards());
+    let hole = vec!["??"; hidden].join(" ");
+    println!("  Dealer  {}", [visible, hole].iter().filter(|part| !part.is_empty()).cloned().collect::<Vec<String>>().join(" "));
+    let mut hands: Vec<&[_]> = player_state.split_hands.iter().map(|split| &split.hand[..]).collect();
+    hands.push(&player_state.hand[..]);
+    hands.extend(player_state.waiting_hands.iter().map(|hand| &hand[..]));
+    for cards in hands {
+        println!("  You     {} ({})", cards_text(cards), total_text(cards));
+    }
+}
+
+fn show(recorded: &Recorded, position: usize, of: usize, step: usize) {
+    let hand = &recorded.hand;
+    let steps = match &recorded.steps {
+        Ok(steps) => steps,
+        Err(error) => {
+            println!("Hand #{} ({} of {}) can't be replayed: {}", hand.hand, position + 1, of, error);
+            let hands: Vec<String> = hand.player_hands.iter().map(|cards| cards_text(cards)).collect();
+            let mut actions: Vec<&str> = hand.actions.iter().map(encode_action).collect();
+            if hand.left {
+                actions.push("leave");
+            }
+            println!("  Dealer  {}", cards_text(&hand.dealer_cards));
+            println!("  You     {}", hands.join(" | "));
+            println!("  Actions {}", actions.join(" "));
+            println!("{}, net {:+}, bankroll {}", hand.outcome, hand.net, hand.bankroll);
+            return;
+        },
+    };
+    let current = &steps[step];
+    let played = match &current.played {
+        ReplayMove::Deal => format!("the deal, bet {}", hand.bet),
+        ReplayMove::Act(action) => encode_action(action).to_string(),
+        ReplayMove::Leave => "leave".to_string(),
+    };
+    println!("Hand #{} ({} of {}), step {} of {}: {}", hand.hand, position + 1, of, step + 1, steps.len(), played);
+    for line in describe(&current.events) {
+        println!("  {}", line);
+    }
+    print_table(&current.state);
+    if step + 1 == steps.len() {
+        println!("{}, net {:+}, bankroll {}", hand.outcome, hand.net, hand.bankroll);
+    }
+}
+
+pub fn run(path: &Path) {
+    let contents = fs::read_to_string(path).unwrap_or_else(|error| exit_with_error(format!("could not read {}: {}", path.display(), error)));
+    let hands = parse_history(&contents).unwrap_or_else(|error| exit_with_error(format!("{}: {}", path.display(), error)));
+    if hands.is_empty() {
+        println!("{} has no hands in it", path.display());
+        return;
+    }
+    let recorded: Vec<Recorded> = hands.into_iter().map(|hand| Recorded { steps: replay(&hand), hand }).collect();
+
+    println!("{}", CONTROLS);
+    let input = InputLines::new();
+    let (mut position, mut step) = (0, 0);
+    loop {
+        show(&recorded[position], position, recorded.len(), step);
+        let line = match input.next() {
+            Input::Line(line) => line,
+            Input::Closed | Input::Interrupted => break,
+        };
+        let last_step = recorded[position].step_count() - 1;
+        match line.trim() {
+            "" | "f" => {
+                if step < last_step {
+                    step += 1;
+                } else if position + 1 < recorded.len() {
+                    position += 1;
+                    step = 0;
+                } else {
+                    println!("That was the last hand");
+                }
+            },
+            "b" => {
+                if step > 0 {
+                    step -= 1;
+                } else if position > 0 {
+                    position -= 1;
+                    step = recorded[position].step_count() - 1;
+                } else {
+                    println!("That was the first hand");
+                }
+            },
+            "n" if position + 1 < recorded.len() => {
+                position += 1;
+                step = 0;
+            },
+            "n" => println!("That was the last hand"),
+            "p" if position > 0 => {
+                position -= 1;
+                step = 0;
+            },
+            "p" => println!("That was the first hand"),
+            "q" | "quit" => break,
+            other => match other.trim_start_matches('#').parse::<u64>() {
+                Ok(number) => match recorded.iter().position(|recorded| recorded.hand.hand == number) {
+                    Option::Some(found) => {
+                        position = found;
+                        step = 0;
+                    },
+                    Option::None => println!("There is no hand #{} in the history", number),
+                },
+                Err(_) => println!("{}", CONTROLS),
+            },
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "std")]
+use rand::Rng;
+
+use crate::dealer::DealerProfile;
+use crate::error::BlackjackError;
+use crate::payout::{Odds, PayoutRounding};
+use crate::protocol::encode_action;
+#[cfg(feature = "std")]
+use crate::shuffle::{SecureShuffle, Shuffler};
+use crate::shuffle::{ShuffleModel, ShuffleRng};
+use crate::{Action, Hand};
+
+// The game being dealt. Pontoon keeps the shape of a blackjack round but
+// changes who wins: the dealer takes every tie, five cards without busting
+// beat anything but a pontoon, and both of the dealer's cards stay face down.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Variant {
+    Blackjack,
+    Pontoon,
+}
+
+// A five card trick pays 2:1 and stands automatically.
+pub const FIVE_CARD_TRICK: usize = 5;
+pub const FIVE_CARD_TRICK_PAYOUT: u64 = 2;
+// Pontoon players may not stick on less than 15.
+pub const PONTOON_MINIMUM_STICK: u32 = 15;
+// Fewer cards than this and a Charlie would win on the deal.
+pub const MIN_CHARLIE: usize = 3;
+// Resplitting up to four hands is the usual limit.
+pub const DEFAULT_MAX_SPLIT_HANDS: usize = 4;
+pub const MAX_SPLIT_HANDS: usize = 8;
+pub const MAX_DECKS: u32 = 8;
+
+impl Variant {
+    // Pontoon twists, sticks and buys where blackjack hits, stands and
+    // doubles; buying raises the stake and carries on with the hand.
+    pub fn action_name(&self, action: &Action) -> &'static str {
+        match (self, action) {
+            (Variant::Pontoon, Action::Hit) => "twist",
+            (Variant::Pontoon, Action::Stand) => "stick",
+            (Variant::Pontoon, Action::DoubleDown) => "buy",
+            _ => encode_action(action),
+        }
+    }
+}
+
+// When the dealer's second card arrives. Without a hole card it is dealt
+// only once the player has finished.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum HoleCard {
+    Dealt,
+    AfterPlayers,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DoubleRule {
+    AnyTwoCards,
+    // Only on hard totals of 9, 10 or 11.
+    NineToEleven,
+}
+
+impl DoubleRule {
+    pub fn allows(&self, total: u32) -> bool {
+        match self {
+            DoubleRule::AnyTwoCards => true,
+            DoubleRule::NineToEleven => (9..=11).contains(&total),
+        }
+    }
+}
+
+// What happens when the shoe runs dry in the middle of a round. Tables that
+// deal to a cut card never get there; a custom or exhausted shoe can.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EmptyShoe {
+    // The discard tray is shuffled back in, leaving the cards on the table
+    // where they are, and the shoe is replaced after the round.
+    Reshuffle,
+    // Nobody draws again: the player stands and the dealer stops.
+    EndHand,
+}
+
+impl EmptyShoe {
+    pub const ALL_VALUES: [EmptyShoe; 2] = [EmptyShoe::Reshuffle, EmptyShoe::EndHand];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EmptyShoe::Reshuffle => "reshuffle",
+            EmptyShoe::EndHand => "end-hand",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<EmptyShoe> {
+        EmptyShoe::ALL_VALUES.iter().find(|rule| rule.name() == name).cloned()
+    }
+}
+
+pub const DEFAULT_PENETRATION: u32 = 75;
+
+// The table rules the engine plays by. Presets in `config::RulesPreset` map
+// onto these; the analyzer accepts any combination.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameRules {
+    pub variant: Variant,
+    pub decks: u32,
+    pub dealer_hits_soft_17: bool,
+    pub dealer: DealerProfile,
+    // Late surrender as the first decision on a hand.
+    pub surrender: bool,
+    // A winning natural pays numerator:denominator.
+    pub blackjack_numerator: u64,
+    pub blackjack_denominator: u64,
+    pub hole_card: HoleCard,
+    // The US peek: with a hole card, a dealer showing a ten or an ace checks
+    // it before anyone plays and a natural settles the round at once, so
+    // nobody doubles or hits into it. Insurance is offered first.
+    pub peek: bool,
+    pub double_on: DoubleRule,
+    pub shuffle_model: ShuffleModel,
+    pub shuffle_rng: ShuffleRng,
+    // Percent of a shoe dealt before the cut card comes out. Only tables
+    // that keep one shoe across hands use it.
+    pub penetration: u32,
+    pub empty_shoe: EmptyShoe,
+    // A blackjack hand of this many cards that hasn't busted wins at once,
+    // whatever the dealer goes on to make; a dealer natural still beats it.
+    pub charlie: Option<usize>,
+    // How many hands a player may end up with by splitting and resplitting;
+    // 1 turns splitting off.
+    pub max_split_hands: usize,
+    // Each split ace is dealt one more card and stands.
+    pub split_aces_one_card: bool,
+    // An ace and a ten after a split counts as a natural rather than 21.
+    pub blackjack_after_split: bool,
+    // Doubling on the first two cards of a split hand.
+    pub double_after_split: bool,
+    // How a blackjack or surrender that doesn't come out whole is paid.
+    pub payout_rounding: PayoutRounding,
+}
+
+impl Default for GameRules {
+    fn default() -> GameRules {
+        GameRules {
+            variant: Variant::Blackjack,
+            decks: 1,
+            dealer_hits_soft_17: false,
+            dealer: DealerProfile::Standard,
+            surrender: true,
+            blackjack_numerator: 3,
+            blackjack_denominator: 2,
+            hole_card: HoleCard::Dealt,
+            peek: true,
+            double_on: DoubleRule::AnyTwoCards,
+            shuffle_model: ShuffleModel::Uniform,
+            shuffle_rng: ShuffleRng::Standard,
+            penetration: DEFAULT_PENETRATION,
+            empty_shoe: EmptyShoe::Reshuffle,
+            charlie: Option::None,
+            max_split_hands: DEFAULT_MAX_SPLIT_HANDS,
+            split_aces_one_card: true,
+            blackjack_after_split: false,
+            double_after_split: true,
+            payout_rounding: PayoutRounding::default(),
+        }
+    }
+}
+
+impl GameRules {
+    pub fn blackjack_odds(&self) -> Odds {
+        Odds { numerator: self.blackjack_numerator, denominator: self.blackjack_denominator }
+    }
+
+    // Parses payouts written as "3:2" or "6-5".
+    pub fn parse_payout(raw: &str) -> Option<(u64, u64)> {
+        let mut parts = raw.split([':', '-']);
+        let numerator = parts.next()?.trim().parse().ok()?;
+        let denominator: u64 = parts.next()?.trim().parse().ok()?;
+        if parts.next().is_some() || denominator == 0 {
+            return Option::None;
+        }
+        Option::Some((numerator, denominator))
+    }
+
+    // Rules a table can actually deal: a shoe the engine allows, a payout
+    // that can be worked out, and a cut card inside the shoe.
+    pub fn validate(&self) -> Result<(), BlackjackError> {
+        if self.decks == 0 || self.decks > MAX_DECKS {
+            return Err(BlackjackError::InvalidDeckCount(self.decks));
+        }
+        if self.blackjack_denominator == 0 {
+            return Err(BlackjackError::InvalidRules("a blackjack payout needs a non-zero denominator"));
+        }
+        if self.penetration > 100 {
+            return Err(BlackjackError::InvalidRules("penetration is a percentage of the shoe"));
+        }
+        if self.charlie.is_some_and(|cards| cards < MIN_CHARLIE) {
+            return Err(BlackjackError::InvalidRules("a Charlie needs at least three cards"));
+        }
+        if self.max_split_hands == 0 || self.max_split_hands > MAX_SPLIT_HANDS {
+            return Err(BlackjackError::InvalidRules("splitting allows 1 to 8 hands"));
+        }
+        Ok(())
+    }
+
+    // The shuffler these rules deal with. `rng` drives a standard one; a
+    // secure one draws from the operating system instead, which can fail.
+    #[cfg(feature = "std")]
+    pub fn shuffler<'a, R: Rng + 'a>(&self, rng: R) -> io::Result<Box<dyn Shuffler + 'a>> {
+        match self.shuffle_rng {
+            ShuffleRng::Standard => Ok(self.shuffle_model.shuffler(rng)),
+            ShuffleRng::Secure => Ok(Box::new(SecureShuffle::new(self.shuffle_model.clone())?)),
+        }
+    }
+
+    // A pontoon five card trick, which pays 2:1 and stands automatically.
+    pub fn is_five_card_trick(&self, hand: &Hand) -> bool {
+        self.variant == Variant::Pontoon && hand.card_count() >= FIVE_CARD_TRICK && !hand.is_bust()
+    }
+
+    // A blackjack hand that has reached the Charlie without busting.
+    pub fn is_charlie(&self, hand: &Hand) -> bool {
+        self.variant == Variant::Blackjack
+            && self.charlie.is_some_and(|cards| hand.card_count() >= cards)
+            && !hand.is_bust()
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{}{} deck{}, {} dealer {} soft 17, {}, {}, {}, {}, blackjack pays {}:{}{}, {}{} shuffle{}",
+            if self.variant == Variant::Pontoon { "pontoon, " } else { "" },
+            self.decks,
+            if self.decks == 1 { "" } else { "s" },
+            self.dealer.name(),
+            if self.dealer_hits_soft_17 { "hits" } else { "stands on" },
+            if self.surrender { "late surrender" } else { "no surrender" },
+            match self.hole_card {
+                HoleCard::Dealt if self.peek => "hole card",
+                HoleCard::Dealt => "hole card, no peek",
+                HoleCard::AfterPlayers => "no hole card",
+            },
+            match self.double_on {
+                DoubleRule::AnyTwoCards => "double on any two cards",
+                DoubleRule::NineToEleven => "double on 9-11",
+            },
+            match (self.max_split_hands, self.double_after_split) {
+                (1, _) => "no splitting".to_string(),
+                (hands, true) => format!("split to {} hands", hands),
+                (hands, false) => format!("split to {} hands, no double after split", hands),
+            },
+            self.blackjack_numerator,
+            self.blackjack_denominator,
+            match self.charlie {
+                Option::Some(cards) => format!(", {} card Charlie", cards),
+                Option::None => String::new(),
+            },
+            if self.shuffle_rng == ShuffleRng::Secure { "secure " } else { "" },
+            self.shuffle_model.name(),
+            if self.empty_shoe == EmptyShoe::EndHand { ", no reshuffle when the shoe runs out" } else { "" }
+        )
+    }
+}
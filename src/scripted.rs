@@ -0,0 +1,42 @@
+use crate::protocol::decode_card;
+use crate::shuffle::ReshuffleKey;
+use crate::{Card, Deck};
+
+// Deals exactly the cards it is given, first card first, so a test or drill
+// can know the whole shoe in advance.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScriptedCardSource {
+    cards: Vec<Card>,
+}
+
+impl ScriptedCardSource {
+    pub fn new(cards: Vec<Card>) -> ScriptedCardSource {
+        ScriptedCardSource { cards }
+    }
+
+    // Whitespace separated cards in protocol notation, e.g. "AS KD 7H".
+    pub fn parse(codes: &str) -> Option<ScriptedCardSource> {
+        codes
+            .split_whitespace()
+            .map(decode_card)
+            .collect::<Option<Vec<Card>>>()
+            .map(ScriptedCardSource::new)
+    }
+
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    pub fn into_deck(self) -> Deck {
+        // The deck deals from the back of its vector.
+        let mut remaining_cards = self.cards;
+        remaining_cards.reverse();
+        Deck {
+            remaining_cards,
+            discard_tray: Vec::new(),
+            burned_cards: Vec::new(),
+            cut_card: Option::None,
+            reshuffle_key: ReshuffleKey::default(),
+        }
+    }
+}
@@ -0,0 +1,802 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use rand::{OsRng, Rng};
+use rust_blackjack::config::{RulesPreset, MAX_DECKS};
+use rust_blackjack::events::{self, UiEvent};
+use rust_blackjack::fairness::SealedShoe;
+use rust_blackjack::json::JsonValue;
+use rust_blackjack::protocol::{encode_card, ClientMessage, ServerMessage, Standing};
+use rust_blackjack::rules::{EmptyShoe, GameRules};
+use rust_blackjack::settlement::settle;
+use rust_blackjack::strategy::strategy_from_name;
+use rust_blackjack::table::{self, Round};
+use rust_blackjack::{describe_actions, describe_dealer, game_message, insurance_prompt, Deck, GameState};
+
+use crate::store::{SavedTable, SessionStore};
+use crate::websocket;
+
+// How long a player has for each decision, and the strategy that plays for
+// them once it has run out, so one slow player doesn't stall a shared table.
+// Without a limit a table waits as long as it takes. A player whose
+// connection drops has `grace` to come back instead, from when their turn
+// comes round, before their hand is surrendered and their seat given up.
+#[derive(Clone, Debug)]
+pub struct DecisionClock {
+    pub limit: Option<Duration>,
+    pub on_timeout: String,
+    pub grace: Duration,
+}
+
+// The table every server opens with, on the rules it was started with.
+const MAIN_TABLE: &str = "main";
+
+// Names go on the leaderboard, so they are kept to a column's width.
+const MAX_NAME_LENGTH: usize = 24;
+
+// A connection's writing half, sending each message as a line over raw TCP
+// or as a JSON text frame over WebSocket.
+struct Connection {
+    stream: TcpStream,
+    websocket: bool,
+}
+
+impl Connection {
+    fn write(&mut self, message: &ServerMessage) -> io::Result<()> {
+        if self.websocket {
+            websocket::write_text(&mut self.stream, &message.to_json().to_string())
+        } else {
+            writeln!(self.stream, "{}", message.to_line())
+        }
+    }
+
+    fn try_clone(&self) -> io::Result<Connection> {
+        Ok(Connection { stream: self.stream.try_clone()?, websocket: self.websocket })
+    }
+
+    fn shutdown(&self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+// Both the table and the chat relayed by other players' threads write to
+// a connection, a whole message at a time.
+type Writer = Arc<Mutex<Connection>>;
+
+fn send(writer: &Writer, message: &ServerMessage) -> io::Result<()> {
+    writer.lock().expect("connection writer poisoned").write(message)
+}
+
+// A connection's reading half, as lines in the raw TCP protocol whichever
+// transport they came over.
+type Incoming = Box<dyn Iterator<Item = io::Result<String>>>;
+
+// Opens a connection that came in, answering its WebSocket handshake first
+// if it came in on the WebSocket address.
+fn open_connection(stream: TcpStream, websocket: bool) -> io::Result<(Writer, Incoming)> {
+    if !websocket {
+        let writer = Arc::new(Mutex::new(Connection { stream: stream.try_clone()?, websocket }));
+        return Ok((writer, Box::new(BufReader::new(stream).lines())));
+    }
+    let reader = websocket::accept(stream)?;
+    let writer = Arc::new(Mutex::new(Connection { stream: reader.get_ref().try_clone()?, websocket }));
+    let replies = Arc::clone(&writer);
+    let messages = websocket::Messages::new(reader, move |opcode, payload| {
+        let mut connection = replies.lock().expect("connection writer poisoned");
+        websocket::write_frame(&mut connection.stream, opcode, payload, false)
+    });
+    // A message that doesn't decode is passed on as it came, to be answered
+    // like any other line the server doesn't understand.
+    let lines = messages.map(|message| {
+        message.map(|text| match JsonValue::parse(&text).and_then(|value| ClientMessage::from_json(&value)) {
+            Option::Some(message) => message.to_line(),
+            Option::None => text,
+        })
+    });
+    Ok((writer, Box::new(lines)))
+}
+
+// What a seat's connection passes on to the table.
+enum Line {
+    Text(String),
+    // The connection went away without the player leaving.
+    Dropped,
+    // A new connection took the seat back, and needs the turn shown again.
+    Resumed,
+}
+
+// A player seated at a table. Their connection's thread reads the lines
+// and passes them on; the table writes back directly.
+struct Seat {
+    name: String,
+    // Only a name the player chose goes on the leaderboard; the address a
+    // seat is otherwise known by changes with every connection.
+    ranked: bool,
+    token: String,
+    writer: Writer,
+    lines: Receiver<Line>,
+}
+
+impl Seat {
+    // A player who has gone away is noticed the next time the table reads
+    // from them, so a failed write can be let go.
+    fn tell(&mut self, message: &ServerMessage) {
+        let _ = send(&self.writer, message);
+    }
+}
+
+// Everyone seated at a table, for the connections relaying their chat, or
+// everyone watching one.
+type Listeners = Arc<Mutex<Vec<Writer>>>;
+
+fn show(listeners: &Listeners, message: &ServerMessage) {
+    for listener in listeners.lock().expect("listeners poisoned").iter() {
+        let _ = send(listener, message);
+    }
+}
+
+// What outlives a seat's connection: where its lines go and where the
+// table writes, which a resume points at the new connection's stream.
+struct Session {
+    // The name the seat plays and chats under.
+    name: String,
+    lines: Sender<Line>,
+    writer: Writer,
+    seated: Listeners,
+    watchers: Listeners,
+    // Bumped on each resume, so the connection that was replaced doesn't
+    // report the seat dropped once it notices.
+    generation: AtomicUsize,
+}
+
+// The sessions of everyone seated, by token.
+type Sessions = Arc<Mutex<HashMap<String, Arc<Session>>>>;
+
+// Shared by the lobby, for the tables and the leaderboard, and the tables,
+// which record each round's results.
+type Store = Arc<Mutex<Box<dyn SessionStore>>>;
+
+fn new_token() -> io::Result<String> {
+    Ok(format!("{:016x}", OsRng::new()?.next_u64()))
+}
+
+struct TableEntry {
+    // The preset the table was opened on, or the house rules.
+    label: String,
+    joins: Sender<Seat>,
+    seated: Listeners,
+    watchers: Listeners,
+}
+
+// Every table, by name. Each one plays on a thread of its own, so a slow
+// player holds up their own table and nobody else's.
+struct Registry {
+    tables: Mutex<BTreeMap<String, TableEntry>>,
+    // Tables ask the thread that owns the shuffler for their shoes.
+    shoes: Mutex<Sender<Sender<Deck>>>,
+    clock: DecisionClock,
+    sessions: Sessions,
+    store: Store,
+    // What each hand counts for on the leaderboard.
+    bet: u64,
+}
+
+impl Registry {
+    fn create(&self, name: &str, label: &str, rules: GameRules) -> Result<(), String> {
+        let mut tables = self.tables.lock().expect("table registry poisoned");
+        if tables.contains_key(name) {
+            return Err(format!("There is already a table called {}", name));
+        }
+        let (joins, waiting) = mpsc::channel();
+        let (seated, watchers): (Listeners, Listeners) = (Arc::default(), Arc::default());
+        let shoes = self.shoes.lock().expect("shoe requests poisoned").clone();
+        // Every card dealt has to come from the shoe that was sealed, so a
+        // shoe that runs dry ends the hand rather than reshuffling cards
+        // nobody can check.
+        let rules = GameRules { empty_shoe: EmptyShoe::EndHand, ..rules };
+        let table = Table {
+            name: name.to_string(),
+            label: label.to_string(),
+            rules,
+            clock: self.clock.clone(),
+            sessions: Arc::clone(&self.sessions),
+            store: Arc::clone(&self.store),
+            bet: self.bet,
+            seats: Vec::new(),
+            seated: Arc::clone(&seated),
+            watchers: Arc::clone(&watchers),
+            sealed: Option::None,
+        };
+        thread::spawn(move || table.run(waiting, shoes));
+        tables.insert(name.to_string(), TableEntry { label: label.to_string(), joins, seated, watchers });
+        Ok(())
+    }
+
+    // A table asked for in the lobby, kept in the store so that it opens
+    // again when the server restarts.
+    fn open(&self, table: SavedTable, base: &GameRules) -> Result<(), String> {
+        let (label, rules) = table_rules(base, &table.rules, table.decks)?;
+        self.create(&table.name, label, rules)?;
+        let name = table.name.clone();
+        if let Err(error) = self.store.lock().expect("store poisoned").save_table(table) {
+            println!("Could not save the {} table: {}", name, error);
+        }
+        Ok(())
+    }
+
+    // Sends a new seat to the table, whose session is then held under the
+    // seat's token.
+    fn join(&self, name: &str, seat: Seat, lines: Sender<Line>) -> Result<Arc<Session>, String> {
+        let tables = self.tables.lock().expect("table registry poisoned");
+        let table = tables.get(name).ok_or_else(|| format!("There is no table called {}", name))?;
+        let (token, writer, player) = (seat.token.clone(), Arc::clone(&seat.writer), seat.name.clone());
+        table.joins.send(seat).map_err(|_| format!("The {} table has closed", name))?;
+        let session = Arc::new(Session {
+            name: player,
+            lines,
+            writer,
+            seated: Arc::clone(&table.seated),
+            watchers: Arc::clone(&table.watchers),
+            generation: AtomicUsize::new(0),
+        });
+        self.sessions.lock().expect("sessions poisoned").insert(token, Arc::clone(&session));
+        Ok(session)
+    }
+
+    // Adds a spectator to the table, returning everyone watching it.
+    fn watch(&self, name: &str, writer: &Writer) -> Result<Listeners, String> {
+        let tables = self.tables.lock().expect("table registry poisoned");
+        let table = tables.get(name).ok_or_else(|| format!("There is no table called {}", name))?;
+        table.watchers.lock().expect("listeners poisoned").push(Arc::clone(writer));
+        Ok(Arc::clone(&table.watchers))
+    }
+
+    // Points a held seat at `connection`, closing whichever connection had
+    // it, and returns its session with the new connection's generation.
+    fn resume(&self, token: &str, connection: Connection) -> Result<(Arc<Session>, usize), String> {
+        let session = self.sessions.lock().expect("sessions poisoned").get(token).cloned();
+        let session = session.ok_or_else(|| "No seat is being held for that token".to_string())?;
+        let generation = {
+            let mut writer = session.writer.lock().expect("connection writer poisoned");
+            writer.shutdown();
+            *writer = connection;
+            session.generation.fetch_add(1, Ordering::SeqCst) + 1
+        };
+        let _ = session.lines.send(Line::Resumed);
+        Ok((session, generation))
+    }
+
+    fn standings(&self) -> Vec<Standing> {
+        self.store.lock().expect("store poisoned").standings()
+    }
+
+    fn describe(&self) -> Vec<String> {
+        let tables = self.tables.lock().expect("table registry poisoned");
+        tables
+            .iter()
+            .map(|(name, table)| {
+                let seated = table.seated.lock().expect("listeners poisoned").len();
+                match table.watchers.lock().expect("listeners poisoned").len() {
+                    0 => format!("{}: {}, {} seated", name, table.label, seated),
+                    watching => format!("{}: {}, {} seated, {} watching", name, table.label, seated, watching),
+                }
+            })
+            .collect()
+    }
+}
+
+struct Table {
+    name: String,
+    label: String,
+    rules: GameRules,
+    clock: DecisionClock,
+    sessions: Sessions,
+    store: Store,
+    bet: u64,
+    seats: Vec<Seat>,
+    seated: Listeners,
+    // Spectators, who see what the whole table could see: every hand as
+    // it is played, but not the hole card until the dealer turns it over.
+    watchers: Listeners,
+    // The shoe in play, committed to when it was opened and revealed once
+    // it is done.
+    sealed: Option<SealedShoe>,
+}
+
+impl Table {
+    fn fresh_shoe(shoes: &Sender<Sender<Deck>>) -> Option<Deck> {
+        let (reply, shoe) = mpsc::channel();
+        shoes.send(reply).ok()?;
+        shoe.recv().ok()
+    }
+
+    fn seat(&mut self, mut seat: Seat) {
+        seat.tell(&ServerMessage::Table(self.name.clone()));
+        seat.tell(&ServerMessage::Message(format!("You sit down at the {} table, {}; you play from the next hand", self.name, self.label)));
+        if let Option::Some(sealed) = &self.sealed {
+            seat.tell(&ServerMessage::Commit(sealed.commitment().to_string()));
+        }
+        for other in self.seats.iter_mut() {
+            other.tell(&ServerMessage::Message(format!("{} sat down", seat.name)));
+        }
+        self.seats.push(seat);
+        self.update_seated();
+    }
+
+    // Tells everyone at the table and watching it.
+    fn announce(&mut self, message: &ServerMessage) {
+        for player in self.seats.iter_mut() {
+            player.tell(message);
+        }
+        show(&self.watchers, message);
+    }
+
+    fn update_seated(&self) {
+        *self.seated.lock().expect("listeners poisoned") = self.seats.iter().map(|seat| Arc::clone(&seat.writer)).collect();
+    }
+
+    // Hand after hand for whoever is seated, waiting for a player when the
+    // table is empty. Players who join mid-hand are dealt in from the next.
+    fn run(mut self, joins: Receiver<Seat>, shoes: Sender<Sender<Deck>>) {
+        let mut shoe = Deck::with_decks(0);
+        loop {
+            if self.seats.is_empty() {
+                match joins.recv() {
+                    Ok(seat) => self.seat(seat),
+                    Err(_) => return,
+                }
+            }
+            while let Ok(seat) = joins.try_recv() {
+                self.seat(seat);
+            }
+            if shoe.needs_shuffle() {
+                if let Option::Some(sealed) = self.sealed.take() {
+                    self.announce(&ServerMessage::Reveal(sealed.reveal().clone()));
+                }
+                shoe = match Table::fresh_shoe(&shoes) {
+                    Option::Some(shoe) => shoe,
+                    Option::None => return,
+                };
+                // Without a nonce nobody could check the shoe, so there is
+                // no point dealing from it.
+                let sealed = match SealedShoe::seal_fresh(&shoe) {
+                    Ok(sealed) => sealed,
+                    Err(error) => {
+                        println!("The {} table could not seal its shoe: {}", self.name, error);
+                        return;
+                    },
+                };
+                self.announce(&ServerMessage::Commit(sealed.commitment().to_string()));
+                self.sealed = Option::Some(sealed);
+            }
+            shoe = self.play_round(shoe);
+        }
+    }
+
+    // Each seat plays in turn, then the dealer once for everyone. Seats
+    // that left or went away are cleared once the round is over.
+    fn play_round(&mut self, shoe: Deck) -> Deck {
+        let mut round = Round::deal(shoe, self.seats.len(), &self.rules);
+        let dealer_cards: Vec<String> = round.view(0).player_state().visible_dealer_cards().iter().map(encode_card).collect();
+        show(&self.watchers, &ServerMessage::Message(format!("New hand: the dealer shows {}", dealer_cards.join(" "))));
+        for (seat, player) in self.seats.iter().enumerate() {
+            let cards: Vec<String> = round.view(seat).player_state().hand.iter().map(encode_card).collect();
+            show(&self.watchers, &ServerMessage::Message(format!("{} is dealt {}", player.name, cards.join(" "))));
+        }
+        let (seats, clock, watchers) = (&mut self.seats, &self.clock, &self.watchers);
+        let leaving: Vec<bool> = (0..seats.len())
+            .map(|seat| {
+                let name = seats[seat].name.clone();
+                if round.is_playing(seat) {
+                    tell_others(seats, seat, &format!("{} to play", name));
+                    show(watchers, &ServerMessage::Message(format!("{} to play", name)));
+                }
+                !play_seat(&mut round, seat, seats, clock, watchers)
+            })
+            .collect();
+        let views: Vec<GameState> = (0..self.seats.len()).map(|seat| round.view(seat)).collect();
+        let (finished, deck) = round.finish();
+        // The dealer plays once for everyone, so only the first seat's
+        // changes tell the spectators about it.
+        for (seat, (view, state)) in views.iter().zip(finished.iter()).enumerate() {
+            let changes: Vec<UiEvent> =
+                events::changes(view, state).into_iter().filter(|event| seat == 0 || !is_dealers(event)).collect();
+            narrate(&self.watchers, &self.seats[seat].name, &changes);
+        }
+        // Every hand counts, including those surrendered for a player who
+        // left, as if it had been played for the server's bet.
+        let results: Vec<(String, i64)> = self
+            .seats
+            .iter()
+            .zip(finished.iter())
+            .filter(|(seat, _)| seat.ranked)
+            .map(|(seat, state)| (seat.name.clone(), settle(state, self.bet, &[]).net))
+            .collect();
+        if let Err(error) = self.store.lock().expect("store poisoned").record_hands(&results) {
+            println!("The {} table could not record its results: {}", self.name, error);
+        }
+        for (seat, state) in self.seats.iter_mut().zip(finished.iter()) {
+            let player_state = state.player_state();
+            let dealer_cards: Vec<String> = player_state.visible_dealer_cards().iter().map(encode_card).collect();
+            seat.tell(&ServerMessage::Message(format!("Dealer had {}", dealer_cards.join(" "))));
+            seat.tell(&ServerMessage::Hand(player_state.hand.to_vec()));
+            seat.tell(&ServerMessage::Outcome(game_message(state).to_string()));
+        }
+        let mut seat = 0;
+        let sessions = &self.sessions;
+        self.seats.retain(|player| {
+            seat += 1;
+            if leaving[seat - 1] {
+                sessions.lock().expect("sessions poisoned").remove(&player.token);
+                // Ends the connection's read, which is how its thread hears.
+                player.writer.lock().expect("connection writer poisoned").shutdown();
+            }
+            !leaving[seat - 1]
+        });
+        let seated = self.seats_text();
+        for player in self.seats.iter_mut() {
+            player.tell(&ServerMessage::Message(format!("{} at the table", seated)));
+        }
+        self.update_seated();
+        table::clear_table(finished, deck)
+    }
+
+    fn seats_text(&self) -> String {
+        match self.seats.len() {
+            1 => "1 player".to_string(),
+            count => format!("{} players", count),
+        }
+    }
+}
+
+fn is_dealers(event: &UiEvent) -> bool {
+    match event {
+        UiEvent::CardDealt { to, .. } | UiEvent::TurnStarted { seat: to, .. } => *to == events::Seat::Dealer,
+        UiEvent::HoleCardTurned { .. } => true,
+        UiEvent::OutcomeDecided { .. } | UiEvent::BankrollChanged { .. } => false,
+    }
+}
+
+// Tells the spectators what the table saw happen to `name`'s hand.
+fn narrate(watchers: &Listeners, name: &str, changes: &[UiEvent]) {
+    for event in changes.iter().filter(|event| event.is_public()) {
+        let text = match event {
+            UiEvent::CardDealt { to: events::Seat::Player, card, .. } => format!("{} is dealt {}", name, encode_card(card)),
+            UiEvent::CardDealt { card, .. } => format!("The dealer draws {}", encode_card(card)),
+            UiEvent::HoleCardTurned { card } => format!("The dealer turns over {}", encode_card(card)),
+            UiEvent::TurnStarted { seat: events::Seat::Player, hand } => format!("{} plays hand {}", name, hand),
+            UiEvent::TurnStarted { .. } => "The dealer plays".to_string(),
+            UiEvent::OutcomeDecided { hand, outcome } => {
+                format!("{}'s hand {}: {}", name, hand, format!("{:?}", outcome).to_lowercase())
+            },
+            UiEvent::BankrollChanged { .. } => continue,
+        };
+        show(watchers, &ServerMessage::Message(text));
+    }
+}
+
+fn tell_others(seats: &mut [Seat], seat: usize, text: &str) {
+    for (other, player) in seats.iter_mut().enumerate() {
+        if other != seat {
+            player.tell(&ServerMessage::Message(text.to_string()));
+        }
+    }
+}
+
+// The seat's next line, or the clock's decision for them once the limit
+// has passed, as the action's name. Dropped only once the player has been
+// gone for the whole grace period.
+fn next_line(round: &Round, seat: usize, seats: &mut [Seat], clock: &DecisionClock) -> Line {
+    let received = match clock.limit {
+        Option::Some(limit) => seats[seat].lines.recv_timeout(limit),
+        Option::None => seats[seat].lines.recv().map_err(|_| RecvTimeoutError::Disconnected),
+    };
+    match received {
+        // A resumed connection only sends once it has said it is back.
+        Ok(Line::Dropped) => {
+            let name = seats[seat].name.clone();
+            tell_others(seats, seat, &format!("{} lost their connection, and their seat is held for them", name));
+            match seats[seat].lines.recv_timeout(clock.grace) {
+                Ok(Line::Resumed) => {
+                    tell_others(seats, seat, &format!("{} is back", name));
+                    Line::Resumed
+                },
+                _ => Line::Dropped,
+            }
+        },
+        Ok(line) => line,
+        Err(RecvTimeoutError::Disconnected) => Line::Dropped,
+        Err(RecvTimeoutError::Timeout) => {
+            let mut strategy = strategy_from_name(&clock.on_timeout).expect("the timeout strategy was checked when parsing");
+            let view = round.view(seat);
+            let action = strategy.choose(&view);
+            let action_name = view.player_state().rules.variant.action_name(&action);
+            let name = seats[seat].name.clone();
+            for (other, player) in seats.iter_mut().enumerate() {
+                let text = if other == seat {
+                    format!("Time is up, so you {}", action_name)
+                } else {
+                    format!("Time is up for {}: {}", name, action_name)
+                };
+                player.tell(&ServerMessage::Message(text));
+            }
+            Line::Text(ClientMessage::Action(action).to_line())
+        },
+    }
+}
+
+// Plays one seat's turn to the end. False when the player left or went
+// away, in which case a hand still in play is surrendered.
+fn play_seat(round: &mut Round, seat: usize, seats: &mut [Seat], clock: &DecisionClock, watchers: &Listeners) -> bool {
+    while round.is_playing(seat) {
+        let game_state = round.view(seat);
+        let player_state = game_state.player_state();
+        let player = &mut seats[seat];
+        let dealer_cards: Vec<String> = player_state.visible_dealer_cards().iter().map(encode_card).collect();
+        player.tell(&ServerMessage::Message(describe_dealer(&dealer_cards.join(" "), player_state.hidden_dealer_cards().len())));
+        player.tell(&ServerMessage::Hand(player_state.hand.to_vec()));
+        if let Option::Some(prompt) = insurance_prompt(&game_state) {
+            player.tell(&ServerMessage::Message(prompt.to_string()));
+        }
+        let actions = describe_actions(&game_state.legal_actions(), &player_state.rules);
+        player.tell(&ServerMessage::Message(format!("You can: {}", actions)));
+        player.tell(&ServerMessage::Prompt);
+
+        let line = match next_line(round, seat, seats, clock) {
+            Line::Text(line) => line,
+            Line::Resumed => continue,
+            Line::Dropped => {
+                round.abandon(seat);
+                narrate(watchers, &seats[seat].name, &events::changes(&game_state, &round.view(seat)));
+                return false;
+            },
+        };
+        let player = &mut seats[seat];
+        let staying = match ClientMessage::parse(&line) {
+            Option::Some(ClientMessage::Action(action)) => {
+                if let Err(error) = round.act(seat, &action) {
+                    player.tell(&ServerMessage::Message(format!("Illegal action: {}", error)));
+                }
+                true
+            },
+            Option::Some(ClientMessage::Leave) => {
+                round.abandon(seat);
+                player.tell(&ServerMessage::Message("You leave the table once the hand is over".to_string()));
+                false
+            },
+            _ => {
+                player.tell(&ServerMessage::Message(format!("Unknown action: {:?}", line)));
+                true
+            },
+        };
+        narrate(watchers, &player.name, &events::changes(&game_state, &round.view(seat)));
+        if !staying {
+            return false;
+        }
+    }
+    true
+}
+
+const HOUSE_RULES: &str = "house rules";
+
+// New rules for a table created from the lobby: a preset and deck count,
+// each defaulting to the server's.
+fn table_rules(base: &GameRules, rules: &Option<String>, decks: Option<u32>) -> Result<(&'static str, GameRules), String> {
+    let decks = decks.unwrap_or(base.decks);
+    if !(1..=MAX_DECKS).contains(&decks) {
+        return Err(format!("A table deals from 1 to {} decks", MAX_DECKS));
+    }
+    match rules {
+        Option::None => Ok((HOUSE_RULES, GameRules { decks, ..base.clone() })),
+        Option::Some(name) => RulesPreset::from_name(name)
+            .map(|preset| (preset.name(), GameRules { dealer: base.dealer.clone(), shuffle_rng: base.shuffle_rng.clone(), ..preset.rules(decks) }))
+            .ok_or_else(|| {
+                let names: Vec<&str> = RulesPreset::ALL_VALUES.iter().map(RulesPreset::name).collect();
+                format!("Unknown rules {:?}, try: {}", name, names.join(", "))
+            }),
+    }
+}
+
+// A spectator's own lines only matter for leaving, since spectators have
+// no say at the table.
+fn spectate(lines: Incoming, writer: &Writer, name: &str, watchers: &Listeners) -> io::Result<()> {
+    let _ = send(writer, &ServerMessage::Message(format!("You are watching the {} table, until you leave", name)));
+    for line in lines.map_while(Result::ok) {
+        if ClientMessage::parse(&line) == Option::Some(ClientMessage::Leave) {
+            break;
+        }
+        let _ = send(writer, &ServerMessage::Message("Spectators only watch; leave stops watching".to_string()));
+    }
+    watchers.lock().expect("listeners poisoned").retain(|watcher| !Arc::ptr_eq(watcher, writer));
+    writer.lock().expect("connection writer poisoned").shutdown();
+    Ok(())
+}
+
+// The lobby: list, create and join tables until the player sits down. Once
+// they have, this thread only passes their lines on to the table.
+fn serve_connection(writer: Writer, mut lines: Incoming, peer: String, registry: &Registry, rules: &GameRules) -> io::Result<()> {
+    let mut player = peer;
+    let mut named = false;
+    send(&writer, &ServerMessage::Message(format!("Tables: {}", registry.describe().join("; "))))?;
+    let (session, generation) = loop {
+        send(&writer, &ServerMessage::Lobby)?;
+        let line = match lines.next() {
+            Option::Some(line) => line?,
+            Option::None => return Ok(()),
+        };
+        let reply = match ClientMessage::parse(&line) {
+            Option::Some(ClientMessage::ListTables) => format!("Tables: {}", registry.describe().join("; ")),
+            Option::Some(ClientMessage::CreateTable { name, rules: preset, decks }) => {
+                match registry.open(SavedTable { name: name.clone(), rules: preset, decks }, rules) {
+                    Ok(()) => format!("Opened the {} table", name),
+                    Err(error) => error,
+                }
+            },
+            Option::Some(ClientMessage::JoinTable(name)) => {
+                let (table_lines, lines) = mpsc::channel();
+                let token = new_token()?;
+                let seat = Seat { name: player.clone(), ranked: named, token: token.clone(), writer: Arc::clone(&writer), lines };
+                match registry.join(&name, seat, table_lines) {
+                    Ok(session) => {
+                        send(&writer, &ServerMessage::Session(token))?;
+                        break (session, 0);
+                    },
+                    Err(error) => error,
+                }
+            },
+            Option::Some(ClientMessage::Watch(name)) => match registry.watch(&name, &writer) {
+                Ok(watchers) => return spectate(lines, &writer, &name, &watchers),
+                Err(error) => error,
+            },
+            Option::Some(ClientMessage::Resume(token)) => {
+                let connection = writer.lock().expect("connection writer poisoned").try_clone()?;
+                match registry.resume(&token, connection) {
+                    // The seat chats under its own name, not this
+                    // connection's address.
+                    Ok((session, generation)) => {
+                        player = session.name.clone();
+                        break (session, generation);
+                    },
+                    Err(error) => error,
+                }
+            },
+            Option::Some(ClientMessage::Name(who)) if who.chars().count() > MAX_NAME_LENGTH => {
+                format!("Names are at most {} characters", MAX_NAME_LENGTH)
+            },
+            Option::Some(ClientMessage::Name(who)) => {
+                player = who;
+                named = true;
+                format!("You play as {}", player)
+            },
+            Option::Some(ClientMessage::Leaderboard) => {
+                send(&writer, &ServerMessage::Leaderboard(registry.standings()))?;
+                continue;
+            },
+            Option::Some(ClientMessage::Leave) => return Ok(()),
+            Option::Some(ClientMessage::Action(_)) | Option::Some(ClientMessage::Chat(_)) => {
+                "Join a table first: join <name>".to_string()
+            },
+            Option::None => {
+                "Lobby commands: tables, create <name> [<rules> [<decks>]], join <name>, watch <name>, resume <token>, name <who>, leaderboard, leave"
+                    .to_string()
+            },
+        };
+        send(&writer, &ServerMessage::Message(reply))?;
+    };
+    // Chat goes straight out to the table from here, so it gets through
+    // while the table waits on some other player's decision.
+    // A read that fails is as good as the connection closing.
+    for line in lines.map_while(Result::ok) {
+        if let Option::Some(ClientMessage::Chat(text)) = ClientMessage::parse(&line) {
+            let chat = ServerMessage::Chat { from: player.clone(), text };
+            show(&session.seated, &chat);
+            show(&session.watchers, &chat);
+        } else if session.lines.send(Line::Text(line)).is_err() {
+            break;
+        }
+    }
+    // Gone from the table, or from the connection: either way the socket
+    // is done with. Unless another connection has taken the seat over, the
+    // table holds it for the grace period.
+    let connection = session.writer.lock().expect("connection writer poisoned");
+    if session.generation.load(Ordering::SeqCst) == generation {
+        let _ = session.lines.send(Line::Dropped);
+        connection.shutdown();
+    }
+    Ok(())
+}
+
+// Takes the connections to `listener` into the lobby, each on a thread of
+// its own, over WebSocket if `websocket`.
+fn take_connections(listener: TcpListener, websocket: bool, registry: Arc<Registry>, rules: GameRules) {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(error) => {
+                println!("Could not take a connection: {}", error);
+                continue;
+            },
+        };
+        let peer = stream.peer_addr().map(|peer| peer.to_string()).unwrap_or_else(|_| "someone".to_string());
+        let (registry, rules) = (Arc::clone(&registry), rules.clone());
+        thread::spawn(move || {
+            let (writer, lines) = match open_connection(stream, websocket) {
+                Ok(connection) => connection,
+                Err(error) => {
+                    println!("Could not take a connection from {}: {}", peer, error);
+                    return;
+                },
+            };
+            println!("{} came into the lobby", peer);
+            if let Err(error) = serve_connection(writer, lines, peer.clone(), &registry, &rules) {
+                println!("{} left with an error: {}", peer, error);
+            } else {
+                println!("{} left", peer);
+            }
+        });
+    }
+}
+
+// Opens the main table and the tables kept in `store`, and takes
+// connections into the lobby on a thread of its own, and WebSocket
+// connections on another when given a `websocket` address, while this
+// thread shuffles a fresh shoe, with `new_deck`, whenever a table asks for
+// one. Each hand goes on the leaderboard as if it had been played for `bet`.
+pub fn run<F>(
+    address: &str,
+    websocket: Option<&str>,
+    rules: &GameRules,
+    clock: DecisionClock,
+    store: Box<dyn SessionStore>,
+    bet: u64,
+    mut new_deck: F,
+) -> io::Result<()>
+where
+    F: FnMut() -> Deck,
+{
+    let listener = TcpListener::bind(address)?;
+    println!("Serving blackjack on {}", listener.local_addr()?);
+    let websocket_listener = match websocket {
+        Option::Some(address) => {
+            let listener = TcpListener::bind(address)?;
+            println!("Serving blackjack over WebSocket on ws://{}/", listener.local_addr()?);
+            Option::Some(listener)
+        },
+        Option::None => Option::None,
+    };
+
+    let (shoes, shoe_requests) = mpsc::channel::<Sender<Deck>>();
+    let saved = store.tables();
+    let registry = Arc::new(Registry {
+        tables: Mutex::new(BTreeMap::new()),
+        shoes: Mutex::new(shoes),
+        clock,
+        sessions: Arc::default(),
+        store: Arc::new(Mutex::new(store)),
+        bet,
+    });
+    registry.create(MAIN_TABLE, HOUSE_RULES, rules.clone()).expect("the registry starts empty");
+    for table in saved {
+        match table_rules(rules, &table.rules, table.decks).and_then(|(label, rules)| registry.create(&table.name, label, rules)) {
+            Ok(()) => println!("Reopened the {} table", table.name),
+            Err(error) => println!("Could not reopen the {} table: {}", table.name, error),
+        }
+    }
+    if let Option::Some(listener) = websocket_listener {
+        let (registry, rules) = (Arc::clone(&registry), rules.clone());
+        thread::spawn(move || take_connections(listener, true, registry, rules));
+    }
+    let rules = rules.clone();
+    thread::spawn(move || take_connections(listener, false, registry, rules));
+
+    for reply in shoe_requests {
+        let _ = reply.send(new_deck());
+    }
+    Ok(())
+}
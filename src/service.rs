@@ -0,0 +1,163 @@
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+
+use rand::{SeedableRng, StdRng};
+
+use crate::events::{self, UiEvent};
+use crate::rules::GameRules;
+use crate::shuffle::Shuffler;
+use crate::{continue_with_game, Action, Deck, GameState};
+
+// The calls of the gRPC service in proto/blackjack.proto, without the
+// transport. A tonic server generated from the proto file keeps one
+// `GameService` and forwards each call to it; the service only needs std,
+// so tonic stays out of the engine's dependencies. Every method takes
+// `&self`, so the service can be shared between the server's tasks.
+
+pub type GameId = u64;
+
+// Finished games are kept for a while, for a late `StreamEvents` to replay,
+// but only this many of them: the oldest finished game goes first.
+pub const MAX_FINISHED_GAMES: usize = 1000;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ServiceError {
+    // NOT_FOUND over gRPC.
+    NotFound(GameId),
+    // FAILED_PRECONDITION over gRPC, explaining what the hand allows.
+    IllegalAction(String),
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ServiceError::NotFound(id) => write!(f, "there is no game {}", id),
+            ServiceError::IllegalAction(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl Error for ServiceError {}
+
+struct Game {
+    state: GameState,
+    // Every public event so far, for streams that start late.
+    log: Vec<UiEvent>,
+    streams: Vec<Sender<UiEvent>>,
+}
+
+impl Game {
+    // Sends the events everyone at the table could see, and ends the
+    // streams once the hand is over.
+    fn publish(&mut self, events: Vec<UiEvent>) {
+        for event in events.into_iter().filter(UiEvent::is_public) {
+            self.streams.retain(|stream| stream.send(event.clone()).is_ok());
+            self.log.push(event);
+        }
+        if !continue_with_game(&self.state) {
+            self.streams.clear();
+        }
+    }
+}
+
+#[derive(Default)]
+struct Games {
+    by_id: HashMap<GameId, Game>,
+    // Oldest first.
+    finished: VecDeque<GameId>,
+}
+
+impl Games {
+    fn finished(&mut self, id: GameId) {
+        self.finished.push_back(id);
+        while self.finished.len() > MAX_FINISHED_GAMES {
+            if let Option::Some(oldest) = self.finished.pop_front() {
+                self.by_id.remove(&oldest);
+            }
+        }
+    }
+}
+
+pub struct GameService {
+    rules: GameRules,
+    // Shuffles the games created without a seed.
+    shuffler: Mutex<Box<dyn Shuffler + Send>>,
+    games: Mutex<Games>,
+    next_id: AtomicU64,
+}
+
+impl GameService {
+    pub fn new(rules: GameRules, shuffler: Box<dyn Shuffler + Send>) -> GameService {
+        GameService { rules, shuffler: Mutex::new(shuffler), games: Mutex::default(), next_id: AtomicU64::new(1) }
+    }
+
+    // A seeded game deals the same shoe every time, like `play --seed`.
+    pub fn create_game(&self, seed: Option<u32>) -> (GameId, GameState) {
+        let mut shoe = Deck::with_decks(self.rules.decks);
+        match seed {
+            Option::Some(seed) => {
+                let mut rng: StdRng = SeedableRng::from_seed(&[seed as usize][..]);
+                shoe.shuffle(&mut rng);
+            },
+            Option::None => shoe.shuffle(self.shuffler.lock().expect("shuffler poisoned").as_mut()),
+        }
+        shoe.burn();
+        shoe.place_cut_card(self.rules.penetration);
+
+        let mut dealt = Vec::new();
+        let state = events::start(shoe, self.rules.clone(), &mut |event| dealt.push(event));
+        let mut game = Game { state: state.clone(), log: Vec::new(), streams: Vec::new() };
+        game.publish(dealt);
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let mut games = self.games.lock().expect("games poisoned");
+        games.by_id.insert(id, game);
+        if !continue_with_game(&state) {
+            games.finished(id);
+        }
+        (id, state)
+    }
+
+    pub fn apply_action(&self, id: GameId, action: &Action) -> Result<GameState, ServiceError> {
+        let mut games = self.games.lock().expect("games poisoned");
+        let game = games.by_id.get_mut(&id).ok_or(ServiceError::NotFound(id))?;
+        let mut changes = Vec::new();
+        match events::act(action, game.state.clone(), &mut |event| changes.push(event)) {
+            Ok(state) => {
+                game.state = state.clone();
+                game.publish(changes);
+                if !continue_with_game(&state) {
+                    games.finished(id);
+                }
+                Ok(state)
+            },
+            Err(illegal) => Err(ServiceError::IllegalAction(illegal.to_string())),
+        }
+    }
+
+    // Forgets the game, finished or not, ending any streams still open on it.
+    pub fn close_game(&self, id: GameId) -> Result<(), ServiceError> {
+        let mut games = self.games.lock().expect("games poisoned");
+        games.by_id.remove(&id).ok_or(ServiceError::NotFound(id))?;
+        games.finished.retain(|finished| *finished != id);
+        Ok(())
+    }
+
+    // The game's events so far, then the rest as they happen; the stream
+    // ends once the hand is over.
+    pub fn stream_events(&self, id: GameId) -> Result<Receiver<UiEvent>, ServiceError> {
+        let mut games = self.games.lock().expect("games poisoned");
+        let game = games.by_id.get_mut(&id).ok_or(ServiceError::NotFound(id))?;
+        let (sender, receiver) = mpsc::channel();
+        for event in &game.log {
+            let _ = sender.send(event.clone());
+        }
+        if continue_with_game(&game.state) {
+            game.streams.push(sender);
+        }
+        Ok(receiver)
+    }
+}
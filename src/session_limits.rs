@@ -0,0 +1,38 @@
+use core::time::Duration;
+
+// Where a session stops on its own: once it is up `stop_win` or down
+// `stop_loss` chips, after `max_hands` hands or once `max_time` has gone
+// by. Unset limits never stop it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SessionLimits {
+    pub stop_win: Option<u64>,
+    pub stop_loss: Option<u64>,
+    pub max_hands: Option<u64>,
+    pub max_time: Option<Duration>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StopReason {
+    StopWin,
+    StopLoss,
+    MaxHands,
+    MaxTime,
+}
+
+impl SessionLimits {
+    // The first limit the session has reached, if any, checked in the
+    // order the fields are declared.
+    pub fn reached(&self, net: i64, hands: u64, elapsed: Duration) -> Option<StopReason> {
+        if self.stop_win.is_some_and(|stop_win| net >= stop_win as i64) {
+            Option::Some(StopReason::StopWin)
+        } else if self.stop_loss.is_some_and(|stop_loss| net <= -(stop_loss as i64)) {
+            Option::Some(StopReason::StopLoss)
+        } else if self.max_hands.is_some_and(|max_hands| hands >= max_hands) {
+            Option::Some(StopReason::MaxHands)
+        } else if self.max_time.is_some_and(|max_time| elapsed >= max_time) {
+            Option::Some(StopReason::MaxTime)
+        } else {
+            Option::None
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use alloc::boxed::Box;
+
+use crate::payout::Odds;
+use crate::promotions::RoundModifier;
+use crate::rules::{GameRules, FIVE_CARD_TRICK_PAYOUT};
+use crate::{is_natural, Card, GameState, HandOutcome};
+
+// Everything a payout rule gets to look at once a round is over.
+#[derive(Debug)]
+pub struct RoundResult<'a> {
+    pub hand: &'a [Card],
+    pub bet: u64,
+    pub won: bool,
+    pub natural: bool,
+}
+
+// A double made for less than the bet, all in, because the bankroll
+// couldn't cover a full one. Hands are numbered in the order they were
+// played, from 0, which is also the order the split hands are kept in.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShortDouble {
+    pub hand: usize,
+    pub extra: u64,
+}
+
+fn hand_stake(bet: u64, doubled: bool, hand: usize, short_double: Option<&ShortDouble>) -> u64 {
+    match short_double {
+        _ if !doubled => bet,
+        Option::Some(short_double) if short_double.hand == hand => bet + short_double.extra,
+        _ => bet * 2,
+    }
+}
+
+// Insurance costs half the bet, rounded down to what can be put down.
+pub fn insurance_stake(bet: u64) -> u64 {
+    bet / 2
+}
+
+// Won (positive) or lost on a hand's stake.
+fn hand_payout(rules: &GameRules, outcome: &Option<HandOutcome>, stake: u64, trick: bool) -> i64 {
+    let odds = match outcome {
+        Option::Some(HandOutcome::Blackjack) => rules.blackjack_odds(),
+        Option::Some(HandOutcome::Won) if trick => Odds { numerator: FIVE_CARD_TRICK_PAYOUT, denominator: 1 },
+        Option::Some(HandOutcome::Won) => Odds::EVEN_MONEY,
+        Option::Some(HandOutcome::Push) => return 0,
+        Option::Some(HandOutcome::Lost) | Option::None => return -(stake as i64),
+    };
+    odds.pay(stake, &rules.payout_rounding) as i64
+}
+
+// What is on the table so far: each hand's bet, doubles and the insurance
+// side bet.
+pub fn staked(state: &GameState, bet: u64, short_double: Option<&ShortDouble>) -> u64 {
+    let player_state = state.player_state();
+    let split_staked: u64 = player_state
+        .split_hands
+        .iter()
+        .enumerate()
+        .map(|(hand, split)| hand_stake(bet, split.doubled, hand, short_double))
+        .sum();
+    let waiting = player_state.waiting_hands.len() as u64 * bet;
+    let insurance = if player_state.insured { insurance_stake(bet) } else { 0 };
+    split_staked + waiting + insurance + hand_stake(bet, player_state.doubled, player_state.split_hands.len(), short_double)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Settlement {
+    // Everything staked, across split hands and doubles.
+    pub bet: u64,
+    // Chips won (positive) or lost (negative), not counting the returned bet.
+    // Includes `insurance`.
+    pub net: i64,
+    // What the insurance side bet won or lost, zero when none was taken.
+    pub insurance: i64,
+}
+
+// A tip for the dealer comes out of a hand's winnings, never the bet.
+pub fn max_tip(settlement: &Settlement) -> u64 {
+    settlement.net.max(0) as u64
+}
+
+// Even money for a win, the table's blackjack payout for a natural, nothing
+// for a push, half the bet for a surrender and the whole bet for a loss; a
+// double down doubles the bet. Even money is a plain win, so it pays 1:1
+// whatever the table's blackjack payout, and a pontoon five card trick pays
+// 2:1. Insurance is a separate half-bet paying 2:1 against a dealer natural.
+// A blackjack or surrender that doesn't come out whole is rounded by the
+// table's `payout_rounding`, what a surrender hands back being the payout.
+// The modifiers are then applied in order, each seeing the previous one's
+// result. Each hand split off earlier is paid the same way on its own bet;
+// the modifiers only see the last hand.
+pub fn settle(state: &GameState, bet: u64, modifiers: &[Box<dyn RoundModifier>]) -> Settlement {
+    settle_with_short_double(state, bet, Option::None, modifiers)
+}
+
+// The same, with one hand doubled for less than the bet.
+pub fn settle_with_short_double(
+    state: &GameState,
+    bet: u64,
+    short_double: Option<&ShortDouble>,
+    modifiers: &[Box<dyn RoundModifier>],
+) -> Settlement {
+    let player_state = state.player_state();
+    let rules = &player_state.rules;
+    let insurance_stake = insurance_stake(bet);
+    let insurance = if !player_state.insured {
+        0
+    } else if is_natural(&player_state.dealer_cards()) {
+        Odds::INSURANCE.pay(insurance_stake, &rules.payout_rounding) as i64
+    } else {
+        -(insurance_stake as i64)
+    };
+    let (split_staked, split_net) = player_state.split_hands.iter().enumerate().fold((0, 0), |(staked, net), (hand, split)| {
+        let stake = hand_stake(bet, split.doubled, hand, short_double);
+        (staked + stake, net + hand_payout(rules, &split.outcome, stake, rules.is_five_card_trick(&split.hand)))
+    });
+    let bet = hand_stake(bet, player_state.doubled, player_state.split_hands.len(), short_double);
+    let round = RoundResult {
+        hand: &player_state.hand,
+        bet,
+        won: matches!(state, GameState::GameWon(_) | GameState::BlackjackWin(_)),
+        natural: matches!(state, GameState::BlackjackWin(_)),
+    };
+    let outcome = |outcome: HandOutcome| hand_payout(rules, &Option::Some(outcome), bet, player_state.five_card_trick());
+    let base = match state {
+        GameState::BlackjackWin(_) => outcome(HandOutcome::Blackjack),
+        GameState::GameWon(_) => outcome(HandOutcome::Won),
+        GameState::Push(_) | GameState::Continuing(_) => 0,
+        GameState::Surrendered(_) => Odds::HALF.pay(bet, &rules.payout_rounding) as i64 - bet as i64,
+        GameState::GameLost(_) => -(bet as i64),
+    };
+    let net = modifiers
+        .iter()
+        .fold(base, |net, modifier| modifier.apply(&round, net));
+    Settlement { bet: bet + split_staked, net: net + split_net + insurance, insurance }
+}
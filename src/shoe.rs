@@ -0,0 +1,205 @@
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use rand::{ChaChaRng, SeedableRng};
+
+use crate::error::BlackjackError;
+#[cfg(not(feature = "std"))]
+use crate::fast_rng::Xoshiro256;
+use crate::rules::{EmptyShoe, MAX_DECKS};
+use crate::shuffle::{ReshuffleKey, Shuffler};
+use crate::{Card, CardSuit, CardValue, Deck};
+
+// A round can't start without two cards each for the player and dealer.
+const INITIAL_DEAL: usize = 4;
+
+// How many cards of each rank a set of cards holds, in the order of
+// `CardValue::ALL_VALUES`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RankCounts {
+    counts: [u32; 13],
+}
+
+fn rank_index(value: &CardValue) -> usize {
+    *value as usize
+}
+
+impl RankCounts {
+    pub fn of(cards: &[Card]) -> RankCounts {
+        let mut counts = RankCounts::default();
+        for card in cards {
+            counts.counts[rank_index(&card.value)] += 1;
+        }
+        counts
+    }
+
+    pub fn count(&self, value: &CardValue) -> u32 {
+        self.counts[rank_index(value)]
+    }
+
+    pub fn total(&self) -> u32 {
+        self.counts.iter().sum()
+    }
+
+    // The chance the next card is of this rank; zero once nothing is left.
+    pub fn probability_of_rank(&self, value: &CardValue) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => self.count(value) as f64 / total as f64,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&CardValue, u32)> + '_ {
+        CardValue::ALL_VALUES.iter().zip(self.counts.iter().cloned())
+    }
+}
+
+#[cfg(feature = "std")]
+fn reshuffler(key: &ReshuffleKey) -> ChaChaRng {
+    ChaChaRng::from_seed(&key[..])
+}
+
+// Without `rand` the same key orders the cards differently.
+#[cfg(not(feature = "std"))]
+fn reshuffler(key: &ReshuffleKey) -> Xoshiro256 {
+    let mut state = [0; 4];
+    for (word, halves) in state.iter_mut().zip(key.chunks(2)) {
+        *word = (u64::from(halves[0]) << 32) | u64::from(halves[1]);
+    }
+    if state == [0; 4] {
+        Xoshiro256::seed_from_u64(0)
+    } else {
+        Xoshiro256::from_state(state)
+    }
+}
+
+// The casino ritual around a shuffled shoe: burn a card, then play down to
+// the cut card and shuffle once the round in progress is over.
+impl Deck {
+    // The top card goes face down into the discards, unseen.
+    pub fn burn(&mut self) {
+        if let Option::Some(card) = self.remaining_cards.pop() {
+            self.burned_cards.push(card);
+        }
+    }
+
+    // Places the cut card so that `penetration` percent of the cards now in
+    // the shoe are dealt before it comes out.
+    pub fn place_cut_card(&mut self, penetration: u32) {
+        let cards = self.remaining_cards.len();
+        let dealt = cards * penetration.min(100) as usize / 100;
+        self.cut_card = Option::Some(cards - dealt);
+    }
+
+    // Cards left when the cut card comes out, if there is one.
+    pub fn cut_card(&self) -> Option<usize> {
+        self.cut_card
+    }
+
+    pub fn burned(&self) -> &[Card] {
+        &self.burned_cards
+    }
+
+    // Shuffles the discard tray and the burned cards back in under whatever
+    // is left with `shuffler`; the cards on the table stay where they are.
+    // The cut card goes to the top, so the shoe is replaced once the round in
+    // progress is over.
+    pub fn reshuffle_discards<S: Shuffler + ?Sized>(&mut self, shuffler: &mut S) {
+        let mut cards: Vec<Card> = self.discard_tray.drain(..).collect();
+        cards.append(&mut self.burned_cards);
+        shuffler.shuffle(&mut cards);
+        self.reshuffle_key = shuffler.reshuffle_key();
+        cards.append(&mut self.remaining_cards);
+        self.remaining_cards = cards;
+        self.cut_card = Option::Some(self.remaining_cards.len());
+    }
+
+    // The next card, shuffling the discards back in first if the shoe is
+    // empty and the rules allow it. The engine holds no shuffler while a
+    // round is dealt, so the reshuffle is keyed by what the table's shuffler
+    // drew when it shuffled the shoe. Every copy of the shoe reshuffles the
+    // same way, as `table::Round` needs.
+    pub fn draw_with(&mut self, empty_shoe: &EmptyShoe) -> Option<Card> {
+        if self.remaining_cards.is_empty() && *empty_shoe == EmptyShoe::Reshuffle {
+            let mut shuffler = reshuffler(&self.reshuffle_key);
+            self.reshuffle_discards(&mut shuffler);
+        }
+        self.draw_card()
+    }
+
+    // True once the cut card has come out, or when too few cards are left to
+    // deal another round. Cards are still dealt past the cut card, so the
+    // round in progress always finishes.
+    pub fn needs_shuffle(&self) -> bool {
+        let remaining = self.remaining_cards.len();
+        remaining < INITIAL_DEAL || self.cut_card.is_some_and(|cut_card| remaining <= cut_card)
+    }
+}
+
+const FAST_SHOE_CARDS: usize = 52 * MAX_DECKS as usize;
+
+// A shoe for the simulator's inner loop that never allocates once it is
+// built: the cards sit in a fixed array, dealing moves an index along it and
+// a reshuffle shuffles the array in place. Every card dealt stays where it
+// was, so what has been seen is the array up to the index.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FastShoe {
+    cards: [Card; FAST_SHOE_CARDS],
+    len: usize,
+    next: usize,
+    // The cut card comes out once this many cards have been dealt.
+    cut: usize,
+}
+
+impl FastShoe {
+    // In order, as a new deck comes out of its box; shuffle it before dealing.
+    pub fn new(decks: u32, penetration: u32) -> Result<FastShoe, BlackjackError> {
+        if decks == 0 || decks > MAX_DECKS {
+            return Err(BlackjackError::InvalidDeckCount(decks));
+        }
+        let len = 52 * decks as usize;
+        let mut cards = [Card { suit: CardSuit::Clubs, value: CardValue::Two }; FAST_SHOE_CARDS];
+        for (index, slot) in cards[..len].iter_mut().enumerate() {
+            *slot = Card::from_index((index % 52) as u8).expect("every index below 52 is a card");
+        }
+        let cut = len * penetration.min(100) as usize / 100;
+        Ok(FastShoe { cards, len, next: 0, cut })
+    }
+
+    // Every card goes back in, whatever has been dealt.
+    pub fn shuffle<S: crate::shuffle::Shuffler + ?Sized>(&mut self, shuffler: &mut S) {
+        shuffler.shuffle(&mut self.cards[..self.len]);
+        self.next = 0;
+    }
+
+    pub fn deal(&mut self) -> Option<Card> {
+        let card = *self.cards[..self.len].get(self.next)?;
+        self.next += 1;
+        Option::Some(card)
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.len - self.next
+    }
+
+    pub fn dealt(&self) -> &[Card] {
+        &self.cards[..self.next]
+    }
+
+    // As `Deck::needs_shuffle`.
+    pub fn needs_shuffle(&self) -> bool {
+        self.remaining() < INITIAL_DEAL || self.next >= self.cut
+    }
+
+    // The same shoe for the engine, to play a round through `GameState`,
+    // with the cards dealt so far in the discard tray.
+    pub fn to_deck(&self) -> Deck {
+        Deck {
+            remaining_cards: self.cards[self.next..self.len].iter().rev().cloned().collect(),
+            discard_tray: self.dealt().to_vec(),
+            burned_cards: Vec::new(),
+            cut_card: Option::Some(self.len - self.cut),
+            reshuffle_key: ReshuffleKey::default(),
+        }
+    }
+}
@@ -0,0 +1,262 @@
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "std")]
+use rand::{ChaChaRng, OsRng, Rng};
+
+use crate::{Card, Deck};
+
+// The secret a shoe reshuffles its discards with should it run dry in the
+// middle of a round.
+pub type ReshuffleKey = [u32; 8];
+
+// Anything that can put a shoe into a new order. Every random number
+// generator is one, shuffling uniformly; other models of a dealer's hands
+// can be dropped in wherever a deck is shuffled.
+pub trait Shuffler {
+    fn shuffle(&mut self, cards: &mut [Card]);
+
+    // Drawn as the shoe is shuffled, so a reshuffle is as unpredictable as
+    // the shuffle was and owes nothing to the discards everyone has seen.
+    // By default it is read off the order a deck of 52 different cards is
+    // shuffled into.
+    fn reshuffle_key(&mut self) -> ReshuffleKey {
+        let mut cards = Deck::with_decks(1).remaining_cards;
+        self.shuffle(&mut cards);
+        let mut key: ReshuffleKey = [0; 8];
+        for (index, card) in cards.iter().enumerate() {
+            let word = &mut key[index % key.len()];
+            *word = word.wrapping_mul(52).wrapping_add(u32::from(card.to_index()));
+        }
+        key
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Rng> Shuffler for R {
+    fn shuffle(&mut self, cards: &mut [Card]) {
+        fisher_yates(cards, self)
+    }
+
+    fn reshuffle_key(&mut self) -> ReshuffleKey {
+        self.gen()
+    }
+}
+
+// Walks from the back, swapping each card with one at or before it, so
+// every order is equally likely.
+#[cfg(feature = "std")]
+pub fn fisher_yates<R: Rng>(cards: &mut [Card], rng: &mut R) {
+    for index in (1..cards.len()).rev() {
+        let other = rng.gen_range(0, index + 1);
+        cards.swap(index, other);
+    }
+}
+
+// How the shoe is put in order between hands: a perfect uniform shuffle, or
+// a model of what a casino dealer's hands actually do, which leaves the
+// clumps shuffle trackers look for.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ShuffleModel {
+    Uniform,
+    Casino,
+}
+
+impl ShuffleModel {
+    pub const ALL_VALUES: [ShuffleModel; 2] = [ShuffleModel::Uniform, ShuffleModel::Casino];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShuffleModel::Uniform => "uniform",
+            ShuffleModel::Casino => "casino",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ShuffleModel> {
+        ShuffleModel::ALL_VALUES.iter().find(|model| model.name() == name).cloned()
+    }
+
+    #[cfg(feature = "std")]
+    pub fn shuffler<'a, R: Rng + 'a>(&self, rng: R) -> Box<dyn Shuffler + 'a> {
+        match self {
+            ShuffleModel::Uniform => Box::new(rng),
+            ShuffleModel::Casino => Box::new(CasinoShuffle { rng }),
+        }
+    }
+}
+
+// Where a shuffle's random numbers come from: the generator the table was
+// given, which a seed can replay, or the operating system, for tables where
+// nobody, the operator included, should be able to predict or replay a shoe.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ShuffleRng {
+    Standard,
+    Secure,
+}
+
+impl ShuffleRng {
+    pub const ALL_VALUES: [ShuffleRng; 2] = [ShuffleRng::Standard, ShuffleRng::Secure];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShuffleRng::Standard => "standard",
+            ShuffleRng::Secure => "secure",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ShuffleRng> {
+        ShuffleRng::ALL_VALUES.iter().find(|rng| rng.name() == name).cloned()
+    }
+}
+
+// Shuffles with ChaCha20 under a fresh key from the operating system every
+// time, so one shoe says nothing about the next and no state outlives it.
+#[cfg(feature = "std")]
+pub struct SecureShuffle {
+    os: OsRng,
+    model: ShuffleModel,
+}
+
+#[cfg(feature = "std")]
+impl SecureShuffle {
+    pub fn new(model: ShuffleModel) -> io::Result<SecureShuffle> {
+        Ok(SecureShuffle { os: OsRng::new()?, model })
+    }
+}
+
+#[cfg(feature = "std")]
+impl Shuffler for SecureShuffle {
+    fn shuffle(&mut self, cards: &mut [Card]) {
+        let rng: ChaChaRng = self.os.gen();
+        self.model.shuffler(rng).shuffle(cards)
+    }
+
+    fn reshuffle_key(&mut self) -> ReshuffleKey {
+        self.os.gen()
+    }
+}
+
+// One of the moves a dealer's shuffle is made of.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ShuffleStep {
+    Riffle,
+    StripCut,
+    Plug,
+    Cut,
+}
+
+// The routine of a shoe dealer: riffle, strip, riffle, plug and cut.
+// Riffles come before and after the strip cut; three leave a shoe far from
+// random, which is the point.
+pub const CASINO_ROUTINE: [ShuffleStep; 6] = [
+    ShuffleStep::Riffle,
+    ShuffleStep::Riffle,
+    ShuffleStep::StripCut,
+    ShuffleStep::Riffle,
+    ShuffleStep::Plug,
+    ShuffleStep::Cut,
+];
+
+#[cfg(feature = "std")]
+impl ShuffleStep {
+    // Works on anything laid out like a shoe, so the same step can move
+    // cards or, for tracking, the positions they started from.
+    pub fn apply<T: Clone, R: Rng>(&self, items: &mut [T], rng: &mut R) {
+        match self {
+            ShuffleStep::Riffle => riffle(items, rng),
+            ShuffleStep::StripCut => strip_cut(items, rng),
+            ShuffleStep::Plug => plug(items, rng),
+            ShuffleStep::Cut => cut(items, rng),
+        }
+    }
+}
+
+// Strip-cut packets run from a few cards to a small handful.
+#[cfg(feature = "std")]
+const STRIP_PACKET: (usize, usize) = (3, 9);
+// The plug moves up to a quarter of the shoe from the bottom into it, and
+// the final cut lands somewhere in the middle three fifths.
+#[cfg(feature = "std")]
+const PLUG_FRACTION: f64 = 0.25;
+#[cfg(feature = "std")]
+const CUT_RANGE: (f64, f64) = (0.2, 0.8);
+
+// `CASINO_ROUTINE`, step by step. The top of the shoe is the end of the
+// slice, where the deck deals from.
+#[cfg(feature = "std")]
+pub struct CasinoShuffle<R: Rng> {
+    rng: R,
+}
+
+#[cfg(feature = "std")]
+impl<R: Rng> Shuffler for CasinoShuffle<R> {
+    fn shuffle(&mut self, cards: &mut [Card]) {
+        for step in CASINO_ROUTINE.iter() {
+            step.apply(cards, &mut self.rng);
+        }
+    }
+
+    fn reshuffle_key(&mut self) -> ReshuffleKey {
+        self.rng.gen()
+    }
+}
+
+// The Gilbert-Shannon-Reeds model: split near the middle, then drop cards
+// from each half with odds in proportion to what is left in it.
+#[cfg(feature = "std")]
+pub fn riffle<T: Clone, R: Rng>(cards: &mut [T], rng: &mut R) {
+    let split = (0..cards.len()).filter(|_| rng.gen::<bool>()).count();
+    let (bottom, top) = cards.split_at(split);
+    let (bottom, top) = (bottom.to_vec(), top.to_vec());
+    let (mut from_bottom, mut from_top) = (0, 0);
+    for slot in cards.iter_mut() {
+        let bottom_left = bottom.len() - from_bottom;
+        let top_left = top.len() - from_top;
+        if rng.gen_range(0, bottom_left + top_left) < bottom_left {
+            *slot = bottom[from_bottom].clone();
+            from_bottom += 1;
+        } else {
+            *slot = top[from_top].clone();
+            from_top += 1;
+        }
+    }
+}
+
+// Pulls small packets off the top one after another onto a new pile, each
+// landing on the last, which reverses the order of the packets but not the
+// cards inside them.
+#[cfg(feature = "std")]
+pub fn strip_cut<T: Clone, R: Rng>(cards: &mut [T], rng: &mut R) {
+    let mut pile = Vec::with_capacity(cards.len());
+    let mut rest = cards.to_vec();
+    while !rest.is_empty() {
+        let size = rng.gen_range(STRIP_PACKET.0, STRIP_PACKET.1).min(rest.len());
+        let packet = rest.split_off(rest.len() - size);
+        pile.extend(packet);
+    }
+    cards.clone_from_slice(&pile);
+}
+
+// Takes a packet from the bottom and pushes it back in at a random depth.
+#[cfg(feature = "std")]
+pub fn plug<T: Clone, R: Rng>(cards: &mut [T], rng: &mut R) {
+    let largest = (cards.len() as f64 * PLUG_FRACTION) as usize;
+    if largest == 0 {
+        return;
+    }
+    let size = rng.gen_range(1, largest + 1);
+    let mut rest = cards.to_vec();
+    let packet: Vec<T> = rest.drain(..size).collect();
+    let depth = rng.gen_range(0, rest.len() + 1);
+    rest.splice(depth..depth, packet);
+    cards.clone_from_slice(&rest);
+}
+
+#[cfg(feature = "std")]
+pub fn cut<T, R: Rng>(cards: &mut [T], rng: &mut R) {
+    let low = (cards.len() as f64 * CUT_RANGE.0) as usize;
+    let high = (cards.len() as f64 * CUT_RANGE.1) as usize;
+    if high > low {
+        cards.rotate_left(rng.gen_range(low, high));
+    }
+}
@@ -0,0 +1,101 @@
+use std::ops::Range;
+
+use rand::Rng;
+
+use crate::shuffle::{ShuffleStep, Shuffler};
+use crate::{Card, Deck};
+
+// A bench for shuffle tracking: following a slug, a clump of cards such as
+// the tens and aces of a rich stretch of discards, through the dealer's
+// shuffle to where it lands in the next shoe. The shuffle is run on the
+// positions the cards started from as well as on the cards, so every step
+// can be asked where any card went. Positions count up from the bottom of
+// the shoe, 0, to the top, where the deck deals from.
+
+// Where the card that started at each position was after every step of a
+// shuffle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShuffleTrace {
+    pub steps: Vec<(ShuffleStep, Vec<usize>)>,
+}
+
+// Runs `routine` over `cards`, leaving them in the order a plain shuffle
+// with the same generator would, and traces it.
+pub fn trace_shuffle<R: Rng>(cards: &mut [Card], routine: &[ShuffleStep], rng: &mut R) -> ShuffleTrace {
+    let original = cards.to_vec();
+    // What started where: the card now at each position came from `order`'s
+    // entry for it.
+    let mut order: Vec<usize> = (0..cards.len()).collect();
+    let mut steps = Vec::new();
+    for step in routine {
+        step.apply(&mut order, rng);
+        let mut positions = vec![0; order.len()];
+        for (position, start) in order.iter().enumerate() {
+            positions[*start] = position;
+        }
+        steps.push((*step, positions));
+    }
+    for (slot, start) in cards.iter_mut().zip(order.iter()) {
+        *slot = original[*start];
+    }
+    ShuffleTrace { steps }
+}
+
+// The shoe as the dealer picks it up to shuffle, from the bottom: the
+// burned cards, then the discards in the order they went into the tray,
+// then whatever was never dealt, as it sat. The card at index `n` of the
+// discard tray starts from `deck.burned().len() + n`.
+pub fn picked_up(deck: &Deck) -> Vec<Card> {
+    let mut cards = deck.burned().to_vec();
+    cards.extend_from_slice(deck.discard_tray());
+    cards.extend_from_slice(&deck.remaining_cards);
+    cards
+}
+
+// Shuffles the last shoe's cards as `picked_up` gathers them into a new
+// one, tracing where each went. The new shoe has no cut card yet, and nothing
+// burned.
+pub fn reshuffle_traced<R: Rng>(deck: &Deck, routine: &[ShuffleStep], rng: &mut R) -> (Deck, ShuffleTrace) {
+    let mut cards = picked_up(deck);
+    let trace = trace_shuffle(&mut cards, routine, rng);
+    let reshuffle_key = rng.reshuffle_key();
+    let shoe = Deck { remaining_cards: cards, discard_tray: Vec::new(), burned_cards: Vec::new(), cut_card: Option::None, reshuffle_key };
+    (shoe, trace)
+}
+
+impl ShuffleTrace {
+    // Where the card that started at `start` was after step `step`,
+    // counting from 0; None past the last step or the shoe.
+    pub fn position_after(&self, step: usize, start: usize) -> Option<usize> {
+        self.steps.get(step)?.1.get(start).cloned()
+    }
+
+    // Where it ended up. A trace of no steps leaves every card in place.
+    pub fn position(&self, start: usize) -> Option<usize> {
+        match self.steps.last() {
+            Option::Some((_, positions)) => positions.get(start).cloned(),
+            Option::None => Option::Some(start),
+        }
+    }
+
+    // Where the cards that started in `slug` ended up, lowest first.
+    pub fn slug_positions(&self, slug: Range<usize>) -> Vec<usize> {
+        let mut positions: Vec<usize> = slug.filter_map(|start| self.position(start)).collect();
+        positions.sort_unstable();
+        positions
+    }
+
+    // The stretch of the shoe the slug is now spread over, from its lowest
+    // card to its highest. The tighter it is, the more the slug is worth
+    // tracking.
+    pub fn slug_span(&self, slug: Range<usize>) -> Option<Range<usize>> {
+        let positions = self.slug_positions(slug);
+        Option::Some(*positions.first()?..*positions.last()? + 1)
+    }
+
+    // How many of the slug's cards sit in `window` of the new shoe, such as
+    // the stretch a tracker means to bet into.
+    pub fn slug_cards_in(&self, slug: Range<usize>, window: Range<usize>) -> usize {
+        self.slug_positions(slug).iter().filter(|position| window.contains(position)).count()
+    }
+}
@@ -0,0 +1,545 @@
+use std::cmp::Ordering;
+use std::thread;
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng, StdRng};
+
+use crate::betting::{stake, BetContext, BetStrategy};
+use crate::counting::HI_LO;
+use crate::export::{outcome_name, HandRecord};
+use crate::fast_rng::Xoshiro256;
+use crate::promotions::RoundModifier;
+use crate::rules::GameRules;
+use crate::session_limits::SessionLimits;
+use crate::settlement::settle;
+use crate::shuffle::Shuffler;
+use crate::stats::ShoeStats;
+use crate::strategy::basic_strategy_for;
+use crate::{continue_with_game, deal_with_action, Action, Deck, GameState, Phase};
+
+#[derive(Debug, Default)]
+pub struct SimulationReport {
+    pub hands: u64,
+    pub wins: u64,
+    pub losses: u64,
+    pub pushes: u64,
+    pub net: i64,
+}
+
+// Combines the partial results of two workers.
+pub trait Merge {
+    fn merge(self, other: Self) -> Self;
+}
+
+impl Merge for SimulationReport {
+    fn merge(mut self, other: SimulationReport) -> SimulationReport {
+        self.hands += other.hands;
+        self.wins += other.wins;
+        self.losses += other.losses;
+        self.pushes += other.pushes;
+        self.net += other.net;
+        self
+    }
+}
+
+// Both halves of a pair of results are merged alongside each other.
+impl<A: Merge, B: Merge> Merge for (A, B) {
+    fn merge(self, other: (A, B)) -> (A, B) {
+        (self.0.merge(other.0), self.1.merge(other.1))
+    }
+}
+
+impl SimulationReport {
+    fn record(&mut self, state: &GameState, net: i64) {
+        self.hands += 1;
+        self.net += net;
+        match state {
+            GameState::GameWon(_) | GameState::BlackjackWin(_) => self.wins += 1,
+            GameState::GameLost(_) | GameState::Surrendered(_) => self.losses += 1,
+            GameState::Push(_) => self.pushes += 1,
+            GameState::Continuing(_) => (),
+        }
+    }
+
+    pub fn net_per_hand(&self) -> f64 {
+        if self.hands == 0 {
+            0.0
+        } else {
+            self.net as f64 / self.hands as f64
+        }
+    }
+}
+
+// Turns down insurance, then hits until the best total reaches 17 and stands.
+// A hand that has run the deck dry stands, as there is nothing left to draw,
+// unless it may not yet, as below 15 at pontoon; it hits then, and the
+// table's empty-shoe rule decides what comes of it.
+fn choose_action(state: &GameState) -> Action {
+    if state.player_state().phase == Phase::Insurance {
+        return Action::DeclineInsurance;
+    }
+    if state.player_state().deck.remaining() == 0 && state.legal_actions().contains(&Action::Stand) {
+        return Action::Stand;
+    }
+    let best_value = state.player_state().hand.total().unwrap_or(0);
+    if best_value < 17 {
+        Action::Hit
+    } else {
+        Action::Stand
+    }
+}
+
+// Every hand is dealt from a fresh deck from `new_deck`.
+pub fn simulate<F>(
+    hands: u64,
+    new_deck: F,
+    rules: &GameRules,
+    bet: u64,
+    modifiers: &[Box<dyn RoundModifier>],
+) -> SimulationReport
+where
+    F: FnMut() -> Deck,
+{
+    simulate_recorded(hands, new_deck, rules, bet, modifiers, 0, |_| ())
+}
+
+// As `simulate`, handing every finished hand to `record`, with bankrolls
+// counted from `bankroll`.
+pub fn simulate_recorded<F, R>(
+    hands: u64,
+    mut new_deck: F,
+    rules: &GameRules,
+    bet: u64,
+    modifiers: &[Box<dyn RoundModifier>],
+    bankroll: u64,
+    mut record: R,
+) -> SimulationReport
+where
+    F: FnMut() -> Deck,
+    R: FnMut(HandRecord),
+{
+    let mut report = SimulationReport::default();
+    for hand in 1..=hands {
+        let deck = new_deck();
+        let running_count = HI_LO.shoe_running_count(&deck);
+        let true_count = HI_LO.shoe_true_count(&deck);
+        let mut state = GameState::start_with_rules(deck, rules.clone());
+        let mut actions = Vec::new();
+        while continue_with_game(&state) {
+            let action = choose_action(&state);
+            state = deal_with_action(&action, state).expect("hitting and standing are always legal");
+            actions.push(action);
+        }
+        let net = settle(&state, bet, modifiers).net;
+        report.record(&state, net);
+        record(HandRecord {
+            hand,
+            player_cards: state.player_state().hand.to_vec(),
+            dealer_cards: state.player_state().dealer_cards(),
+            actions,
+            running_count,
+            true_count,
+            bet,
+            outcome: outcome_name(&state),
+            net,
+            bankroll: bankroll as i64 + report.net,
+        });
+    }
+    report
+}
+
+// Back-counting: watch a shoe without betting, sit down once the true
+// count reaches `enter_at` and, once it falls below `leave_below`, leave for
+// another table and a shoe that hasn't been counted yet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Wonging {
+    pub enter_at: f64,
+    pub leave_below: f64,
+}
+
+// As `simulate`, but each shoe from `new_shoe` is dealt down to its cut
+// card, as at a table, rather than replaced after every hand, and every
+// hand goes into the `ShoeStats` by the true count it was dealt at. The
+// shoe still in play at the end isn't counted as finished, nor is one left
+// when wonging. When wonging, `hands` is every hand dealt while at a table,
+// whether watched or played, and the report has only the ones played; the
+// watched hands are played the same way, standing in for the table. Every
+// hand is played by basic strategy, as the counter would play it, rather
+// than by `simulate`'s hit-to-17.
+pub fn simulate_shoes<F>(
+    hands: u64,
+    mut new_shoe: F,
+    rules: &GameRules,
+    bet: u64,
+    modifiers: &[Box<dyn RoundModifier>],
+    wonging: Option<&Wonging>,
+) -> (SimulationReport, ShoeStats)
+where
+    F: FnMut() -> Deck,
+{
+    let mut report = SimulationReport::default();
+    let mut shoe_stats = ShoeStats::default();
+    let mut shoe = new_shoe();
+    let mut cards = shoe.remaining();
+    let mut seated = wonging.is_none();
+    for _ in 0..hands {
+        if shoe.needs_shuffle() {
+            shoe_stats.record_shoe(cards.saturating_sub(shoe.remaining()), cards);
+            shoe = new_shoe();
+            cards = shoe.remaining();
+        }
+        let mut true_count = HI_LO.shoe_true_count(&shoe);
+        if let Option::Some(wonging) = wonging {
+            if seated && true_count < wonging.leave_below {
+                seated = false;
+                shoe_stats.departures += 1;
+                shoe = new_shoe();
+                cards = shoe.remaining();
+                true_count = HI_LO.shoe_true_count(&shoe);
+            }
+            seated = seated || true_count >= wonging.enter_at;
+        }
+        let mut state = GameState::start_with_rules(shoe, rules.clone());
+        while continue_with_game(&state) {
+            let action = basic_strategy_for(&state);
+            state = deal_with_action(&action, state).expect("basic strategy only picks legal actions");
+        }
+        if seated {
+            let net = settle(&state, bet, modifiers).net;
+            report.record(&state, net);
+            shoe_stats.record_hand(true_count, bet, net);
+        } else {
+            shoe_stats.observed += 1;
+        }
+        shoe = state.discard_hand();
+    }
+    (report, shoe_stats)
+}
+
+// The generator a simulation shuffles with: `StdRng`, or the much faster
+// but non-cryptographic xoshiro256**. Either replays a run for the same
+// seed, thread count and generator; the two deal different hands from the
+// same seed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SimulationRng {
+    Std,
+    Fast,
+}
+
+impl SimulationRng {
+    pub const ALL_VALUES: [SimulationRng; 2] = [SimulationRng::Std, SimulationRng::Fast];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SimulationRng::Std => "std",
+            SimulationRng::Fast => "fast",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<SimulationRng> {
+        SimulationRng::ALL_VALUES.iter().find(|rng| rng.name() == name).cloned()
+    }
+}
+
+// A generator a worker builds for itself from its seed.
+pub trait WorkerRng: Rng + Sized {
+    fn for_seed(seed: u64) -> Self;
+}
+
+impl WorkerRng for StdRng {
+    fn for_seed(seed: u64) -> StdRng {
+        SeedableRng::from_seed(&[seed as usize][..])
+    }
+}
+
+impl WorkerRng for Xoshiro256 {
+    fn for_seed(seed: u64) -> Xoshiro256 {
+        Xoshiro256::seed_from_u64(seed)
+    }
+}
+
+// Splits `hands` as evenly as possible across `threads` workers. Worker n
+// shuffles with its own generator seeded from `first_seed + n`, so a run is
+// reproducible for a given seed and thread count, and the partial results
+// are folded together with `Merge`.
+pub fn parallel_batch<G, R, F>(hands: u64, threads: usize, first_seed: u64, work: F) -> R
+where
+    G: WorkerRng,
+    R: Merge + Default + Send,
+    F: Fn(u64, &mut G) -> R + Sync,
+{
+    let threads = threads.max(1) as u64;
+    let work = &work;
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|worker| {
+                let share = hands / threads + if worker < hands % threads { 1 } else { 0 };
+                scope.spawn(move || {
+                    let mut rng = G::for_seed(first_seed.wrapping_add(worker));
+                    work(share, &mut rng)
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("simulation worker panicked"))
+            .fold(R::default(), R::merge)
+    })
+}
+
+#[derive(Debug)]
+pub struct SeedResult {
+    pub seed: u64,
+    pub report: SimulationReport,
+}
+
+// Per-seed results of running one configuration over many seeds, sorted from
+// the worst net per hand to the best.
+#[derive(Debug)]
+pub struct SweepReport {
+    pub results: Vec<SeedResult>,
+}
+
+impl SweepReport {
+    pub fn mean(&self) -> f64 {
+        if self.results.is_empty() {
+            return 0.0;
+        }
+        self.results.iter().map(|result| result.report.net_per_hand()).sum::<f64>() / self.results.len() as f64
+    }
+
+    pub fn standard_deviation(&self) -> f64 {
+        if self.results.len() < 2 {
+            return 0.0;
+        }
+        let mean = self.mean();
+        let squares: f64 = self.results
+            .iter()
+            .map(|result| (result.report.net_per_hand() - mean).powi(2))
+            .sum();
+        (squares / (self.results.len() - 1) as f64).sqrt()
+    }
+
+    pub fn standard_error(&self) -> f64 {
+        if self.results.is_empty() {
+            0.0
+        } else {
+            self.standard_deviation() / (self.results.len() as f64).sqrt()
+        }
+    }
+
+    // `fraction` between 0 and 1, nearest-rank.
+    pub fn percentile(&self, fraction: f64) -> f64 {
+        if self.results.is_empty() {
+            return 0.0;
+        }
+        let index = ((self.results.len() - 1) as f64 * fraction).round() as usize;
+        self.results[index].report.net_per_hand()
+    }
+}
+
+// The `seeds` seeds from `first_seed` on, wrapping round past `u64::MAX` as
+// `parallel_batch`'s worker seeds do, are spread over `threads` workers, and
+// every seed plays `hands` hands from its own seeded shuffle.
+pub fn seed_sweep<G: WorkerRng>(
+    first_seed: u64,
+    seeds: u64,
+    hands: u64,
+    rules: &GameRules,
+    bet: u64,
+    modifiers: &[Box<dyn RoundModifier>],
+    threads: usize,
+) -> SweepReport {
+    let threads = threads.max(1) as u64;
+    let mut results: Vec<SeedResult> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|worker| {
+                scope.spawn(move || {
+                    (0..seeds)
+                        .skip(worker as usize)
+                        .step_by(threads as usize)
+                        .map(|offset| {
+                            let seed = first_seed.wrapping_add(offset);
+                            let mut shuffler = rules.shuffle_model.shuffler(G::for_seed(seed));
+                            let new_deck = || {
+                                let mut deck = Deck::with_decks(rules.decks);
+                                deck.shuffle(shuffler.as_mut());
+                                deck
+                            };
+                            SeedResult { seed, report: simulate(hands, new_deck, rules, bet, modifiers) }
+                        })
+                        .collect::<Vec<SeedResult>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("simulation worker panicked"))
+            .collect()
+    });
+    results.sort_by(|a, b| {
+        a.report.net_per_hand()
+            .partial_cmp(&b.report.net_per_hand())
+            .unwrap_or(Ordering::Equal)
+    });
+    SweepReport { results }
+}
+
+// Many sessions of one betting strategy, each starting from the same
+// bankroll. Final bankrolls are kept as sums so workers can be merged.
+#[derive(Debug, Default)]
+pub struct BankrollReport {
+    pub sessions: u64,
+    // Sessions that went broke, whether they ended there or rebought.
+    pub ruined: u64,
+    pub hands: u64,
+    // Opening bets, before any doubles.
+    pub wagered: u64,
+    pub net: i64,
+    // Buy-ins after going broke, and the chips they brought, when sessions
+    // rebuy rather than end.
+    pub rebuys: u64,
+    pub rebought: u64,
+    // Less anything rebought, so a session that rebuys finishes where its
+    // play left it.
+    pub final_bankroll_total: f64,
+    pub final_bankroll_squares: f64,
+}
+
+impl Merge for BankrollReport {
+    fn merge(mut self, other: BankrollReport) -> BankrollReport {
+        self.sessions += other.sessions;
+        self.ruined += other.ruined;
+        self.hands += other.hands;
+        self.wagered += other.wagered;
+        self.net += other.net;
+        self.rebuys += other.rebuys;
+        self.rebought += other.rebought;
+        self.final_bankroll_total += other.final_bankroll_total;
+        self.final_bankroll_squares += other.final_bankroll_squares;
+        self
+    }
+}
+
+impl BankrollReport {
+    pub fn risk_of_ruin(&self) -> f64 {
+        if self.sessions == 0 {
+            0.0
+        } else {
+            self.ruined as f64 / self.sessions as f64
+        }
+    }
+
+    pub fn mean_final_bankroll(&self) -> f64 {
+        if self.sessions == 0 {
+            0.0
+        } else {
+            self.final_bankroll_total / self.sessions as f64
+        }
+    }
+
+    // Spread of where sessions finish.
+    pub fn final_bankroll_variance(&self) -> f64 {
+        if self.sessions < 2 {
+            return 0.0;
+        }
+        let mean = self.mean_final_bankroll();
+        let squares = self.final_bankroll_squares - self.sessions as f64 * mean * mean;
+        (squares / (self.sessions - 1) as f64).max(0.0)
+    }
+
+    // Net result for every chip put on the table.
+    pub fn return_on_wagers(&self) -> f64 {
+        if self.wagered == 0 {
+            0.0
+        } else {
+            self.net as f64 / self.wagered as f64
+        }
+    }
+}
+
+pub(crate) fn new_shoe(rules: &GameRules, shuffler: &mut dyn Shuffler) -> Deck {
+    let mut deck = Deck::with_decks(rules.decks);
+    deck.shuffle(shuffler);
+    deck.burn();
+    deck.place_cut_card(rules.penetration);
+    deck
+}
+
+// What a simulated session starts with and bets from.
+#[derive(Clone, Debug)]
+pub struct Stakes {
+    pub bankroll: u64,
+    pub base_bet: u64,
+    // Buy back in up to `bankroll` on going broke, rather than stop.
+    pub rebuy: bool,
+}
+
+// Plays `sessions` sessions of basic strategy from the stakes' bankroll,
+// staking what a fresh strategy from `new_strategy` says, each until one of
+// `limits` is reached; without a hand limit, a session only ends at one of
+// the others. The shoe lasts until the cut card, so counting strategies see
+// real counts. A session that can't cover the base bet ends there unless it
+// rebuys, and counts as ruined either way.
+pub fn bankroll_sessions<F, G: Rng>(
+    sessions: u64,
+    limits: &SessionLimits,
+    rules: &GameRules,
+    stakes: &Stakes,
+    new_strategy: F,
+    rng: &mut G,
+) -> BankrollReport
+where
+    F: Fn() -> Box<dyn BetStrategy>,
+{
+    let mut report = BankrollReport::default();
+    let mut shuffler = rules.shuffle_model.shuffler(rng);
+    for _ in 0..sessions {
+        let mut strategy = new_strategy();
+        let base_bet = stakes.base_bet;
+        let mut chips = stakes.bankroll;
+        let mut shoe = new_shoe(rules, shuffler.as_mut());
+        let mut net = 0;
+        let mut rebought = 0;
+        for played in 0.. {
+            if limits.reached(net, played, Duration::ZERO).is_some() {
+                break;
+            }
+            if chips < base_bet {
+                if !stakes.rebuy || stakes.bankroll < base_bet {
+                    break;
+                }
+                report.rebuys += 1;
+                rebought += stakes.bankroll - chips;
+                chips = stakes.bankroll;
+            }
+            if shoe.needs_shuffle() {
+                shoe = new_shoe(rules, shuffler.as_mut());
+            }
+            let context = BetContext { bankroll: chips, base_bet, true_count: HI_LO.shoe_true_count(&shoe) };
+            let bet = stake(strategy.as_mut(), &context);
+            let mut state = GameState::start_with_rules(shoe, rules.clone());
+            while continue_with_game(&state) {
+                let action = basic_strategy_for(&state);
+                state = deal_with_action(&action, state).expect("basic strategy only picks legal actions");
+            }
+            let hand_net = settle(&state, bet, &[]).net;
+            chips = (chips as i64 + hand_net).max(0) as u64;
+            net += hand_net;
+            strategy.record(hand_net);
+            report.hands += 1;
+            report.wagered += bet;
+            report.net += hand_net;
+            shoe = state.discard_hand();
+        }
+        report.sessions += 1;
+        if chips < base_bet || rebought > 0 {
+            report.ruined += 1;
+        }
+        report.rebought += rebought;
+        let finished = chips as f64 - rebought as f64;
+        report.final_bankroll_total += finished;
+        report.final_bankroll_squares += finished * finished;
+    }
+    report
+}
@@ -0,0 +1,381 @@
+use std::collections::HashMap;
+
+use crate::deviation::upcard_value;
+use crate::rules::{GameRules, HoleCard, Variant};
+use crate::shoe::RankCounts;
+use crate::{is_natural, Action, Card, CardValue};
+
+// Exact expected values for the decisions on a hand, found by enumerating
+// every way the remaining cards can come out. Values are in units of the
+// initial bet.
+//
+// The enumeration follows this engine's rules. When the dealer peeks, a
+// decision is only ever made once the hole card has been found not to make
+// a natural, so the values are conditioned on that; without the peek a
+// dealer natural takes the whole bet including any double. Doubles are
+// limited as the table's rules say. Splits are valued as two
+// independent hands drawn from the same shoe without resplitting, which is
+// the usual approximation; split aces and a split ace and ten are played as
+// the rules say.
+
+// Ten ranks by point value: ace, two to nine, then every ten-valued card.
+const RANKS: usize = 10;
+
+type Counts = [u32; RANKS];
+
+// Dealer outcomes: finishing on 17 to 21, busting, or a natural.
+const DEALER_OUTCOMES: usize = 7;
+const DEALER_BUST: usize = 5;
+const DEALER_NATURAL: usize = 6;
+
+fn points(card: &Card) -> u32 {
+    value_points(&card.value)
+}
+
+fn value_points(value: &CardValue) -> u32 {
+    match upcard_value(value) {
+        11 => 1,
+        value => value,
+    }
+}
+
+fn rank_index(points: u32) -> usize {
+    points as usize - 1
+}
+
+fn rank_points(index: usize) -> u32 {
+    index as u32 + 1
+}
+
+// A hand's running total, counting one ace as 11 when that doesn't bust.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+struct Total {
+    hard: u32,
+    has_ace: bool,
+}
+
+impl Total {
+    fn of(cards: &[u32]) -> Total {
+        Total { hard: cards.iter().sum(), has_ace: cards.contains(&1) }
+    }
+
+    fn add(self, points: u32) -> Total {
+        Total { hard: self.hard + points, has_ace: self.has_ace || points == 1 }
+    }
+
+    fn is_soft(self) -> bool {
+        self.has_ace && self.hard + 10 <= 21
+    }
+
+    fn best(self) -> u32 {
+        if self.is_soft() { self.hard + 10 } else { self.hard }
+    }
+
+    fn is_bust(self) -> bool {
+        self.hard > 21
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecisionValues {
+    pub stand: f64,
+    pub hit: f64,
+    pub double: Option<f64>,
+    pub split: Option<f64>,
+    pub surrender: Option<f64>,
+}
+
+impl DecisionValues {
+    pub fn all(&self) -> Vec<(Action, f64)> {
+        let mut values = vec![(Action::Stand, self.stand), (Action::Hit, self.hit)];
+        if let Option::Some(value) = self.double {
+            values.push((Action::DoubleDown, value));
+        }
+        if let Option::Some(value) = self.split {
+            values.push((Action::SplitCards, value));
+        }
+        if let Option::Some(value) = self.surrender {
+            values.push((Action::Surrender, value));
+        }
+        values
+    }
+
+    pub fn best(&self) -> (Action, f64) {
+        self.all()
+            .into_iter()
+            .fold((Action::Stand, f64::NEG_INFINITY), |best, candidate| if candidate.1 > best.1 { candidate } else { best })
+    }
+}
+
+// How the dealer's hand ends from an upcard, as chances that add up to one.
+// Where the dealer has peeked, a natural is already ruled out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DealerOutcomes {
+    // Finishing on 17, 18, 19, 20 and 21.
+    pub totals: [f64; 5],
+    pub bust: f64,
+    pub natural: f64,
+}
+
+struct Solver<'a> {
+    rules: &'a GameRules,
+    upcard: u32,
+    // The hole card is known not to make a natural.
+    peeked: bool,
+    dealer_memo: HashMap<(Counts, Total, bool), [f64; DEALER_OUTCOMES]>,
+    hit_memo: HashMap<(Counts, Total), f64>,
+}
+
+impl<'a> Solver<'a> {
+    fn dealer_stands(&self, total: Total) -> bool {
+        let best = total.best();
+        best > 17 || (best == 17 && !(total.is_soft() && self.rules.dealer_hits_soft_17))
+    }
+
+    // Outcome probabilities for a dealer holding `total`; `one_card` is set
+    // while only the upcard is out, so the next card can make a natural.
+    fn dealer(&mut self, counts: Counts, total: Total, one_card: bool) -> [f64; DEALER_OUTCOMES] {
+        if let Option::Some(outcomes) = self.dealer_memo.get(&(counts, total, one_card)) {
+            return *outcomes;
+        }
+        let mut outcomes = [0.0; DEALER_OUTCOMES];
+        if total.is_bust() {
+            outcomes[DEALER_BUST] = 1.0;
+        } else if !one_card && self.dealer_stands(total) {
+            outcomes[total.best() as usize - 17] = 1.0;
+        } else {
+            let mut remaining: u32 = counts.iter().sum();
+            let natural = |index: usize| one_card && total.add(rank_points(index)).best() == 21;
+            if self.peeked && one_card {
+                remaining -= (0..RANKS).filter(|&index| natural(index)).map(|index| counts[index]).sum::<u32>();
+            }
+            if remaining == 0 {
+                // An exhausted shoe leaves the dealer where they are, which
+                // below 17 is as good as a bust against any standing hand.
+                match total.best() {
+                    best if best >= 17 => outcomes[best as usize - 17] = 1.0,
+                    _ => outcomes[DEALER_BUST] = 1.0,
+                }
+            }
+            for index in 0..RANKS {
+                if counts[index] == 0 || (self.peeked && natural(index)) {
+                    continue;
+                }
+                let probability = counts[index] as f64 / remaining as f64;
+                let mut next_counts = counts;
+                next_counts[index] -= 1;
+                let next_total = total.add(rank_points(index));
+                if natural(index) {
+                    outcomes[DEALER_NATURAL] += probability;
+                    continue;
+                }
+                let next = self.dealer(next_counts, next_total, false);
+                for outcome in 0..DEALER_OUTCOMES {
+                    outcomes[outcome] += probability * next[outcome];
+                }
+            }
+        }
+        self.dealer_memo.insert((counts, total, one_card), outcomes);
+        outcomes
+    }
+
+    fn stand(&mut self, counts: Counts, total: Total) -> f64 {
+        if total.is_bust() {
+            return -1.0;
+        }
+        let upcard = Total::of(&[self.upcard]);
+        let outcomes = self.dealer(counts, upcard, true);
+        let player = total.best() as usize;
+        let mut value = outcomes[DEALER_BUST] - outcomes[DEALER_NATURAL];
+        for dealer in 17..=21 {
+            let probability = outcomes[dealer - 17];
+            if player > dealer {
+                value += probability;
+            } else if player < dealer {
+                value -= probability;
+            }
+        }
+        value
+    }
+
+    fn draws(&self, counts: Counts) -> Vec<(f64, Counts, u32)> {
+        let remaining: u32 = counts.iter().sum();
+        (0..RANKS)
+            .filter(|&index| counts[index] > 0)
+            .map(|index| {
+                let mut next_counts = counts;
+                next_counts[index] -= 1;
+                (counts[index] as f64 / remaining as f64, next_counts, rank_points(index))
+            })
+            .collect()
+    }
+
+    // The better of standing and hitting again, from here on.
+    fn best_after_hit(&mut self, counts: Counts, total: Total) -> f64 {
+        if total.is_bust() {
+            return -1.0;
+        }
+        let stand = self.stand(counts, total);
+        if total.best() == 21 {
+            return stand;
+        }
+        stand.max(self.hit(counts, total))
+    }
+
+    fn hit(&mut self, counts: Counts, total: Total) -> f64 {
+        if let Option::Some(value) = self.hit_memo.get(&(counts, total)) {
+            return *value;
+        }
+        let draws = self.draws(counts);
+        let value = if draws.is_empty() {
+            self.stand(counts, total)
+        } else {
+            draws
+                .into_iter()
+                .map(|(probability, next_counts, points)| probability * self.best_after_hit(next_counts, total.add(points)))
+                .sum()
+        };
+        self.hit_memo.insert((counts, total), value);
+        value
+    }
+
+    fn double(&mut self, counts: Counts, total: Total) -> f64 {
+        let draws = self.draws(counts);
+        if draws.is_empty() {
+            return 2.0 * self.stand(counts, total);
+        }
+        draws
+            .into_iter()
+            .map(|(probability, next_counts, points)| probability * 2.0 * self.stand(next_counts, total.add(points)))
+            .sum()
+    }
+
+    // What a natural is worth against this upcard.
+    fn natural(&mut self, counts: Counts) -> f64 {
+        let dealer = self.dealer(counts, Total::of(&[self.upcard]), true);
+        let payout = self.rules.blackjack_numerator as f64 / self.rules.blackjack_denominator as f64;
+        payout * (1.0 - dealer[DEALER_NATURAL])
+    }
+
+    // One of the two split hands: it takes a card, then is played as well
+    // as possible, doubling if the rules allow it after a split. Aces may
+    // only take one card and stand.
+    fn split_hand(&mut self, counts: Counts, points: u32) -> f64 {
+        self.draws(counts)
+            .into_iter()
+            .map(|(probability, next_counts, drawn)| {
+                let total = Total::of(&[points, drawn]);
+                let value = if total.best() == 21 && self.rules.blackjack_after_split {
+                    self.natural(next_counts)
+                } else if points == 1 && self.rules.split_aces_one_card {
+                    self.stand(next_counts, total)
+                } else if self.rules.double_after_split && self.rules.double_on.allows(total.best()) {
+                    let double = self.double(next_counts, total);
+                    self.best_after_hit(next_counts, total).max(double)
+                } else {
+                    self.best_after_hit(next_counts, total)
+                };
+                probability * value
+            })
+            .sum()
+    }
+}
+
+fn counts_of(shoe: &RankCounts) -> Counts {
+    let mut counts = [0; RANKS];
+    for (value, count) in shoe.iter() {
+        counts[rank_index(value_points(value))] += count;
+    }
+    counts
+}
+
+fn solver<'a>(dealer_upcard: &Card, rules: &'a GameRules) -> Solver<'a> {
+    let upcard = points(dealer_upcard);
+    Solver {
+        rules,
+        upcard,
+        peeked: rules.peek
+            && rules.hole_card == HoleCard::Dealt
+            && rules.variant == Variant::Blackjack
+            && (upcard == 1 || upcard == 10),
+        dealer_memo: HashMap::new(),
+        hit_memo: HashMap::new(),
+    }
+}
+
+// `shoe` counts every card the player hasn't seen, including the dealer's
+// hole card; `hand` and `dealer_upcard` must not be in it.
+pub fn solve(hand: &[Card], dealer_upcard: &Card, shoe: &RankCounts, rules: &GameRules) -> DecisionValues {
+    solve_from(&mut solver(dealer_upcard, rules), hand, counts_of(shoe))
+}
+
+// Several hands against one upcard, sharing what is worked out along the
+// way, which is far quicker than solving them one by one. Here `shoe`
+// counts every card but the upcard, and each hand's cards are taken out of
+// it in turn.
+pub fn solve_each(hands: &[Vec<Card>], dealer_upcard: &Card, shoe: &RankCounts, rules: &GameRules) -> Vec<DecisionValues> {
+    let mut solver = solver(dealer_upcard, rules);
+    hands
+        .iter()
+        .map(|hand| {
+            let mut counts = counts_of(shoe);
+            for card in hand {
+                let count = &mut counts[rank_index(points(card))];
+                *count = count.saturating_sub(1);
+            }
+            solve_from(&mut solver, hand, counts)
+        })
+        .collect()
+}
+
+fn solve_from(solver: &mut Solver, hand: &[Card], counts: Counts) -> DecisionValues {
+    let rules = solver.rules;
+    let hand_points: Vec<u32> = hand.iter().map(points).collect();
+    let total = Total::of(&hand_points);
+    let two_cards = hand.len() == 2;
+
+    let stand = if is_natural(hand) {
+        solver.natural(counts)
+    } else {
+        solver.stand(counts, total)
+    };
+    let hit = solver.hit(counts, total);
+    let double = if two_cards && rules.double_on.allows(total.best()) {
+        Option::Some(solver.double(counts, total))
+    } else {
+        Option::None
+    };
+    let split = if two_cards && hand_points[0] == hand_points[1] && rules.max_split_hands > 1 {
+        Option::Some(2.0 * solver.split_hand(counts, hand_points[0]))
+    } else {
+        Option::None
+    };
+    let surrender = if two_cards && rules.surrender { Option::Some(-0.5) } else { Option::None };
+    DecisionValues { stand, hit, double, split, surrender }
+}
+
+// With `shoe` as for `solve`.
+pub fn dealer_outcomes(dealer_upcard: &Card, shoe: &RankCounts, rules: &GameRules) -> DealerOutcomes {
+    let mut solver = solver(dealer_upcard, rules);
+    let outcomes = solver.dealer(counts_of(shoe), Total::of(&[solver.upcard]), true);
+    DealerOutcomes {
+        totals: [outcomes[0], outcomes[1], outcomes[2], outcomes[3], outcomes[4]],
+        bust: outcomes[DEALER_BUST],
+        natural: outcomes[DEALER_NATURAL],
+    }
+}
+
+// The insurance decision against a dealer ace, priced by the chance that
+// the hole card, one of the cards in `shoe`, is worth ten. Insurance is a
+// side bet of half the stake paying 2 to 1, so it is valued apart from the
+// hand and declining it is worth nothing; even money is a sure 1 against
+// the blackjack payout when the dealer doesn't have one.
+pub fn insurance_values(natural: bool, shoe: &RankCounts, rules: &GameRules) -> Vec<(Action, f64)> {
+    let ten = counts_of(shoe)[rank_index(10)] as f64 / shoe.total().max(1) as f64;
+    if natural {
+        let payout = rules.blackjack_numerator as f64 / rules.blackjack_denominator as f64;
+        vec![(Action::EvenMoney, 1.0), (Action::DeclineInsurance, (1.0 - ten) * payout)]
+    } else {
+        vec![(Action::Insurance, 0.5 * (3.0 * ten - 1.0)), (Action::DeclineInsurance, 0.0)]
+    }
+}
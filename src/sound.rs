@@ -0,0 +1,69 @@
+#[cfg(feature = "audio")]
+use std::io::{self, Write};
+
+use crate::events::outcome_of;
+use crate::GameState;
+
+// The moments a frontend might want to make a noise about. The engine only
+// says what happened; whether it beeps, plays a sample or stays quiet is up
+// to the player a frontend plugs in.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Sound {
+    CardDealt,
+    HoleCardTurned,
+    Won,
+    Blackjack,
+    Lost,
+    Bust,
+    Push,
+}
+
+pub trait SoundPlayer {
+    fn play(&self, sound: &Sound);
+}
+
+pub struct Silent;
+
+impl SoundPlayer for Silent {
+    fn play(&self, _sound: &Sound) {}
+}
+
+// Rings the terminal bell for the sounds worth interrupting for: a win, a
+// blackjack and a bust. A player backed by an audio library, such as rodio,
+// plugs in through the same trait.
+#[cfg(feature = "audio")]
+pub struct TerminalBell;
+
+#[cfg(feature = "audio")]
+impl SoundPlayer for TerminalBell {
+    fn play(&self, sound: &Sound) {
+        let rings = match sound {
+            Sound::Blackjack => 2,
+            Sound::Won | Sound::Bust => 1,
+            _ => 0,
+        };
+        let mut out = io::stdout();
+        for _ in 0..rings {
+            let _ = out.write_all(b"\x07");
+        }
+        let _ = out.flush();
+    }
+}
+
+// The player the audio feature selects: the bell when it is built in, and
+// silence otherwise.
+pub fn default_player() -> Box<dyn SoundPlayer> {
+    #[cfg(feature = "audio")]
+    {
+        Box::new(TerminalBell)
+    }
+    #[cfg(not(feature = "audio"))]
+    {
+        Box::new(Silent)
+    }
+}
+
+// How a finished hand sounds; a hand still in play makes none.
+pub fn outcome_sound(state: &GameState) -> Option<Sound> {
+    outcome_of(state).map(|outcome| outcome.sound())
+}
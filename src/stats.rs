@@ -0,0 +1,223 @@
+use crate::currency::Currency;
+use crate::settlement::Settlement;
+use crate::simulation::Merge;
+use crate::GameState;
+
+// Running totals for one sitting at the table, updated after every hand.
+#[derive(Debug, Default)]
+pub struct SessionStats {
+    pub hands: u64,
+    pub wins: u64,
+    pub losses: u64,
+    pub pushes: u64,
+    pub blackjacks: u64,
+    pub busts: u64,
+    pub surrenders: u64,
+    pub net: i64,
+    pub biggest_win: i64,
+    pub biggest_loss: i64,
+    // Positive while on a run of wins, negative on a run of losses. Pushes
+    // neither extend nor break a run.
+    pub streak: i64,
+    pub longest_winning_streak: u64,
+    pub longest_losing_streak: u64,
+    // Tips handed to the dealer, and what they came to. They are an expense
+    // of the session rather than a result of the hands, so `net` leaves
+    // them out.
+    pub tips: u64,
+    pub tipped: u64,
+}
+
+impl SessionStats {
+    pub fn new() -> SessionStats {
+        SessionStats::default()
+    }
+
+    pub fn record(&mut self, state: &GameState, settlement: &Settlement) {
+        self.hands += 1;
+        self.net += settlement.net;
+        self.biggest_win = self.biggest_win.max(settlement.net);
+        self.biggest_loss = self.biggest_loss.min(settlement.net);
+        match state {
+            GameState::GameWon(_) | GameState::BlackjackWin(_) => {
+                self.wins += 1;
+                self.streak = if self.streak > 0 { self.streak + 1 } else { 1 };
+                self.longest_winning_streak = self.longest_winning_streak.max(self.streak as u64);
+            },
+            GameState::GameLost(_) | GameState::Surrendered(_) => {
+                self.losses += 1;
+                self.streak = if self.streak < 0 { self.streak - 1 } else { -1 };
+                self.longest_losing_streak = self.longest_losing_streak.max(-self.streak as u64);
+            },
+            GameState::Push(_) => self.pushes += 1,
+            GameState::Continuing(_) => (),
+        }
+        match state {
+            GameState::BlackjackWin(_) => self.blackjacks += 1,
+            GameState::Surrendered(_) => self.surrenders += 1,
+            GameState::GameLost(player_state) if player_state.hand.is_bust() => self.busts += 1,
+            _ => (),
+        }
+    }
+
+    pub fn record_tip(&mut self, tip: u64) {
+        self.tips += 1;
+        self.tipped += tip;
+    }
+
+    pub fn current_streak(&self) -> String {
+        match self.streak {
+            0 => "none".to_string(),
+            1 => "1 win".to_string(),
+            -1 => "1 loss".to_string(),
+            wins if wins > 0 => format!("{} wins", wins),
+            losses => format!("{} losses", -losses),
+        }
+    }
+
+    pub fn render(&self) -> String {
+        self.render_in(&Currency::default())
+    }
+
+    // Tips get lines of their own once there have been any.
+    pub fn render_in(&self, currency: &Currency) -> String {
+        let mut lines = vec![
+            format!("Hands played:    {}", self.hands),
+            format!("Won/lost/pushed: {}/{}/{}", self.wins, self.losses, self.pushes),
+            format!("Blackjacks:      {}", self.blackjacks),
+            format!("Busts:           {}", self.busts),
+            format!("Surrenders:      {}", self.surrenders),
+            format!("Biggest win:     {}", currency.format_signed(self.biggest_win)),
+            format!("Biggest loss:    {}", currency.format_signed(self.biggest_loss)),
+            format!("Net result:      {}", currency.format_signed(self.net)),
+            format!("Current streak:  {}", self.current_streak()),
+            format!(
+                "Longest streaks: {} won, {} lost",
+                self.longest_winning_streak, self.longest_losing_streak
+            ),
+        ];
+        if self.tips > 0 {
+            lines.push(format!("Dealer tips:     {} for {}", self.tips, currency.format(self.tipped as i64)));
+            lines.push(format!("Net after tips:  {}", currency.format_signed(self.net - self.tipped as i64)));
+        }
+        lines.join("\n")
+    }
+}
+
+// True counts below and above these share the end buckets.
+pub const LOWEST_COUNT_BUCKET: i32 = -5;
+pub const HIGHEST_COUNT_BUCKET: i32 = 6;
+const COUNT_BUCKETS: usize = (HIGHEST_COUNT_BUCKET - LOWEST_COUNT_BUCKET + 1) as usize;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CountBucket {
+    pub hands: u64,
+    pub wagered: u64,
+    pub net: i64,
+}
+
+impl CountBucket {
+    // Won or lost for every chip bet at this count.
+    pub fn return_on_wagers(&self) -> f64 {
+        if self.wagered == 0 {
+            0.0
+        } else {
+            self.net as f64 / self.wagered as f64
+        }
+    }
+}
+
+// What a counter sees over shoes dealt down to the cut card: the hands
+// bucketed by the Hi-Lo true count they were dealt at, rounded down, and
+// how deep into each shoe the dealing went.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ShoeStats {
+    // From `LOWEST_COUNT_BUCKET` up.
+    pub buckets: [CountBucket; COUNT_BUCKETS],
+    // Shoes dealt until they were replaced, and the cards dealt from them
+    // out of the cards they held after the burn.
+    pub shoes: u64,
+    pub cards_dealt: u64,
+    pub cards_in_shoes: u64,
+    // When back-counting, the hands watched without a bet and the tables
+    // left when the count fell. Only hands played go into the buckets.
+    pub observed: u64,
+    pub departures: u64,
+}
+
+impl Merge for ShoeStats {
+    fn merge(mut self, other: ShoeStats) -> ShoeStats {
+        for (bucket, other) in self.buckets.iter_mut().zip(other.buckets.iter()) {
+            bucket.hands += other.hands;
+            bucket.wagered += other.wagered;
+            bucket.net += other.net;
+        }
+        self.shoes += other.shoes;
+        self.cards_dealt += other.cards_dealt;
+        self.cards_in_shoes += other.cards_in_shoes;
+        self.observed += other.observed;
+        self.departures += other.departures;
+        self
+    }
+}
+
+impl ShoeStats {
+    pub fn bucket_of(true_count: f64) -> usize {
+        let count = (true_count.floor() as i32).clamp(LOWEST_COUNT_BUCKET, HIGHEST_COUNT_BUCKET);
+        (count - LOWEST_COUNT_BUCKET) as usize
+    }
+
+    pub fn record_hand(&mut self, true_count: f64, bet: u64, net: i64) {
+        let bucket = &mut self.buckets[ShoeStats::bucket_of(true_count)];
+        bucket.hands += 1;
+        bucket.wagered += bet;
+        bucket.net += net;
+    }
+
+    pub fn record_shoe(&mut self, dealt: usize, cards: usize) {
+        self.shoes += 1;
+        self.cards_dealt += dealt as u64;
+        self.cards_in_shoes += cards as u64;
+    }
+
+    // The share of each shoe dealt, taken over all of them.
+    pub fn penetration(&self) -> f64 {
+        if self.cards_in_shoes == 0 {
+            0.0
+        } else {
+            self.cards_dealt as f64 / self.cards_in_shoes as f64
+        }
+    }
+
+    pub fn hands(&self) -> u64 {
+        self.buckets.iter().map(|bucket| bucket.hands).sum()
+    }
+
+    // A line for each count that came up.
+    pub fn render(&self) -> String {
+        let hands = self.hands().max(1) as f64;
+        let mut lines = vec![format!("Shoes finished:  {}, {:.1}% dealt on average", self.shoes, self.penetration() * 100.0)];
+        if self.observed > 0 || self.departures > 0 {
+            lines.push(format!("Hands watched:   {} without a bet, {} played", self.observed, self.hands()));
+            lines.push(format!("Tables left:     {}", self.departures));
+        }
+        lines.push(format!("{:<12}{:>10}{:>8}{:>14}", "True count", "Hands", "Share", "Per chip bet"));
+        for (index, bucket) in self.buckets.iter().enumerate().filter(|(_, bucket)| bucket.hands > 0) {
+            let count = LOWEST_COUNT_BUCKET + index as i32;
+            let label = match count {
+                LOWEST_COUNT_BUCKET => format!("{} or less", count),
+                HIGHEST_COUNT_BUCKET => format!("+{} or more", count),
+                count if count > 0 => format!("+{}", count),
+                count => count.to_string(),
+            };
+            lines.push(format!(
+                "{:<12}{:>10}{:>7.1}%{:>+14.4}",
+                label,
+                bucket.hands,
+                bucket.hands as f64 / hands * 100.0,
+                bucket.return_on_wagers()
+            ));
+        }
+        lines.join("\n")
+    }
+}
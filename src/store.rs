@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rust_blackjack::protocol::Standing;
+
+// What the server keeps across restarts. Seats can't outlive the process,
+// since their connections don't, and the server keeps no bankrolls, so what
+// is kept is the tables opened from the lobby, which are opened again when
+// the server next starts, and each player's results for the leaderboard.
+// The file store keeps it to std; a SQLite or Redis store would be one more
+// implementation of `SessionStore`.
+
+// A table as it was asked for in the lobby, so a table on the house rules
+// follows the server's rules when they change.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SavedTable {
+    pub name: String,
+    pub rules: Option<String>,
+    pub decks: Option<u32>,
+}
+
+pub trait SessionStore: Send {
+    fn tables(&self) -> Vec<SavedTable>;
+    fn save_table(&mut self, table: SavedTable) -> io::Result<()>;
+    // Best first: the most won, then the fewest hands it took.
+    fn standings(&self) -> Vec<Standing>;
+    // Adds a round's hands to the players' results, each the player's
+    // name and what their hand won or lost.
+    fn record_hands(&mut self, hands: &[(String, i64)]) -> io::Result<()>;
+}
+
+// Forgets everything when the server stops.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    tables: Vec<SavedTable>,
+    players: BTreeMap<String, Standing>,
+}
+
+impl SessionStore for MemoryStore {
+    fn tables(&self) -> Vec<SavedTable> {
+        self.tables.clone()
+    }
+
+    fn save_table(&mut self, table: SavedTable) -> io::Result<()> {
+        self.tables.push(table);
+        Ok(())
+    }
+
+    fn standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = self.players.values().cloned().collect();
+        standings.sort_by(|a, b| b.net.cmp(&a.net).then(a.hands.cmp(&b.hands)));
+        standings
+    }
+
+    fn record_hands(&mut self, hands: &[(String, i64)]) -> io::Result<()> {
+        for (name, net) in hands {
+            let standing = self.players.entry(name.clone()).or_insert_with(|| Standing { name: name.clone(), hands: 0, net: 0 });
+            standing.hands += 1;
+            standing.net += net;
+        }
+        Ok(())
+    }
+}
+
+pub fn render_standings(standings: &[Standing]) -> String {
+    if standings.is_empty() {
+        return "Nobody has played a hand yet".to_string();
+    }
+    let mut lines = vec![format!("{:<4} {:<24} {:>8} {:>10}", "", "Player", "Hands", "Net")];
+    for (place, standing) in standings.iter().enumerate() {
+        lines.push(format!("{:<4} {:<24} {:>8} {:>+10}", format!("{}.", place + 1), standing.name, standing.hands, standing.net));
+    }
+    lines.join("\n")
+}
+
+// Tab separated text, a record to a line with its kind first, rewritten
+// whole on each change.
+#[derive(Debug)]
+pub struct FileStore {
+    path: PathBuf,
+    memory: MemoryStore,
+}
+
+fn parse_player(fields: &[&str]) -> Option<Standing> {
+    match fields {
+        [name, hands, net] => Option::Some(Standing { name: name.to_string(), hands: hands.parse().ok()?, net: net.parse().ok()? }),
+        _ => Option::None,
+    }
+}
+
+fn parse_table(fields: &[&str]) -> Option<SavedTable> {
+    let optional = |index: usize| fields.get(index).filter(|field| !field.is_empty());
+    Option::Some(SavedTable {
+        name: fields.first().filter(|name| !name.is_empty())?.to_string(),
+        rules: optional(1).map(|rules| rules.to_string()),
+        decks: match optional(2) {
+            Option::Some(decks) => Option::Some(decks.parse().ok()?),
+            Option::None => Option::None,
+        },
+    })
+}
+
+impl FileStore {
+    // A file that isn't there yet is an empty store.
+    pub fn open(path: &Path) -> io::Result<FileStore> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(error),
+        };
+        let mut memory = MemoryStore::default();
+        for line in contents.lines() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            // Lines this version doesn't know are skipped rather than
+            // failing the whole store.
+            match fields.split_first() {
+                Option::Some((&"table", fields)) => memory.tables.extend(parse_table(fields)),
+                Option::Some((&"player", fields)) => {
+                    if let Option::Some(standing) = parse_player(fields) {
+                        memory.players.insert(standing.name.clone(), standing);
+                    }
+                },
+                _ => {},
+            }
+        }
+        Ok(FileStore { path: path.to_path_buf(), memory })
+    }
+
+    // Written beside the store and renamed over it, so a server stopped
+    // mid-write leaves the old store rather than half of the new one.
+    fn write(&self) -> io::Result<()> {
+        let mut contents = String::new();
+        for table in &self.memory.tables {
+            let decks = table.decks.map(|decks| decks.to_string()).unwrap_or_default();
+            contents.push_str(&format!("table\t{}\t{}\t{}\n", table.name, table.rules.as_deref().unwrap_or(""), decks));
+        }
+        for standing in self.memory.players.values() {
+            contents.push_str(&format!("player\t{}\t{}\t{}\n", standing.name, standing.hands, standing.net));
+        }
+        if let Option::Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let staged = self.path.with_extension("tmp");
+        fs::write(&staged, contents)?;
+        fs::rename(&staged, &self.path)
+    }
+}
+
+impl SessionStore for FileStore {
+    fn tables(&self) -> Vec<SavedTable> {
+        self.memory.tables()
+    }
+
+    fn save_table(&mut self, table: SavedTable) -> io::Result<()> {
+        self.memory.save_table(table)?;
+        self.write()
+    }
+
+    fn standings(&self) -> Vec<Standing> {
+        self.memory.standings()
+    }
+
+    fn record_hands(&mut self, hands: &[(String, i64)]) -> io::Result<()> {
+        self.memory.record_hands(hands)?;
+        self.write()
+    }
+}
@@ -0,0 +1,268 @@
+use crate::counting::{CountingSystem, HI_LO};
+use crate::deviation::{triggered, upcard_value};
+use crate::rules::{Variant, PONTOON_MINIMUM_STICK};
+use crate::{Action, Card, GameState, HandTotals, Phase, PlayerState};
+
+// Multi-deck basic strategy, dealer stands on soft 17, double after split
+// allowed unless the rules say otherwise, no surrender.
+
+fn is_pair(hand: &[Card]) -> bool {
+    hand.len() == 2 && upcard_value(&hand[0].value) == upcard_value(&hand[1].value)
+}
+
+// The three parts of the chart a two-card hand is read from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum HandCategory {
+    Hard,
+    Soft,
+    Pair,
+}
+
+impl HandCategory {
+    pub const ALL_VALUES: [HandCategory; 3] = [HandCategory::Hard, HandCategory::Soft, HandCategory::Pair];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HandCategory::Hard => "hard totals",
+            HandCategory::Soft => "soft totals",
+            HandCategory::Pair => "pairs",
+        }
+    }
+
+    pub fn of(hand: &[Card]) -> HandCategory {
+        if is_pair(hand) {
+            HandCategory::Pair
+        } else if HandTotals::of(hand).is_soft() {
+            HandCategory::Soft
+        } else {
+            HandCategory::Hard
+        }
+    }
+}
+
+// Without doubling after a split, the small pairs that split mostly to
+// double the hands they make are split less often.
+fn pair_action(pair_value: u32, dealer: u32, double_after_split: bool) -> Option<Action> {
+    let split = match pair_value {
+        11 | 8 => true,
+        10 | 5 => false,
+        9 => dealer != 7 && dealer != 10 && dealer != 11,
+        7 => dealer <= 7,
+        3 | 2 if double_after_split => dealer <= 7,
+        3 | 2 => (4..=7).contains(&dealer),
+        6 if double_after_split => dealer <= 6,
+        6 => (3..=6).contains(&dealer),
+        4 => double_after_split && (dealer == 5 || dealer == 6),
+        _ => false,
+    };
+    if split {
+        Option::Some(Action::SplitCards)
+    } else {
+        Option::None
+    }
+}
+
+fn soft_action(total: u32, dealer: u32, can_double: bool) -> Action {
+    let double_range = match total {
+        19..=21 => return Action::Stand,
+        18 => 3..=6,
+        17 => 3..=6,
+        15 | 16 => 4..=6,
+        _ => 5..=6,
+    };
+    if double_range.contains(&dealer) {
+        if can_double {
+            Action::DoubleDown
+        } else if total == 18 {
+            Action::Stand
+        } else {
+            Action::Hit
+        }
+    } else if total == 18 && dealer <= 8 {
+        Action::Stand
+    } else {
+        Action::Hit
+    }
+}
+
+fn hard_action(total: u32, dealer: u32, can_double: bool) -> Action {
+    let double = |allowed: bool| if allowed && can_double { Action::DoubleDown } else { Action::Hit };
+    match total {
+        17..=21 => Action::Stand,
+        13..=16 => if dealer <= 6 { Action::Stand } else { Action::Hit },
+        12 => if (4..=6).contains(&dealer) { Action::Stand } else { Action::Hit },
+        11 => double(dealer <= 10),
+        10 => double(dealer <= 9),
+        9 => double((3..=6).contains(&dealer)),
+        _ => Action::Hit,
+    }
+}
+
+pub fn basic_strategy(hand: &[Card], dealer_upcard: &Card) -> Action {
+    chart_action(hand, dealer_upcard, true, hand.len() == 2, true)
+}
+
+fn chart_action(
+    hand: &[Card],
+    dealer_upcard: &Card,
+    can_split: bool,
+    can_double: bool,
+    double_after_split: bool,
+) -> Action {
+    let dealer = upcard_value(&dealer_upcard.value);
+
+    if can_split && is_pair(hand) {
+        if let Option::Some(action) = pair_action(upcard_value(&hand[0].value), dealer, double_after_split) {
+            return action;
+        }
+    }
+
+    let totals = HandTotals::of(hand);
+    match totals.best() {
+        Option::Some(total) if totals.is_soft() => soft_action(total, dealer, can_double),
+        Option::Some(total) => hard_action(total, dealer, can_double),
+        Option::None => Action::Stand,
+    }
+}
+
+// Pontoon shows no dealer card to play against, so the hand is played on
+// its own: buy on 10 or 11, twist to 15, keep twisting soft hands below 19
+// and four-card hands with little risk of busting, for the five card trick.
+fn pontoon_action(player_state: &PlayerState, legal_actions: &[Action]) -> Action {
+    let soft = player_state.hand.is_soft();
+    let total = player_state.hand.total().unwrap_or(0);
+    let action = if legal_actions.contains(&Action::DoubleDown) && !soft && (total == 10 || total == 11) {
+        Action::DoubleDown
+    } else if total < PONTOON_MINIMUM_STICK || (soft && total < 19) || (player_state.hand.card_count() == 4 && total <= 16) {
+        Action::Hit
+    } else {
+        Action::Stand
+    };
+    if legal_actions.contains(&action) {
+        action
+    } else {
+        Action::Hit
+    }
+}
+
+// The chart play for the hand in `state`, restricted to what is legal right
+// now: insurance is always declined, a pair that can't be split is played by
+// its total, and a double the table doesn't allow becomes the chart's
+// fallback play.
+pub fn basic_strategy_for(state: &GameState) -> Action {
+    let player_state = state.player_state();
+    let legal_actions = state.legal_actions();
+    if player_state.phase == Phase::Insurance {
+        return Action::DeclineInsurance;
+    }
+    if player_state.rules.variant == Variant::Pontoon {
+        return pontoon_action(player_state, &legal_actions);
+    }
+    let upcard = match player_state.dealer_upcard() {
+        Option::Some(upcard) => upcard,
+        Option::None => return Action::Stand,
+    };
+    let action = chart_action(
+        &player_state.hand,
+        upcard,
+        legal_actions.contains(&Action::SplitCards),
+        legal_actions.contains(&Action::DoubleDown),
+        player_state.rules.double_after_split,
+    );
+    if legal_actions.contains(&action) {
+        action
+    } else {
+        Action::Stand
+    }
+}
+
+// How a seat the computer fills plays its hands.
+pub trait Strategy {
+    fn name(&self) -> &str;
+    fn choose(&mut self, state: &GameState) -> Action;
+}
+
+// Plays the chart, as far as the rules allow.
+#[derive(Debug, Default)]
+pub struct BasicStrategy;
+
+impl Strategy for BasicStrategy {
+    fn name(&self) -> &str {
+        "basic strategy"
+    }
+
+    fn choose(&mut self, state: &GameState) -> Action {
+        basic_strategy_for(state)
+    }
+}
+
+// Basic strategy with the counting system's index plays laid over it, at
+// the true count the seat can see. A deviation the rules don't allow here,
+// such as a split, leaves the chart play in place.
+pub fn index_strategy_for(state: &GameState, system: &CountingSystem) -> Action {
+    let player_state = state.player_state();
+    if player_state.rules.variant == Variant::Pontoon {
+        return basic_strategy_for(state);
+    }
+    let legal_actions = state.legal_actions();
+    let true_count = system.true_count(player_state);
+    if player_state.phase == Phase::Insurance {
+        let take = if legal_actions.contains(&Action::EvenMoney) { Action::EvenMoney } else { Action::Insurance };
+        return if true_count >= system.insurance_index as f64 { take } else { Action::DeclineInsurance };
+    }
+    if let Option::Some(upcard) = player_state.dealer_upcard() {
+        let deviation = triggered(system.surrender_indices, &player_state.hand, upcard, true_count)
+            .filter(|deviation_index| legal_actions.contains(&deviation_index.deviation))
+            .or_else(|| triggered(system.indices, &player_state.hand, upcard, true_count))
+            .map(|deviation_index| deviation_index.deviation.clone())
+            .filter(|action| legal_actions.contains(action));
+        if let Option::Some(action) = deviation {
+            return action;
+        }
+    }
+    basic_strategy_for(state)
+}
+
+// Counts the shoe and plays its system's indices.
+pub struct IndexStrategy {
+    pub system: &'static CountingSystem,
+}
+
+impl Strategy for IndexStrategy {
+    fn name(&self) -> &str {
+        "index play"
+    }
+
+    fn choose(&mut self, state: &GameState) -> Action {
+        index_strategy_for(state, self.system)
+    }
+}
+
+// Stands on anything, and turns down insurance while it is on offer: what a
+// player who lets the clock run out is taken to do.
+pub struct StandPat;
+
+impl Strategy for StandPat {
+    fn name(&self) -> &str {
+        "standing pat"
+    }
+
+    fn choose(&mut self, state: &GameState) -> Action {
+        if state.legal_actions().contains(&Action::Stand) {
+            Action::Stand
+        } else {
+            Action::DeclineInsurance
+        }
+    }
+}
+
+pub const STRATEGY_NAMES: [&str; 3] = ["basic", "index", "stand"];
+
+pub fn strategy_from_name(name: &str) -> Option<Box<dyn Strategy>> {
+    match name {
+        "basic" => Option::Some(Box::new(BasicStrategy)),
+        "index" => Option::Some(Box::new(IndexStrategy { system: &HI_LO })),
+        "stand" => Option::Some(Box::new(StandPat)),
+        _ => Option::None,
+    }
+}
@@ -0,0 +1,195 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::rules::Variant;
+use crate::strategy::{basic_strategy_for, Strategy};
+use crate::strategy_table::{csv_header, rows, upcard_index, ChartPlay, TableHand};
+use crate::{Action, Card, GameState, Phase};
+
+// A strategy of the player's own, read from a chart in the CSV that
+// `generate-tables --chart` writes: the header `hand,2,3,4,5,6,7,8,9,T,A`,
+// then a line for every hard total from 5 to 21, soft total from 13 to 21
+// and pair, such as `hard 16,S,S,S,S,S,H,H,H,H,H`. A file missing any of
+// them, or with a play that can't be read, is refused as a whole, so a
+// half-finished chart never plays a hand.
+
+// The --bot-strategy name for a `CustomStrategy`, which comes from a file
+// rather than from `strategy_from_name`.
+pub const CUSTOM_STRATEGY: &str = "custom";
+
+#[derive(Debug)]
+pub enum StrategyFileError {
+    Io(io::Error),
+    Header,
+    UnknownHand { line: usize, hand: String },
+    UnknownPlay { line: usize, play: String },
+    WrongLength { line: usize, plays: usize },
+    Repeated { line: usize, hand: String },
+    Missing(Vec<String>),
+}
+
+impl fmt::Display for StrategyFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StrategyFileError::Io(error) => write!(f, "could not read the strategy: {}", error),
+            StrategyFileError::Header => write!(f, "the first line should be {}", csv_header()),
+            StrategyFileError::UnknownHand { line, hand } => {
+                write!(f, "line {}: {:?} is not a hand, try hard 16, soft 18 or pair 8,8", line, hand)
+            },
+            StrategyFileError::UnknownPlay { line, play } => {
+                write!(f, "line {}: {:?} is not a play, use S, H, D, Ds, P, R, Rs or Rp", line, play)
+            },
+            StrategyFileError::WrongLength { line, plays } => {
+                write!(f, "line {}: {} plays where there should be one for each of the 10 upcards", line, plays)
+            },
+            StrategyFileError::Repeated { line, hand } => write!(f, "line {}: {} is already in the chart", line, hand),
+            StrategyFileError::Missing(hands) => write!(f, "the chart has no line for {}", hands.join(", ")),
+        }
+    }
+}
+
+impl std::error::Error for StrategyFileError {}
+
+impl From<io::Error> for StrategyFileError {
+    fn from(error: io::Error) -> StrategyFileError {
+        StrategyFileError::Io(error)
+    }
+}
+
+// The hand's label and the rest of the line. The label is quoted when it
+// holds a comma, as a pair's does.
+fn split_label(line: &str) -> (&str, &str) {
+    if let Option::Some(quoted) = line.strip_prefix('"') {
+        if let Option::Some(end) = quoted.find('"') {
+            let rest = &quoted[end + 1..];
+            return (&quoted[..end], rest.strip_prefix(',').unwrap_or(rest));
+        }
+    }
+    match line.find(',') {
+        Option::Some(comma) => (&line[..comma], &line[comma + 1..]),
+        Option::None => (line, ""),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CustomStrategy {
+    // One play per row and upcard, in the table's order.
+    plays: Vec<ChartPlay>,
+}
+
+impl CustomStrategy {
+    pub fn load(path: &Path) -> Result<CustomStrategy, StrategyFileError> {
+        CustomStrategy::parse(&fs::read_to_string(path)?)
+    }
+
+    // Blank lines are skipped; case and spaces around a cell don't matter.
+    pub fn parse(text: &str) -> Result<CustomStrategy, StrategyFileError> {
+        let rows = rows();
+        let upcards = csv_header().split(',').count() - 1;
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+        let header = lines.next().map(|(_, line)| line.split(',').map(str::trim).collect::<Vec<&str>>().join(","));
+        if !header.is_some_and(|header| header.eq_ignore_ascii_case(&csv_header())) {
+            return Err(StrategyFileError::Header);
+        }
+
+        let mut found: Vec<Option<Vec<ChartPlay>>> = vec![Option::None; rows.len()];
+        for (line, text) in lines {
+            let (label, rest) = split_label(text);
+            let label = label.split_whitespace().collect::<Vec<&str>>().join(" ");
+            let row = rows
+                .iter()
+                .position(|row| row.label().eq_ignore_ascii_case(&label))
+                .ok_or_else(|| StrategyFileError::UnknownHand { line, hand: label.clone() })?;
+            let plays = rest
+                .split(',')
+                .map(str::trim)
+                .map(|code| ChartPlay::from_code(code).ok_or_else(|| StrategyFileError::UnknownPlay { line, play: code.to_string() }))
+                .collect::<Result<Vec<ChartPlay>, StrategyFileError>>()?;
+            if plays.len() != upcards {
+                return Err(StrategyFileError::WrongLength { line, plays: plays.len() });
+            }
+            if found[row].is_some() {
+                return Err(StrategyFileError::Repeated { line, hand: rows[row].label() });
+            }
+            found[row] = Option::Some(plays);
+        }
+
+        let missing: Vec<String> =
+            rows.iter().zip(found.iter()).filter(|(_, plays)| plays.is_none()).map(|(row, _)| row.label()).collect();
+        if !missing.is_empty() {
+            return Err(StrategyFileError::Missing(missing));
+        }
+        Ok(CustomStrategy { plays: found.into_iter().flatten().flatten().collect() })
+    }
+
+    pub fn play(&self, row: TableHand, upcard: &Card) -> Option<ChartPlay> {
+        let index = rows().iter().position(|candidate| *candidate == row)?;
+        self.plays.get(index * (self.plays.len() / rows().len()) + upcard_index(upcard)).cloned()
+    }
+
+    // The chart's play for the hand in `state`, as far as the rules allow
+    // it. A pair that can't be split is played by its total, and a pair of
+    // twos or aces with no total row hits. Insurance is always declined,
+    // and pontoon is played by the built-in chart.
+    pub fn action_for(&self, state: &GameState) -> Action {
+        let player_state = state.player_state();
+        if player_state.phase != Phase::Playing || player_state.rules.variant != Variant::Blackjack {
+            return basic_strategy_for(state);
+        }
+        let upcard = match player_state.dealer_upcard() {
+            Option::Some(upcard) => upcard,
+            Option::None => return basic_strategy_for(state),
+        };
+        let legal_actions = state.legal_actions();
+        let allowed = |action: &Action| legal_actions.contains(action);
+        let hand = &player_state.hand;
+        // A split the hand can't make is played from the total's row, where
+        // a split is read as a hit.
+        let by_total = || match TableHand::total_of(hand).and_then(|row| self.play(row, upcard)) {
+            Option::Some(ChartPlay::Split) | Option::None => ChartPlay::Hit,
+            Option::Some(ChartPlay::SurrenderOrSplit) => ChartPlay::Surrender,
+            Option::Some(play) => play,
+        };
+        let play = match TableHand::of(hand).and_then(|row| self.play(row, upcard)).unwrap_or(ChartPlay::Hit) {
+            ChartPlay::Split if allowed(&Action::SplitCards) => ChartPlay::Split,
+            ChartPlay::SurrenderOrSplit if allowed(&Action::Surrender) => ChartPlay::Surrender,
+            ChartPlay::SurrenderOrSplit if allowed(&Action::SplitCards) => ChartPlay::Split,
+            ChartPlay::Split | ChartPlay::SurrenderOrSplit => by_total(),
+            play => play,
+        };
+        let action = match play {
+            ChartPlay::Stand => Action::Stand,
+            ChartPlay::Hit => Action::Hit,
+            ChartPlay::Split => Action::SplitCards,
+            ChartPlay::Double if allowed(&Action::DoubleDown) => Action::DoubleDown,
+            ChartPlay::Double => Action::Hit,
+            ChartPlay::DoubleOrStand if allowed(&Action::DoubleDown) => Action::DoubleDown,
+            ChartPlay::DoubleOrStand => Action::Stand,
+            ChartPlay::Surrender | ChartPlay::SurrenderOrStand if allowed(&Action::Surrender) => Action::Surrender,
+            ChartPlay::Surrender => Action::Hit,
+            ChartPlay::SurrenderOrStand => Action::Stand,
+            ChartPlay::SurrenderOrSplit => Action::SplitCards,
+        };
+        if allowed(&action) {
+            action
+        } else {
+            basic_strategy_for(state)
+        }
+    }
+}
+
+impl Strategy for CustomStrategy {
+    fn name(&self) -> &str {
+        "a custom strategy"
+    }
+
+    fn choose(&mut self, state: &GameState) -> Action {
+        self.action_for(state)
+    }
+}
@@ -0,0 +1,517 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::thread;
+
+use crate::composition::ShoeComposition;
+use crate::deviation::upcard_value;
+use crate::rules::{GameRules, Variant};
+use crate::shoe::RankCounts;
+use crate::solver::{self, DecisionValues};
+use crate::strategy::{basic_strategy_for, Strategy};
+use crate::{Action, Card, CardSuit, CardValue, GameState, Phase};
+
+// The solver's values for every starting hand against every upcard, dealt
+// from a full shoe, worked out once for a rule set and kept on disk so the
+// advisor and the bots can look a hand up instead of enumerating it again.
+//
+// The file is a header, then one record per hand and upcard in the order
+// of `rows()` and `UPCARDS`: stand, hit, double, split and surrender as
+// little-endian f32s, NaN where the play isn't open. The header holds the
+// hash of the rules the values were worked out for, and a file made for
+// other rules is refused rather than read.
+
+const MAGIC: &[u8; 4] = b"BJST";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 4 + 1 + 8;
+const VALUES_PER_CELL: usize = 5;
+
+// Two to nine, then the ten and the ace.
+const UPCARDS: [CardValue; 10] = [
+    CardValue::Two,
+    CardValue::Three,
+    CardValue::Four,
+    CardValue::Five,
+    CardValue::Six,
+    CardValue::Seven,
+    CardValue::Eight,
+    CardValue::Nine,
+    CardValue::Ten,
+    CardValue::Ace,
+];
+
+// A row of the table. Hard and soft totals are looked up for any hand that
+// isn't a pair; a pair is named by the points of one card, the ace as 11.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TableHand {
+    Hard(u32),
+    Soft(u32),
+    Pair(u32),
+}
+
+impl TableHand {
+    pub fn of(hand: &[Card]) -> Option<TableHand> {
+        match hand {
+            [first, second] if upcard_value(&first.value) == upcard_value(&second.value) => {
+                Option::Some(TableHand::Pair(upcard_value(&first.value)))
+            },
+            _ => TableHand::total_of(hand),
+        }
+    }
+
+    // The hard or soft total row, for a pair too. There are none for a pair
+    // of twos or of aces played as a total.
+    pub fn total_of(hand: &[Card]) -> Option<TableHand> {
+        let points: Vec<u32> = hand.iter().map(|card| upcard_value(&card.value)).collect();
+        let hard: u32 = points.iter().map(|&points| if points == 11 { 1 } else { points }).sum();
+        let row = if points.contains(&11) && hard + 10 <= 21 { TableHand::Soft(hard + 10) } else { TableHand::Hard(hard) };
+        Option::Some(row).filter(|row| rows().contains(row))
+    }
+
+    // As a chart's CSV names the row: hard 16, soft 18 or pair 8,8.
+    pub fn label(&self) -> String {
+        format!("{} {}", row_kind(self).to_lowercase(), row_label(self))
+    }
+
+    // The hands the row is worked out from: every two cards that make a
+    // hard total without being a pair, and otherwise the one hand that
+    // stands for the row. Hard 20 and 21 and soft 21 take three cards.
+    fn hands(&self) -> Vec<Vec<CardValue>> {
+        let value = |points: u32| UPCARDS[if points == 11 { 9 } else { points as usize - 2 }];
+        match *self {
+            TableHand::Hard(20) => vec![vec![CardValue::Ten, CardValue::Eight, CardValue::Two]],
+            TableHand::Hard(21) => vec![vec![CardValue::Ten, CardValue::Nine, CardValue::Two]],
+            TableHand::Hard(total) => (2..=10)
+                .filter(|&low| total > 2 * low && total - low <= 10)
+                .map(|low| vec![value(low), value(total - low)])
+                .collect(),
+            TableHand::Soft(21) => vec![vec![CardValue::Ace, CardValue::Five, CardValue::Five]],
+            TableHand::Soft(total) => vec![vec![CardValue::Ace, value(total - 11)]],
+            TableHand::Pair(points) => vec![vec![value(points), value(points)]],
+        }
+    }
+}
+
+// Hard 5 to 21, soft 13 to 21, and the pairs from twos to aces.
+pub(crate) fn rows() -> Vec<TableHand> {
+    let hard = (5..=21).map(TableHand::Hard);
+    let soft = (13..=21).map(TableHand::Soft);
+    let pairs = (2..=11).map(TableHand::Pair);
+    hard.chain(soft).chain(pairs).collect()
+}
+
+pub(crate) fn upcard_index(card: &Card) -> usize {
+    UPCARDS.iter().position(|value| upcard_value(value) == upcard_value(&card.value)).unwrap_or(0)
+}
+
+#[derive(Debug)]
+pub enum TableError {
+    Io(io::Error),
+    NotATable,
+    // Made for a different rule set, or by another version of the format.
+    Stale,
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TableError::Io(error) => write!(f, "could not access the strategy table: {}", error),
+            TableError::NotATable => write!(f, "not a strategy table"),
+            TableError::Stale => write!(f, "the strategy table was made for other rules"),
+        }
+    }
+}
+
+impl std::error::Error for TableError {}
+
+impl From<io::Error> for TableError {
+    fn from(error: io::Error) -> TableError {
+        TableError::Io(error)
+    }
+}
+
+// FNV-1a over every rule the values depend on, so the hash stays the same
+// from build to build and only changes when the game does. The shuffle and
+// the dealer's manners don't change what a play is worth.
+pub fn rules_hash(rules: &GameRules) -> u64 {
+    let key = format!(
+        "{:?} {} {} {} {:?} {} {:?} {} {} {} {} {}:{}",
+        rules.variant,
+        rules.decks,
+        rules.dealer_hits_soft_17,
+        rules.surrender,
+        rules.hole_card,
+        rules.peek,
+        rules.double_on,
+        rules.max_split_hands,
+        rules.double_after_split,
+        rules.split_aces_one_card,
+        rules.blackjack_after_split,
+        rules.blackjack_numerator,
+        rules.blackjack_denominator,
+    );
+    key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrategyTable {
+    rules_hash: u64,
+    // One cell per row and upcard.
+    cells: Vec<[f32; VALUES_PER_CELL]>,
+}
+
+// The weighted mean of each play's value; a play missing from any of the
+// hands is missing from the cell.
+fn cell_of(weighted: &[(f64, DecisionValues)]) -> [f32; VALUES_PER_CELL] {
+    let total: f64 = weighted.iter().map(|(weight, _)| weight).sum();
+    let mean = |value: fn(&DecisionValues) -> Option<f64>| {
+        let sum: f64 = weighted.iter().map(|(weight, values)| weight * value(values).unwrap_or(f64::NAN)).sum();
+        (sum / total) as f32
+    };
+    [
+        mean(|values| Option::Some(values.stand)),
+        mean(|values| Option::Some(values.hit)),
+        mean(|values| values.double),
+        mean(|values| values.split),
+        mean(|values| values.surrender),
+    ]
+}
+
+// Every row against one upcard. A row made from several hands takes their
+// values weighted by how likely each is to be dealt, so a total is played
+// as it turns up rather than as any one way of making it.
+fn column(upcard: &CardValue, rules: &GameRules) -> Vec<[f32; VALUES_PER_CELL]> {
+    let card = |value: &CardValue| Card { suit: CardSuit::Spades, value: *value };
+    let mut shoe = ShoeComposition::new(rules.decks);
+    shoe.remove(upcard, Option::Some(1));
+    let available = |value: &CardValue| {
+        shoe.cards().iter().filter(|card| upcard_value(&card.value) == upcard_value(value)).count() as f64
+    };
+    let rows: Vec<Vec<Vec<CardValue>>> = rows().iter().map(TableHand::hands).collect();
+    let hands: Vec<Vec<Card>> = rows.iter().flatten().map(|hand| hand.iter().map(card).collect()).collect();
+    let mut values = solver::solve_each(&hands, &card(upcard), &RankCounts::of(shoe.cards()), rules).into_iter();
+    rows.iter()
+        .map(|hands| {
+            let weighted: Vec<(f64, DecisionValues)> = hands
+                .iter()
+                .map(|hand| (hand.iter().map(available).product(), values.next().expect("a value for every hand")))
+                .collect();
+            cell_of(&weighted)
+        })
+        .collect()
+}
+
+impl StrategyTable {
+    // Solves every cell, a thread to each upcard; a release build takes
+    // some seconds on a few cores. Only blackjack rules have values to
+    // work out.
+    pub fn generate(rules: &GameRules) -> StrategyTable {
+        let columns: Vec<Vec<[f32; VALUES_PER_CELL]>> = thread::scope(|scope| {
+            let workers: Vec<_> = UPCARDS.iter().map(|upcard| scope.spawn(move || column(upcard, rules))).collect();
+            workers.into_iter().map(|worker| worker.join().expect("a strategy table worker panicked")).collect()
+        });
+        let cells = (0..rows().len()).flat_map(|row| columns.iter().map(move |column| column[row])).collect();
+        StrategyTable { rules_hash: rules_hash(rules), cells }
+    }
+
+    pub fn is_for(&self, rules: &GameRules) -> bool {
+        self.rules_hash == rules_hash(rules)
+    }
+
+    // The values for a hand against an upcard. Doubles, splits and surrender
+    // are only given for the first two cards of a hand.
+    pub fn values(&self, hand: &[Card], upcard: &Card) -> Option<DecisionValues> {
+        let row = TableHand::of(hand)?;
+        let index = rows().iter().position(|candidate| *candidate == row)? * UPCARDS.len() + upcard_index(upcard);
+        let cell = self.cells.get(index)?;
+        let two_cards = hand.len() == 2;
+        let value = |value: f32| Option::Some(value as f64).filter(|value| two_cards && !value.is_nan());
+        Option::Some(DecisionValues {
+            stand: cell[0] as f64,
+            hit: cell[1] as f64,
+            double: value(cell[2]),
+            split: value(cell[3]),
+            surrender: value(cell[4]),
+        })
+    }
+
+    // The best play in every cell, as a crib sheet for `rules` with a grid
+    // each for hard totals, soft totals and pairs.
+    pub fn chart(&self, format: ChartFormat, rules: &GameRules) -> String {
+        let sections = [("Hard totals", "Hard"), ("Soft totals", "Soft"), ("Pairs", "Pair")];
+        let grids: Vec<(&str, &str, Grid)> = sections
+            .iter()
+            .map(|&(title, kind)| {
+                let lines = rows()
+                    .iter()
+                    .zip(self.cells.chunks(UPCARDS.len()))
+                    .filter(|(row, _)| row_kind(row) == kind)
+                    .map(|(row, cells)| (row_label(row), cells.iter().map(|cell| best_play(cell).code()).collect()))
+                    .collect();
+                (title, kind, lines)
+            })
+            .collect();
+        let upcards = upcard_labels();
+        let mut chart = String::new();
+        match format {
+            ChartFormat::Text => {
+                chart.push_str(&format!("Rules: {}\n{}\n", rules.describe(), CHART_LEGEND));
+                let line = |label: &str, cells: &[&str]| {
+                    let cells: String = cells.iter().map(|cell| format!("{:<3}", cell)).collect();
+                    format!("{:<6}{}", label, cells).trim_end().to_string()
+                };
+                for (_, kind, lines) in &grids {
+                    chart.push_str(&format!("\n{}\n", line(kind, &upcards)));
+                    for (label, plays) in lines {
+                        chart.push_str(&format!("{}\n", line(label, plays)));
+                    }
+                }
+            },
+            ChartFormat::Csv => {
+                chart.push_str(&format!("{}\n", csv_header()));
+                for (_, kind, lines) in &grids {
+                    for (label, plays) in lines {
+                        chart.push_str(&format!("\"{} {}\",{}\n", kind.to_lowercase(), label, plays.join(",")));
+                    }
+                }
+            },
+            ChartFormat::Markdown => {
+                chart.push_str(&format!("# Strategy for {}\n\n{}\n", rules.describe(), CHART_LEGEND));
+                for (title, kind, lines) in &grids {
+                    chart.push_str(&format!("\n## {}\n\n| {} | {} |\n", title, kind, upcards.join(" | ")));
+                    chart.push_str(&format!("|---|{}\n", "---|".repeat(upcards.len())));
+                    for (label, plays) in lines {
+                        chart.push_str(&format!("| {} | {} |\n", label, plays.join(" | ")));
+                    }
+                }
+            },
+        }
+        chart
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.cells.len() * VALUES_PER_CELL * 4);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.rules_hash.to_le_bytes());
+        for cell in &self.cells {
+            for value in cell {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<StrategyTable, TableError> {
+        if bytes.len() < HEADER_LEN || &bytes[..4] != MAGIC {
+            return Err(TableError::NotATable);
+        }
+        if bytes[4] != VERSION {
+            return Err(TableError::Stale);
+        }
+        let mut hash = [0; 8];
+        hash.copy_from_slice(&bytes[5..HEADER_LEN]);
+        let body = &bytes[HEADER_LEN..];
+        if body.len() != rows().len() * UPCARDS.len() * VALUES_PER_CELL * 4 {
+            return Err(TableError::NotATable);
+        }
+        let cells = body
+            .chunks(VALUES_PER_CELL * 4)
+            .map(|chunk| {
+                let mut cell = [0.0; VALUES_PER_CELL];
+                for (value, raw) in cell.iter_mut().zip(chunk.chunks(4)) {
+                    *value = f32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]);
+                }
+                cell
+            })
+            .collect();
+        Ok(StrategyTable { rules_hash: u64::from_le_bytes(hash), cells })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Option::Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_bytes())
+    }
+
+    // Reads a table made for `rules`; one made for anything else is stale.
+    pub fn load(path: &Path, rules: &GameRules) -> Result<StrategyTable, TableError> {
+        let table = StrategyTable::from_bytes(&fs::read(path)?)?;
+        if !table.is_for(rules) {
+            return Err(TableError::Stale);
+        }
+        Ok(table)
+    }
+}
+
+// How a chart is written out: as text for the terminal, or as CSV or
+// Markdown to print.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChartFormat {
+    Text,
+    Csv,
+    Markdown,
+}
+
+impl ChartFormat {
+    // Picked by the file's extension.
+    pub fn from_path(path: &Path) -> Option<ChartFormat> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Option::Some("csv") => Option::Some(ChartFormat::Csv),
+            Option::Some("md") => Option::Some(ChartFormat::Markdown),
+            _ => Option::None,
+        }
+    }
+}
+
+const CHART_LEGEND: &str = "S stand, H hit, D double or else hit, Ds double or else stand, P split, \
+R surrender or else hit, Rs surrender or else stand, Rp surrender or else split";
+
+// A play as a chart writes it. Doubles and surrenders say what to do where
+// they aren't allowed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChartPlay {
+    Stand,
+    Hit,
+    Double,
+    DoubleOrStand,
+    Split,
+    Surrender,
+    SurrenderOrStand,
+    SurrenderOrSplit,
+}
+
+impl ChartPlay {
+    pub const ALL_VALUES: [ChartPlay; 8] = [
+        ChartPlay::Stand,
+        ChartPlay::Hit,
+        ChartPlay::Double,
+        ChartPlay::DoubleOrStand,
+        ChartPlay::Split,
+        ChartPlay::Surrender,
+        ChartPlay::SurrenderOrStand,
+        ChartPlay::SurrenderOrSplit,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ChartPlay::Stand => "S",
+            ChartPlay::Hit => "H",
+            ChartPlay::Double => "D",
+            ChartPlay::DoubleOrStand => "Ds",
+            ChartPlay::Split => "P",
+            ChartPlay::Surrender => "R",
+            ChartPlay::SurrenderOrStand => "Rs",
+            ChartPlay::SurrenderOrSplit => "Rp",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<ChartPlay> {
+        ChartPlay::ALL_VALUES.iter().find(|play| play.code().eq_ignore_ascii_case(code)).cloned()
+    }
+}
+
+// A line per hand: its label, then the play against each upcard.
+type Grid = Vec<(String, Vec<&'static str>)>;
+
+const STAND: usize = 0;
+const HIT: usize = 1;
+const DOUBLE: usize = 2;
+const SPLIT: usize = 3;
+
+// The best play in a cell. A tie goes to the play listed first.
+fn best_play(cell: &[f32; VALUES_PER_CELL]) -> ChartPlay {
+    let best_of = |plays: &[usize]| {
+        plays.iter().cloned().filter(|&play| !cell[play].is_nan()).fold(STAND, |best, play| if cell[play] > cell[best] { play } else { best })
+    };
+    match best_of(&[STAND, HIT, DOUBLE, SPLIT, 4]) {
+        STAND => ChartPlay::Stand,
+        HIT => ChartPlay::Hit,
+        DOUBLE if best_of(&[STAND, HIT]) == HIT => ChartPlay::Double,
+        DOUBLE => ChartPlay::DoubleOrStand,
+        SPLIT => ChartPlay::Split,
+        _ => match best_of(&[STAND, HIT, SPLIT]) {
+            HIT => ChartPlay::Surrender,
+            SPLIT => ChartPlay::SurrenderOrSplit,
+            _ => ChartPlay::SurrenderOrStand,
+        },
+    }
+}
+
+fn upcard_labels() -> Vec<&'static str> {
+    UPCARDS.iter().map(|value| card_label(upcard_value(value))).collect()
+}
+
+// The first line of a chart's CSV: hand, then the upcards from 2 to A.
+pub(crate) fn csv_header() -> String {
+    format!("hand,{}", upcard_labels().join(","))
+}
+
+fn row_kind(row: &TableHand) -> &'static str {
+    match row {
+        TableHand::Hard(_) => "Hard",
+        TableHand::Soft(_) => "Soft",
+        TableHand::Pair(_) => "Pair",
+    }
+}
+
+fn card_label(points: u32) -> &'static str {
+    match points {
+        2 => "2",
+        3 => "3",
+        4 => "4",
+        5 => "5",
+        6 => "6",
+        7 => "7",
+        8 => "8",
+        9 => "9",
+        10 => "T",
+        _ => "A",
+    }
+}
+
+// Totals as numbers and pairs as the two cards, such as 8,8 or A,A.
+fn row_label(row: &TableHand) -> String {
+    match row {
+        TableHand::Hard(total) | TableHand::Soft(total) => total.to_string(),
+        TableHand::Pair(points) => format!("{},{}", card_label(*points), card_label(*points)),
+    }
+}
+
+// The --bot-strategy name for `TableStrategy`, which needs a table for the
+// rules and so can't come from `strategy_from_name`.
+pub const TABLE_STRATEGY: &str = "table";
+
+// Plays the best value in the table of the plays open to the hand, which
+// for a full shoe is basic strategy worked out for these exact rules.
+// Insurance is turned down, and a hand the table has no row for, or any
+// pontoon hand, is played by the chart.
+pub struct TableStrategy {
+    pub table: StrategyTable,
+}
+
+impl Strategy for TableStrategy {
+    fn name(&self) -> &str {
+        "the strategy table"
+    }
+
+    fn choose(&mut self, state: &GameState) -> Action {
+        let player_state = state.player_state();
+        if player_state.phase != Phase::Playing || player_state.rules.variant != Variant::Blackjack {
+            return basic_strategy_for(state);
+        }
+        let legal_actions = state.legal_actions();
+        let values = player_state.dealer_upcard().and_then(|upcard| self.table.values(&player_state.hand, upcard));
+        let best = values.and_then(|values| {
+            values
+                .all()
+                .into_iter()
+                .filter(|(action, _)| legal_actions.contains(action))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(action, _)| action)
+        });
+        best.unwrap_or_else(|| basic_strategy_for(state))
+    }
+}
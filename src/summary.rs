@@ -0,0 +1,68 @@
+use crate::json::JsonValue;
+
+// What a session amounted to, for scripts that wrap the CLI.
+#[derive(Debug, Default)]
+pub struct SessionSummary {
+    pub hands: u64,
+    pub wins: u64,
+    pub losses: u64,
+    pub pushes: u64,
+    // Decisions scored against basic strategy, and how many missed.
+    pub decisions: u64,
+    pub mistakes: u64,
+    // Count-based index spots that came up, and how many were played by
+    // the index.
+    pub deviation_spots: u64,
+    pub deviations_taken: u64,
+    // Wall-clock length of the session.
+    pub seconds: u64,
+    pub starting_bankroll: u64,
+    // Chips bought back in for after going broke, which the net leaves out.
+    pub rebuys: u64,
+    pub rebought: u64,
+    pub net: i64,
+}
+
+impl SessionSummary {
+    pub fn new(starting_bankroll: u64) -> SessionSummary {
+        SessionSummary { starting_bankroll, ..SessionSummary::default() }
+    }
+
+    // A session counted in `unit`ths of a chip, as play counts one under a
+    // currency with decimal places, cashed out in whole chips. A fraction
+    // left over is dropped, never rounded up.
+    pub fn in_whole_units(self, unit: u64) -> SessionSummary {
+        let final_bankroll = self.final_bankroll().div_euclid(unit as i64);
+        let starting_bankroll = self.starting_bankroll / unit;
+        let rebought = self.rebought.div_ceil(unit);
+        SessionSummary {
+            starting_bankroll,
+            rebought,
+            net: final_bankroll - starting_bankroll as i64 - rebought as i64,
+            ..self
+        }
+    }
+
+    pub fn final_bankroll(&self) -> i64 {
+        self.starting_bankroll as i64 + self.rebought as i64 + self.net
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        JsonValue::object(vec![
+            ("hands", JsonValue::Number(self.hands as f64)),
+            ("wins", JsonValue::Number(self.wins as f64)),
+            ("losses", JsonValue::Number(self.losses as f64)),
+            ("pushes", JsonValue::Number(self.pushes as f64)),
+            ("decisions", JsonValue::Number(self.decisions as f64)),
+            ("mistakes", JsonValue::Number(self.mistakes as f64)),
+            ("deviation_spots", JsonValue::Number(self.deviation_spots as f64)),
+            ("deviations_taken", JsonValue::Number(self.deviations_taken as f64)),
+            ("seconds", JsonValue::Number(self.seconds as f64)),
+            ("starting_bankroll", JsonValue::Number(self.starting_bankroll as f64)),
+            ("rebuys", JsonValue::Number(self.rebuys as f64)),
+            ("rebought", JsonValue::Number(self.rebought as f64)),
+            ("net", JsonValue::Number(self.net as f64)),
+            ("final_bankroll", JsonValue::Number(self.final_bankroll() as f64)),
+        ])
+    }
+}
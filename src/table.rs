@@ -0,0 +1,193 @@
+use std::mem;
+
+use crate::rules::{GameRules, HoleCard, Variant};
+use crate::strategy::Strategy;
+use crate::{
+    continue_with_game, deal_with_action, settle_against_dealer, Action, DealtCard, Deck, GameState, Hand, Phase,
+    PlayerState,
+};
+
+// One round at a table of several seats sharing a shoe and a dealer. Each
+// seat is an ordinary game, except that a hand needing the dealer waits
+// until every seat has played; the dealer then plays once for all of them.
+#[derive(Debug)]
+pub struct Round {
+    seats: Vec<GameState>,
+    deck: Deck,
+}
+
+fn empty_deck() -> Deck {
+    Deck::with_decks(0)
+}
+
+impl Round {
+    // Cards go round the table twice, the dealer taking the last card each
+    // time.
+    pub fn deal(mut deck: Deck, seats: usize, rules: &GameRules) -> Round {
+        let empty_shoe = &rules.empty_shoe;
+        let mut hands: Vec<Hand> = vec![Hand::new(); seats];
+        let mut dealer = Vec::new();
+        let face_up = rules.variant != Variant::Pontoon;
+        for hand in hands.iter_mut() {
+            if let Option::Some(card) = deck.draw_with(empty_shoe) {
+                hand.push_card(card);
+            }
+        }
+        dealer.extend(deck.draw_with(empty_shoe).map(|card| DealtCard { card, face_up }));
+        for hand in hands.iter_mut() {
+            if let Option::Some(card) = deck.draw_with(empty_shoe) {
+                hand.push_card(card);
+            }
+        }
+        if rules.hole_card == HoleCard::Dealt {
+            dealer.extend(deck.draw_with(empty_shoe).map(|card| DealtCard { card, face_up: false }));
+        }
+        let seats = hands
+            .into_iter()
+            .map(|hand| {
+                GameState::open(PlayerState {
+                    deck: empty_deck(),
+                    hand,
+                    split_hands: Vec::new(),
+                    waiting_hands: Vec::new(),
+                    dealer_hand: dealer.clone(),
+                    doubled: false,
+                    phase: Phase::Playing,
+                    insured: false,
+                    even_money: false,
+                    shares_dealer: true,
+                    rules: rules.clone(),
+                })
+            })
+            .collect();
+        Round { seats, deck }
+    }
+
+    pub fn seats(&self) -> &[GameState] {
+        &self.seats
+    }
+
+    pub fn deck(&self) -> &Deck {
+        &self.deck
+    }
+
+    // Whether the seat still has a decision to make.
+    pub fn is_playing(&self, seat: usize) -> bool {
+        continue_with_game(&self.seats[seat])
+    }
+
+    // The seat as its player sees the table, with the shared shoe in place
+    // of its own. The other seats' hands are face up, so they go in the
+    // view's discard tray and counts include every card dealt to them.
+    pub fn view(&self, seat: usize) -> GameState {
+        let mut state = self.seats[seat].clone();
+        let mut deck = self.deck.clone();
+        for (other, other_state) in self.seats.iter().enumerate() {
+            if other != seat {
+                deck.discard(other_state.player_state().player_cards());
+            }
+        }
+        state.player_state_mut().deck = deck;
+        state
+    }
+
+    // The seat walks away, surrendering a hand still in play.
+    pub fn abandon(&mut self, seat: usize) {
+        let state = self.seats.remove(seat);
+        self.seats.insert(seat, state.abandon());
+    }
+
+    // Lets a strategy play the seat to the end, returning what it chose. A
+    // choice the rules don't allow ends the seat's turn where it stands.
+    pub fn play_with(&mut self, seat: usize, strategy: &mut dyn Strategy) -> Vec<Action> {
+        let mut actions = Vec::new();
+        while self.is_playing(seat) {
+            let action = strategy.choose(&self.view(seat));
+            if self.act(seat, &action).is_err() {
+                break;
+            }
+            actions.push(action);
+        }
+        actions
+    }
+
+    // Plays one action for a seat, drawing from the shared shoe. An illegal
+    // action leaves the seat as it was and explains what is allowed.
+    pub fn act(&mut self, seat: usize, action: &Action) -> Result<(), String> {
+        let mut state = self.seats.remove(seat);
+        state.player_state_mut().deck = mem::replace(&mut self.deck, empty_deck());
+        let (mut state, result) = match deal_with_action(action, state) {
+            Ok(state) => (state, Ok(())),
+            Err(illegal) => {
+                let message = illegal.to_string();
+                (*illegal.state, Err(message))
+            },
+        };
+        self.deck = mem::replace(&mut state.player_state_mut().deck, empty_deck());
+        self.seats.insert(seat, state);
+        result
+    }
+
+    // The dealer's turn. Each waiting seat is settled against its own copy
+    // of the shoe; the dealer plays the same way whatever the seat holds, so
+    // every copy deals the same cards, and the copy the dealer drew most
+    // from carries on as the shoe. Every seat then sees the dealer's final
+    // hand, which insurance bets are settled against.
+    pub fn finish(self) -> (Vec<GameState>, Deck) {
+        let shoe = self.deck;
+        let mut deck = shoe.clone();
+        let mut seats: Vec<GameState> = self
+            .seats
+            .into_iter()
+            .map(|state| match state {
+                GameState::Continuing(mut player_state) if player_state.phase == Phase::AwaitingDealer => {
+                    player_state.shares_dealer = false;
+                    player_state.phase = Phase::Playing;
+                    player_state.deck = shoe.clone();
+                    let mut state = settle_against_dealer(player_state);
+                    let used = mem::replace(&mut state.player_state_mut().deck, empty_deck());
+                    if used.remaining() < deck.remaining() {
+                        deck = used;
+                    }
+                    state
+                },
+                finished => finished,
+            })
+            .collect();
+
+        let mut dealer = seats
+            .iter()
+            .map(|state| state.player_state().dealer_hand.clone())
+            .max_by_key(|dealer_hand| dealer_hand.len())
+            .unwrap_or_default();
+        let insured = seats.iter().find(|state| state.player_state().insured);
+        if let (1, Option::Some(state)) = (dealer.len(), insured) {
+            let empty_shoe = &state.player_state().rules.empty_shoe;
+            dealer.extend(deck.draw_with(empty_shoe).map(|card| DealtCard { card, face_up: true }));
+        }
+        for dealt in dealer.iter_mut() {
+            dealt.face_up = true;
+        }
+        for state in seats.iter_mut() {
+            let player_state = state.player_state_mut();
+            player_state.shares_dealer = false;
+            player_state.dealer_hand = dealer.clone();
+        }
+        (seats, deck)
+    }
+}
+
+// Clears the table after `Round::finish`: every seat's hand, and the dealer's
+// hand once, go to the discard tray of the shoe the round was dealt from.
+pub fn clear_table(seats: Vec<GameState>, mut deck: Deck) -> Deck {
+    let mut dealer = Vec::new();
+    for state in seats {
+        let player_state = state.into_player_state();
+        deck.discard(player_state.player_cards());
+        if dealer.is_empty() {
+            dealer = player_state.dealer_hand;
+        }
+    }
+    deck.discard(dealer.into_iter().map(|dealt| dealt.card));
+    deck
+}
@@ -0,0 +1,149 @@
+use std::cmp::Reverse;
+
+use crate::settlement::staked;
+use crate::{Action, GameState};
+
+// Elimination tournaments: everyone starts with the same stack, plays a set
+// number of hands a round, and the smallest stacks leave at the end of each
+// round until one player is left.
+
+#[derive(Clone, Debug)]
+pub struct TournamentRules {
+    pub starting_chips: u64,
+    pub hands_per_round: u32,
+    pub eliminated_per_round: usize,
+    pub minimum_bet: u64,
+    pub maximum_bet: u64,
+}
+
+impl Default for TournamentRules {
+    fn default() -> TournamentRules {
+        TournamentRules {
+            starting_chips: 1000,
+            hands_per_round: 5,
+            eliminated_per_round: 1,
+            minimum_bet: 10,
+            maximum_bet: 200,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Entrant {
+    pub name: String,
+    pub chips: u64,
+    // The round the player went out in, if they have.
+    pub eliminated_in: Option<u32>,
+}
+
+#[derive(Debug)]
+pub struct Tournament {
+    pub rules: TournamentRules,
+    pub entrants: Vec<Entrant>,
+    // Rounds start at 1.
+    pub round: u32,
+    pub hands_played: u64,
+}
+
+impl Tournament {
+    pub fn new(names: Vec<String>, rules: TournamentRules) -> Tournament {
+        let entrants = names
+            .into_iter()
+            .map(|name| Entrant { name, chips: rules.starting_chips, eliminated_in: Option::None })
+            .collect();
+        Tournament { rules, entrants, round: 1, hands_played: 0 }
+    }
+
+    // Players still in, by seat.
+    pub fn remaining(&self) -> Vec<usize> {
+        (0..self.entrants.len()).filter(|&index| self.entrants[index].eliminated_in.is_none()).collect()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.remaining().len() <= 1
+    }
+
+    // The first to bet moves one place round the table every hand.
+    pub fn betting_order(&self) -> Vec<usize> {
+        let mut order = self.remaining();
+        if !order.is_empty() {
+            let start = (self.hands_played % order.len() as u64) as usize;
+            order.rotate_left(start);
+        }
+        order
+    }
+
+    // Bets are clamped to the table limits and what the player has.
+    pub fn clamp_bet(&self, entrant: usize, bet: u64) -> u64 {
+        bet.clamp(self.rules.minimum_bet, self.rules.maximum_bet).min(self.entrants[entrant].chips)
+    }
+
+    // A double or a split puts up another `bet`, which has to come out of
+    // the chips the entrant has left behind what `state` already stakes.
+    pub fn afford(&self, entrant: usize, state: &GameState, bet: u64, action: &Action) -> Result<(), String> {
+        if !matches!(action, Action::DoubleDown | Action::SplitCards) {
+            return Ok(());
+        }
+        let behind = self.entrants[entrant].chips.saturating_sub(staked(state, bet, Option::None));
+        if behind < bet {
+            return Err(format!("that needs {} more chips and only {} are left", bet, behind));
+        }
+        Ok(())
+    }
+
+    // Applies one hand's results. A player who can no longer cover the
+    // minimum bet is out at once.
+    pub fn record_hand(&mut self, results: &[(usize, i64)]) {
+        for &(entrant, net) in results {
+            let entrant = &mut self.entrants[entrant];
+            entrant.chips = (entrant.chips as i64 + net).max(0) as u64;
+            if entrant.chips < self.rules.minimum_bet && entrant.eliminated_in.is_none() {
+                entrant.eliminated_in = Option::Some(self.round);
+            }
+        }
+        self.hands_played += 1;
+    }
+
+    // Knocks out the smallest stacks, never the last player standing, and
+    // starts the next round. Ties go against the later seat. Returns who
+    // went out.
+    pub fn end_round(&mut self) -> Vec<usize> {
+        let mut remaining = self.remaining();
+        remaining.sort_by_key(|&index| (self.entrants[index].chips, Reverse(index)));
+        let count = self.rules.eliminated_per_round.min(remaining.len().saturating_sub(1));
+        let eliminated: Vec<usize> = remaining.into_iter().take(count).collect();
+        for &index in &eliminated {
+            self.entrants[index].eliminated_in = Option::Some(self.round);
+        }
+        self.round += 1;
+        eliminated
+    }
+
+    // Players still in by stack, then everyone else by how long they lasted.
+    pub fn standings(&self) -> Vec<&Entrant> {
+        let mut standings: Vec<&Entrant> = self.entrants.iter().collect();
+        standings.sort_by_key(|entrant| {
+            (Reverse(entrant.eliminated_in.unwrap_or(u32::MAX)), Reverse(entrant.chips))
+        });
+        standings
+    }
+
+    pub fn winner(&self) -> Option<&Entrant> {
+        self.standings().into_iter().next()
+    }
+
+    pub fn render_standings(&self) -> String {
+        self.standings()
+            .iter()
+            .enumerate()
+            .map(|(place, entrant)| {
+                let status = match entrant.eliminated_in {
+                    Option::Some(round) => format!("out in round {}", round),
+                    Option::None => "still in".to_string(),
+                };
+                format!("{:>2}. {:<12} {:>6} chips  {}", place + 1, entrant.name, entrant.chips, status)
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
@@ -0,0 +1,259 @@
+use rust_blackjack::betting::{stake, BetContext, BetStrategy};
+use rust_blackjack::counting::HI_LO;
+use rust_blackjack::protocol::encode_card;
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::settlement::settle;
+use rust_blackjack::strategy::{BasicStrategy, Strategy};
+use rust_blackjack::table::{clear_table, Round};
+use rust_blackjack::tournament::{Tournament, TournamentRules};
+use rust_blackjack::{
+    describe_actions, describe_dealer, game_message, insurance_answer, insurance_prompt, outcome_message, Action, Deck,
+};
+
+use crate::input::{Input, InputLines};
+use crate::{cards_text, total_text};
+
+// The human always takes the first seat.
+const HUMAN: usize = 0;
+
+fn bot_name(number: usize) -> String {
+    format!("Bot {}", number)
+}
+
+// None when the player has had enough.
+fn read_line(input: &InputLines) -> Option<String> {
+    match input.next() {
+        Input::Line(line) if !matches!(line.trim(), "quit" | "q") => Option::Some(line),
+        _ => Option::None,
+    }
+}
+
+fn ask_bet(tournament: &Tournament, input: &InputLines) -> Option<u64> {
+    let rules = &tournament.rules;
+    loop {
+        println!(
+            "Your bet ({}-{}, you have {} chips; enter for {}):",
+            rules.minimum_bet,
+            rules.maximum_bet,
+            tournament.entrants[HUMAN].chips,
+            rules.minimum_bet
+        );
+        let line = read_line(input)?;
+        if line.trim().is_empty() {
+            return Option::Some(tournament.clamp_bet(HUMAN, rules.minimum_bet));
+        }
+        match line.trim().parse() {
+            Ok(bet) => return Option::Some(tournament.clamp_bet(HUMAN, bet)),
+            Err(_) => println!("{:?} is not a number of chips", line.trim()),
+        }
+    }
+}
+
+fn print_table(round: &Round, seats: &[usize], tournament: &Tournament) {
+    if let Option::Some(state) = round.seats().first() {
+        let player_state = state.player_state();
+        let shown: Vec<String> = player_state.visible_dealer_cards().iter().map(encode_card).collect();
+        println!("{}", describe_dealer(&shown.join(" "), player_state.hidden_dealer_cards().len()));
+    }
+    for (seat, state) in round.seats().iter().enumerate() {
+        let player_state = state.player_state();
+        let split_hands = player_state.split_hands.iter().map(|split| &split.hand[..]);
+        for hand in split_hands.chain(std::iter::once(&player_state.hand[..])) {
+            println!("  {:<8} {:<20} {}", tournament.entrants[seats[seat]].name, cards_text(hand), total_text(hand));
+        }
+    }
+}
+
+// Returns false if the human walked away mid-hand. Doubles and splits the
+// human's chips can't cover are turned down.
+fn play_human(
+    round: &mut Round,
+    seat: usize,
+    seats: &[usize],
+    bet: u64,
+    tournament: &Tournament,
+    input: &InputLines,
+) -> bool {
+    while round.is_playing(seat) {
+        print_table(round, seats, tournament);
+        let state = &round.seats()[seat];
+        if let Option::Some(prompt) = insurance_prompt(state) {
+            println!("{} (y/n)", prompt);
+        }
+        println!("You can: {}", describe_actions(&state.legal_actions(), &state.player_state().rules));
+        let line = match read_line(input) {
+            Option::Some(line) => line,
+            Option::None => return false,
+        };
+        let action = match insurance_answer(state, &line) {
+            Option::Some(action) => Ok(action),
+            Option::None => line.parse::<Action>().map_err(|error| error.to_string()),
+        };
+        let afforded = action.and_then(|action| {
+            if state.legal_actions().contains(&action) {
+                tournament.afford(HUMAN, state, bet, &action)?;
+            }
+            Ok(action)
+        });
+        if let Err(error) = afforded.and_then(|action| round.act(seat, &action)) {
+            println!("{}", error);
+        }
+    }
+    true
+}
+
+fn play_bot(round: &mut Round, seat: usize, name: &str, strategy: &mut dyn Strategy) {
+    let variant = round.seats()[seat].player_state().rules.variant.clone();
+    for action in round.play_with(seat, strategy) {
+        println!("{} chooses to {}", name, variant.action_name(&action));
+    }
+}
+
+// What a bot stakes, by its bet strategy and within the table limits.
+fn bot_bet(tournament: &Tournament, entrant: usize, strategy: &mut dyn BetStrategy, shoe: &Deck) -> u64 {
+    let context = BetContext {
+        bankroll: tournament.entrants[entrant].chips,
+        base_bet: tournament.rules.minimum_bet,
+        true_count: HI_LO.shoe_true_count(shoe),
+    };
+    tournament.clamp_bet(entrant, stake(strategy, &context))
+}
+
+// Plays one hand for everyone still in, handing back the shoe and whether
+// the human is still at the table. Bot `n` bets by `bot_bets[n - 1]`.
+fn play_hand(
+    tournament: &mut Tournament,
+    rules: &GameRules,
+    shoe: Deck,
+    bot_bets: &mut [Box<dyn BetStrategy>],
+    input: &InputLines,
+) -> (Deck, bool) {
+    let seats = tournament.betting_order();
+    let mut bets = Vec::new();
+    for &entrant in &seats {
+        let bet = if entrant == HUMAN {
+            match ask_bet(tournament, input) {
+                Option::Some(bet) => bet,
+                Option::None => return (shoe, false),
+            }
+        } else {
+            let bet = bot_bet(tournament, entrant, bot_bets[entrant - 1].as_mut(), &shoe);
+            println!("{} bets {}", tournament.entrants[entrant].name, bet);
+            bet
+        };
+        bets.push(bet);
+    }
+
+    let mut round = Round::deal(shoe, seats.len(), rules);
+    let mut stayed = true;
+    for (seat, &entrant) in seats.iter().enumerate() {
+        if entrant == HUMAN {
+            stayed = play_human(&mut round, seat, &seats, bets[seat], tournament, input);
+            if !stayed {
+                break;
+            }
+        } else {
+            play_bot(&mut round, seat, &tournament.entrants[entrant].name, &mut BasicStrategy);
+        }
+    }
+    if !stayed {
+        let (states, deck) = round.finish();
+        return (clear_table(states, deck), false);
+    }
+
+    let (states, deck) = round.finish();
+    println!("----- Hand {} -----", tournament.hands_played + 1);
+    let dealer_cards = states[0].player_state().dealer_cards();
+    println!("{:<10}{:<20}{}", "Dealer", cards_text(&dealer_cards), total_text(&dealer_cards));
+    let mut results = Vec::new();
+    for ((state, &entrant), &bet) in states.iter().zip(&seats).zip(&bets) {
+        let settlement = settle(state, bet, &[]);
+        let name = &tournament.entrants[entrant].name;
+        for split in state.player_state().split_hands.iter() {
+            let message = split.outcome.as_ref().map(outcome_message).unwrap_or("");
+            println!("{:<10}{:<20}{:<10}{}", name, cards_text(&split.hand), total_text(&split.hand), message);
+        }
+        let hand = &state.player_state().hand;
+        println!(
+            "{:<10}{:<20}{:<10}{:<36}{:+}",
+            name,
+            cards_text(hand),
+            total_text(hand),
+            game_message(state),
+            settlement.net
+        );
+        if entrant != HUMAN {
+            bot_bets[entrant - 1].record(settlement.net);
+        }
+        results.push((entrant, settlement.net));
+    }
+    tournament.record_hand(&results);
+    (clear_table(states, deck), true)
+}
+
+// Runs a tournament for the human against a computer player for each of
+// `bot_bets`, which bet by them, dealing shoes from `new_deck`. Returns the
+// finishing place of the human.
+pub fn run<F>(
+    rules: &GameRules,
+    tournament_rules: TournamentRules,
+    mut bot_bets: Vec<Box<dyn BetStrategy>>,
+    mut new_deck: F,
+) -> usize
+where
+    F: FnMut() -> Deck,
+{
+    let mut names = vec!["You".to_string()];
+    names.extend((1..=bot_bets.len()).map(bot_name));
+    let mut tournament = Tournament::new(names, tournament_rules);
+    let input = InputLines::new();
+    let mut shoe = new_deck();
+    let mut watching = false;
+
+    println!(
+        "Tournament: {} players, {} chips each, {} hands a round, lowest {} out each round.",
+        tournament.entrants.len(),
+        tournament.rules.starting_chips,
+        tournament.rules.hands_per_round,
+        tournament.rules.eliminated_per_round
+    );
+    println!("Rules: {}", rules.describe());
+    println!("Type quit to leave; you forfeit your seat.");
+
+    'rounds: while !tournament.is_over() {
+        println!("===== Round {} =====", tournament.round);
+        for _ in 0..tournament.rules.hands_per_round {
+            if tournament.is_over() {
+                break;
+            }
+            if tournament.entrants[HUMAN].eliminated_in.is_some() && !watching {
+                // Once the human is out the bots play the tournament out.
+                println!("You are out; the bots play on.");
+                watching = true;
+            }
+            if shoe.needs_shuffle() {
+                shoe = new_deck();
+            }
+            let (deck, stayed) = play_hand(&mut tournament, rules, shoe, &mut bot_bets, &input);
+            shoe = deck;
+            if !stayed {
+                tournament.entrants[HUMAN].chips = 0;
+                tournament.entrants[HUMAN].eliminated_in = Option::Some(tournament.round);
+                println!("You left the tournament.");
+                break 'rounds;
+            }
+        }
+        for entrant in tournament.end_round() {
+            let entrant = &tournament.entrants[entrant];
+            println!("Eliminated: {} with {} chips", entrant.name, entrant.chips);
+        }
+        println!("Standings:\n{}", tournament.render_standings());
+    }
+
+    if let Option::Some(winner) = tournament.winner() {
+        println!("{} won the tournament with {} chips", winner.name, winner.chips);
+    }
+    let place = tournament.standings().iter().position(|entrant| entrant.name == "You").unwrap_or(0) + 1;
+    println!("You finished in place {} of {}", place, tournament.entrants.len());
+    place
+}
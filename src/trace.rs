@@ -0,0 +1,150 @@
+use std::fmt;
+
+use crate::json::JsonValue;
+use crate::protocol::encode_card;
+use crate::{best_hand_value, Card, GameState, Hand, HandOutcome, Phase};
+
+// A developer's view of the game state, for `play --trace-state`: the state
+// as a JSON tree, and what changed in it from one action to the next. serde
+// isn't a dependency, so the tree is built by hand from the fields that
+// matter to the rules; the shoe is only counted.
+
+fn cards(cards: &[Card]) -> JsonValue {
+    JsonValue::Array(cards.iter().map(|card| JsonValue::String(encode_card(card))).collect())
+}
+
+// A busted hand has no total.
+fn total(cards: &[Card]) -> JsonValue {
+    best_hand_value(cards).map_or(JsonValue::Null, |total| JsonValue::Number(total as f64))
+}
+
+fn count(count: usize) -> JsonValue {
+    JsonValue::Number(count as f64)
+}
+
+fn hand(hand: &Hand, doubled: bool, outcome: &str) -> JsonValue {
+    JsonValue::object(vec![
+        ("cards", cards(hand)),
+        ("total", total(hand)),
+        ("soft", JsonValue::Bool(hand.is_soft())),
+        ("doubled", JsonValue::Bool(doubled)),
+        ("outcome", JsonValue::String(outcome.to_string())),
+    ])
+}
+
+fn state_name(state: &GameState) -> &'static str {
+    match state {
+        GameState::GameWon(_) => "won",
+        GameState::BlackjackWin(_) => "blackjack",
+        GameState::GameLost(_) => "lost",
+        GameState::Push(_) => "push",
+        GameState::Surrendered(_) => "surrendered",
+        GameState::Continuing(_) => "continuing",
+    }
+}
+
+// The player's hands in the order they are played, the one being played at
+// `playing`. `staked` is what is on the table and `bankroll` what is left
+// beside it.
+pub fn snapshot(state: &GameState, staked: u64, bankroll: i64) -> JsonValue {
+    let player_state = state.player_state();
+    let mut hands: Vec<JsonValue> = player_state
+        .split_hands
+        .iter()
+        .map(|split| {
+            let outcome = match split.outcome {
+                Option::Some(HandOutcome::Won) => "won",
+                Option::Some(HandOutcome::Blackjack) => "blackjack",
+                Option::Some(HandOutcome::Lost) => "lost",
+                Option::Some(HandOutcome::Push) => "push",
+                Option::None => "waiting for the dealer",
+            };
+            hand(&split.hand, split.doubled, outcome)
+        })
+        .collect();
+    hands.push(hand(&player_state.hand, player_state.doubled, state_name(state)));
+    hands.extend(player_state.waiting_hands.iter().rev().map(|waiting| hand(waiting, false, "waiting")));
+    let dealer: Vec<JsonValue> = player_state
+        .dealer_hand
+        .iter()
+        .map(|dealt| JsonValue::object(vec![("card", JsonValue::String(encode_card(&dealt.card))), ("face_up", JsonValue::Bool(dealt.face_up))]))
+        .collect();
+    let phase = match player_state.phase {
+        Phase::Insurance => "insurance",
+        Phase::Playing => "playing",
+        Phase::AwaitingDealer => "awaiting dealer",
+    };
+    JsonValue::object(vec![
+        ("state", JsonValue::String(state_name(state).to_string())),
+        ("phase", JsonValue::String(phase.to_string())),
+        ("hands", JsonValue::Array(hands)),
+        ("playing", count(player_state.split_hands.len())),
+        ("dealer", JsonValue::object(vec![("cards", JsonValue::Array(dealer)), ("total", total(&player_state.dealer_cards()))])),
+        ("insured", JsonValue::Bool(player_state.insured)),
+        ("even_money", JsonValue::Bool(player_state.even_money)),
+        (
+            "shoe",
+            JsonValue::object(vec![
+                ("remaining", count(player_state.deck.remaining_cards.len())),
+                ("discards", count(player_state.deck.discard_tray.len())),
+                ("burned", count(player_state.deck.burned_cards.len())),
+            ]),
+        ),
+        ("staked", JsonValue::Number(staked as f64)),
+        ("bankroll", JsonValue::Number(bankroll as f64)),
+    ])
+}
+
+// One value that differs between two snapshots, at a path such as
+// `hands[0].total`. A value only on one side was added or removed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change {
+    pub path: String,
+    pub before: Option<JsonValue>,
+    pub after: Option<JsonValue>,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.before, &self.after) {
+            (Option::Some(JsonValue::Number(before)), Option::Some(JsonValue::Number(after))) => {
+                write!(f, "{}: {} -> {} ({:+})", self.path, before, after, after - before)
+            },
+            (Option::Some(before), Option::Some(after)) => write!(f, "{}: {} -> {}", self.path, before, after),
+            (Option::None, Option::Some(after)) => write!(f, "{}: + {}", self.path, after),
+            (Option::Some(before), Option::None) => write!(f, "{}: - {}", self.path, before),
+            (Option::None, Option::None) => write!(f, "{}: unchanged", self.path),
+        }
+    }
+}
+
+fn diff_into(path: &str, before: Option<&JsonValue>, after: Option<&JsonValue>, changes: &mut Vec<Change>) {
+    match (before, after) {
+        (Option::Some(JsonValue::Object(old)), Option::Some(JsonValue::Object(new))) => {
+            let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                diff_into(&path, old.get(key), new.get(key), changes);
+            }
+        },
+        (Option::Some(JsonValue::Array(old)), Option::Some(JsonValue::Array(new))) => {
+            for index in 0..old.len().max(new.len()) {
+                diff_into(&format!("{}[{}]", path, index), old.get(index), new.get(index), changes);
+            }
+        },
+        (before, after) if before != after => {
+            changes.push(Change { path: path.to_string(), before: before.cloned(), after: after.cloned() })
+        },
+        _ => {},
+    }
+}
+
+// Every leaf that changed, objects compared key by key and arrays index by
+// index, so a card dealt to a hand shows as one added at the end of it.
+pub fn diff(before: &JsonValue, after: &JsonValue) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_into("", Option::Some(before), Option::Some(after), &mut changes);
+    changes
+}
@@ -0,0 +1,136 @@
+use std::io::{self, BufRead};
+
+use rand::Rng;
+
+use rust_blackjack::deviation::upcard_value;
+use rust_blackjack::strategy::{basic_strategy, HandCategory};
+use rust_blackjack::{Action, Card, CardSuit, CardValue};
+
+use crate::print_hand;
+
+// How one part of the chart is going. Misses and tries start from one and
+// two imaginary answers, so an untried category counts as half learned.
+#[derive(Debug, Default)]
+struct Drill {
+    correct: u64,
+    total: u64,
+}
+
+impl Drill {
+    fn miss_rate(&self) -> f64 {
+        (self.total - self.correct + 1) as f64 / (self.total + 2) as f64
+    }
+}
+
+fn random_card<R: Rng>(rng: &mut R, value: CardValue) -> Card {
+    let suit = CardSuit::ALL_VALUES[rng.gen_range(0, CardSuit::ALL_VALUES.len())];
+    Card { suit, value }
+}
+
+fn random_value<R: Rng>(rng: &mut R, values: &[CardValue]) -> CardValue {
+    values[rng.gen_range(0, values.len())]
+}
+
+// Two cards that read from the given part of the chart.
+fn deal_hand<R: Rng>(rng: &mut R, category: &HandCategory) -> Vec<Card> {
+    let all = &CardValue::ALL_VALUES;
+    let non_aces: Vec<CardValue> = all.iter().filter(|value| **value != CardValue::Ace).cloned().collect();
+    match category {
+        HandCategory::Pair => {
+            let value = random_value(rng, all);
+            vec![random_card(rng, value), random_card(rng, value)]
+        },
+        HandCategory::Soft => {
+            let small: Vec<CardValue> = non_aces.iter().filter(|value| upcard_value(value) < 10).cloned().collect();
+            let other = random_value(rng, &small);
+            vec![random_card(rng, CardValue::Ace), random_card(rng, other)]
+        },
+        HandCategory::Hard => loop {
+            let first = random_value(rng, &non_aces);
+            let second = random_value(rng, &non_aces);
+            if upcard_value(&first) != upcard_value(&second) {
+                break vec![random_card(rng, first), random_card(rng, second)];
+            }
+        },
+    }
+}
+
+// Picks the next part of the chart to drill, in proportion to how often
+// each has been missed, as an index into `HandCategory::ALL_VALUES`.
+fn pick_category<R: Rng>(rng: &mut R, drills: &[Drill]) -> usize {
+    let weights: Vec<f64> = drills.iter().map(Drill::miss_rate).collect();
+    let mut roll = rng.gen::<f64>() * weights.iter().sum::<f64>();
+    for (index, weight) in weights.iter().enumerate() {
+        if roll < *weight {
+            return index;
+        }
+        roll -= weight;
+    }
+    weights.len() - 1
+}
+
+// Deals a decision point at a time and grades each answer against basic
+// strategy until stdin runs out or the player quits. The categories missed
+// most are dealt most. Returns how many answers were correct out of how
+// many were given.
+pub fn run<R: Rng>(mut rng: R) -> (u64, u64) {
+    let stdin = io::stdin();
+    let mut stdin_lines = stdin.lock().lines();
+    let mut drills: Vec<Drill> = HandCategory::ALL_VALUES.iter().map(|_| Drill::default()).collect();
+
+    println!("Basic strategy practice. Type an action for each hand, or quit to stop.");
+
+    loop {
+        let category = pick_category(&mut rng, &drills);
+        let hand = deal_hand(&mut rng, &HandCategory::ALL_VALUES[category]);
+        let upcard = random_value(&mut rng, &CardValue::ALL_VALUES);
+        let dealer_upcard = random_card(&mut rng, upcard);
+
+        println!("Dealer shows {:?}", dealer_upcard);
+        print_hand(&hand);
+
+        let expected = basic_strategy(&hand, &dealer_upcard);
+        let action = loop {
+            let line = match stdin_lines.next() {
+                Option::Some(Ok(line)) => line,
+                _ => return score(&drills),
+            };
+            if line.trim() == "quit" {
+                return score(&drills);
+            }
+            match line.parse::<Action>() {
+                Ok(action) => break action,
+                Err(error) => println!("{}", error),
+            }
+        };
+
+        let drill = &mut drills[category];
+        drill.total += 1;
+        if action == expected {
+            drill.correct += 1;
+            println!("Correct!");
+        } else {
+            println!("Basic strategy says {:?}, you chose {:?}", expected, action);
+        }
+    }
+}
+
+fn score(drills: &[Drill]) -> (u64, u64) {
+    let correct = drills.iter().map(|drill| drill.correct).sum();
+    let total = drills.iter().map(|drill| drill.total).sum();
+    if total > 0 {
+        println!("You got {} of {} right ({:.0}%)", correct, total, 100.0 * correct as f64 / total as f64);
+        for (category, drill) in HandCategory::ALL_VALUES.iter().zip(drills) {
+            if drill.total > 0 {
+                println!(
+                    "  {:<12} {} of {} ({:.0}%)",
+                    category.name(),
+                    drill.correct,
+                    drill.total,
+                    100.0 * drill.correct as f64 / drill.total as f64
+                );
+            }
+        }
+    }
+    (correct, total)
+}
@@ -0,0 +1,195 @@
+use crate::summary::SessionSummary;
+
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+pub const SECONDS_PER_WEEK: u64 = 7 * SECONDS_PER_DAY;
+// The rolling win rate looks back over this many weeks, including the
+// current one, so a single bad evening doesn't dominate the line.
+pub const ROLLING_WEEKS: usize = 4;
+
+const BAR_WIDTH: usize = 30;
+
+// One stored session: when it ended, which command produced it and what it
+// amounted to.
+#[derive(Debug)]
+pub struct SessionRecord {
+    pub timestamp: u64,
+    pub kind: String,
+    pub summary: SessionSummary,
+}
+
+// Sessions bucketed into calendar weeks starting on Monday.
+#[derive(Debug, Default)]
+pub struct WeekTrend {
+    pub week_start: u64,
+    pub sessions: u64,
+    pub hands: u64,
+    pub wins: u64,
+    pub decided: u64,
+    pub mistakes: u64,
+    pub deviation_spots: u64,
+    pub deviations_taken: u64,
+    pub seconds: u64,
+    pub rolling_win_rate: Option<f64>,
+}
+
+fn ratio(numerator: u64, denominator: u64) -> Option<f64> {
+    if denominator == 0 {
+        Option::None
+    } else {
+        Option::Some(numerator as f64 / denominator as f64)
+    }
+}
+
+impl WeekTrend {
+    // Wins over hands that were actually dealt to a result, so practice
+    // drills don't drag it down.
+    pub fn win_rate(&self) -> Option<f64> {
+        ratio(self.wins, self.decided)
+    }
+
+    pub fn mistakes_per_hand(&self) -> Option<f64> {
+        ratio(self.mistakes, self.hands)
+    }
+
+    // How often a count-based index play was made when the count called for
+    // it.
+    pub fn counting_accuracy(&self) -> Option<f64> {
+        ratio(self.deviations_taken, self.deviation_spots)
+    }
+
+    pub fn hands_per_hour(&self) -> Option<f64> {
+        ratio(self.hands * 3600, self.seconds)
+    }
+}
+
+// Midnight UTC on the Monday of the timestamp's week. The Unix epoch was a
+// Thursday, three days after a Monday.
+fn week_start(timestamp: u64) -> u64 {
+    let day = timestamp / SECONDS_PER_DAY;
+    let days_since_monday = (day + 3) % 7;
+    (day * SECONDS_PER_DAY).saturating_sub(days_since_monday * SECONDS_PER_DAY)
+}
+
+// Only weeks that have at least one session get a row.
+pub fn weekly_trends(records: &[SessionRecord]) -> Vec<WeekTrend> {
+    let mut weeks: Vec<WeekTrend> = Vec::new();
+    let mut sorted: Vec<&SessionRecord> = records.iter().collect();
+    sorted.sort_by_key(|record| record.timestamp);
+    for record in sorted {
+        let start = week_start(record.timestamp);
+        if weeks.last().map(|week| week.week_start) != Option::Some(start) {
+            weeks.push(WeekTrend { week_start: start, ..WeekTrend::default() });
+        }
+        let week = weeks.last_mut().expect("a week was just pushed");
+        let summary = &record.summary;
+        week.sessions += 1;
+        week.hands += summary.hands;
+        week.wins += summary.wins;
+        week.decided += summary.wins + summary.losses + summary.pushes;
+        week.mistakes += summary.mistakes;
+        week.deviation_spots += summary.deviation_spots;
+        week.deviations_taken += summary.deviations_taken;
+        week.seconds += summary.seconds;
+    }
+    for index in 0..weeks.len() {
+        let window = &weeks[(index + 1).saturating_sub(ROLLING_WEEKS)..=index];
+        let wins = window.iter().map(|week| week.wins).sum();
+        let decided = window.iter().map(|week| week.decided).sum();
+        weeks[index].rolling_win_rate = ratio(wins, decided);
+    }
+    weeks
+}
+
+// Days since the epoch to a proleptic Gregorian year, month and day.
+pub fn civil_date(timestamp: u64) -> (i64, u32, u32) {
+    let days = (timestamp / SECONDS_PER_DAY) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+pub fn format_date(timestamp: u64) -> String {
+    let (year, month, day) = civil_date(timestamp);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn csv_number(value: Option<f64>) -> String {
+    value.map(|value| format!("{:.4}", value)).unwrap_or_default()
+}
+
+pub fn to_csv(weeks: &[WeekTrend]) -> String {
+    let mut csv = String::from(
+        "week_start,sessions,hands,win_rate,rolling_win_rate,mistakes_per_hand,counting_accuracy,hands_per_hour\n"
+    );
+    for week in weeks {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{}\n",
+            format_date(week.week_start),
+            week.sessions,
+            week.hands,
+            csv_number(week.win_rate()),
+            csv_number(week.rolling_win_rate),
+            csv_number(week.mistakes_per_hand()),
+            csv_number(week.counting_accuracy()),
+            csv_number(week.hands_per_hour())
+        ));
+    }
+    csv
+}
+
+// A horizontal bar per week, scaled to `scale` (or to the largest value when
+// there is no natural maximum). Weeks without data show a blank bar.
+fn chart<F>(title: &str, weeks: &[WeekTrend], scale: Option<f64>, value: F, label: fn(f64) -> String) -> String
+where
+    F: Fn(&WeekTrend) -> Option<f64>,
+{
+    let values: Vec<Option<f64>> = weeks.iter().map(&value).collect();
+    let maximum = scale.unwrap_or_else(|| values.iter().flatten().cloned().fold(0.0, f64::max));
+    let mut rendered = format!("{}\n", title);
+    for (week, value) in weeks.iter().zip(values) {
+        let (bar, text) = match value {
+            Option::Some(value) if maximum > 0.0 => {
+                let length = ((value / maximum).min(1.0) * BAR_WIDTH as f64).round() as usize;
+                ("#".repeat(length), label(value))
+            },
+            Option::Some(value) => (String::new(), label(value)),
+            Option::None => (String::new(), "-".to_string()),
+        };
+        rendered.push_str(&format!("  {} |{:<width$}| {}\n", format_date(week.week_start), bar, text, width = BAR_WIDTH));
+    }
+    rendered
+}
+
+fn percent(value: f64) -> String {
+    format!("{:.1}%", value * 100.0)
+}
+
+fn per_hand(value: f64) -> String {
+    format!("{:.2}", value)
+}
+
+fn per_hour(value: f64) -> String {
+    format!("{:.0}", value)
+}
+
+pub fn render_charts(weeks: &[WeekTrend]) -> String {
+    [
+        chart(
+            &format!("Win rate ({}-week rolling)", ROLLING_WEEKS),
+            weeks,
+            Option::Some(1.0),
+            |week| week.rolling_win_rate,
+            percent,
+        ),
+        chart("Mistakes per hand", weeks, Option::None, WeekTrend::mistakes_per_hand, per_hand),
+        chart("Counting accuracy (index plays made)", weeks, Option::Some(1.0), WeekTrend::counting_accuracy, percent),
+        chart("Hands per hour", weeks, Option::None, WeekTrend::hands_per_hour, per_hour),
+    ]
+    .join("\n")
+}
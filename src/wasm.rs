@@ -0,0 +1,113 @@
+use rand::{SeedableRng, StdRng};
+
+use crate::json::JsonValue;
+use crate::protocol::{encode_action, encode_card};
+use crate::{
+    calculate_current_hand_value, deal_with_action, parse_action, Card, Deck, GameState,
+};
+
+// A JS-friendly facade over the engine: only integers, strings and an opaque
+// handle cross the boundary, and the shuffle is driven by a caller supplied
+// seed because wasm32-unknown-unknown has no `thread_rng`. These are the
+// functions a `wasm_bindgen` shim would export to JavaScript; there is no
+// such shim yet.
+pub struct Game {
+    state: Option<GameState>,
+}
+
+impl Game {
+    pub fn state(&self) -> Option<&GameState> {
+        self.state.as_ref()
+    }
+
+    // Plays text as a player would type it at the prompt, one action a line.
+    // Lines that don't parse or aren't legal are skipped, and so is
+    // everything once the hand is over. Returns how many lines were played.
+    // No input makes it panic; the fuzz target under fuzz/ holds it to that.
+    pub fn apply_raw_input(&mut self, input: &str) -> usize {
+        input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter(|line| apply_action(self, line))
+            .count()
+    }
+}
+
+pub fn new_game(seed: u32) -> Game {
+    let mut rng: StdRng = SeedableRng::from_seed(&[seed as usize][..]);
+    let mut deck = Deck::new();
+    deck.shuffle(&mut rng);
+    Game { state: Option::Some(GameState::start(deck)) }
+}
+
+// Returns false when the action is not recognised or not legal right now;
+// the game is unchanged.
+pub fn apply_action(game: &mut Game, raw_action: &str) -> bool {
+    match (parse_action(raw_action), game.state.take()) {
+        (Option::Some(action), Option::Some(state)) => {
+            match deal_with_action(&action, state) {
+                Ok(state) => {
+                    game.state = Option::Some(state);
+                    true
+                },
+                Err(illegal) => {
+                    game.state = Option::Some(*illegal.state);
+                    false
+                },
+            }
+        },
+        (Option::None, state) => {
+            game.state = state;
+            false
+        },
+        (Option::Some(_), Option::None) => false,
+    }
+}
+
+fn cards_json(cards: &[Card]) -> JsonValue {
+    JsonValue::Array(cards.iter().map(|card| JsonValue::String(encode_card(card))).collect())
+}
+
+pub fn state_json(game: &Game) -> String {
+    match &game.state {
+        Option::Some(state) => game_state_json(state).to_string(),
+        Option::None => JsonValue::Null.to_string(),
+    }
+}
+
+// The hand as the player sees it, for anything that hands states to a
+// script or a web page.
+pub fn game_state_json(state: &GameState) -> JsonValue {
+    let status = match state {
+        GameState::GameWon(_) => "won",
+        GameState::BlackjackWin(_) => "blackjack",
+        GameState::GameLost(_) => "lost",
+        GameState::Push(_) => "push",
+        GameState::Surrendered(_) => "surrendered",
+        GameState::Continuing(_) => "continuing",
+    };
+    let player_state = state.player_state();
+    let values = calculate_current_hand_value(&player_state.create_hand_values())
+        .iter()
+        .map(|hand_value| JsonValue::Number(hand_value.value as f64))
+        .collect();
+    let legal_actions = state
+        .legal_actions()
+        .iter()
+        .map(|action| JsonValue::String(encode_action(action).to_string()))
+        .collect();
+    JsonValue::object(vec![
+        ("status", JsonValue::String(status.to_string())),
+        ("hand", cards_json(&player_state.hand)),
+        (
+            "split_hands",
+            JsonValue::Array(player_state.split_hands.iter().map(|split| cards_json(&split.hand)).collect()),
+        ),
+        ("dealer", cards_json(&player_state.visible_dealer_cards())),
+        ("dealer_hidden_cards", JsonValue::Number(player_state.hidden_dealer_cards().len() as f64)),
+        ("values", JsonValue::Array(values)),
+        ("legal_actions", JsonValue::Array(legal_actions)),
+        ("insured", JsonValue::Bool(player_state.insured)),
+        ("even_money", JsonValue::Bool(player_state.even_money)),
+    ])
+}
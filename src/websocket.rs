@@ -0,0 +1,369 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+use rand::{thread_rng, Rng};
+
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+// Every protocol message is a line or two of JSON, so a message anywhere near
+// this long is not one; it is refused before anything is allocated for it.
+const MAX_MESSAGE_LENGTH: usize = 64 * 1024;
+
+// The upgrade request has this long to arrive, a line at a time, and this
+// many bytes a line.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_HEADER_LINE: u64 = 8 * 1024;
+
+// Minimal RFC 6455 client: one text message per protocol message, no
+// extensions and no binary frames.
+pub struct WebSocket {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+impl WebSocket {
+    pub fn connect(url: &str) -> io::Result<WebSocket> {
+        let without_scheme = url
+            .strip_prefix("ws://")
+            .ok_or_else(|| invalid_data("WebSocket addresses must start with ws://"))?;
+        let (host, path) = match without_scheme.find('/') {
+            Option::Some(index) => (&without_scheme[..index], &without_scheme[index..]),
+            Option::None => (without_scheme, "/"),
+        };
+
+        let stream = TcpStream::connect(host)?;
+        let mut writer = stream.try_clone()?;
+        let mut reader = BufReader::new(stream);
+
+        let mut nonce = [0u8; 16];
+        thread_rng().fill_bytes(&mut nonce);
+        let key = base64_encode(&nonce);
+
+        write!(
+            writer,
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+            path, host, key
+        )?;
+
+        let status_line = read_header_line(&mut reader)?;
+        if !status_line.starts_with("HTTP/1.1 101") {
+            return Err(invalid_data(&format!("WebSocket upgrade refused: {}", status_line.trim())));
+        }
+
+        let expected_accept = accept_key(&key);
+        let mut accepted = false;
+        loop {
+            let header = read_header_line(&mut reader)?;
+            if header.is_empty() {
+                break;
+            }
+            if let Option::Some((name, value)) = split_header(&header) {
+                if name.eq_ignore_ascii_case("Sec-WebSocket-Accept") && value == expected_accept {
+                    accepted = true;
+                }
+            }
+        }
+        if !accepted {
+            return Err(invalid_data("WebSocket server sent a bad Sec-WebSocket-Accept"));
+        }
+
+        Ok(WebSocket { reader, writer })
+    }
+
+    pub fn send_text(&mut self, text: &str) -> io::Result<()> {
+        // Client frames must always be masked.
+        write_frame(&mut self.writer, OPCODE_TEXT, text.as_bytes(), true)
+    }
+
+    // Returns `None` once the server closes the connection.
+    pub fn read_text(&mut self) -> io::Result<Option<String>> {
+        let writer = &mut self.writer;
+        read_message(&mut self.reader, false, |opcode, payload| write_frame(writer, opcode, payload, true))
+    }
+}
+
+// The server's end of a connection that was answered with `accept`: each
+// text message, as the client sent it, with control frames answered through
+// `reply`, which writes a frame to the client.
+pub struct Messages<F> {
+    reader: BufReader<TcpStream>,
+    reply: F,
+}
+
+impl<F> Messages<F>
+where
+    F: FnMut(u8, &[u8]) -> io::Result<()>,
+{
+    pub fn new(reader: BufReader<TcpStream>, reply: F) -> Messages<F> {
+        Messages { reader, reply }
+    }
+}
+
+impl<F> Iterator for Messages<F>
+where
+    F: FnMut(u8, &[u8]) -> io::Result<()>,
+{
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<io::Result<String>> {
+        read_message(&mut self.reader, true, &mut self.reply).transpose()
+    }
+}
+
+// Answers the upgrade request a browser opens a WebSocket with, returning
+// the connection to read its messages from. Anything else is refused with a
+// 400 and an error.
+pub fn accept(stream: TcpStream) -> io::Result<BufReader<TcpStream>> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+    let next_line = |reader: &mut BufReader<TcpStream>| {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left == Duration::from_secs(0) {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "WebSocket handshake took too long"));
+        }
+        reader.get_ref().set_read_timeout(Option::Some(left))?;
+        read_header_line(reader)
+    };
+
+    let request_line = next_line(&mut reader)?;
+    let (mut key, mut upgrade, mut connection, mut version) = (Option::None, false, false, false);
+    loop {
+        let header = next_line(&mut reader)?;
+        if header.is_empty() {
+            break;
+        }
+        if let Option::Some((name, value)) = split_header(&header) {
+            if name.eq_ignore_ascii_case("Upgrade") {
+                upgrade = value.eq_ignore_ascii_case("websocket");
+            } else if name.eq_ignore_ascii_case("Connection") {
+                connection = value.split(',').any(|token| token.trim().eq_ignore_ascii_case("upgrade"));
+            } else if name.eq_ignore_ascii_case("Sec-WebSocket-Version") {
+                version = value == "13";
+            } else if name.eq_ignore_ascii_case("Sec-WebSocket-Key") {
+                key = Option::Some(value.to_string());
+            }
+        }
+    }
+    let key = match key {
+        Option::Some(key) if request_line.starts_with("GET ") && upgrade && connection && version => key,
+        _ => {
+            let _ = write!(writer, "HTTP/1.1 400 Bad Request\r\nConnection: close\r\nContent-Length: 0\r\n\r\n");
+            return Err(invalid_data("Not a WebSocket upgrade request"));
+        },
+    };
+    write!(
+        writer,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(&key)
+    )?;
+    reader.get_ref().set_read_timeout(Option::None)?;
+    Ok(reader)
+}
+
+// A handshake line without its line ending, refused if it runs past
+// `MAX_HEADER_LINE` or the connection closes partway through it.
+fn read_header_line(reader: &mut BufReader<TcpStream>) -> io::Result<String> {
+    let mut line = String::new();
+    reader.by_ref().take(MAX_HEADER_LINE).read_line(&mut line)?;
+    if !line.ends_with('\n') {
+        return Err(invalid_data("WebSocket handshake line was too long or cut short"));
+    }
+    Ok(line.trim_end().to_string())
+}
+
+fn split_header(header: &str) -> Option<(&str, &str)> {
+    let index = header.find(':')?;
+    Option::Some((header[..index].trim(), header[index + 1..].trim()))
+}
+
+// Server frames go out unmasked; clients mask theirs.
+pub fn write_frame<W: Write>(writer: &mut W, opcode: u8, payload: &[u8], masked: bool) -> io::Result<()> {
+    let mask_bit = if masked { 0x80 } else { 0 };
+    let mut frame = vec![0x80 | opcode];
+    let length = payload.len();
+    if length < 126 {
+        frame.push(mask_bit | length as u8);
+    } else if length <= 0xFFFF {
+        frame.push(mask_bit | 126);
+        frame.extend_from_slice(&(length as u16).to_be_bytes());
+    } else {
+        frame.push(mask_bit | 127);
+        frame.extend_from_slice(&(length as u64).to_be_bytes());
+    }
+    if masked {
+        let mut mask = [0u8; 4];
+        thread_rng().fill_bytes(&mut mask);
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, byte)| byte ^ mask[i % 4]));
+    } else {
+        frame.extend_from_slice(payload);
+    }
+    // One write a frame, so frames written from two threads don't interleave.
+    writer.write_all(&frame)
+}
+
+pub fn write_text<W: Write>(writer: &mut W, text: &str) -> io::Result<()> {
+    write_frame(writer, OPCODE_TEXT, text.as_bytes(), false)
+}
+
+// Reads frames until a whole text message has arrived, answering pings and
+// closes through `reply` on the way. Returns `None` once the other end
+// closes the connection. A server insists that frames come `from_client`
+// masked, as RFC 6455 has them.
+fn read_message<R, F>(reader: &mut R, from_client: bool, mut reply: F) -> io::Result<Option<String>>
+where
+    R: Read,
+    F: FnMut(u8, &[u8]) -> io::Result<()>,
+{
+    let mut message = Vec::new();
+    loop {
+        let mut header = [0u8; 2];
+        if let Err(error) = reader.read_exact(&mut header) {
+            return if error.kind() == io::ErrorKind::UnexpectedEof { Ok(Option::None) } else { Err(error) };
+        }
+        let fin = header[0] & 0x80 != 0;
+        let opcode = header[0] & 0x0F;
+        let masked = header[1] & 0x80 != 0;
+        if from_client && !masked {
+            return Err(invalid_data("WebSocket client frames must be masked"));
+        }
+        let length = match header[1] & 0x7F {
+            126 => {
+                let mut extended = [0u8; 2];
+                reader.read_exact(&mut extended)?;
+                u16::from_be_bytes(extended) as u64
+            },
+            127 => {
+                let mut extended = [0u8; 8];
+                reader.read_exact(&mut extended)?;
+                u64::from_be_bytes(extended)
+            },
+            short => short as u64,
+        };
+        if length > (MAX_MESSAGE_LENGTH - message.len()) as u64 {
+            return Err(invalid_data("WebSocket message was too long"));
+        }
+        let mut mask = [0u8; 4];
+        if masked {
+            reader.read_exact(&mut mask)?;
+        }
+        let mut payload = vec![0u8; length as usize];
+        reader.read_exact(&mut payload)?;
+        if masked {
+            for (i, byte) in payload.iter_mut().enumerate() {
+                *byte ^= mask[i % 4];
+            }
+        }
+
+        match opcode {
+            OPCODE_TEXT | OPCODE_CONTINUATION => {
+                message.extend_from_slice(&payload);
+                if fin {
+                    return String::from_utf8(message)
+                        .map(Option::Some)
+                        .map_err(|_| invalid_data("WebSocket text frame was not UTF-8"));
+                }
+            },
+            OPCODE_CLOSE => {
+                let _ = reply(OPCODE_CLOSE, &[]);
+                return Ok(Option::None);
+            },
+            OPCODE_PING => reply(OPCODE_PONG, &payload)?,
+            OPCODE_PONG => (),
+            _ => return Err(invalid_data("Unsupported WebSocket frame")),
+        }
+    }
+}
+
+pub fn accept_key(key: &str) -> String {
+    let mut input = key.as_bytes().to_vec();
+    input.extend_from_slice(HANDSHAKE_GUID.as_bytes());
+    base64_encode(&sha1(&input))
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut result = String::new();
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).cloned().unwrap_or(0) as u32;
+        let b2 = chunk.get(2).cloned().unwrap_or(0) as u32;
+        let triple = (b0 << 16) | (b1 << 8) | b2;
+        result.push(BASE64_ALPHABET[(triple >> 18) as usize & 0x3F] as char);
+        result.push(BASE64_ALPHABET[(triple >> 12) as usize & 0x3F] as char);
+        if chunk.len() > 1 {
+            result.push(BASE64_ALPHABET[(triple >> 6) as usize & 0x3F] as char);
+        } else {
+            result.push('=');
+        }
+        if chunk.len() > 2 {
+            result.push(BASE64_ALPHABET[triple as usize & 0x3F] as char);
+        } else {
+            result.push('=');
+        }
+    }
+    result
+}
+
+pub fn sha1(input: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    let mut message = input.to_vec();
+    let bit_length = (input.len() as u64).wrapping_mul(8);
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&bit_length.to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for i in 0..16 {
+            w[i] = u32::from_be_bytes([block[4 * i], block[4 * i + 1], block[4 * i + 2], block[4 * i + 3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let (mut a, mut b, mut c, mut d, mut e) = (h[0], h[1], h[2], h[3], h[4]);
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        h[0] = h[0].wrapping_add(a);
+        h[1] = h[1].wrapping_add(b);
+        h[2] = h[2].wrapping_add(c);
+        h[3] = h[3].wrapping_add(d);
+        h[4] = h[4].wrapping_add(e);
+    }
+
+    let mut digest = [0u8; 20];
+    for (i, word) in h.iter().enumerate() {
+        digest[4 * i..4 * i + 4].copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
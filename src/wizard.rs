@@ -0,0 +1,86 @@
+use std::io::{self, BufRead, Write};
+
+use rust_blackjack::config::{config_path, CoachLevel, Config, ConfigError, RulesPreset};
+
+fn ask(lines: &mut dyn Iterator<Item = io::Result<String>>, question: &str, default: &str) -> io::Result<String> {
+    print!("{} [{}]: ", question, default);
+    io::stdout().flush()?;
+    match lines.next() {
+        Option::Some(line) => {
+            let line = line?;
+            let answer = line.trim();
+            Ok(if answer.is_empty() { default.to_string() } else { answer.to_string() })
+        },
+        Option::None => Ok(default.to_string()),
+    }
+}
+
+fn ask_until<T, F>(lines: &mut dyn Iterator<Item = io::Result<String>>, question: &str, default: &str, parse: F) -> io::Result<T>
+where
+    F: Fn(&str) -> Option<T>,
+{
+    loop {
+        let answer = ask(lines, question, default)?;
+        if let Option::Some(value) = parse(&answer) {
+            return Ok(value);
+        }
+        println!("Sorry, {:?} isn't one of the choices.", answer);
+    }
+}
+
+fn names<T>(values: &[T], name: fn(&T) -> &'static str) -> String {
+    values.iter().map(name).collect::<Vec<&str>>().join("/")
+}
+
+pub fn run() -> Result<Config, ConfigError> {
+    let defaults = Config::default();
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    println!("Welcome to blackjack! Let's set a few preferences before the first game.");
+
+    let rules_preset = ask_until(
+        &mut lines,
+        &format!("Rules preset ({})", names(&RulesPreset::ALL_VALUES, RulesPreset::name)),
+        defaults.rules_preset.name(),
+        RulesPreset::from_name,
+    )?;
+    // Only the bankroll can make a config that `validate` turns down, such
+    // as one too small for the default bet, which every launch would then
+    // ignore. It is asked again until it passes.
+    let mut config = loop {
+        let bankroll = ask_until(
+            &mut lines,
+            "Starting bankroll",
+            &defaults.bankroll.to_string(),
+            |answer| answer.parse().ok(),
+        )?;
+        let config = Config { rules_preset: rules_preset.clone(), bankroll, ..Config::default() };
+        match config.validate() {
+            Ok(()) => break config,
+            Err(error) => println!("Sorry, {}.", error),
+        }
+    };
+    let coach = ask_until(
+        &mut lines,
+        &format!("Coach level ({})", names(&CoachLevel::ALL_VALUES, CoachLevel::name)),
+        defaults.coach.name(),
+        CoachLevel::from_name,
+    )?;
+    let color = ask_until(
+        &mut lines,
+        "Use colors (yes/no)",
+        if defaults.color { "yes" } else { "no" },
+        |answer| match answer {
+            "yes" | "y" => Option::Some(true),
+            "no" | "n" => Option::Some(false),
+            _ => Option::None,
+        },
+    )?;
+
+    config.coach = coach;
+    config.color = color;
+    config.save()?;
+    println!("Saved your preferences to {}", config_path().display());
+    Ok(config)
+}
@@ -0,0 +1,52 @@
+// Hands that wait for their decisions: two tables played together on one
+// thread with the decisions coming from another, and a clock that stands for
+// a player who doesn't answer.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::thread;
+use std::time::Duration;
+
+use rust_blackjack::async_game::{action_channel, block_on, block_on_all, AsyncGame};
+use rust_blackjack::events::{Outcome, UiEvent};
+use rust_blackjack::scripted::ScriptedCardSource;
+use rust_blackjack::{Action, GameState};
+
+fn game(script: &str) -> AsyncGame {
+    let deck = ScriptedCardSource::parse(script).expect("valid card codes").into_deck();
+    AsyncGame::new(GameState::start(deck))
+}
+
+#[test]
+fn tables_wait_on_their_own_decisions() {
+    let (first_sender, mut first) = action_channel();
+    let (second_sender, mut second) = action_channel();
+    let decisions = thread::spawn(move || {
+        // The second table hears first; the first has to wait its turn.
+        second_sender.send(Action::Hit);
+        thread::sleep(Duration::from_millis(20));
+        first_sender.send(Action::Stand);
+    });
+    let (mut first_events, mut second_events) = (Vec::new(), Vec::new());
+    let hands: Vec<Pin<Box<dyn Future<Output = GameState> + '_>>> = vec![
+        Box::pin(game("TS 6H 8D AS").play(&mut first, |event| first_events.push(event))),
+        Box::pin(game("TS 6H 8D TC 5C").play(&mut second, |event| second_events.push(event))),
+    ];
+    let finished = block_on_all(hands);
+    decisions.join().expect("the decisions were sent");
+    assert!(matches!(finished[0], GameState::GameWon(_)));
+    assert!(matches!(finished[1], GameState::GameLost(_)));
+    assert_eq!(first_events.last(), Option::Some(&UiEvent::OutcomeDecided { hand: 1, outcome: Outcome::Won }));
+    assert_eq!(second_events.last(), Option::Some(&UiEvent::OutcomeDecided { hand: 1, outcome: Outcome::Bust }));
+}
+
+#[test]
+fn a_decision_not_made_in_time_stands_and_a_hung_up_player_surrenders() {
+    let (sender, mut actions) = action_channel();
+    let timed = game("TS 6H 8D AS").with_decision_time(Duration::from_millis(10));
+    let finished = block_on(timed.play(&mut actions, |_| ()));
+    assert!(matches!(finished, GameState::GameWon(_)));
+    drop(sender);
+    let finished = block_on(game("TS 6H 8D AS").play(&mut actions, |_| ()));
+    assert!(matches!(finished, GameState::Surrendered(_)));
+}
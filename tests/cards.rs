@@ -0,0 +1,19 @@
+// Cards as the numbers 0 to 51.
+
+use rust_blackjack::{Card, CardSuit, CardValue};
+
+#[test]
+fn every_card_has_its_own_index_and_reads_back_from_it() {
+    let mut seen = Vec::new();
+    for suit in CardSuit::ALL_VALUES {
+        for value in CardValue::ALL_VALUES {
+            let card = Card { suit, value };
+            let index = card.to_index();
+            assert_eq!(Card::from_index(index), Option::Some(card));
+            assert!(!seen.contains(&index));
+            seen.push(index);
+        }
+    }
+    assert_eq!(seen.iter().max(), Option::Some(&51));
+    assert_eq!(Card::from_index(52), Option::None);
+}
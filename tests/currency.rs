@@ -0,0 +1,38 @@
+// Amounts are whole numbers of the currency's smallest unit, written and
+// read with its symbol, places and separator.
+
+use rust_blackjack::currency::Currency;
+use rust_blackjack::summary::SessionSummary;
+
+fn dollars() -> Currency {
+    Currency { symbol: "$".to_string(), decimals: 2, separator: Option::Some(',') }
+}
+
+#[test]
+fn amounts_are_written_in_the_currency() {
+    let dollars = dollars();
+    assert_eq!(dollars.format(123450), "$1,234.50");
+    assert_eq!(dollars.format(-5), "-$0.05");
+    assert_eq!(dollars.format_signed(750), "+$7.50");
+    assert_eq!(dollars.format_approximate(31.25), "$0.31");
+    assert_eq!(Currency::default().format(1000), "1000");
+    assert_eq!(Currency::default().format_signed(-10), "-10");
+}
+
+#[test]
+fn amounts_are_read_up_to_the_currency_s_places() {
+    let dollars = dollars();
+    assert_eq!(dollars.parse("12.5"), Option::Some(1250));
+    assert_eq!(dollars.parse("$1,000"), Option::Some(100000));
+    assert_eq!(dollars.parse("12.505"), Option::None);
+    assert_eq!(dollars.parse("2x25"), Option::None);
+    assert_eq!(Currency::default().parse("35"), Option::Some(35));
+    assert_eq!(Currency::default().parse("3.5"), Option::None);
+}
+
+#[test]
+fn a_session_cashes_out_in_whole_units() {
+    let summary = SessionSummary { starting_bankroll: 100000, rebought: 50, net: 1250, ..SessionSummary::default() };
+    let summary = summary.in_whole_units(100);
+    assert_eq!((summary.starting_bankroll, summary.rebought, summary.final_bankroll()), (1000, 1, 1013));
+}
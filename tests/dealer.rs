@@ -0,0 +1,96 @@
+// The dealer's drawing rule at the totals where S17 and H17 part ways, and
+// the soft totals that are easy to get wrong: several aces, and hands that
+// were soft until the last card.
+
+use rust_blackjack::dealer::{dealer_total, DealerProfile};
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::scripted::ScriptedCardSource;
+use rust_blackjack::{deal_with_action, Action, Card, GameState};
+
+fn cards(codes: &str) -> Vec<Card> {
+    ScriptedCardSource::parse(codes).expect("valid card codes").cards().to_vec()
+}
+
+fn rules(hits_soft_17: bool) -> GameRules {
+    GameRules { dealer_hits_soft_17: hits_soft_17, ..GameRules::default() }
+}
+
+#[test]
+fn soft_totals_count_one_ace_as_eleven() {
+    let cases = [
+        ("AS 6H", (17, true)),
+        ("AS AH 5D", (17, true)),
+        ("AS AH AD 4C", (17, true)),
+        ("AS 6H TD", (17, false)),
+        ("AS 5H AD", (17, true)),
+        ("AS AH", (12, true)),
+        ("AS AH AD AC TS", (14, false)),
+        ("AS TH", (21, true)),
+        ("9S 7H", (16, false)),
+        ("TS 7H", (17, false)),
+        ("TS 6H AD 5C", (22, false)),
+    ];
+    for (hand, expected) in cases.iter() {
+        assert_eq!(dealer_total(&cards(hand)), *expected, "{}", hand);
+    }
+}
+
+// Whether the dealer draws, standing on soft 17 and hitting it.
+#[test]
+fn the_dealer_draws_to_seventeen_and_on_soft_seventeen_under_h17() {
+    let cases = [
+        ("AS 6H", false, true),
+        ("AS AH 5D", false, true),
+        ("AS AH AD 4C", false, true),
+        ("AS 5H AD", false, true),
+        ("AS 6H TD", false, false),
+        ("TS 7H", false, false),
+        ("9S 7H", true, true),
+        ("AS 5H", true, true),
+        ("AS AH AD AC 2S", true, true),
+        ("AS 7H", false, false),
+        ("AS AH 6D", false, false),
+        ("6S 5H AD AC 4S", false, false),
+        ("AS TH", false, false),
+        ("TS 6H 8D", false, false),
+    ];
+    let policy = DealerProfile::Standard.policy();
+    for (hand, s17, h17) in cases.iter() {
+        assert_eq!(policy.must_draw(&cards(hand), &rules(false)), *s17, "S17 {}", hand);
+        assert_eq!(policy.must_draw(&cards(hand), &rules(true)), *h17, "H17 {}", hand);
+    }
+}
+
+// Deals the player T-8 and the dealer an upcard and hole card, stands, and
+// returns the cards the dealer ends with. The script is in dealing order:
+// player, upcard, player, hole card, then the dealer's draws.
+fn dealer_finishes_with(upcard: &str, hole_card: &str, draws: &str, hits_soft_17: bool) -> Vec<Card> {
+    let script = format!("TS {} 8D {} {}", upcard, hole_card, draws);
+    let deck = ScriptedCardSource::parse(&script).expect("valid card codes").into_deck();
+    let state = GameState::start_with_rules(deck, rules(hits_soft_17));
+    match deal_with_action(&Action::Stand, state) {
+        Ok(state) => state.player_state().dealer_cards(),
+        Err(illegal) => panic!("standing on 18 was refused: {}", illegal),
+    }
+}
+
+#[test]
+fn dealer_draw_sequences_follow_the_soft_17_rule() {
+    // (upcard, hole card, cards left to draw, S17 ends with, H17 ends with)
+    let cases = [
+        ("6H", "AS", "5C 4D 2C", "6H AS", "6H AS 5C 4D 2C"),
+        ("5H", "AS", "AD TC", "5H AS AD", "5H AS AD TC"),
+        ("4H", "AS", "AD AC 2S", "4H AS AD AC", "4H AS AD AC 2S"),
+        ("6H", "AS", "TD", "6H AS", "6H AS TD"),
+        ("5H", "AS", "TD 2C", "5H AS TD 2C", "5H AS TD 2C"),
+        ("2H", "AS", "AD 3C 4S", "2H AS AD 3C", "2H AS AD 3C 4S"),
+        ("7H", "KS", "5C", "7H KS", "7H KS"),
+        ("7H", "AS", "5C", "7H AS", "7H AS"),
+        ("9H", "7S", "AC 5C", "9H 7S AC", "9H 7S AC"),
+        ("6H", "TS", "AC 5C", "6H TS AC", "6H TS AC"),
+    ];
+    for (upcard, hole_card, draws, s17, h17) in cases.iter() {
+        assert_eq!(dealer_finishes_with(upcard, hole_card, draws, false), cards(s17), "S17 {} {}", upcard, hole_card);
+        assert_eq!(dealer_finishes_with(upcard, hole_card, draws, true), cards(h17), "H17 {} {}", upcard, hole_card);
+    }
+}
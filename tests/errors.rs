@@ -0,0 +1,76 @@
+// The fallible constructors turn bad values down with an error instead of
+// panicking.
+
+use std::convert::TryFrom;
+
+use rust_blackjack::chips::{check_split, double_stake, BetError, TableLimits};
+use rust_blackjack::error::BlackjackError;
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::scripted::ScriptedCardSource;
+use rust_blackjack::settlement::{settle_with_short_double, staked, ShortDouble};
+use rust_blackjack::{Action, Card, CardSuit, CardValue, Deck, GameState, HandValue};
+
+#[test]
+fn hand_values_stop_at_twenty_one() {
+    assert_eq!(HandValue::try_from(21).map(|hand_value| hand_value.value).ok(), Option::Some(21));
+    assert!(matches!(HandValue::try_from(22), Err(BlackjackError::InvalidHandValue(22))));
+}
+
+#[test]
+fn cards_decode_or_say_why_not() {
+    assert_eq!(Card::try_from("AS").ok(), Option::Some(Card { suit: CardSuit::Spades, value: CardValue::Ace }));
+    assert!(matches!(Card::try_from("1S"), Err(BlackjackError::InvalidCard(_))));
+    assert!(matches!(CardValue::try_from('Z'), Err(BlackjackError::InvalidCard(_))));
+    assert!(matches!(Deck::try_with_decks(0), Err(BlackjackError::InvalidDeckCount(0))));
+}
+
+#[test]
+fn short_shoes_and_bad_rules_are_refused() {
+    let mut deck = Deck::new();
+    while deck.remaining() > 2 {
+        deck.draw_card();
+    }
+    let dealt = GameState::try_start_with_rules(deck.clone(), GameRules::default());
+    assert!(matches!(dealt, Err(BlackjackError::EmptyShoe { needed: 4, remaining: 2 })));
+    while deck.draw_card().is_some() {}
+    assert!(matches!(deck.try_draw_card(), Err(BlackjackError::EmptyShoe { .. })));
+
+    let rules = GameRules { blackjack_denominator: 0, ..GameRules::default() };
+    assert!(matches!(GameState::try_start_with_rules(Deck::new(), rules), Err(BlackjackError::InvalidRules(_))));
+}
+
+#[test]
+fn illegal_actions_convert() {
+    let state = GameState::start(Deck::new());
+    let error: BlackjackError = rust_blackjack::deal_with_action(&Action::Insurance, state).unwrap_err().into();
+    assert!(matches!(error, BlackjackError::IllegalAction(_)));
+}
+
+#[test]
+fn bets_stay_within_the_table_and_the_bankroll() {
+    let limits = TableLimits { minimum: 5, maximum: 500 };
+    assert_eq!(limits.check(25, 100), Ok(()));
+    assert_eq!(limits.check(1, 100), Err(BetError::BelowMinimum { bet: 1, minimum: 5 }));
+    assert_eq!(limits.check(600, 1000), Err(BetError::AboveMaximum { bet: 600, maximum: 500 }));
+    assert_eq!(limits.check(50, 40), Err(BetError::OverBankroll { bet: 50, bankroll: 40 }));
+    assert_eq!(check_split(10, 9), Err(BetError::CannotAffordSplit { bet: 10, available: 9 }));
+    assert_eq!(double_stake(10, 0), Err(BetError::CannotAffordDouble));
+    let error: BlackjackError = BetError::CannotAffordDouble.into();
+    assert!(matches!(error, BlackjackError::InvalidBet(BetError::CannotAffordDouble)));
+}
+
+// 11 against a dealer 16, doubled with the 4 chips left on a bet of 10; the
+// dealer busts and the double pays on the 14 staked.
+#[test]
+fn doubling_for_less_puts_down_what_is_left() {
+    let deck = ScriptedCardSource::parse("6S 6H 5D TC TS TD").unwrap().into_deck();
+    let state = GameState::start_with_rules(deck, GameRules::default());
+    assert_eq!(staked(&state, 10, Option::None), 10);
+    let extra = double_stake(10, 14 - staked(&state, 10, Option::None)).unwrap();
+    assert_eq!(extra, 4);
+    let short_double = ShortDouble { hand: 0, extra };
+    let state = rust_blackjack::deal_with_action(&Action::DoubleDown, state).unwrap();
+    assert_eq!(staked(&state, 10, Option::Some(&short_double)), 14);
+    let settlement = settle_with_short_double(&state, 10, Option::Some(&short_double), &[]);
+    assert_eq!((settlement.bet, settlement.net), (14, 14));
+}
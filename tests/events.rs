@@ -0,0 +1,62 @@
+// The events a frontend sees for a hand, from the deal to the outcome, in
+// the order the cards were dealt.
+
+use std::convert::TryFrom;
+use std::sync::mpsc;
+
+use rust_blackjack::events::{self, Outcome, Seat, UiEvent};
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::scripted::ScriptedCardSource;
+use rust_blackjack::{Action, Card};
+
+fn card(code: &str) -> Card {
+    Card::try_from(code).expect("valid card code")
+}
+
+fn dealt(to: Seat, code: &str, face_up: bool) -> UiEvent {
+    UiEvent::CardDealt { to, card: card(code), face_up }
+}
+
+#[test]
+fn standing_turns_over_the_hole_card_and_decides_the_hand() {
+    let deck = ScriptedCardSource::parse("TS 6H 8D AS").expect("valid card codes").into_deck();
+    let mut seen = Vec::new();
+    let state = events::start(deck, GameRules::default(), &mut |event| seen.push(event));
+    events::act(&Action::Stand, state, &mut |event| seen.push(event)).expect("standing is legal");
+    assert_eq!(
+        seen,
+        vec![
+            dealt(Seat::Player, "TS", true),
+            dealt(Seat::Dealer, "6H", true),
+            dealt(Seat::Player, "8D", true),
+            dealt(Seat::Dealer, "AS", false),
+            UiEvent::TurnStarted { seat: Seat::Player, hand: 1 },
+            UiEvent::TurnStarted { seat: Seat::Dealer, hand: 1 },
+            UiEvent::HoleCardTurned { card: card("AS") },
+            UiEvent::OutcomeDecided { hand: 1, outcome: Outcome::Won },
+        ]
+    );
+}
+
+#[test]
+fn a_bust_reaches_a_channel_and_an_illegal_action_sends_nothing() {
+    let deck = ScriptedCardSource::parse("TS 6H 8D TC 5C").expect("valid card codes").into_deck();
+    let (mut sender, receiver) = mpsc::channel();
+    let state = events::start(deck, GameRules::default(), &mut sender);
+    let state = match events::act(&Action::Insurance, state, &mut sender) {
+        Ok(_) => panic!("insurance was offered against a six"),
+        Err(illegal) => *illegal.state,
+    };
+    events::act(&Action::Hit, state, &mut sender).expect("hitting 18 is legal");
+    drop(sender);
+    let seen: Vec<UiEvent> = receiver.iter().skip(5).collect();
+    assert_eq!(
+        seen,
+        vec![
+            dealt(Seat::Player, "5C", true),
+            UiEvent::TurnStarted { seat: Seat::Dealer, hand: 1 },
+            UiEvent::HoleCardTurned { card: card("TC") },
+            UiEvent::OutcomeDecided { hand: 1, outcome: Outcome::Bust },
+        ]
+    );
+}
@@ -0,0 +1,29 @@
+// A shoe's commitment holds up against its own reveal and nothing else.
+
+use rust_blackjack::fairness::{self, FairnessError, Reveal, SealedShoe};
+use rust_blackjack::scripted::ScriptedCardSource;
+
+#[test]
+fn sha256_matches_the_standard_vectors() {
+    assert_eq!(fairness::hex(&fairness::sha256(b"")), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+    assert_eq!(fairness::hex(&fairness::sha256(b"abc")), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    assert_eq!(
+        fairness::hex(&fairness::sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+        "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+    );
+}
+
+#[test]
+fn a_reveal_verifies_only_against_its_own_shoe() {
+    let deck = ScriptedCardSource::parse("TS 6H 8D AS").expect("valid card codes").into_deck();
+    let sealed = SealedShoe::seal(&deck, "0123");
+    assert_eq!(sealed.reveal().order, "TS 6H 8D AS");
+    let cards = fairness::verify(sealed.commitment(), sealed.reveal()).expect("the shoe is as committed");
+    assert_eq!(cards.len(), 4);
+
+    let stacked = Reveal { nonce: "0123".to_string(), order: "AS 6H 8D TS".to_string() };
+    match fairness::verify(sealed.commitment(), &stacked) {
+        Err(FairnessError::Mismatch { committed, .. }) => assert_eq!(committed, sealed.commitment()),
+        other => panic!("a reordered shoe verified: {:?}", other),
+    }
+}
@@ -0,0 +1,34 @@
+// The fast simulation generator is xoshiro256** exactly, and a worker's
+// seed replays its run.
+
+use rand::Rng;
+
+use rust_blackjack::fast_rng::Xoshiro256;
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::simulation::{parallel_batch, simulate, SimulationReport};
+use rust_blackjack::Deck;
+
+#[test]
+fn it_matches_the_reference_outputs() {
+    let mut rng = Xoshiro256::from_state([1, 2, 3, 4]);
+    let outputs: Vec<u64> = (0..4).map(|_| rng.next_u64()).collect();
+    assert_eq!(outputs, vec![11520, 0, 1509978240, 1215971899390074240]);
+}
+
+#[test]
+fn a_seed_and_thread_count_replay_the_same_simulation() {
+    let rules = GameRules::default();
+    let run = || {
+        parallel_batch(2_000, 3, 17, |share, rng: &mut Xoshiro256| {
+            let new_deck = || {
+                let mut deck = Deck::with_decks(rules.decks);
+                deck.shuffle(rng);
+                deck
+            };
+            simulate(share, new_deck, &rules, 10, &[])
+        })
+    };
+    let (first, second): (SimulationReport, SimulationReport) = (run(), run());
+    assert_eq!((first.hands, first.wins, first.net), (second.hands, second.wins, second.net));
+    assert_eq!(first.hands, 2_000);
+}
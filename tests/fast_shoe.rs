@@ -0,0 +1,30 @@
+// The preallocated shoe deals each card once per shuffle and hands the rest
+// to the engine in the same order.
+
+use rand::{SeedableRng, StdRng};
+
+use rust_blackjack::shoe::FastShoe;
+use rust_blackjack::Card;
+
+#[test]
+fn a_shuffled_shoe_deals_every_card_once_and_the_rest_as_a_deck() {
+    let mut rng: StdRng = SeedableRng::from_seed(&[3usize][..]);
+    let mut shoe = FastShoe::new(2, 75).expect("two decks is a valid shoe");
+    shoe.shuffle(&mut rng);
+    let mut dealt: Vec<Card> = (0..10).filter_map(|_| shoe.deal()).collect();
+    assert_eq!(shoe.dealt(), &dealt[..]);
+
+    let mut deck = shoe.to_deck();
+    assert_eq!(deck.remaining(), 94);
+    let next = shoe.deal();
+    assert_eq!(next, deck.draw_card());
+    assert!(!shoe.needs_shuffle());
+
+    dealt.extend(next);
+    dealt.extend(std::iter::from_fn(|| shoe.deal()));
+    let mut every: Vec<u8> = dealt.iter().map(Card::to_index).collect();
+    assert!(shoe.needs_shuffle());
+    every.sort_unstable();
+    let two_decks: Vec<u8> = (0..52).flat_map(|index| vec![index, index]).collect();
+    assert_eq!(every, two_decks);
+}
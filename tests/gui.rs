@@ -0,0 +1,32 @@
+// The desktop table's model, played through its buttons the way a window
+// would: deal, press what's enabled, and check the bankroll follows.
+#![cfg(feature = "gui")]
+
+use rust_blackjack::chips::TableLimits;
+use rust_blackjack::gui::TableWindow;
+use rust_blackjack::rules::GameRules;
+
+#[test]
+fn a_window_plays_hands_through_its_buttons() {
+    let mut window = TableWindow::new(GameRules::default(), TableLimits::default(), 100, 7);
+    window.set_bet(23);
+    assert_eq!(window.view().bet.value, 20);
+    for _ in 0..20 {
+        assert!(window.deal());
+        if !window.view().can_deal {
+            assert!(!window.deal(), "dealt again mid-hand");
+        }
+        while !window.view().can_deal {
+            let view = window.view();
+            let enabled: Vec<_> = view.buttons.iter().filter(|button| button.enabled).collect();
+            assert!(!enabled.is_empty(), "a hand in play with nothing to press");
+            assert!(window.press(&enabled[0].action));
+        }
+        let view = window.view();
+        assert!(!view.message.is_empty());
+        assert!(view.log.iter().any(|line| line.starts_with("Net ")));
+        if view.bankroll < 20 {
+            break;
+        }
+    }
+}
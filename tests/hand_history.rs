@@ -0,0 +1,87 @@
+// Hand histories read back as they were written, in both forms.
+
+use std::convert::TryFrom;
+
+use rust_blackjack::events::{Outcome, UiEvent};
+use rust_blackjack::hand_history::{parse_history, replay, HistoryError, HistoryHand, ReplayError, ReplayMove};
+use rust_blackjack::payout::{PayoutRounding, Rounding};
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::{Action, Card};
+
+fn cards(codes: &str) -> Vec<Card> {
+    codes.split_whitespace().map(|code| Card::try_from(code).expect("valid card code")).collect()
+}
+
+fn split_hand() -> HistoryHand {
+    HistoryHand {
+        hand: 3,
+        rules: GameRules {
+            decks: 6,
+            dealer_hits_soft_17: true,
+            charlie: Option::Some(5),
+            payout_rounding: PayoutRounding { rounding: Rounding::Nearest, step: 5 },
+            ..GameRules::default()
+        },
+        bet: 25,
+        shoe: Option::Some(cards("9D 3S 9C QD 4H 4S 8C")),
+        actions: vec![Action::SplitCards, Action::Stand],
+        left: true,
+        player_hands: vec![cards("9D 4H"), cards("9C 4S")],
+        dealer_cards: cards("3S QD 8C"),
+        outcome: "surrendered".to_string(),
+        net: -37,
+        bankroll: 963,
+    }
+}
+
+#[test]
+fn text_and_json_histories_read_back_the_same_hands() {
+    let hand = split_hand();
+    let mut unshod = split_hand();
+    unshod.shoe = Option::None;
+    unshod.hand = 4;
+
+    let text = format!("# rust-blackjack hand history v1\n\n{}\n\n# a comment\n{}\n", hand.to_text(), unshod.to_text());
+    assert_eq!(parse_history(&text), Ok(vec![hand.clone(), unshod.clone()]));
+
+    let json = format!("{{\"format\":\"rust-blackjack hand history\",\"version\":1}}\n{}\n{}\n", hand.to_json(), unshod.to_json());
+    assert_eq!(parse_history(&json), Ok(vec![hand, unshod]));
+}
+
+#[test]
+fn other_versions_and_broken_hands_are_turned_down() {
+    assert_eq!(parse_history("# rust-blackjack hand history v2\n"), Err(HistoryError::UnsupportedVersion("2".to_string())));
+    assert_eq!(parse_history("hand,player_cards\n"), Err(HistoryError::UnknownFormat));
+
+    let text = split_hand().to_text().replace("Bet: 25", "Bet: lots");
+    match parse_history(&format!("# rust-blackjack hand history v1\n\n{}", text)) {
+        Err(HistoryError::Malformed { line: 3, .. }) => {},
+        other => panic!("expected the hand starting on line 3 to be malformed, got {:?}", other),
+    }
+}
+
+#[test]
+fn a_hand_replays_through_the_engine_as_it_was_written() {
+    let mut hand = HistoryHand {
+        hand: 1,
+        rules: GameRules::default(),
+        bet: 10,
+        shoe: Option::Some(cards("TS 6H 7D 9C 5S")),
+        actions: vec![Action::Hit],
+        left: false,
+        player_hands: vec![cards("TS 7D 5S")],
+        dealer_cards: cards("6H 9C"),
+        outcome: "bust".to_string(),
+        net: -10,
+        bankroll: 990,
+    };
+    let steps = replay(&hand).expect("the hand replays");
+    let played: Vec<&ReplayMove> = steps.iter().map(|step| &step.played).collect();
+    assert_eq!(played, vec![&ReplayMove::Deal, &ReplayMove::Act(Action::Hit)]);
+    assert!(steps[1].events.contains(&UiEvent::OutcomeDecided { hand: 1, outcome: Outcome::Bust }));
+
+    hand.actions.clear();
+    assert_eq!(replay(&hand).err(), Option::Some(ReplayError::Diverged));
+    hand.shoe = Option::None;
+    assert_eq!(replay(&hand).err(), Option::Some(ReplayError::NoShoe));
+}
@@ -0,0 +1,38 @@
+// Counting a hand from its hard total and aces agrees with listing every way
+// of counting it, card value by card value.
+
+use rust_blackjack::{calculate_current_hand_value, card_value_to_hand_value, combine_possible_values, CardValue, HandValue};
+
+fn every_way(hand: &[CardValue]) -> Vec<u32> {
+    let mut values: Vec<u32> = hand
+        .iter()
+        .map(card_value_to_hand_value)
+        .fold(vec![HandValue { value: 0 }], |x, y| combine_possible_values(&x, &y))
+        .into_iter()
+        .map(|hand_value| hand_value.value)
+        .collect();
+    values.sort_unstable();
+    values.dedup();
+    values
+}
+
+#[test]
+fn every_hand_of_up_to_five_cards_counts_as_before() {
+    let mut hands: Vec<Vec<CardValue>> = vec![Vec::new()];
+    for _ in 0..5 {
+        hands = hands
+            .iter()
+            .flat_map(|hand| {
+                CardValue::ALL_VALUES.iter().map(move |value| {
+                    let mut hand = hand.clone();
+                    hand.push(*value);
+                    hand
+                })
+            })
+            .collect();
+        for hand in &hands {
+            let values: Vec<u32> = calculate_current_hand_value(hand).into_iter().map(|hand_value| hand_value.value).collect();
+            assert_eq!(values, every_way(hand), "{:?}", hand);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+// Drives the engine through random shoes, rules and action sequences, legal
+// or not, and checks the invariants after every step. Each case is seeded,
+// so a failure names the seed that reproduces it.
+
+use rand::{Rng, SeedableRng, StdRng};
+
+use rust_blackjack::config::RulesPreset;
+use rust_blackjack::dealer::DealerProfile;
+use rust_blackjack::invariants::{check_deck, check_hand_value, check_state};
+use rust_blackjack::rules::{EmptyShoe, GameRules};
+use rust_blackjack::table::{clear_table, Round};
+use rust_blackjack::wasm::{new_game, state_json};
+use rust_blackjack::{continue_with_game, deal_with_action, Action, Deck, GameState};
+
+const CASES: usize = 300;
+
+fn seeded(seed: usize) -> StdRng {
+    SeedableRng::from_seed(&[seed][..])
+}
+
+fn random_rules(rng: &mut StdRng) -> GameRules {
+    let preset = &RulesPreset::ALL_VALUES[rng.gen_range(0, RulesPreset::ALL_VALUES.len())];
+    GameRules {
+        dealer_hits_soft_17: rng.gen(),
+        peek: rng.gen(),
+        dealer: DealerProfile::ALL_VALUES[rng.gen_range(0, DealerProfile::ALL_VALUES.len())].clone(),
+        penetration: rng.gen_range(50, 91),
+        empty_shoe: EmptyShoe::ALL_VALUES[rng.gen_range(0, EmptyShoe::ALL_VALUES.len())].clone(),
+        charlie: if rng.gen() { Option::Some(rng.gen_range(3, 8)) } else { Option::None },
+        max_split_hands: rng.gen_range(1, 9),
+        split_aces_one_card: rng.gen(),
+        blackjack_after_split: rng.gen(),
+        double_after_split: rng.gen(),
+        ..preset.rules(rng.gen_range(1, 9))
+    }
+}
+
+fn new_shoe(rng: &mut StdRng, rules: &GameRules) -> Deck {
+    let mut deck = Deck::with_decks(rules.decks);
+    deck.shuffle(rng);
+    deck.burn();
+    deck.place_cut_card(rules.penetration);
+    deck
+}
+
+// Mostly a legal action, and now and then any action at all, which the
+// engine has to turn down without disturbing the hand.
+fn random_action(rng: &mut StdRng, state: &GameState) -> Action {
+    let legal = state.legal_actions();
+    if legal.is_empty() || rng.gen_range(0, 5) == 0 {
+        Action::ALL_VALUES[rng.gen_range(0, Action::ALL_VALUES.len())].clone()
+    } else {
+        legal[rng.gen_range(0, legal.len())].clone()
+    }
+}
+
+fn assert_holds(state: &GameState, seed: usize) {
+    if let Err(violation) = check_state(state) {
+        panic!("seed {}: {} in {:?}", seed, violation, state);
+    }
+}
+
+#[test]
+fn fresh_shoes_are_consistent() {
+    for decks in 1..=8 {
+        let mut rng = seeded(decks as usize);
+        let rules = GameRules { decks, ..GameRules::default() };
+        assert!(new_shoe(&mut rng, &rules).is_consistent());
+    }
+}
+
+#[test]
+fn single_hands_keep_their_invariants() {
+    for seed in 0..CASES {
+        let mut rng = seeded(seed);
+        let rules = random_rules(&mut rng);
+        let mut state = GameState::start_with_rules(new_shoe(&mut rng, &rules), rules);
+        assert_holds(&state, seed);
+        while continue_with_game(&state) {
+            let action = random_action(&mut rng, &state);
+            state = match deal_with_action(&action, state) {
+                Ok(next_state) => next_state,
+                Err(illegal) => *illegal.state,
+            };
+            assert_holds(&state, seed);
+        }
+    }
+}
+
+// A shoe dealt to the cut card, hand after hand, never loses or gains a
+// card.
+#[test]
+fn a_shoe_is_conserved_across_hands() {
+    for seed in 0..CASES / 10 {
+        let mut rng = seeded(seed);
+        let rules = random_rules(&mut rng);
+        let mut shoe = new_shoe(&mut rng, &rules);
+        while !shoe.needs_shuffle() {
+            let mut state = GameState::start_with_rules(shoe, rules.clone());
+            while continue_with_game(&state) {
+                let action = random_action(&mut rng, &state);
+                state = deal_with_action(&action, state).unwrap_or_else(|illegal| *illegal.state);
+                assert_holds(&state, seed);
+            }
+            shoe = state.discard_hand();
+            assert_eq!(check_deck(&shoe), Ok(()), "seed {}", seed);
+        }
+    }
+}
+
+#[test]
+fn shared_tables_keep_their_invariants() {
+    for seed in 0..CASES / 3 {
+        let mut rng = seeded(seed);
+        let rules = random_rules(&mut rng);
+        let seats = rng.gen_range(1, 8);
+        let mut round = Round::deal(new_shoe(&mut rng, &rules), seats, &rules);
+        for seat in 0..seats {
+            while round.is_playing(seat) {
+                let view = round.view(seat);
+                assert_holds(&view, seed);
+                let action = random_action(&mut rng, &view);
+                let _ = round.act(seat, &action);
+            }
+        }
+        let (states, deck) = round.finish();
+        for state in states.iter() {
+            assert_eq!(check_hand_value(&state.player_state().hand), Ok(()), "seed {}", seed);
+        }
+        assert_eq!(check_deck(&clear_table(states, deck)), Ok(()), "seed {}", seed);
+    }
+}
+
+// Shoes dealt right down to their last few cards, so hands run them dry
+// and either reshuffle the discards or end where they stand.
+#[test]
+fn exhausted_shoes_keep_their_invariants() {
+    for seed in 0..CASES {
+        let mut rng = seeded(seed);
+        let rules = GameRules { decks: 1, ..random_rules(&mut rng) };
+        let mut shoe = new_shoe(&mut rng, &rules);
+        let left = rng.gen_range(0, 6);
+        while shoe.remaining() > left {
+            let state = GameState::start_with_rules(shoe, rules.clone());
+            shoe = state.discard_hand();
+        }
+        let mut state = GameState::start_with_rules(shoe, rules.clone());
+        assert_holds(&state, seed);
+        while continue_with_game(&state) {
+            let hit = state.legal_actions().contains(&Action::Hit) && rng.gen_range(0, 4) > 0;
+            let action = if hit { Action::Hit } else { random_action(&mut rng, &state) };
+            state = deal_with_action(&action, state).unwrap_or_else(|illegal| *illegal.state);
+            assert_holds(&state, seed);
+        }
+    }
+}
+
+// A quick stand-in for the fuzz target for builds without cargo-fuzz: words
+// the game knows mixed with noise, fed to the raw input entry point.
+#[test]
+fn raw_input_never_panics() {
+    let words = [
+        "hit", "h", "stand", "2", "double-down", "split", "surrender", "y", "n", "insurance", "0",
+        "99999999999999999999", "\u{0}", "é", "",
+    ];
+    for seed in 0..CASES {
+        let mut rng = seeded(seed);
+        let lines: Vec<String> = (0..rng.gen_range(0, 12))
+            .map(|_| {
+                if rng.gen() {
+                    words[rng.gen_range(0, words.len())].to_string()
+                } else {
+                    (0..rng.gen_range(0, 8)).map(|_| rng.gen::<u8>() as char).collect()
+                }
+            })
+            .collect();
+        let mut game = new_game(seed as u32);
+        game.apply_raw_input(&lines.join("\n"));
+        state_json(&game);
+        if let Option::Some(state) = game.state() {
+            assert_holds(state, seed);
+        }
+    }
+}
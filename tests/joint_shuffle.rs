@@ -0,0 +1,43 @@
+// Two sides of the joint shuffle end up with the same shoe, and neither can
+// swap its secret once it has committed.
+
+use rust_blackjack::fairness::FairnessError;
+use rust_blackjack::joint_shuffle::{JointShuffle, Role};
+use rust_blackjack::protocol::encode_card;
+use rust_blackjack::Deck;
+
+fn order(deck: &mut Deck) -> Vec<String> {
+    let mut cards = Vec::new();
+    while let Option::Some(card) = deck.draw_card() {
+        cards.push(encode_card(&card));
+    }
+    cards
+}
+
+#[test]
+fn both_sides_deal_the_same_shoe() {
+    let mut dealer = JointShuffle::with_secret(Role::Dealer, "dealer secret");
+    let mut player = JointShuffle::with_secret(Role::Player, "player secret");
+    assert!(matches!(dealer.reveal(), Err(FairnessError::OutOfOrder(_))));
+    dealer.receive_commitment(&player.commitment()).expect("first commitment");
+    player.receive_commitment(&dealer.commitment()).expect("first commitment");
+    let (dealer_secret, player_secret) = (dealer.reveal().expect("both committed"), player.reveal().expect("both committed"));
+    let mut dealers_shoe = dealer.receive_secret(&player_secret, 2).expect("the player's secret matches");
+    let mut players_shoe = player.receive_secret(&dealer_secret, 2).expect("the dealer's secret matches");
+    let (dealt, also_dealt) = (order(&mut dealers_shoe), order(&mut players_shoe));
+    assert_eq!(dealt.len(), 104);
+    assert_eq!(dealt, also_dealt);
+    assert_ne!(dealt, order(&mut Deck::with_decks(2)));
+}
+
+#[test]
+fn a_secret_swapped_after_committing_is_caught() {
+    let mut dealer = JointShuffle::with_secret(Role::Dealer, "dealer secret");
+    let player = JointShuffle::with_secret(Role::Player, "player secret");
+    dealer.receive_commitment(&player.commitment()).expect("first commitment");
+    dealer.reveal().expect("both committed");
+    match dealer.receive_secret("a kinder secret", 1) {
+        Err(FairnessError::Mismatch { committed, .. }) => assert_eq!(committed, player.commitment()),
+        other => panic!("a swapped secret was taken: {:?}", other.map(|_| ())),
+    }
+}
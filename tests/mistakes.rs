@@ -0,0 +1,41 @@
+// Mistakes are priced as what the book play was worth over the one made.
+
+use rust_blackjack::mistakes::{Mistake, MistakeLog};
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::shoe::RankCounts;
+use rust_blackjack::solver::insurance_values;
+use rust_blackjack::{Action, Card, CardSuit, CardValue};
+
+fn card(value: CardValue) -> Card {
+    Card { suit: CardSuit::Spades, value }
+}
+
+#[test]
+fn insurance_is_priced_by_the_tens_left() {
+    let rules = GameRules::default();
+    // A third of the cards worth ten: an even bet.
+    let shoe = RankCounts::of(&[card(CardValue::King), card(CardValue::Two), card(CardValue::Three)]);
+    let values = insurance_values(false, &shoe, &rules);
+    assert!(values.iter().all(|(_, value)| value.abs() < 1e-9));
+    let even_money = insurance_values(true, &RankCounts::of(&[card(CardValue::Two)]), &rules);
+    assert_eq!(even_money[0], (Action::EvenMoney, 1.0));
+    assert_eq!(even_money[1].0, Action::DeclineInsurance);
+    assert!(even_money[1].1 > 1.0);
+}
+
+#[test]
+fn the_report_adds_up_the_cost() {
+    let mut log = MistakeLog::new();
+    assert_eq!(log.render(), "Mistakes: none");
+    log.record(Mistake {
+        hand_number: 3,
+        hand: vec![card(CardValue::Ten), card(CardValue::Six)],
+        dealer_upcard: card(CardValue::Seven),
+        action_taken: Action::Stand,
+        advised: Action::Hit,
+        cost: 0.0625,
+        bet: 20,
+    });
+    assert_eq!(log.total_chips(), 1.25);
+    assert!(log.render().ends_with("hand 3: TS 6S (16) v 7S: you chose stand, the book plays hit, cost 0.0625 (1.25)"));
+}
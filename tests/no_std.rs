@@ -0,0 +1,17 @@
+// The rules engine builds without std, the way anyone else would build it.
+
+use std::env;
+use std::process::Command;
+
+#[test]
+fn the_engine_builds_without_std() {
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let manifest = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+    // A target directory of its own, as the one running the tests is locked.
+    let target = concat!(env!("CARGO_TARGET_TMPDIR"), "/no_std");
+    let output = Command::new(cargo)
+        .args(["build", "--lib", "--no-default-features", "--offline", "--manifest-path", manifest, "--target-dir", target])
+        .output()
+        .expect("cargo could not be run");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
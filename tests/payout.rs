@@ -0,0 +1,106 @@
+// Payouts are worked out exactly and rounded once, by the table's rule.
+
+use rust_blackjack::payout::{Odds, PayoutRounding, Rounding};
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::scripted::ScriptedCardSource;
+use rust_blackjack::settlement::{max_tip, settle, staked};
+use rust_blackjack::stats::SessionStats;
+use rust_blackjack::{deal_with_action, Action, GameState};
+
+fn rounding(rounding: Rounding, step: u64) -> PayoutRounding {
+    PayoutRounding { rounding, step }
+}
+
+// Cards go to the player, the dealer, the player and the dealer.
+fn start(cards: &str, rules: &GameRules) -> GameState {
+    GameState::start_with_rules(ScriptedCardSource::parse(cards).unwrap().into_deck(), rules.clone())
+}
+
+#[test]
+fn odds_pay_exactly_when_they_come_out_whole() {
+    let three_to_two = Odds { numerator: 3, denominator: 2 };
+    for direction in Rounding::ALL_VALUES.iter() {
+        assert_eq!(three_to_two.pay(10, &rounding(direction.clone(), 1)), 15);
+        assert_eq!(three_to_two.pay(10, &rounding(direction.clone(), 25)), 15);
+        assert_eq!(Odds::INSURANCE.pay(7, &rounding(direction.clone(), 5)), 14);
+    }
+    assert_eq!(Odds { numerator: 6, denominator: 5 }.pay(5, &PayoutRounding::default()), 6);
+    assert_eq!(Odds::HALF.pay(0, &PayoutRounding::default()), 0);
+    assert_eq!(three_to_two.pay(u64::MAX / 2, &PayoutRounding::default()), u64::MAX / 4 * 3 + 1);
+}
+
+#[test]
+fn fractions_round_to_the_step() {
+    let three_to_two = Odds { numerator: 3, denominator: 2 };
+    // 7.5 on a bet of 5.
+    assert_eq!(three_to_two.pay(5, &rounding(Rounding::Down, 1)), 7);
+    assert_eq!(three_to_two.pay(5, &rounding(Rounding::Nearest, 1)), 8);
+    assert_eq!(three_to_two.pay(5, &rounding(Rounding::Up, 1)), 8);
+    assert_eq!(three_to_two.pay(5, &rounding(Rounding::Down, 5)), 5);
+    assert_eq!(three_to_two.pay(5, &rounding(Rounding::Nearest, 5)), 10);
+    assert_eq!(three_to_two.pay(5, &rounding(Rounding::Up, 5)), 10);
+    // 6.6 on a bet of 11 at 3:5; nearest rounds it up, and a step of 5 down.
+    let three_to_five = Odds { numerator: 3, denominator: 5 };
+    assert_eq!(three_to_five.pay(11, &rounding(Rounding::Nearest, 1)), 7);
+    assert_eq!(three_to_five.pay(11, &rounding(Rounding::Nearest, 5)), 5);
+    // A step of 0 counts as 1.
+    assert_eq!(three_to_two.pay(5, &rounding(Rounding::Up, 0)), 8);
+}
+
+#[test]
+fn blackjacks_and_surrenders_settle_by_the_table_s_rounding() {
+    let rules = |rounding: Rounding, step: u64| GameRules {
+        payout_rounding: PayoutRounding { rounding, step },
+        ..GameRules::default()
+    };
+    let natural = "AS 9H KD 7C";
+    assert_eq!(settle(&start(natural, &rules(Rounding::Down, 1)), 5, &[]).net, 7);
+    assert_eq!(settle(&start(natural, &rules(Rounding::Nearest, 1)), 5, &[]).net, 8);
+    assert_eq!(settle(&start(natural, &rules(Rounding::Down, 5)), 5, &[]).net, 5);
+    let six_to_five = GameRules { blackjack_numerator: 6, blackjack_denominator: 5, ..GameRules::default() };
+    assert_eq!(settle(&start(natural, &six_to_five), 5, &[]).net, 6);
+    assert_eq!(settle(&start(natural, &six_to_five), 7, &[]).net, 8);
+
+    // 16 against a 9: half an odd bet back, the half chip as rounded.
+    let surrender = |rules: &GameRules, bet: u64| {
+        let state = deal_with_action(&Action::Surrender, start("TS 9H 6D 7C", rules)).unwrap();
+        settle(&state, bet, &[]).net
+    };
+    assert_eq!(surrender(&rules(Rounding::Down, 1), 10), -5);
+    assert_eq!(surrender(&rules(Rounding::Down, 1), 5), -3);
+    assert_eq!(surrender(&rules(Rounding::Nearest, 1), 5), -2);
+    assert_eq!(surrender(&rules(Rounding::Up, 5), 15), -5);
+}
+
+// The dealer has a natural under the ace: insurance on a bet of 5 costs 2
+// and pays 4, while the 19 loses the bet. Against a soft 18 the insurance
+// is lost and the 19 wins.
+#[test]
+fn insurance_pays_two_to_one_on_what_it_cost() {
+    let state = start("TS AH 9D KC", &GameRules::default());
+    let state = deal_with_action(&Action::Insurance, state).unwrap();
+    assert_eq!(staked(&state, 5, Option::None), 7);
+    let settlement = settle(&state, 5, &[]);
+    assert_eq!((settlement.insurance, settlement.net), (4, -1));
+
+    let state = deal_with_action(&Action::Insurance, start("TS AH 9D 7C", &GameRules::default())).unwrap();
+    let state = deal_with_action(&Action::Stand, state).unwrap();
+    let settlement = settle(&state, 5, &[]);
+    assert_eq!((settlement.insurance, settlement.net), (-2, 3));
+}
+
+// A tip comes out of what a hand won and is kept apart from the result.
+#[test]
+fn tips_come_out_of_winnings() {
+    let won = start("AS 9H KD 7C", &GameRules::default());
+    let lost = deal_with_action(&Action::Surrender, start("TS 9H 6D 7C", &GameRules::default())).unwrap();
+    assert_eq!(max_tip(&settle(&won, 10, &[])), 15);
+    assert_eq!(max_tip(&settle(&lost, 10, &[])), 0);
+
+    let mut stats = SessionStats::new();
+    stats.record(&won, &settle(&won, 10, &[]));
+    stats.record_tip(2);
+    stats.record_tip(3);
+    assert_eq!((stats.net, stats.tips, stats.tipped), (15, 2, 5));
+    assert!(stats.render().ends_with("Dealer tips:     2 for 5\nNet after tips:  +10"));
+}
@@ -0,0 +1,62 @@
+// The secure shuffle deals a full shoe like any other, but draws its order
+// from the operating system rather than the generator it was handed.
+
+use rand::{SeedableRng, StdRng};
+use rust_blackjack::rules::{EmptyShoe, GameRules};
+use rust_blackjack::shuffle::ShuffleRng;
+use rust_blackjack::Deck;
+
+fn shuffled(rules: &GameRules) -> Vec<u8> {
+    let rng: StdRng = SeedableRng::from_seed(&[7][..]);
+    let mut deck = Deck::with_decks(rules.decks);
+    deck.shuffle(rules.shuffler(rng).unwrap().as_mut());
+    let mut cards = Vec::new();
+    while let Option::Some(card) = deck.draw_card() {
+        cards.push(card.to_index());
+    }
+    cards
+}
+
+#[test]
+fn a_secure_shuffle_ignores_the_seeded_generator() {
+    let standard = GameRules { decks: 6, ..GameRules::default() };
+    let secure = GameRules { shuffle_rng: ShuffleRng::Secure, ..standard.clone() };
+    assert_eq!(shuffled(&standard), shuffled(&standard));
+
+    let first = shuffled(&secure);
+    let mut sorted = first.clone();
+    sorted.sort();
+    let mut expected = shuffled(&standard);
+    expected.sort();
+    assert_eq!(sorted, expected);
+    assert_ne!(first, shuffled(&secure));
+    assert_ne!(first, shuffled(&standard));
+}
+
+// Two shoes whose discards went into the tray in the same order still
+// reshuffle differently, as the new order comes from each shoe's shuffler.
+#[test]
+fn a_reshuffle_owes_nothing_to_the_discards() {
+    let reshuffled = |seed: usize| {
+        let mut rng: StdRng = SeedableRng::from_seed(&[seed][..]);
+        let mut deck = Deck::with_decks(1);
+        deck.shuffle(&mut rng);
+        let mut dealt = Vec::new();
+        while let Option::Some(card) = deck.draw_card() {
+            dealt.push(card);
+        }
+        dealt.sort_by_key(|card| card.to_index());
+        deck.discard(dealt);
+        let mut cards = Vec::new();
+        while let Option::Some(card) = deck.draw_with(&EmptyShoe::Reshuffle) {
+            cards.push(card.to_index());
+            if cards.len() == 52 {
+                break;
+            }
+        }
+        cards
+    };
+    assert_eq!(reshuffled(1).len(), 52);
+    assert_eq!(reshuffled(1), reshuffled(1));
+    assert_ne!(reshuffled(1), reshuffled(2));
+}
@@ -0,0 +1,65 @@
+// The gRPC service's calls, played without a transport.
+
+use rand::{SeedableRng, StdRng};
+
+use rust_blackjack::events::UiEvent;
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::service::{GameService, ServiceError, MAX_FINISHED_GAMES};
+use rust_blackjack::strategy::{StandPat, Strategy};
+use rust_blackjack::{continue_with_game, Action};
+
+fn service() -> GameService {
+    let rng: StdRng = SeedableRng::from_seed(&[1][..]);
+    GameService::new(GameRules::default(), Box::new(rng))
+}
+
+#[test]
+fn a_stream_replays_the_deal_and_ends_with_the_hand() {
+    let service = service();
+    let (id, mut state) = service.create_game(Option::Some(7));
+    let events = service.stream_events(id).expect("the game exists");
+    while continue_with_game(&state) {
+        state = service.apply_action(id, &StandPat.choose(&state)).expect("standing pat is legal");
+    }
+    let seen: Vec<UiEvent> = events.iter().collect();
+    assert!(seen.iter().all(UiEvent::is_public));
+    assert!(matches!(seen.first(), Option::Some(UiEvent::CardDealt { .. })));
+    assert!(matches!(seen.last(), Option::Some(UiEvent::OutcomeDecided { .. })));
+
+    let (_, first) = service.create_game(Option::Some(7));
+    let (_, second) = service.create_game(Option::Some(7));
+    assert_eq!(first, second);
+}
+
+#[test]
+fn unknown_games_and_finished_hands_are_errors() {
+    let service = service();
+    assert_eq!(service.apply_action(99, &Action::Hit).unwrap_err(), ServiceError::NotFound(99));
+    assert!(service.stream_events(99).is_err());
+
+    let (id, mut state) = service.create_game(Option::Some(3));
+    while continue_with_game(&state) {
+        state = service.apply_action(id, &StandPat.choose(&state)).expect("standing pat is legal");
+    }
+    assert!(matches!(service.apply_action(id, &Action::Hit), Err(ServiceError::IllegalAction(_))));
+}
+
+#[test]
+fn closed_games_are_gone_and_finished_ones_are_forgotten_in_time() {
+    let service = service();
+    let (id, _) = service.create_game(Option::None);
+    service.close_game(id).expect("the game exists");
+    assert_eq!(service.apply_action(id, &Action::Stand).unwrap_err(), ServiceError::NotFound(id));
+    assert_eq!(service.close_game(id).unwrap_err(), ServiceError::NotFound(id));
+
+    let mut finished = Vec::new();
+    while finished.len() <= MAX_FINISHED_GAMES {
+        let (id, mut state) = service.create_game(Option::None);
+        while continue_with_game(&state) {
+            state = service.apply_action(id, &StandPat.choose(&state)).expect("standing pat is legal");
+        }
+        finished.push(id);
+    }
+    assert!(service.stream_events(finished[0]).is_err());
+    assert!(service.stream_events(finished[1]).is_ok());
+}
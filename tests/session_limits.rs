@@ -0,0 +1,22 @@
+// A session stops at the first limit it reaches, and never without one.
+
+use std::time::Duration;
+
+use rust_blackjack::session_limits::{SessionLimits, StopReason};
+
+#[test]
+fn limits_stop_a_session_once_reached() {
+    let limits = SessionLimits {
+        stop_win: Option::Some(100),
+        stop_loss: Option::Some(50),
+        max_hands: Option::Some(20),
+        max_time: Option::Some(Duration::from_secs(600)),
+    };
+    let minute = Duration::from_secs(60);
+    assert_eq!(limits.reached(99, 19, minute), Option::None);
+    assert_eq!(limits.reached(100, 19, minute), Option::Some(StopReason::StopWin));
+    assert_eq!(limits.reached(-50, 19, minute), Option::Some(StopReason::StopLoss));
+    assert_eq!(limits.reached(0, 20, minute), Option::Some(StopReason::MaxHands));
+    assert_eq!(limits.reached(0, 5, minute * 10), Option::Some(StopReason::MaxTime));
+    assert_eq!(SessionLimits::default().reached(-1000, 1000, minute * 600), Option::None);
+}
@@ -0,0 +1,70 @@
+// Shoes dealt to the cut card are bucketed by the true count of each hand.
+
+use rand::SeedableRng;
+use rand::StdRng;
+
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::simulation::{simulate_shoes, Merge, Wonging};
+use rust_blackjack::stats::{ShoeStats, HIGHEST_COUNT_BUCKET, LOWEST_COUNT_BUCKET};
+use rust_blackjack::Deck;
+
+fn shoe(rules: &GameRules, rng: &mut StdRng) -> Deck {
+    let mut deck = Deck::with_decks(rules.decks);
+    deck.shuffle(rng);
+    deck.burn();
+    deck.place_cut_card(rules.penetration);
+    deck
+}
+
+#[test]
+fn true_counts_round_down_into_buckets() {
+    let zero = (0 - LOWEST_COUNT_BUCKET) as usize;
+    assert_eq!(ShoeStats::bucket_of(0.0), zero);
+    assert_eq!(ShoeStats::bucket_of(0.99), zero);
+    assert_eq!(ShoeStats::bucket_of(-0.01), zero - 1);
+    assert_eq!(ShoeStats::bucket_of(-40.0), 0);
+    assert_eq!(ShoeStats::bucket_of(40.0), (HIGHEST_COUNT_BUCKET - LOWEST_COUNT_BUCKET) as usize);
+
+    let mut low = ShoeStats::default();
+    low.record_hand(-1.5, 10, -10);
+    low.record_shoe(200, 260);
+    let mut high = ShoeStats::default();
+    high.record_hand(2.0, 10, 15);
+    high.record_hand(2.5, 20, -20);
+    high.record_shoe(190, 260);
+    let merged = low.merge(high);
+    assert_eq!(merged.hands(), 3);
+    assert_eq!(merged.buckets[ShoeStats::bucket_of(2.0)].net, -5);
+    assert!((merged.buckets[ShoeStats::bucket_of(2.0)].return_on_wagers() + 5.0 / 30.0).abs() < 1e-12);
+    assert!((merged.penetration() - 390.0 / 520.0).abs() < 1e-12);
+}
+
+#[test]
+fn every_hand_is_bucketed_and_shoes_reach_the_cut_card() {
+    let rules = GameRules { decks: 6, ..GameRules::default() };
+    let mut rng: StdRng = SeedableRng::from_seed(&[7][..]);
+    let new_shoe = || shoe(&rules, &mut rng);
+    let (report, stats) = simulate_shoes(5000, new_shoe, &rules, 10, &[], Option::None);
+    assert_eq!(stats.hands(), report.hands);
+    assert_eq!(stats.buckets.iter().map(|bucket| bucket.net).sum::<i64>(), report.net);
+    assert!(stats.shoes > 50);
+    // The cut card comes out in the middle of a round, which is finished.
+    let cut = rules.penetration as f64 / 100.0;
+    assert!(stats.penetration() >= cut && stats.penetration() < cut + 0.03, "{}", stats.penetration());
+}
+
+// Sitting down at +2 and leaving below 0, no hand is bet below 0 and every
+// hand dealt is either watched or played.
+#[test]
+fn back_counting_only_bets_once_the_count_is_up() {
+    let rules = GameRules { decks: 6, ..GameRules::default() };
+    let mut rng: StdRng = SeedableRng::from_seed(&[7][..]);
+    let new_shoe = || shoe(&rules, &mut rng);
+    let wonging = Wonging { enter_at: 2.0, leave_below: 0.0 };
+    let (report, stats) = simulate_shoes(5000, new_shoe, &rules, 10, &[], Option::Some(&wonging));
+    assert_eq!(stats.observed + report.hands, 5000);
+    assert!(report.hands > 0 && stats.observed > report.hands);
+    assert!(stats.departures > 0);
+    let zero = ShoeStats::bucket_of(0.0);
+    assert!(stats.buckets[..zero].iter().all(|bucket| bucket.hands == 0));
+}
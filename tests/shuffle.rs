@@ -0,0 +1,22 @@
+// The steps of the casino shuffle move cards around without losing any.
+
+use rand::SeedableRng;
+use rand::StdRng;
+
+use rust_blackjack::shuffle::ShuffleStep;
+use rust_blackjack::shuffle_tracking::picked_up;
+use rust_blackjack::Deck;
+
+#[test]
+fn a_strip_cut_reorders_the_deck_and_keeps_its_cards() {
+    let original = picked_up(&Deck::with_decks(1));
+    let mut stripped = original.clone();
+    let mut rng: StdRng = SeedableRng::from_seed(&[1][..]);
+    ShuffleStep::StripCut.apply(&mut stripped, &mut rng);
+    assert_ne!(stripped, original);
+    let mut sorted = stripped.clone();
+    sorted.sort_by_key(|card| card.to_index());
+    let mut expected = original.clone();
+    expected.sort_by_key(|card| card.to_index());
+    assert_eq!(sorted, expected);
+}
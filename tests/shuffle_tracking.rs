@@ -0,0 +1,76 @@
+// A traced shuffle deals the same shoe as an untraced one and knows where
+// every card went, and every step of the dealer's routine moves some.
+
+use rand::SeedableRng;
+use rand::StdRng;
+
+use rust_blackjack::shuffle::{ShuffleModel, CASINO_ROUTINE};
+use rust_blackjack::shuffle_tracking::{picked_up, reshuffle_traced, trace_shuffle};
+use rust_blackjack::Deck;
+
+fn seeded(seed: usize) -> StdRng {
+    SeedableRng::from_seed(&[seed][..])
+}
+
+#[test]
+fn tracing_follows_every_card_through_the_casino_shuffle() {
+    let original = picked_up(&Deck::with_decks(6));
+    let mut shuffled = Deck::with_decks(6);
+    shuffled.shuffle(&mut *ShuffleModel::Casino.shuffler(seeded(11)));
+    let mut traced = original.clone();
+    let trace = trace_shuffle(&mut traced, &CASINO_ROUTINE, &mut seeded(11));
+    assert_eq!(traced, picked_up(&shuffled));
+    assert_eq!(trace.steps.len(), CASINO_ROUTINE.len());
+    for (start, card) in original.iter().enumerate() {
+        assert_eq!(traced[trace.position(start).unwrap()], *card);
+    }
+    assert_eq!(trace.position(original.len()), Option::None);
+    assert_eq!(trace.position_after(CASINO_ROUTINE.len(), 0), Option::None);
+}
+
+#[test]
+fn a_slug_is_found_wherever_it_lands() {
+    let mut cards = picked_up(&Deck::with_decks(2));
+    let trace = trace_shuffle(&mut cards, &CASINO_ROUTINE, &mut seeded(3));
+    let slug = 20..52;
+    let positions = trace.slug_positions(slug.clone());
+    assert_eq!(positions.len(), slug.len());
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+    let span = trace.slug_span(slug.clone()).unwrap();
+    assert_eq!((span.start, span.end), (positions[0], positions[positions.len() - 1] + 1));
+    assert_eq!(trace.slug_cards_in(slug.clone(), span), slug.len());
+    assert_eq!(trace.slug_span(0..0), Option::None);
+}
+
+#[test]
+fn reshuffling_keeps_every_card_of_the_last_shoe() {
+    let mut deck = Deck::with_decks(1);
+    deck.burn();
+    for _ in 0..10 {
+        let card = deck.draw_card().unwrap();
+        deck.discard(Option::Some(card));
+    }
+    let gathered = picked_up(&deck);
+    assert_eq!(gathered.len(), 52);
+    assert_eq!(gathered[1..11], *deck.discard_tray());
+    let (shoe, trace) = reshuffle_traced(&deck, &CASINO_ROUTINE, &mut seeded(5));
+    assert_eq!(shoe.remaining(), 52);
+    let dealt = picked_up(&shoe);
+    for (start, card) in gathered.iter().enumerate() {
+        assert_eq!(dealt[trace.position(start).unwrap()], *card);
+    }
+}
+
+#[test]
+fn every_step_of_the_casino_shuffle_moves_cards() {
+    let mut cards = picked_up(&Deck::with_decks(2));
+    let trace = trace_shuffle(&mut cards, &CASINO_ROUTINE, &mut seeded(7));
+    let mut before: Vec<usize> = (0..cards.len()).collect();
+    for (step, (name, after)) in trace.steps.iter().enumerate() {
+        assert_ne!(*after, before, "step {} ({:?}) left the shoe as it was", step, name);
+        let mut moved = after.clone();
+        moved.sort_unstable();
+        assert_eq!(moved, (0..cards.len()).collect::<Vec<usize>>());
+        before = after.clone();
+    }
+}
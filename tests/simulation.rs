@@ -0,0 +1,16 @@
+// Simulated hands only ever take legal actions, however short the shoe.
+
+use rust_blackjack::config::RulesPreset;
+use rust_blackjack::rules::{EmptyShoe, GameRules};
+use rust_blackjack::scripted::ScriptedCardSource;
+use rust_blackjack::simulation::simulate;
+
+#[test]
+fn a_pontoon_hand_that_empties_the_shoe_under_fifteen_keeps_going() {
+    let deuces = ScriptedCardSource::parse("2C 2D 2H 2S 2C 2D").expect("valid card codes");
+    for empty_shoe in [EmptyShoe::EndHand, EmptyShoe::Reshuffle] {
+        let rules = GameRules { empty_shoe, ..RulesPreset::Pontoon.rules(1) };
+        let report = simulate(5, || deuces.clone().into_deck(), &rules, 10, &[]);
+        assert_eq!(report.hands, 5);
+    }
+}
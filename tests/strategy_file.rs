@@ -0,0 +1,39 @@
+// A strategy file is only taken whole: every hand in the chart, with a play
+// that can be read for every upcard.
+
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::strategy_file::{CustomStrategy, StrategyFileError};
+use rust_blackjack::strategy_table::{rules_hash, ChartFormat, ChartPlay, StrategyTable, TableHand};
+use rust_blackjack::{Card, CardSuit, CardValue};
+
+// The CSV chart of a table of zeros, where every play ties and stands.
+fn chart() -> String {
+    let rules = GameRules::default();
+    let mut bytes = b"BJST".to_vec();
+    bytes.push(1);
+    bytes.extend_from_slice(&rules_hash(&rules).to_le_bytes());
+    bytes.resize(bytes.len() + 36 * 10 * 5 * 4, 0);
+    StrategyTable::from_bytes(&bytes).unwrap().chart(ChartFormat::Csv, &rules)
+}
+
+#[test]
+fn a_generated_chart_reads_back() {
+    let text = chart().replace("\"hard 16\",S,S,S,S,S,S", "\"hard 16\",S,S,S,S,S,h");
+    let strategy = CustomStrategy::parse(&text).unwrap();
+    let upcard = |value| Card { suit: CardSuit::Clubs, value };
+    assert_eq!(strategy.play(TableHand::Hard(16), &upcard(CardValue::Six)), Option::Some(ChartPlay::Stand));
+    assert_eq!(strategy.play(TableHand::Hard(16), &upcard(CardValue::Seven)), Option::Some(ChartPlay::Hit));
+}
+
+#[test]
+fn an_incomplete_chart_is_refused() {
+    let text = chart();
+    let without_pairs: Vec<&str> = text.lines().filter(|line| !line.contains("pair 8,8")).collect();
+    match CustomStrategy::parse(&without_pairs.join("\n")) {
+        Err(StrategyFileError::Missing(hands)) => assert_eq!(hands, vec!["pair 8,8".to_string()]),
+        other => panic!("expected a missing hand, got {:?}", other),
+    }
+    let unknown = text.replace("\"soft 18\",S", "\"soft 18\",Q");
+    assert!(matches!(CustomStrategy::parse(&unknown), Err(StrategyFileError::UnknownPlay { line: 24, .. })));
+    assert!(matches!(CustomStrategy::parse("hard 16,S"), Err(StrategyFileError::Header)));
+}
@@ -0,0 +1,73 @@
+// Strategy tables are keyed by the rules that change what a play is worth,
+// and a file is only read back for the rules it was made for.
+
+use std::env;
+use std::fs;
+
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::shuffle::ShuffleModel;
+use rust_blackjack::strategy_table::{rules_hash, ChartFormat, StrategyTable, TableError, TableHand};
+use rust_blackjack::{Card, CardSuit, CardValue};
+
+fn hand(values: &[CardValue]) -> Vec<Card> {
+    values.iter().map(|value| Card { suit: CardSuit::Hearts, value: *value }).collect()
+}
+
+// A header for `rules` and a zero for every value: 36 rows, 10 upcards and
+// five plays.
+fn table_bytes(rules: &GameRules) -> Vec<u8> {
+    let mut bytes = b"BJST".to_vec();
+    bytes.push(1);
+    bytes.extend_from_slice(&rules_hash(rules).to_le_bytes());
+    bytes.resize(bytes.len() + 36 * 10 * 5 * 4, 0);
+    bytes
+}
+
+#[test]
+fn the_hash_follows_the_rules_that_matter() {
+    let rules = GameRules::default();
+    let shuffled = GameRules { shuffle_model: ShuffleModel::Casino, ..rules.clone() };
+    let h17 = GameRules { dealer_hits_soft_17: !rules.dealer_hits_soft_17, ..rules.clone() };
+    assert_eq!(rules_hash(&rules), rules_hash(&shuffled));
+    assert_ne!(rules_hash(&rules), rules_hash(&h17));
+}
+
+#[test]
+fn hands_are_looked_up_by_total() {
+    use CardValue::*;
+    assert_eq!(TableHand::of(&hand(&[Ten, Six])), Option::Some(TableHand::Hard(16)));
+    assert_eq!(TableHand::of(&hand(&[Five, Four, Seven])), Option::Some(TableHand::Hard(16)));
+    assert_eq!(TableHand::of(&hand(&[Ace, Seven])), Option::Some(TableHand::Soft(18)));
+    assert_eq!(TableHand::of(&hand(&[King, Queen])), Option::Some(TableHand::Pair(10)));
+    assert_eq!(TableHand::of(&hand(&[Ace, Ace])), Option::Some(TableHand::Pair(11)));
+    assert_eq!(TableHand::of(&hand(&[Ten, Nine, Five])), Option::None);
+}
+
+#[test]
+fn a_table_for_other_rules_is_stale() {
+    let rules = GameRules::default();
+    let h17 = GameRules { dealer_hits_soft_17: !rules.dealer_hits_soft_17, ..rules.clone() };
+    let path = env::temp_dir().join(format!("strategy-table-{}.bin", std::process::id()));
+    fs::write(&path, table_bytes(&rules)).unwrap();
+    let loaded = StrategyTable::load(&path, &rules);
+    let stale = StrategyTable::load(&path, &h17);
+    fs::remove_file(&path).unwrap();
+    let table = loaded.unwrap();
+    assert_eq!(StrategyTable::from_bytes(&table.to_bytes()).unwrap(), table);
+    assert!(matches!(stale, Err(TableError::Stale)));
+    assert!(matches!(StrategyTable::from_bytes(b"not a table"), Err(TableError::NotATable)));
+}
+
+#[test]
+fn a_chart_has_a_line_for_every_hand() {
+    let rules = GameRules::default();
+    let table = StrategyTable::from_bytes(&table_bytes(&rules)).unwrap();
+    let csv = table.chart(ChartFormat::Csv, &rules);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 37);
+    assert_eq!(lines[0], "hand,2,3,4,5,6,7,8,9,T,A");
+    assert_eq!(lines[36], "\"pair A,A\",S,S,S,S,S,S,S,S,S,S");
+    let markdown = table.chart(ChartFormat::Markdown, &rules);
+    assert!(markdown.starts_with(&format!("# Strategy for {}", rules.describe())));
+    assert_eq!(markdown.matches("\n## ").count(), 3);
+}
@@ -0,0 +1,21 @@
+// A tournament stack pays for doubles and splits only as far as it goes.
+
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::table::Round;
+use rust_blackjack::tournament::{Tournament, TournamentRules};
+use rust_blackjack::{Action, Deck};
+
+fn tournament(starting_chips: u64) -> Tournament {
+    Tournament::new(vec!["You".to_string()], TournamentRules { starting_chips, ..TournamentRules::default() })
+}
+
+#[test]
+fn a_double_needs_another_bet_left_behind_the_first() {
+    let round = Round::deal(Deck::with_decks(1), 1, &GameRules::default());
+    let state = &round.seats()[0];
+    let short = tournament(30);
+    assert_eq!(short.afford(0, state, 20, &Action::DoubleDown), Err("that needs 20 more chips and only 10 are left".to_string()));
+    assert!(short.afford(0, state, 20, &Action::SplitCards).is_err());
+    assert_eq!(short.afford(0, state, 20, &Action::Hit), Ok(()));
+    assert_eq!(tournament(40).afford(0, state, 20, &Action::DoubleDown), Ok(()));
+}
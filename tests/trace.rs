@@ -0,0 +1,30 @@
+// What --trace-state reports an action changed in the game state.
+
+use rust_blackjack::json::JsonValue;
+use rust_blackjack::rules::GameRules;
+use rust_blackjack::scripted::ScriptedCardSource;
+use rust_blackjack::trace::{diff, snapshot, Change};
+use rust_blackjack::{deal_with_action, Action, GameState};
+
+fn number(value: f64) -> Option<JsonValue> {
+    Option::Some(JsonValue::Number(value))
+}
+
+#[test]
+fn a_hit_shows_the_card_dealt_and_the_new_total() {
+    let deck = ScriptedCardSource::parse("TS 6H 4D 9C 5S").expect("valid card codes").into_deck();
+    let state = GameState::start_with_rules(deck, GameRules::default());
+    let before = snapshot(&state, 10, 990);
+    assert!(diff(&before, &before).is_empty());
+
+    let after = snapshot(&deal_with_action(&Action::Hit, state).expect("hitting is legal"), 10, 990);
+    assert_eq!(
+        diff(&before, &after),
+        vec![
+            Change { path: "hands[0].cards[2]".to_string(), before: Option::None, after: Option::Some(JsonValue::String("5S".to_string())) },
+            Change { path: "hands[0].total".to_string(), before: number(14.0), after: number(19.0) },
+            Change { path: "shoe.remaining".to_string(), before: number(1.0), after: number(0.0) },
+        ]
+    );
+    assert_eq!(diff(&before, &after)[1].to_string(), "hands[0].total: 14 -> 19 (+5)");
+}